@@ -34,7 +34,7 @@ CREATE TABLE payments (
 
     -- Timestamps for tracking
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP, payref TEXT,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP, payref TEXT, expires_at TIMESTAMP, priority INTEGER NOT NULL DEFAULT 1, refund_address TEXT, version INTEGER NOT NULL DEFAULT 0, resubmitted_from TEXT REFERENCES payments(id),
 
     FOREIGN KEY (payment_batch_id) REFERENCES payment_batches(id),
     -- Ensures a client can't accidentally submit the same payment twice.
@@ -83,6 +83,215 @@ CREATE TABLE payment_batches (
     -- Timestamps
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
-, intermediate_context_json TEXT);
+, intermediate_context_json TEXT, next_retry_at TIMESTAMP, required_confirmations INTEGER, pr_funds_released_at TIMESTAMP, version INTEGER NOT NULL DEFAULT 0, confirmations INTEGER, tip_height INTEGER, tx_creation_started_at TIMESTAMP);
 CREATE INDEX idx_payments_status ON payments(status);
 CREATE INDEX idx_payment_batches_status ON payment_batches(status);
+CREATE INDEX idx_payments_expires_at ON payments(expires_at);
+CREATE INDEX idx_payments_status_priority ON payments(status, priority DESC, created_at);
+CREATE TABLE events (
+    -- Monotonically increasing sequence number, never reused.
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- Types: PAYMENT_CREATED, PAYMENT_EXPIRED, BATCH_CONFIRMED, BATCH_FAILED
+    event_type TEXT NOT NULL,
+
+    -- ID of the payment or payment batch the event refers to.
+    entity_id TEXT NOT NULL,
+
+    -- Event specific details as JSON.
+    payload TEXT NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE sqlite_sequence(name,seq);
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- Who performed the action: 'system' for workers, 'api' for requests made through the HTTP API.
+    actor TEXT NOT NULL,
+
+    -- Types: PAYMENT_CREATED, PAYMENT_CANCELLED, PAYMENT_EXPIRED, BATCH_CREATED, BATCH_STATUS_CHANGED,
+    -- BATCH_RESOLVED_CONFIRMED, BATCH_RESOLVED_FAILED
+    action TEXT NOT NULL,
+
+    -- ID of the payment or payment batch the entry refers to.
+    entity_id TEXT NOT NULL,
+
+    before_status TEXT,
+    after_status TEXT,
+
+    -- Free-form context, e.g. an error message or the reason given by an operator.
+    details TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+, prev_hash TEXT, entry_hash TEXT);
+CREATE INDEX idx_audit_log_entity_id ON audit_log(entity_id);
+CREATE TABLE payment_tags (
+    payment_id TEXT NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (payment_id, tag)
+);
+CREATE INDEX idx_payment_tags_tag ON payment_tags(tag);
+CREATE TABLE reconciliation_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_name TEXT NOT NULL,
+
+    -- Balance reported by the Payment Receiver, in µT.
+    available INTEGER NOT NULL,
+    locked INTEGER NOT NULL,
+    unconfirmed INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+
+    -- Processor view: batches not in a final status and the sum of their payments, plus all confirmed payments.
+    in_flight_batch_count INTEGER NOT NULL,
+    in_flight_amount INTEGER NOT NULL,
+    confirmed_amount INTEGER NOT NULL,
+
+    -- How far the locked balance lies outside the range expected for the in-flight batches, in µT.
+    discrepancy INTEGER NOT NULL,
+
+    -- Statuses: OK, DISCREPANCY
+    status TEXT NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_reconciliation_reports_account_name ON reconciliation_reports(account_name, created_at);
+CREATE TABLE ledger_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- One journal per confirmed batch.
+    journal_id TEXT NOT NULL REFERENCES payment_batches(id),
+    -- Set for payout entries, NULL for fee entries.
+    payment_id TEXT REFERENCES payments(id),
+
+    -- Entry types: PAYOUT, NETWORK_FEE
+    entry_type TEXT NOT NULL,
+    -- Ledger accounts: hot_wallet:<account name>, recipient:<address>, network_fees
+    debit_account TEXT NOT NULL,
+    credit_account TEXT NOT NULL,
+    -- In µT.
+    amount INTEGER NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_ledger_entries_journal_id ON ledger_entries(journal_id);
+CREATE INDEX idx_ledger_entries_created_at ON ledger_entries(created_at);
+CREATE TABLE refunds (
+    refund_payment_id TEXT PRIMARY KEY NOT NULL REFERENCES payments(id),
+    original_payment_id TEXT NOT NULL REFERENCES payments(id),
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_refunds_original_payment_id ON refunds(original_payment_id);
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE accounts (
+    -- Lower-cased, like the keys of the configured accounts.
+    name TEXT PRIMARY KEY NOT NULL,
+
+    -- Name as given when the account was created.
+    display_name TEXT NOT NULL,
+
+    -- Hex encoded nonce and ciphertext of the view key, encrypted with ACCOUNTS_ENCRYPTION_KEY.
+    encrypted_view_key TEXT NOT NULL,
+
+    -- Hex encoded public spend key.
+    public_spend_key TEXT NOT NULL,
+
+    -- Disabled accounts are not loaded. Their existing payments and batches are kept.
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE signing_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id TEXT NOT NULL REFERENCES payment_batches(id),
+    step_index INTEGER NOT NULL,
+
+    -- NULL if the process could not be started, timed out or was terminated by a signal.
+    exit_code INTEGER,
+    success BOOLEAN NOT NULL,
+    timed_out BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Truncated to 64 KiB each.
+    stdout TEXT NOT NULL DEFAULT '',
+    stderr TEXT NOT NULL DEFAULT '',
+
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_signing_attempts_batch_id ON signing_attempts(batch_id);
+CREATE TABLE payment_groups (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE payment_group_payments (
+    payment_id TEXT PRIMARY KEY NOT NULL REFERENCES payments(id),
+    group_id TEXT NOT NULL REFERENCES payment_groups(id)
+);
+CREATE INDEX idx_payment_group_payments_group_id ON payment_group_payments(group_id);
+CREATE INDEX idx_payments_resubmitted_from ON payments(resubmitted_from);
+CREATE TABLE import_jobs (
+    id TEXT PRIMARY KEY NOT NULL,
+    status TEXT NOT NULL DEFAULT 'QUEUED',
+    format TEXT NOT NULL,
+    default_account_name TEXT,
+    -- JSON array of the accounts the submitting client may use, NULL for every account.
+    allowed_accounts TEXT,
+    -- The uploaded file, cleared once the job has finished.
+    content BLOB,
+    total_rows INTEGER NOT NULL,
+    processed_rows INTEGER NOT NULL DEFAULT 0,
+    created INTEGER NOT NULL DEFAULT 0,
+    duplicates INTEGER NOT NULL DEFAULT 0,
+    invalid INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP
+);
+CREATE INDEX idx_import_jobs_status ON import_jobs(status, created_at);
+CREATE TABLE import_job_rows (
+    job_id TEXT NOT NULL REFERENCES import_jobs(id),
+    row_number INTEGER NOT NULL,
+    client_id TEXT,
+    status TEXT NOT NULL,
+    payment_id TEXT REFERENCES payments(id),
+    error TEXT,
+    PRIMARY KEY (job_id, row_number)
+);
+CREATE TABLE IF NOT EXISTS "idempotency_keys" (
+    idempotency_key TEXT NOT NULL,
+
+    -- Keys are scoped to the endpoint they were used with.
+    request_path TEXT NOT NULL,
+
+    -- And to the API client that used them: the API key name, or `jwt:<subject>` for a JWT. Empty if no
+    -- authentication is configured.
+    client_name TEXT NOT NULL DEFAULT '',
+
+    -- Hex encoded SHA-256 of the request body.
+    request_hash TEXT NOT NULL,
+
+    -- Both NULL while the original request is still being processed.
+    response_status INTEGER,
+    response_body TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (idempotency_key, request_path, client_name)
+);
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
+CREATE TABLE superseded_pr_locks (
+    pr_idempotency_key TEXT PRIMARY KEY NOT NULL,
+    batch_id TEXT NOT NULL REFERENCES payment_batches(id),
+    account_name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_superseded_pr_locks_created_at ON superseded_pr_locks(created_at);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_payment_batches_status;
+DROP INDEX IF EXISTS idx_payments_status;
+DROP TABLE IF EXISTS payments;
+DROP TABLE IF EXISTS payment_batches;
@@ -0,0 +1 @@
+ALTER TABLE payment_batches DROP COLUMN intermediate_context_json;
@@ -0,0 +1 @@
+ALTER TABLE payment_batches DROP COLUMN next_retry_at;
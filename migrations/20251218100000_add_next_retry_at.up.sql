@@ -0,0 +1 @@
+ALTER TABLE payment_batches ADD COLUMN next_retry_at TIMESTAMP;
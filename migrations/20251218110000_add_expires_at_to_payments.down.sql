@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_payments_expires_at;
+
+ALTER TABLE payments DROP COLUMN expires_at;
@@ -0,0 +1,3 @@
+ALTER TABLE payments ADD COLUMN expires_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_payments_expires_at ON payments(expires_at);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_payments_status_priority;
+
+ALTER TABLE payments DROP COLUMN priority;
@@ -0,0 +1,4 @@
+-- 0 = LOW, 1 = NORMAL, 2 = HIGH
+ALTER TABLE payments ADD COLUMN priority INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_payments_status_priority ON payments(status, priority DESC, created_at);
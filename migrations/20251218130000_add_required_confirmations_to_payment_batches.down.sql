@@ -0,0 +1 @@
+ALTER TABLE payment_batches DROP COLUMN required_confirmations;
@@ -0,0 +1 @@
+ALTER TABLE payment_batches ADD COLUMN required_confirmations INTEGER;
@@ -0,0 +1,17 @@
+-- Outbox of domain events, written in the same transaction as the state change they describe.
+-- Consumers page through it by sequence number to resync after downtime.
+CREATE TABLE IF NOT EXISTS events (
+    -- Monotonically increasing sequence number, never reused.
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- Types: PAYMENT_CREATED, PAYMENT_EXPIRED, BATCH_CONFIRMED, BATCH_FAILED
+    event_type TEXT NOT NULL,
+
+    -- ID of the payment or payment batch the event refers to.
+    entity_id TEXT NOT NULL,
+
+    -- Event specific details as JSON.
+    payload TEXT NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,20 @@
+-- Responses of POST requests sent with an Idempotency-Key header, replayed when the request is retried.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    idempotency_key TEXT NOT NULL,
+
+    -- Keys are scoped to the endpoint they were used with.
+    request_path TEXT NOT NULL,
+
+    -- Hex encoded SHA-256 of the request body.
+    request_hash TEXT NOT NULL,
+
+    -- Both NULL while the original request is still being processed.
+    response_status INTEGER,
+    response_body TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (idempotency_key, request_path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at);
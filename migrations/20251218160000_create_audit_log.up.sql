@@ -0,0 +1,24 @@
+-- Durable trail of status changes and operator actions for compliance review.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- Who performed the action: 'system' for workers, 'api' for requests made through the HTTP API.
+    actor TEXT NOT NULL,
+
+    -- Types: PAYMENT_CREATED, PAYMENT_CANCELLED, PAYMENT_EXPIRED, BATCH_CREATED, BATCH_STATUS_CHANGED,
+    -- BATCH_RESOLVED_CONFIRMED, BATCH_RESOLVED_FAILED
+    action TEXT NOT NULL,
+
+    -- ID of the payment or payment batch the entry refers to.
+    entity_id TEXT NOT NULL,
+
+    before_status TEXT,
+    after_status TEXT,
+
+    -- Free-form context, e.g. an error message or the reason given by an operator.
+    details TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity_id ON audit_log(entity_id);
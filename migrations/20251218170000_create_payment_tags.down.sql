@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_payment_tags_tag;
+
+DROP TABLE IF EXISTS payment_tags;
@@ -0,0 +1,9 @@
+-- Free-form labels used to group payments, e.g. all payments of a payout run.
+CREATE TABLE IF NOT EXISTS payment_tags (
+    payment_id TEXT NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (payment_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_tags_tag ON payment_tags(tag);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_reconciliation_reports_account_name;
+
+DROP TABLE IF EXISTS reconciliation_reports;
@@ -0,0 +1,26 @@
+-- Periodic comparison of the Payment Receiver's balance of an account with the processor's view of its payments.
+CREATE TABLE IF NOT EXISTS reconciliation_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_name TEXT NOT NULL,
+
+    -- Balance reported by the Payment Receiver, in µT.
+    available INTEGER NOT NULL,
+    locked INTEGER NOT NULL,
+    unconfirmed INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+
+    -- Processor view: batches not in a final status and the sum of their payments, plus all confirmed payments.
+    in_flight_batch_count INTEGER NOT NULL,
+    in_flight_amount INTEGER NOT NULL,
+    confirmed_amount INTEGER NOT NULL,
+
+    -- How far the locked balance lies outside the range expected for the in-flight batches, in µT.
+    discrepancy INTEGER NOT NULL,
+
+    -- Statuses: OK, DISCREPANCY
+    status TEXT NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_reports_account_name ON reconciliation_reports(account_name, created_at);
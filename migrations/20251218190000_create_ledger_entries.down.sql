@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_ledger_entries_created_at;
+DROP INDEX IF EXISTS idx_ledger_entries_journal_id;
+
+DROP TABLE IF EXISTS ledger_entries;
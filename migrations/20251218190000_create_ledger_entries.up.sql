@@ -0,0 +1,23 @@
+-- Double-entry journal of confirmed payouts. Every row debits `debit_account` and credits `credit_account` with
+-- `amount`, so the entries of a journal always balance. Rows are only ever inserted.
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- One journal per confirmed batch.
+    journal_id TEXT NOT NULL REFERENCES payment_batches(id),
+    -- Set for payout entries, NULL for fee entries.
+    payment_id TEXT REFERENCES payments(id),
+
+    -- Entry types: PAYOUT, NETWORK_FEE
+    entry_type TEXT NOT NULL,
+    -- Ledger accounts: hot_wallet:<account name>, recipient:<address>, network_fees
+    debit_account TEXT NOT NULL,
+    credit_account TEXT NOT NULL,
+    -- In µT.
+    amount INTEGER NOT NULL,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_journal_id ON ledger_entries(journal_id);
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_created_at ON ledger_entries(created_at);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_refunds_original_payment_id;
+
+DROP TABLE IF EXISTS refunds;
+
+ALTER TABLE payments DROP COLUMN refund_address;
@@ -0,0 +1,12 @@
+-- Address a payment can be refunded to, supplied when the payment is created.
+ALTER TABLE payments ADD COLUMN refund_address TEXT;
+
+-- Links a refund, which is processed as a regular payment, to the payment it refunds.
+CREATE TABLE IF NOT EXISTS refunds (
+    refund_payment_id TEXT PRIMARY KEY NOT NULL REFERENCES payments(id),
+    original_payment_id TEXT NOT NULL REFERENCES payments(id),
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_refunds_original_payment_id ON refunds(original_payment_id);
@@ -0,0 +1 @@
+ALTER TABLE payment_batches DROP COLUMN pr_funds_released_at;
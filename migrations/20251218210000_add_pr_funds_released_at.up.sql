@@ -0,0 +1,2 @@
+-- Set once the Payment Receiver lock of a failed or cancelled batch has been released.
+ALTER TABLE payment_batches ADD COLUMN pr_funds_released_at TIMESTAMP;
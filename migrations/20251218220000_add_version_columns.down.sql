@@ -0,0 +1,2 @@
+ALTER TABLE payment_batches DROP COLUMN version;
+ALTER TABLE payments DROP COLUMN version;
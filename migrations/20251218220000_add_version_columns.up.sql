@@ -0,0 +1,3 @@
+-- Optimistic concurrency: incremented on every update of a row.
+ALTER TABLE payments ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE payment_batches ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,20 @@
+-- Payment receiver accounts created through the API, in addition to the accounts in the configuration.
+CREATE TABLE IF NOT EXISTS accounts (
+    -- Lower-cased, like the keys of the configured accounts.
+    name TEXT PRIMARY KEY NOT NULL,
+
+    -- Name as given when the account was created.
+    display_name TEXT NOT NULL,
+
+    -- Hex encoded nonce and ciphertext of the view key, encrypted with ACCOUNTS_ENCRYPTION_KEY.
+    encrypted_view_key TEXT NOT NULL,
+
+    -- Hex encoded public spend key.
+    public_spend_key TEXT NOT NULL,
+
+    -- Disabled accounts are not loaded. Their existing payments and batches are kept.
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
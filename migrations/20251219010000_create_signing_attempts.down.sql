@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_signing_attempts_batch_id;
+
+DROP TABLE IF EXISTS signing_attempts;
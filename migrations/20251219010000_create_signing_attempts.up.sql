@@ -0,0 +1,20 @@
+-- Output of every console wallet invocation that signed a batch step, for diagnosing failed signings.
+CREATE TABLE IF NOT EXISTS signing_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id TEXT NOT NULL REFERENCES payment_batches(id),
+    step_index INTEGER NOT NULL,
+
+    -- NULL if the process could not be started, timed out or was terminated by a signal.
+    exit_code INTEGER,
+    success BOOLEAN NOT NULL,
+    timed_out BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Truncated to 64 KiB each.
+    stdout TEXT NOT NULL DEFAULT '',
+    stderr TEXT NOT NULL DEFAULT '',
+
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_signing_attempts_batch_id ON signing_attempts(batch_id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_payment_group_payments_group_id;
+
+DROP TABLE IF EXISTS payment_group_payments;
+DROP TABLE IF EXISTS payment_groups;
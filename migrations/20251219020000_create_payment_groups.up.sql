@@ -0,0 +1,13 @@
+-- Payments created by a single bulk request that the batch creator may split into several batches.
+CREATE TABLE IF NOT EXISTS payment_groups (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS payment_group_payments (
+    payment_id TEXT PRIMARY KEY NOT NULL REFERENCES payments(id),
+    group_id TEXT NOT NULL REFERENCES payment_groups(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_group_payments_group_id ON payment_group_payments(group_id);
@@ -0,0 +1,2 @@
+ALTER TABLE payment_batches DROP COLUMN tip_height;
+ALTER TABLE payment_batches DROP COLUMN confirmations;
@@ -0,0 +1,3 @@
+-- Latest confirmation count observed by the confirmation checker, and the chain tip height it was observed at.
+ALTER TABLE payment_batches ADD COLUMN confirmations INTEGER;
+ALTER TABLE payment_batches ADD COLUMN tip_height INTEGER;
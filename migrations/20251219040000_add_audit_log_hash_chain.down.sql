@@ -0,0 +1,3 @@
+-- Drops the hash chain; `verify-audit-log` reports all entries as unchained afterwards.
+ALTER TABLE audit_log DROP COLUMN entry_hash;
+ALTER TABLE audit_log DROP COLUMN prev_hash;
@@ -0,0 +1,5 @@
+-- Hash chain over the audit log, so retroactive changes can be detected with `verify-audit-log`.
+-- entry_hash is the SHA-256 of the entry's contents and prev_hash, the entry_hash of the entry before it.
+-- Entries written before this migration have neither and are reported as unchained.
+ALTER TABLE audit_log ADD COLUMN prev_hash TEXT;
+ALTER TABLE audit_log ADD COLUMN entry_hash TEXT;
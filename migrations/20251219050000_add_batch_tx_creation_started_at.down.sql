@@ -0,0 +1 @@
+ALTER TABLE payment_batches DROP COLUMN tx_creation_started_at;
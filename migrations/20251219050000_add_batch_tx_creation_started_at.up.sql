@@ -0,0 +1,2 @@
+-- Set when the unsigned transaction creator first picks up a batch. Until then, payments can be appended to it.
+ALTER TABLE payment_batches ADD COLUMN tx_creation_started_at TIMESTAMP;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_payments_resubmitted_from;
+
+ALTER TABLE payments DROP COLUMN resubmitted_from;
@@ -0,0 +1,4 @@
+-- Links a payment created by resubmitting a failed payment to the payment it replaces.
+ALTER TABLE payments ADD COLUMN resubmitted_from TEXT REFERENCES payments(id);
+
+CREATE INDEX IF NOT EXISTS idx_payments_resubmitted_from ON payments(resubmitted_from);
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS import_job_rows;
+
+DROP INDEX IF EXISTS idx_import_jobs_status;
+
+DROP TABLE IF EXISTS import_jobs;
@@ -0,0 +1,33 @@
+-- Payment files submitted through POST /v1/import-jobs, imported in the background by the import_job_runner worker.
+CREATE TABLE IF NOT EXISTS import_jobs (
+    id TEXT PRIMARY KEY NOT NULL,
+    status TEXT NOT NULL DEFAULT 'QUEUED',
+    format TEXT NOT NULL,
+    default_account_name TEXT,
+    -- JSON array of the accounts the submitting client may use, NULL for every account.
+    allowed_accounts TEXT,
+    -- The uploaded file, cleared once the job has finished.
+    content BLOB,
+    total_rows INTEGER NOT NULL,
+    processed_rows INTEGER NOT NULL DEFAULT 0,
+    created INTEGER NOT NULL DEFAULT 0,
+    duplicates INTEGER NOT NULL DEFAULT 0,
+    invalid INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_jobs_status ON import_jobs(status, created_at);
+
+-- Outcome of each processed row of an import job.
+CREATE TABLE IF NOT EXISTS import_job_rows (
+    job_id TEXT NOT NULL REFERENCES import_jobs(id),
+    row_number INTEGER NOT NULL,
+    client_id TEXT,
+    status TEXT NOT NULL,
+    payment_id TEXT REFERENCES payments(id),
+    error TEXT,
+    PRIMARY KEY (job_id, row_number)
+);
@@ -0,0 +1,29 @@
+-- Keys used by several clients collapse into one; the oldest stored response is kept.
+CREATE TABLE idempotency_keys_old (
+    idempotency_key TEXT NOT NULL,
+
+    -- Keys are scoped to the endpoint they were used with.
+    request_path TEXT NOT NULL,
+
+    -- Hex encoded SHA-256 of the request body.
+    request_hash TEXT NOT NULL,
+
+    -- Both NULL while the original request is still being processed.
+    response_status INTEGER,
+    response_body TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (idempotency_key, request_path)
+);
+
+INSERT OR IGNORE INTO idempotency_keys_old (idempotency_key, request_path, request_hash, response_status, response_body, created_at)
+SELECT idempotency_key, request_path, request_hash, response_status, response_body, created_at
+FROM idempotency_keys
+ORDER BY created_at;
+
+DROP INDEX IF EXISTS idx_idempotency_keys_created_at;
+DROP TABLE idempotency_keys;
+ALTER TABLE idempotency_keys_old RENAME TO idempotency_keys;
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,33 @@
+-- Stored responses were only keyed by key and path, so a client reusing another client's Idempotency-Key received
+-- that client's response. SQLite cannot change a primary key in place, so the table is rebuilt.
+CREATE TABLE idempotency_keys_new (
+    idempotency_key TEXT NOT NULL,
+
+    -- Keys are scoped to the endpoint they were used with.
+    request_path TEXT NOT NULL,
+
+    -- And to the API client that used them: the API key name, or `jwt:<subject>` for a JWT. Empty if no
+    -- authentication is configured.
+    client_name TEXT NOT NULL DEFAULT '',
+
+    -- Hex encoded SHA-256 of the request body.
+    request_hash TEXT NOT NULL,
+
+    -- Both NULL while the original request is still being processed.
+    response_status INTEGER,
+    response_body TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (idempotency_key, request_path, client_name)
+);
+
+INSERT INTO idempotency_keys_new (idempotency_key, request_path, request_hash, response_status, response_body, created_at)
+SELECT idempotency_key, request_path, request_hash, response_status, response_body, created_at
+FROM idempotency_keys;
+
+DROP INDEX IF EXISTS idx_idempotency_keys_created_at;
+DROP TABLE idempotency_keys;
+ALTER TABLE idempotency_keys_new RENAME TO idempotency_keys;
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,4 @@
+-- Locks that were not released yet are left to expire.
+DROP INDEX IF EXISTS idx_superseded_pr_locks_created_at;
+
+DROP TABLE IF EXISTS superseded_pr_locks;
@@ -0,0 +1,10 @@
+-- Payment Receiver locks a batch gave up when its payments changed and it prepared its transaction anew under a new
+-- idempotency key. The maintenance worker releases them and deletes the row.
+CREATE TABLE IF NOT EXISTS superseded_pr_locks (
+    pr_idempotency_key TEXT PRIMARY KEY NOT NULL,
+    batch_id TEXT NOT NULL REFERENCES payment_batches(id),
+    account_name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_superseded_pr_locks_created_at ON superseded_pr_locks(created_at);
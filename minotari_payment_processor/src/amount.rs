@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    Decode, Encode, Sqlite, Type,
+    encode::IsNull,
+    error::BoxDynError,
+    sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+};
+use std::fmt;
+use tari_transaction_components::tari_amount::MicroMinotari;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("Amount must be positive")]
+    NotPositive,
+    #[error("Amount {0} exceeds the maximum of {max}", max = i64::MAX)]
+    TooLarge(u64),
+    #[error("Amount overflow")]
+    Overflow,
+}
+
+/// A positive payment amount in µT.
+///
+/// SQLite stores integers as `i64`, so the amount is limited to `1..=i64::MAX` and every value of this type
+/// round-trips through the database unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub struct PaymentAmount(MicroMinotari);
+
+impl PaymentAmount {
+    pub fn as_micro_minotari(&self) -> MicroMinotari {
+        self.0
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0.as_u64()
+    }
+
+    pub fn as_i64(&self) -> i64 {
+        // Cannot truncate, the constructors reject anything above i64::MAX.
+        self.0.as_u64() as i64
+    }
+
+    /// Sums the amounts, failing instead of wrapping on overflow.
+    pub fn checked_sum<I: IntoIterator<Item = Self>>(amounts: I) -> Result<u64, AmountError> {
+        amounts.into_iter().try_fold(0u64, |total, amount| {
+            total
+                .checked_add(amount.as_u64())
+                .filter(|total| *total <= i64::MAX as u64)
+                .ok_or(AmountError::Overflow)
+        })
+    }
+}
+
+impl TryFrom<u64> for PaymentAmount {
+    type Error = AmountError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        if value == 0 {
+            return Err(AmountError::NotPositive);
+        }
+        if value > i64::MAX as u64 {
+            return Err(AmountError::TooLarge(value));
+        }
+        Ok(PaymentAmount(MicroMinotari::from(value)))
+    }
+}
+
+impl TryFrom<i64> for PaymentAmount {
+    type Error = AmountError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        if value <= 0 {
+            return Err(AmountError::NotPositive);
+        }
+        Self::try_from(value as u64)
+    }
+}
+
+impl From<PaymentAmount> for u64 {
+    fn from(amount: PaymentAmount) -> Self {
+        amount.as_u64()
+    }
+}
+
+impl From<PaymentAmount> for MicroMinotari {
+    fn from(amount: PaymentAmount) -> Self {
+        amount.0
+    }
+}
+
+impl fmt::Display for PaymentAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_u64())
+    }
+}
+
+impl Type<Sqlite> for PaymentAmount {
+    fn type_info() -> SqliteTypeInfo {
+        <i64 as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <i64 as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for PaymentAmount {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        <i64 as Encode<'q, Sqlite>>::encode_by_ref(&self.as_i64(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for PaymentAmount {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <i64 as Decode<'r, Sqlite>>::decode(value)?;
+        Ok(PaymentAmount::try_from(value)?)
+    }
+}
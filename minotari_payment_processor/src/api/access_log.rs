@@ -0,0 +1,134 @@
+use axum::{
+    body::{Body, Bytes, HttpBody, to_bytes},
+    extract::{MatchedPath, Request},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::{Level, debug, info, log_enabled};
+use serde_json::Value;
+use std::time::Instant;
+
+use crate::{
+    api::error::ApiError,
+    utils::log::{mask_amount, mask_string},
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api::access";
+
+/// Larger bodies are not logged.
+const MAX_LOGGED_BODY_BYTES: u64 = 64 * 1024;
+/// Routes whose bodies are never logged, e.g. because they are large or not JSON.
+const BODY_LOGGING_DISABLED_ROUTES: &[&str] = &["/v1/payments/import", "/v1/payments/export", "/v1/ledger/export"];
+/// JSON fields logged as `mask_string` output.
+const MASKED_STRING_FIELDS: &[&str] = &["recipient_address", "address", "view_key", "public_spend_key"];
+/// JSON fields logged as `mask_amount` output.
+const MASKED_AMOUNT_FIELDS: &[&str] = &["amount", "total_amount", "amount_to_lock", "fee"];
+
+/// Logs every request with its method, path, status and latency.
+///
+/// With DEBUG enabled for the `minotari_payment_processor::api::access` target, JSON request bodies and the
+/// bodies of error responses are logged too, with recipient addresses and amounts masked.
+pub async fn access_log_layer(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let log_bodies = log_enabled!(target: LOG_TARGET, Level::Debug)
+        && !request
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|route| BODY_LOGGING_DISABLED_ROUTES.contains(&route.as_str()));
+
+    let (request, request_body) = if log_bodies && is_small_json(request.headers()) {
+        let (parts, body) = request.into_parts();
+        let bytes = match to_bytes(body, MAX_LOGGED_BODY_BYTES as usize).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return ApiError::BadRequest(format!("Failed to read request body: {}", e)).into_response();
+            },
+        };
+        let masked = mask_body(&bytes);
+        (Request::from_parts(parts, Body::from(bytes)), Some(masked))
+    } else {
+        (request, None)
+    };
+
+    let response = next.run(request).await;
+    let status = response.status();
+    let latency_ms = start.elapsed().as_millis();
+
+    info!(target: LOG_TARGET, "{} {} {} {}ms", method, path, status.as_u16(), latency_ms);
+
+    if let Some(request_body) = request_body {
+        debug!(target: LOG_TARGET, "{} {} request body: {}", method, path, request_body);
+    }
+
+    let small_response = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_LOGGED_BODY_BYTES);
+    if !log_bodies || !(status.is_client_error() || status.is_server_error()) || !small_response {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    match to_bytes(body, MAX_LOGGED_BODY_BYTES as usize).await {
+        Ok(bytes) => {
+            debug!(
+                target: LOG_TARGET,
+                "{} {} response body: {}",
+                method,
+                path,
+                mask_body(&bytes)
+            );
+            Response::from_parts(parts, Body::from(bytes))
+        },
+        Err(e) => ApiError::InternalServerError(format!("Failed to read response body: {}", e)).into_response(),
+    }
+}
+
+fn is_small_json(headers: &HeaderMap) -> bool {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    let is_small = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok())
+        .is_some_and(|len| len <= MAX_LOGGED_BODY_BYTES);
+    is_json && is_small
+}
+
+fn mask_body(bytes: &Bytes) -> String {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut json) => {
+            mask_fields(&mut json);
+            json.to_string()
+        },
+        Err(_) => format!("<{} bytes, not JSON>", bytes.len()),
+    }
+}
+
+fn mask_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if MASKED_STRING_FIELDS.contains(&key.as_str()) {
+                    if let Some(s) = field.as_str() {
+                        *field = Value::String(mask_string(s));
+                    }
+                } else if MASKED_AMOUNT_FIELDS.contains(&key.as_str()) {
+                    if let Some(amount) = field.as_u64() {
+                        *field = Value::String(mask_amount(amount));
+                    }
+                } else {
+                    mask_fields(field);
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(mask_fields),
+        _ => {},
+    }
+}
@@ -0,0 +1,350 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    api::{API_ACTOR, AppState, error::ApiError},
+    config::{derive_address, parse_public_spend_key, parse_view_key},
+    db::{
+        account::StoredAccount,
+        audit_log::{AuditAction, AuditEntry},
+        payment::Payment,
+        payment_batch::PaymentBatch,
+    },
+    encryption::EncryptionKey,
+    payment_receiver,
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+const MAX_ACCOUNT_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LastConfirmedBatch {
+    pub batch_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mined_height: Option<i64>,
+    pub confirmed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountResponse {
+    pub name: String,
+    /// One-sided Tari address derived from the configured view and spend keys.
+    pub address: String,
+    /// Number of RECEIVED payments waiting to be batched.
+    pub pending_payment_count: i64,
+    /// Sum of those payments, in µT.
+    pub pending_amount: i64,
+    /// Number of batches that have not reached a final status.
+    pub in_flight_batch_count: i64,
+    /// Sum of the payments in those batches, in µT.
+    pub in_flight_amount: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_confirmed_batch: Option<LastConfirmedBatch>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/accounts",
+    responses(
+        (status = 200, description = "Configured accounts with their processing statistics", body = Vec<AccountResponse>),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_accounts(State(state): State<AppState>) -> Result<Json<Vec<AccountResponse>>, ApiError> {
+    let configured_accounts = state.env.accounts.current();
+    let mut accounts: Vec<_> = configured_accounts.values().collect();
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut conn = state.db_pool.acquire().await?;
+
+    let mut response = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let pending = Payment::pending_summary(&mut conn, &account.name).await?;
+        let in_flight = PaymentBatch::in_flight_summary(&mut conn, &account.name).await?;
+        let last_confirmed_batch = PaymentBatch::find_last_confirmed(&mut conn, &account.name)
+            .await?
+            .map(|batch| LastConfirmedBatch {
+                batch_id: batch.id,
+                mined_height: batch.mined_height,
+                confirmed_at: batch.updated_at,
+            });
+
+        response.push(AccountResponse {
+            name: account.name.clone(),
+            address: account.address.to_base58(),
+            pending_payment_count: pending.payment_count,
+            pending_amount: pending.total_amount,
+            in_flight_batch_count: in_flight.batch_count,
+            in_flight_amount: in_flight.total_amount,
+            last_confirmed_batch,
+        });
+    }
+
+    Ok(Json(response))
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountBalanceResponse {
+    pub account_name: String,
+    /// Spendable balance reported by the Payment Receiver, in µT.
+    pub available: i64,
+    /// Balance locked for pending transactions, in µT.
+    pub locked: i64,
+    /// Incoming balance that is not confirmed yet, in µT.
+    pub unconfirmed: i64,
+    pub total: i64,
+    /// Number of batches of this account that have not reached a final status.
+    pub in_flight_batch_count: i64,
+    /// Sum of the payments in those batches, in µT.
+    pub in_flight_amount: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/accounts/{name}/balance",
+    params(
+        ("name" = String, Path, description = "Name of a configured account")
+    ),
+    responses(
+        (status = 200, description = "Account balance retrieved successfully", body = AccountBalanceResponse),
+        (status = 404, description = "Account not found in configuration", body = ApiError),
+        (status = 502, description = "Payment Receiver request failed", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_account_balance(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<AccountBalanceResponse>, ApiError> {
+    let accounts = state.env.accounts.current();
+    let account = accounts
+        .get(&name.to_lowercase())
+        .ok_or_else(|| ApiError::NotFound(format!("Account '{}' not found in configuration", name)))?;
+
+    let balance = payment_receiver::get_balance(&state.client_config, &state.env.network_retry, &account.name)
+        .await
+        .map_err(|e| ApiError::BadGateway(format!("Failed to fetch balance from Payment Receiver: {}", e)))?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    let in_flight = PaymentBatch::in_flight_summary(&mut conn, &account.name).await?;
+
+    Ok(Json(AccountBalanceResponse {
+        account_name: account.name.clone(),
+        available: balance.available,
+        locked: balance.locked,
+        unconfirmed: balance.unconfirmed,
+        total: balance.total,
+        in_flight_batch_count: in_flight.batch_count,
+        in_flight_amount: in_flight.total_amount,
+    }))
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateAccountRequest {
+    /// Letters, digits, `-` and `_`. Names are case-insensitive and must not be used by another account.
+    pub name: String,
+    /// Hex encoded private view key. Stored encrypted.
+    pub view_key: String,
+    /// Hex encoded public spend key.
+    pub public_spend_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StoredAccountResponse {
+    pub name: String,
+    /// One-sided Tari address derived from the view and spend keys.
+    pub address: String,
+    /// Whether the account is loaded. Disabled accounts keep their payments and batches.
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/accounts",
+    request_body = CreateAccountRequest,
+    responses(
+        (status = 201, description = "Account created and loaded", body = StoredAccountResponse),
+        (status = 400, description = "Bad request (Invalid name or keys)", body = ApiError),
+        (status = 403, description = "Runtime account management is disabled (ACCOUNTS_ENCRYPTION_KEY not set)", body = ApiError),
+        (status = 409, description = "An account with this name already exists", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_create_account(
+    State(state): State<AppState>,
+    Json(request): Json<CreateAccountRequest>,
+) -> Result<(StatusCode, Json<StoredAccountResponse>), ApiError> {
+    let encryption_key = encryption_key(&state)?;
+    validate_account_name(&request.name)?;
+    let view_key = parse_view_key(request.view_key.trim())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid view_key: {}", e)))?;
+    let public_spend_key = parse_public_spend_key(request.public_spend_key.trim())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid public_spend_key: {}", e)))?;
+    let address = derive_address(&view_key, &public_spend_key, state.env.tari_network)
+        .map_err(|e| ApiError::BadRequest(format!("Failed to derive address: {}", e)))?;
+
+    if state.env.accounts.current().contains_key(&request.name.to_lowercase()) {
+        return Err(ApiError::Conflict(format!("Account '{}' already exists", request.name)));
+    }
+
+    let encrypted_view_key = encryption_key
+        .encrypt(request.view_key.trim().as_bytes())
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+
+    let mut tx = state.db_writer.begin().await?;
+    if StoredAccount::find_by_name(&mut tx, &request.name).await?.is_some() {
+        return Err(ApiError::Conflict(format!("Account '{}' already exists", request.name)));
+    }
+    let stored = StoredAccount::create(
+        &mut tx,
+        &request.name,
+        &encrypted_view_key,
+        request.public_spend_key.trim(),
+    )
+    .await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::AccountCreated,
+        &stored.name,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    info!(target: LOG_TARGET, "Account '{}' created via the API.", stored.display_name);
+    reload_stored_accounts(&state).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(StoredAccountResponse {
+            name: stored.display_name,
+            address: address.to_base58(),
+            enabled: stored.enabled,
+            created_at: stored.created_at,
+            updated_at: stored.updated_at,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/accounts/{name}/disable",
+    params(
+        ("name" = String, Path, description = "Name of an account created through the API")
+    ),
+    responses(
+        (status = 200, description = "Account disabled. New payments for it are rejected.", body = StoredAccountResponse),
+        (status = 403, description = "Runtime account management is disabled (ACCOUNTS_ENCRYPTION_KEY not set)", body = ApiError),
+        (status = 404, description = "No account of this name was created through the API", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_disable_account(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<StoredAccountResponse>, ApiError> {
+    set_account_enabled(&state, &name, false).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/accounts/{name}/enable",
+    params(
+        ("name" = String, Path, description = "Name of an account created through the API")
+    ),
+    responses(
+        (status = 200, description = "Account enabled and loaded", body = StoredAccountResponse),
+        (status = 403, description = "Runtime account management is disabled (ACCOUNTS_ENCRYPTION_KEY not set)", body = ApiError),
+        (status = 404, description = "No account of this name was created through the API", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_enable_account(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<StoredAccountResponse>, ApiError> {
+    set_account_enabled(&state, &name, true).await
+}
+
+async fn set_account_enabled(
+    state: &AppState,
+    name: &str,
+    enabled: bool,
+) -> Result<Json<StoredAccountResponse>, ApiError> {
+    let encryption_key = encryption_key(state)?;
+
+    let mut tx = state.db_writer.begin().await?;
+    let stored = StoredAccount::set_enabled(&mut tx, name, enabled)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Account '{}' was not created through the API", name)))?;
+    let action = if enabled {
+        AuditAction::AccountEnabled
+    } else {
+        AuditAction::AccountDisabled
+    };
+    AuditEntry::record(&mut tx, API_ACTOR, action, &stored.name, None, None, None).await?;
+    tx.commit().await?;
+
+    info!(
+        target: LOG_TARGET,
+        "Account '{}' {} via the API.",
+        stored.display_name,
+        if enabled { "enabled" } else { "disabled" }
+    );
+    reload_stored_accounts(state).await?;
+
+    let account = state
+        .env
+        .account_from_stored(&stored, encryption_key)
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to load account: {:#}", e)))?;
+    Ok(Json(StoredAccountResponse {
+        name: stored.display_name,
+        address: account.address.to_base58(),
+        enabled: stored.enabled,
+        created_at: stored.created_at,
+        updated_at: stored.updated_at,
+    }))
+}
+
+fn encryption_key(state: &AppState) -> Result<&EncryptionKey, ApiError> {
+    state.env.accounts_encryption_key.as_ref().ok_or_else(|| {
+        ApiError::Forbidden("Runtime account management is disabled. Set ACCOUNTS_ENCRYPTION_KEY to enable it.".into())
+    })
+}
+
+fn validate_account_name(name: &str) -> Result<(), ApiError> {
+    if name.is_empty() || name.len() > MAX_ACCOUNT_NAME_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "Account name must have 1 to {} characters",
+            MAX_ACCOUNT_NAME_LENGTH
+        )));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(ApiError::BadRequest(
+            "Account name may only contain letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn reload_stored_accounts(state: &AppState) -> Result<(), ApiError> {
+    let mut conn = state.db_pool.acquire().await?;
+    state
+        .env
+        .load_stored_accounts(&mut conn)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to load stored accounts: {:#}", e)))?;
+    Ok(())
+}
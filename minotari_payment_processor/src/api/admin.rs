@@ -0,0 +1,827 @@
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::stream;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tempfile::NamedTempFile;
+use tokio::io::AsyncReadExt;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::{
+        API_ACTOR, AppState, DbWriter,
+        error::{ApiError, ValidationErrors},
+    },
+    db::{
+        self,
+        audit_log::{AuditAction, AuditEntry},
+        payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus, StepPayload},
+        setting::ProcessingPause,
+        signing_attempt::SigningAttempt,
+    },
+    workers::transaction_signer,
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+const DEFAULT_BATCH_LIST_LIMIT: i64 = 100;
+const MAX_BATCH_LIST_LIMIT: i64 = 1000;
+const DEFAULT_STUCK_AFTER_SECS: i64 = 60 * 60;
+/// Bytes read from the backup file per chunk of the response body.
+const BACKUP_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct BatchListQuery {
+    pub status: Option<PaymentBatchStatus>,
+    pub account_name: Option<String>,
+    /// Maximum number of batches to return. Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct StuckBatchesQuery {
+    /// Batches that have not changed for this many seconds are reported. Defaults to 3600.
+    pub older_than_secs: Option<i64>,
+    /// Maximum number of batches to return. Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PaymentBatchResponse {
+    pub batch_id: String,
+    pub account_name: String,
+    pub status: PaymentBatchStatus,
+    pub retry_count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<PaymentBatch> for PaymentBatchResponse {
+    fn from(batch: PaymentBatch) -> Self {
+        PaymentBatchResponse {
+            batch_id: batch.id,
+            account_name: batch.account_name,
+            status: batch.status,
+            retry_count: batch.retry_count,
+            error_message: batch.error_message,
+            created_at: batch.created_at,
+            updated_at: batch.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReloadAccountsResponse {
+    /// Names of the accounts configured after the reload, sorted.
+    pub accounts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PauseStatusResponse {
+    /// Whether processing is paused for all accounts.
+    pub paused: bool,
+    /// Lower-cased names of the individually paused accounts, sorted.
+    pub paused_accounts: Vec<String>,
+}
+
+impl From<ProcessingPause> for PauseStatusResponse {
+    fn from(pause: ProcessingPause) -> Self {
+        PauseStatusResponse {
+            paused: pause.global,
+            paused_accounts: pause.accounts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SigningAttemptResponse {
+    pub step_index: i64,
+    /// `None` if the console wallet could not be started, timed out or was terminated by a signal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    pub success: bool,
+    pub timed_out: bool,
+    /// Truncated to 64 KiB.
+    pub stdout: String,
+    /// Truncated to 64 KiB.
+    pub stderr: String,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SigningAttempt> for SigningAttemptResponse {
+    fn from(attempt: SigningAttempt) -> Self {
+        SigningAttemptResponse {
+            step_index: attempt.step_index,
+            exit_code: attempt.exit_code,
+            success: attempt.success,
+            timed_out: attempt.timed_out,
+            stdout: attempt.stdout,
+            stderr: attempt.stderr,
+            duration_ms: attempt.duration_ms,
+            created_at: attempt.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ResolveFailedRequest {
+    /// Reason stored on the batch and its payments.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ForceConfirmRequest {
+    /// Height of the block that mined the batch's transaction, as verified by the operator. At most `i64::MAX`.
+    pub mined_height: u64,
+    /// Hex encoded hash of that block's header.
+    pub mined_header_hash: String,
+    /// Why the batch is confirmed by hand. Recorded in the audit log.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ForceFailRequest {
+    /// Reason stored on the batch and its payments, and recorded in the audit log.
+    pub reason: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/payment-batches",
+    params(BatchListQuery),
+    responses(
+        (status = 200, description = "Batches, newest first", body = Vec<PaymentBatchResponse>),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_batches(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<BatchListQuery>,
+) -> Result<Json<Vec<PaymentBatchResponse>>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_BATCH_LIST_LIMIT)
+        .clamp(1, MAX_BATCH_LIST_LIMIT);
+    let batches = PaymentBatch::list(&mut conn, query.status, query.account_name.as_deref(), limit).await?;
+
+    Ok(Json(batches.into_iter().map(PaymentBatchResponse::from).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/payment-batches/stuck",
+    params(StuckBatchesQuery),
+    responses(
+        (status = 200, description = "In-flight batches that have not changed for a while, least recently updated first", body = Vec<PaymentBatchResponse>),
+        (status = 400, description = "Bad request (Negative or too large older_than_secs)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_stuck_batches(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<StuckBatchesQuery>,
+) -> Result<Json<Vec<PaymentBatchResponse>>, ApiError> {
+    let older_than_secs = query.older_than_secs.unwrap_or(DEFAULT_STUCK_AFTER_SECS);
+    if older_than_secs < 0 {
+        return Err(ApiError::BadRequest("older_than_secs must not be negative".to_string()));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_BATCH_LIST_LIMIT)
+        .clamp(1, MAX_BATCH_LIST_LIMIT);
+
+    let mut conn = db_pool.acquire().await?;
+    let updated_before = TimeDelta::try_seconds(older_than_secs)
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| ApiError::BadRequest("older_than_secs is too large".to_string()))?;
+    let batches = PaymentBatch::find_stuck(&mut conn, updated_before, limit).await?;
+
+    Ok(Json(batches.into_iter().map(PaymentBatchResponse::from).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/payment-batches/needs-review",
+    responses(
+        (status = 200, description = "Batches awaiting manual review", body = Vec<PaymentBatchResponse>),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_batches_needing_review(
+    State(db_pool): State<SqlitePool>,
+) -> Result<Json<Vec<PaymentBatchResponse>>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    let batches = PaymentBatch::find_by_status(&mut conn, PaymentBatchStatus::NeedsReview).await?;
+
+    Ok(Json(batches.into_iter().map(PaymentBatchResponse::from).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/payment-batches/{batch_id}/resolve-confirmed",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    responses(
+        (status = 200, description = "Batch and its payments marked as confirmed", body = PaymentBatchResponse),
+        (status = 400, description = "Bad request (Batch is not awaiting review)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "Batch changed its status concurrently", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_resolve_batch_confirmed(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Path(batch_id): Path<String>,
+) -> Result<Json<PaymentBatchResponse>, ApiError> {
+    let mut tx = db_pool.begin().await?;
+
+    ensure_needs_review(&mut tx, &batch_id).await?;
+    PaymentBatch::resolve_as_confirmed(&mut tx, &batch_id).await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::BatchResolvedConfirmed,
+        &batch_id,
+        Some(&PaymentBatchStatus::NeedsReview.to_string()),
+        Some(&PaymentBatchStatus::Confirmed.to_string()),
+        None,
+    )
+    .await?;
+    let batch = find_batch(&mut tx, &batch_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(PaymentBatchResponse::from(batch)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/payment-batches/{batch_id}/resolve-failed",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    request_body = ResolveFailedRequest,
+    responses(
+        (status = 200, description = "Batch and its payments marked as failed", body = PaymentBatchResponse),
+        (status = 400, description = "Bad request (Batch is not awaiting review)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "Batch changed its status concurrently", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_resolve_batch_failed(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Path(batch_id): Path<String>,
+    Json(request): Json<ResolveFailedRequest>,
+) -> Result<Json<PaymentBatchResponse>, ApiError> {
+    if request.reason.trim().is_empty() {
+        return Err(ApiError::BadRequest("Reason must not be empty".to_string()));
+    }
+
+    let mut tx = db_pool.begin().await?;
+
+    ensure_needs_review(&mut tx, &batch_id).await?;
+    PaymentBatch::update_to_failed(&mut tx, &batch_id, &request.reason).await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::BatchResolvedFailed,
+        &batch_id,
+        Some(&PaymentBatchStatus::NeedsReview.to_string()),
+        Some(&PaymentBatchStatus::Failed.to_string()),
+        Some(&request.reason),
+    )
+    .await?;
+    let batch = find_batch(&mut tx, &batch_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(PaymentBatchResponse::from(batch)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/payment-batches/{batch_id}/force-confirm",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    request_body = ForceConfirmRequest,
+    responses(
+        (status = 200, description = "Batch and its payments marked as confirmed at the given block", body = PaymentBatchResponse),
+        (status = 400, description = "Bad request (Batch was not broadcast or is not stuck)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "Batch changed concurrently", body = ApiError),
+        (status = 422, description = "Invalid block or empty reason", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_force_confirm_batch(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Path(batch_id): Path<String>,
+    Json(request): Json<ForceConfirmRequest>,
+) -> Result<Json<PaymentBatchResponse>, ApiError> {
+    let mut errors = ValidationErrors::default();
+    if i64::try_from(request.mined_height).is_err() {
+        errors.add("mined_height", format!("Must be at most {}", i64::MAX));
+    }
+    let mined_header_hash = errors.check("mined_header_hash", parse_header_hash(&request.mined_header_hash));
+    if request.reason.trim().is_empty() {
+        errors.add("reason", "Reason must not be empty");
+    }
+    errors.into_result()?;
+    let Some(mined_header_hash) = mined_header_hash else {
+        unreachable!("validation failures are returned above");
+    };
+
+    let mut tx = db_pool.begin().await?;
+
+    let batch = find_forceable(&mut tx, &batch_id).await?;
+    if !(batch.status == PaymentBatchStatus::NeedsReview || batch.status.is_post_broadcast()) {
+        return Err(ApiError::BadRequest(format!(
+            "Batch is in status {} and was not broadcast",
+            batch.status
+        )));
+    }
+    PaymentBatch::force_to_confirmed(
+        &mut tx,
+        &batch_id,
+        batch.version,
+        request.mined_height,
+        &mined_header_hash,
+        &request.reason,
+    )
+    .await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::BatchForceConfirmed,
+        &batch_id,
+        Some(&batch.status.to_string()),
+        Some(&PaymentBatchStatus::Confirmed.to_string()),
+        Some(&request.reason),
+    )
+    .await?;
+    let batch = find_batch(&mut tx, &batch_id).await?;
+
+    tx.commit().await?;
+
+    info!(
+        target: LOG_TARGET,
+        "Batch {} force-confirmed at height {}: {}", batch_id, request.mined_height, request.reason
+    );
+    Ok(Json(PaymentBatchResponse::from(batch)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/payment-batches/{batch_id}/force-fail",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    request_body = ForceFailRequest,
+    responses(
+        (status = 200, description = "Batch and its payments marked as failed", body = PaymentBatchResponse),
+        (status = 400, description = "Bad request (Batch is final or not stuck)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "Batch changed concurrently", body = ApiError),
+        (status = 422, description = "Empty reason", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_force_fail_batch(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Path(batch_id): Path<String>,
+    Json(request): Json<ForceFailRequest>,
+) -> Result<Json<PaymentBatchResponse>, ApiError> {
+    if request.reason.trim().is_empty() {
+        return Err(ApiError::field("reason", "Reason must not be empty"));
+    }
+
+    let mut tx = db_pool.begin().await?;
+
+    let batch = find_forceable(&mut tx, &batch_id).await?;
+    PaymentBatch::force_to_failed(&mut tx, &batch_id, batch.version, &request.reason).await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::BatchForceFailed,
+        &batch_id,
+        Some(&batch.status.to_string()),
+        Some(&PaymentBatchStatus::Failed.to_string()),
+        Some(&request.reason),
+    )
+    .await?;
+    let batch = find_batch(&mut tx, &batch_id).await?;
+
+    tx.commit().await?;
+
+    info!(target: LOG_TARGET, "Batch {} force-failed: {}", batch_id, request.reason);
+    Ok(Json(PaymentBatchResponse::from(batch)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/payment-batches/{batch_id}/signing-attempts",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    responses(
+        (status = 200, description = "Console wallet invocations that signed steps of the batch, oldest first", body = Vec<SigningAttemptResponse>),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_signing_attempts(
+    State(db_pool): State<SqlitePool>,
+    Path(batch_id): Path<String>,
+) -> Result<Json<Vec<SigningAttemptResponse>>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    find_batch(&mut conn, &batch_id).await?;
+    let attempts = SigningAttempt::find_by_batch_id(&mut conn, &batch_id).await?;
+
+    Ok(Json(attempts.into_iter().map(SigningAttemptResponse::from).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payment-batches/{batch_id}/unsigned",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    responses(
+        (status = 200, description = "The batch's unsigned BatchPayload, to be signed on an offline machine", content_type = "application/json", body = String),
+        (status = 400, description = "Bad request (Batch is not awaiting signature)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_unsigned_batch(
+    State(db_pool): State<SqlitePool>,
+    Path(batch_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    let batch = find_awaiting_signature(&mut conn, &batch_id).await?;
+    let unsigned_tx_json = batch
+        .unsigned_tx_json
+        .ok_or_else(|| ApiError::InternalServerError("Batch has no unsigned transaction".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"unsigned-{}.json\"", batch.id),
+            ),
+        ],
+        unsigned_tx_json,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payment-batches/{batch_id}/signed",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    request_body(content = String, description = "The BatchPayload from `GET /v1/payment-batches/{batch_id}/unsigned`, with every step signed", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Signed transactions stored, the batch is awaiting broadcast", body = PaymentBatchResponse),
+        (status = 400, description = "Bad request (Batch is not awaiting signature)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "Batch changed its status concurrently", body = ApiError),
+        (status = 422, description = "The steps do not match the batch's unsigned transactions or are not signed", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_upload_signed_batch(
+    State(state): State<AppState>,
+    Path(batch_id): Path<String>,
+    Json(signed): Json<BatchPayload>,
+) -> Result<Json<PaymentBatchResponse>, ApiError> {
+    let mut tx = state.db_writer.begin().await?;
+
+    let batch = find_awaiting_signature(&mut tx, &batch_id).await?;
+    let unsigned_tx_json = batch
+        .unsigned_tx_json
+        .as_deref()
+        .ok_or_else(|| ApiError::InternalServerError("Batch has no unsigned transaction".to_string()))?;
+    let mut payload =
+        BatchPayload::from_json(unsigned_tx_json).map_err(|e| ApiError::InternalServerError(format!("{:#}", e)))?;
+
+    // Only the signatures are taken from the upload. Everything else, e.g. what the broadcaster checks the signed
+    // transactions against, stays as the batch prepared it.
+    if signed.steps.len() != payload.steps.len() {
+        return Err(ApiError::field(
+            "steps",
+            format!("Expected {} steps, got {}", payload.steps.len(), signed.steps.len()),
+        ));
+    }
+    let mut errors = ValidationErrors::default();
+    for (i, (step, signed_step)) in payload.steps.iter_mut().zip(signed.steps).enumerate() {
+        if signed_step.tx_id != step.tx_id {
+            errors.add(
+                format!("steps[{}].tx_id", i),
+                format!("Expected tx_id {}, got {}", step.tx_id, signed_step.tx_id),
+            );
+            continue;
+        }
+        if !matches!(signed_step.payload, StepPayload::Signed(_)) {
+            errors.add(format!("steps[{}].payload", i), "Step is not signed");
+            continue;
+        }
+        step.payload = signed_step.payload;
+        if let Err(e) = step.transaction() {
+            errors.add(format!("steps[{}].payload", i), format!("{:#}", e));
+        }
+    }
+    errors.into_result()?;
+
+    PaymentBatch::update_to_signing_in_progress(&mut tx, &batch_id, batch.version).await?;
+    transaction_signer::store_signed_payload(&mut tx, &batch, &payload)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("{:#}", e)))?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::BatchSignatureUploaded,
+        &batch_id,
+        Some(&PaymentBatchStatus::AwaitingSignature.to_string()),
+        Some(&PaymentBatchStatus::AwaitingBroadcast.to_string()),
+        None,
+    )
+    .await?;
+    let batch = find_batch(&mut tx, &batch_id).await?;
+
+    tx.commit().await?;
+    state.wakeups.broadcaster.notify_one();
+
+    Ok(Json(PaymentBatchResponse::from(batch)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/reload-accounts",
+    responses(
+        (status = 200, description = "Accounts reloaded from the configuration", body = ReloadAccountsResponse),
+        (status = 500, description = "Configuration could not be loaded. The previous accounts stay active.", body = ApiError)
+    )
+)]
+pub async fn api_reload_accounts(State(state): State<AppState>) -> Result<Json<ReloadAccountsResponse>, ApiError> {
+    let env = state.env.clone();
+    // Resolving secrets may run external commands.
+    tokio::task::spawn_blocking(move || env.reload_accounts())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Account reload task failed: {}", e)))?
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to reload accounts: {:#}", e)))?;
+
+    let mut accounts: Vec<_> = state.env.accounts.current().values().map(|a| a.name.clone()).collect();
+    accounts.sort();
+    info!(target: LOG_TARGET, "Reloaded {} accounts via the API.", accounts.len());
+
+    Ok(Json(ReloadAccountsResponse { accounts }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/backup",
+    responses(
+        (status = 200, description = "Consistent copy of the SQLite database, taken with `VACUUM INTO`", content_type = "application/vnd.sqlite3", body = Vec<u8>),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_backup_database(State(db_pool): State<SqlitePool>) -> Result<Response, ApiError> {
+    // The copy is written to a temporary file first, which is removed once the response has been sent.
+    let backup_file = NamedTempFile::new()
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to create the backup file: {}", e)))?;
+    db::backup(&db_pool, backup_file.path())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to back up the database: {:#}", e)))?;
+
+    let file = tokio::fs::File::open(backup_file.path())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to open the backup file: {}", e)))?;
+    let size = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to read the backup file: {}", e)))?
+        .len();
+    info!(target: LOG_TARGET, "Streaming a database backup of {} bytes via the API.", size);
+
+    let chunks = stream::try_unfold((file, backup_file), |(mut file, backup_file)| async move {
+        let mut chunk = vec![0u8; BACKUP_CHUNK_SIZE];
+        let read = file.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok::<_, std::io::Error>(None);
+        }
+        chunk.truncate(read);
+        Ok(Some((chunk, (file, backup_file))))
+    });
+
+    let filename = format!("payment-processor-{}.db", Utc::now().format("%Y%m%dT%H%M%SZ"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/vnd.sqlite3".to_string()),
+            (header::CONTENT_LENGTH, size.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/pause",
+    responses(
+        (status = 200, description = "Current maintenance mode state", body = PauseStatusResponse),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_pause_status(State(db_pool): State<SqlitePool>) -> Result<Json<PauseStatusResponse>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+    Ok(Json(ProcessingPause::load(&mut conn).await?.into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/pause",
+    responses(
+        (status = 200, description = "Processing paused for all accounts. Batches already being signed, broadcast or confirmed are finished.", body = PauseStatusResponse),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_pause(State(state): State<AppState>) -> Result<Json<PauseStatusResponse>, ApiError> {
+    set_paused(&state, None, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/resume",
+    responses(
+        (status = 200, description = "Global pause lifted. Individually paused accounts stay paused.", body = PauseStatusResponse),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_resume(State(state): State<AppState>) -> Result<Json<PauseStatusResponse>, ApiError> {
+    set_paused(&state, None, false).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/accounts/{name}/pause",
+    params(
+        ("name" = String, Path, description = "Name of the account")
+    ),
+    responses(
+        (status = 200, description = "Processing paused for the account", body = PauseStatusResponse),
+        (status = 404, description = "Account not found in configuration", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_pause_account(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<PauseStatusResponse>, ApiError> {
+    if !state.env.accounts.current().contains_key(&name.to_lowercase()) {
+        return Err(ApiError::NotFound(format!(
+            "Account '{}' not found in configuration",
+            name
+        )));
+    }
+    set_paused(&state, Some(&name), true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/accounts/{name}/resume",
+    params(
+        ("name" = String, Path, description = "Name of the account")
+    ),
+    responses(
+        (status = 200, description = "Processing resumed for the account, unless paused globally", body = PauseStatusResponse),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_resume_account(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<PauseStatusResponse>, ApiError> {
+    set_paused(&state, Some(&name), false).await
+}
+
+async fn set_paused(
+    state: &AppState,
+    account_name: Option<&str>,
+    paused: bool,
+) -> Result<Json<PauseStatusResponse>, ApiError> {
+    let mut tx = state.db_writer.begin().await?;
+
+    if ProcessingPause::set_paused(&mut tx, account_name, paused).await? {
+        let action = if paused {
+            AuditAction::ProcessingPaused
+        } else {
+            AuditAction::ProcessingResumed
+        };
+        AuditEntry::record(
+            &mut tx,
+            API_ACTOR,
+            action,
+            account_name.unwrap_or("*"),
+            None,
+            None,
+            None,
+        )
+        .await?;
+        info!(
+            target: LOG_TARGET,
+            "Processing {} for {}.",
+            if paused { "paused" } else { "resumed" },
+            account_name.map_or("all accounts".to_string(), |name| format!("account '{}'", name))
+        );
+    }
+    let pause = ProcessingPause::load(&mut tx).await?;
+
+    tx.commit().await?;
+
+    if !paused {
+        state.wakeups.batch_creator.notify_one();
+        state.wakeups.unsigned_tx_creator.notify_one();
+    }
+
+    Ok(Json(pause.into()))
+}
+
+async fn find_batch(conn: &mut sqlx::SqliteConnection, batch_id: &str) -> Result<PaymentBatch, ApiError> {
+    PaymentBatch::find_by_id(conn, batch_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment batch not found".to_string()))
+}
+
+async fn find_awaiting_signature(conn: &mut sqlx::SqliteConnection, batch_id: &str) -> Result<PaymentBatch, ApiError> {
+    let batch = find_batch(conn, batch_id).await?;
+    if !matches!(batch.status, PaymentBatchStatus::AwaitingSignature) {
+        return Err(ApiError::BadRequest(format!(
+            "Batch is in status {} and not awaiting signature",
+            batch.status
+        )));
+    }
+    Ok(batch)
+}
+
+async fn ensure_needs_review(conn: &mut sqlx::SqliteConnection, batch_id: &str) -> Result<(), ApiError> {
+    let batch = find_batch(conn, batch_id).await?;
+    if !matches!(batch.status, PaymentBatchStatus::NeedsReview) {
+        return Err(ApiError::BadRequest(format!(
+            "Batch is in status {} and cannot be resolved manually",
+            batch.status
+        )));
+    }
+    Ok(())
+}
+
+/// Loads a batch that may be forced into a final status: one awaiting review, or any other non-final batch that has
+/// not changed for `DEFAULT_STUCK_AFTER_SECS`, so a worker still processing it isn't overruled.
+async fn find_forceable(conn: &mut sqlx::SqliteConnection, batch_id: &str) -> Result<PaymentBatch, ApiError> {
+    let batch = find_batch(conn, batch_id).await?;
+    if batch.status.is_final() {
+        return Err(ApiError::BadRequest(format!(
+            "Batch is in final status {}",
+            batch.status
+        )));
+    }
+    let stuck_since = Utc::now() - chrono::Duration::seconds(DEFAULT_STUCK_AFTER_SECS);
+    if batch.status != PaymentBatchStatus::NeedsReview && batch.updated_at >= stuck_since {
+        return Err(ApiError::BadRequest(format!(
+            "Batch in status {} changed less than {}s ago and is not stuck",
+            batch.status, DEFAULT_STUCK_AFTER_SECS
+        )));
+    }
+    Ok(batch)
+}
+
+fn parse_header_hash(hash: &str) -> Result<String, String> {
+    let bytes = hex::decode(hash.trim()).map_err(|e| format!("Not a hex string: {}", e))?;
+    if bytes.len() != 32 {
+        return Err(format!("Expected 32 bytes, got {}", bytes.len()));
+    }
+    Ok(hex::encode(bytes))
+}
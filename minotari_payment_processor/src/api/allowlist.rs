@@ -0,0 +1,38 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::warn;
+use std::net::SocketAddr;
+
+use crate::api::{AppState, error::ApiError};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+
+/// Rejects requests from source IPs outside `API_ALLOWED_NETWORKS` with `403 Forbidden`.
+/// Requests without a known source address are rejected as well.
+pub async fn allowlist_layer(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(allowed_networks) = &state.env.api_allowed_networks else {
+        return next.run(request).await;
+    };
+
+    // IPv4 clients of a dual-stack listener show up as IPv4-mapped IPv6 addresses.
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical());
+
+    if client.is_some_and(|ip| allowed_networks.iter().any(|network| network.contains(&ip))) {
+        return next.run(request).await;
+    }
+
+    warn!(
+        target: LOG_TARGET,
+        "Rejected {} {} from {}: not in an allowed network.",
+        request.method(),
+        request.uri().path(),
+        client.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string())
+    );
+    ApiError::Forbidden("Client address is not allowed".to_string()).into_response()
+}
@@ -0,0 +1,90 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::error::ApiError,
+    db::audit_log::{AuditAction, AuditEntry},
+};
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct AuditQuery {
+    /// Only return entries of this payment or payment batch.
+    pub entity_id: Option<String>,
+    /// Only entries with a greater ID are returned. Defaults to 0 (from the beginning).
+    pub after_id: Option<i64>,
+    /// Maximum number of entries to return. Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditEntryResponse {
+    pub id: i64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+    fn from(entry: AuditEntry) -> Self {
+        AuditEntryResponse {
+            id: entry.id,
+            actor: entry.actor,
+            action: entry.action,
+            entity_id: entry.entity_id,
+            before_status: entry.before_status,
+            after_status: entry.after_status,
+            details: entry.details,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit log entries, oldest first", body = Vec<AuditEntryResponse>),
+        (status = 400, description = "Bad request (Invalid limit)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_audit_entries(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntryResponse>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    if !(1..=MAX_AUDIT_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_AUDIT_LIMIT
+        )));
+    }
+
+    let mut conn = db_pool.acquire().await?;
+    let entries = AuditEntry::find(
+        &mut conn,
+        query.entity_id.as_deref(),
+        query.after_id.unwrap_or(0),
+        limit,
+    )
+    .await?;
+
+    Ok(Json(entries.into_iter().map(AuditEntryResponse::from).collect()))
+}
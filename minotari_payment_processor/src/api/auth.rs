@@ -0,0 +1,130 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::warn;
+
+use crate::{
+    api::{AppState, error::ApiError},
+    config::{ApiKey, ApiRole},
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+
+/// The authenticated caller, added to the request by `auth_layer`.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    /// Name of the API key, or `jwt:<subject>` for a JWT. `None` if no authentication is configured.
+    pub key_name: Option<String>,
+    pub role: ApiRole,
+    /// Accounts the client may create and cancel payments for. `None` allows every account.
+    pub accounts: Option<Vec<String>>,
+}
+
+impl ApiClient {
+    /// Rejects the request with `403 Forbidden` if the client may not use `account_name`.
+    pub fn check_account(&self, account_name: &str) -> Result<(), ApiError> {
+        if let Some(accounts) = &self.accounts
+            && !accounts
+                .iter()
+                .any(|account| account.eq_ignore_ascii_case(account_name))
+        {
+            return Err(ApiError::Forbidden(format!(
+                "This API client may not use account '{}'",
+                account_name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Authenticates requests with `Authorization: Bearer <token>`, where the token is one of `API_KEYS` or a JWT
+/// accepted by the `JwtValidator`. Missing or invalid tokens are rejected with `401 Unauthorized`. Without
+/// configured keys or JWT issuer every request is let through with the admin role.
+pub async fn auth_layer(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    if state.env.api_keys.is_empty() && state.jwt_validator.is_none() {
+        request.extensions_mut().insert(ApiClient {
+            key_name: None,
+            role: ApiRole::Admin,
+            accounts: None,
+        });
+        return next.run(request).await;
+    }
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    let Some(token) = token else {
+        return unauthorized("Missing API key. Send it as 'Authorization: Bearer <key>'.");
+    };
+
+    let key_hash = ApiKey::hash(token);
+    let client = match state.env.api_keys.iter().find(|key| key.key_hash == key_hash) {
+        Some(key) => ApiClient {
+            key_name: Some(key.name.clone()),
+            role: key.role,
+            accounts: key.accounts.clone(),
+        },
+        None => {
+            let Some(validator) = &state.jwt_validator else {
+                warn!(
+                    target: LOG_TARGET,
+                    "Rejected {} {}: unknown API key.",
+                    request.method(),
+                    request.uri().path()
+                );
+                return unauthorized("Unknown API key");
+            };
+            match validator.validate(token).await {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Rejected {} {}: invalid token: {:#}",
+                        request.method(),
+                        request.uri().path(),
+                        e
+                    );
+                    return unauthorized(&format!("Invalid token: {:#}", e));
+                },
+            }
+        },
+    };
+
+    request.extensions_mut().insert(client);
+    next.run(request).await
+}
+
+/// Rejects requests whose API key lacks `role` with `403 Forbidden`. Must run inside `auth_layer`.
+pub async fn require_role(role: ApiRole, request: Request, next: Next) -> Response {
+    let Some(client) = request.extensions().get::<ApiClient>() else {
+        return ApiError::InternalServerError("Request was not authenticated".to_string()).into_response();
+    };
+    if client.role >= role {
+        return next.run(request).await;
+    }
+
+    warn!(
+        target: LOG_TARGET,
+        "Rejected {} {} for API key '{}': requires the {} role, the key has {}.",
+        request.method(),
+        request.uri().path(),
+        client.key_name.as_deref().unwrap_or("-"),
+        role,
+        client.role
+    );
+    ApiError::Forbidden(format!("This API key lacks the {} role", role)).into_response()
+}
+
+fn unauthorized(message: &str) -> Response {
+    let mut response = ApiError::Unauthorized(message.to_string()).into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
@@ -0,0 +1,26 @@
+use axum::{
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::error::ApiError;
+
+/// Replaces the plain-text `413 Payload Too Large` rejections of axum's body extractors with the JSON error format
+/// of the API, so clients can handle an oversized request like any other error.
+pub async fn payload_too_large_layer(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+    ApiError::PayloadTooLarge("Request body exceeds the size limit of this endpoint".to_string()).into_response()
+}
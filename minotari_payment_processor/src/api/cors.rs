@@ -0,0 +1,22 @@
+use axum::http::{HeaderName, header};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{api::idempotency::IDEMPOTENT_REPLAYED_HEADER, config::CorsConfig};
+
+/// Answers preflight requests and adds the CORS headers to responses for the configured origins.
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let allow_origin = match &config.allowed_origins {
+        Some(origins) => AllowOrigin::list(origins.iter().cloned()),
+        None => AllowOrigin::any(),
+    };
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(config.allowed_methods.clone())
+        .allow_headers(config.allowed_headers.clone())
+        // Scripts may read these, e.g. to back off or detect a replayed payment request.
+        .expose_headers([
+            header::RETRY_AFTER,
+            HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+        ])
+        .max_age(config.max_age)
+}
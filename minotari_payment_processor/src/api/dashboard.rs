@@ -0,0 +1,9 @@
+use axum::response::Html;
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// Operations dashboard. A static page that polls the stats, health and admin batch endpoints from the browser, so
+/// it shows the same data as the API and needs no state of its own.
+pub async fn api_get_dashboard() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
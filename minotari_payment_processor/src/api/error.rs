@@ -1,12 +1,15 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
 use utoipa::ToSchema;
 
+use crate::db::payment_batch::PaymentBatchError;
+
 #[derive(Debug, Error, ToSchema)]
 pub enum ApiError {
     #[error("Internal server error: {0}")]
@@ -18,6 +21,73 @@ pub enum ApiError {
     NotFound(String),
     #[error("Bad request: {0}")]
     BadRequest(String),
+    /// A 422 listing every invalid field of the request.
+    #[error("Invalid request: {}", .0.iter().map(|e| format!("{}: {}", e.field, e.message)).collect::<Vec<_>>().join(", "))]
+    Validation(Vec<FieldError>),
+    #[error("Upstream service error: {0}")]
+    BadGateway(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+    #[error("Too many requests: {message}")]
+    TooManyRequests { message: String, retry_after_secs: u64 },
+    /// The service is temporarily not accepting work. `code` is a stable identifier clients can match on.
+    #[error("Service unavailable: {message}")]
+    ServiceUnavailable {
+        code: &'static str,
+        message: String,
+        retry_after_secs: u64,
+    },
+}
+
+/// A problem with a single request field. `field` is its path in the request, e.g. `items[2].amount`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ApiError {
+    /// A validation error for a single field.
+    pub fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        ApiError::Validation(vec![FieldError {
+            field: field.into(),
+            message: message.into(),
+        }])
+    }
+}
+
+/// Collects the problems of a request instead of stopping at the first, so a client submitting a large batch can fix
+/// everything in one pass.
+#[derive(Debug, Default)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns the value of `result`, or records its error under `field` and returns `None`.
+    pub fn check<T>(&mut self, field: impl Into<String>, result: Result<T, String>) -> Option<T> {
+        result.map_err(|message| self.add(field, message)).ok()
+    }
+
+    /// Fails with a 422 listing all collected problems, if there are any.
+    pub fn into_result(self) -> Result<(), ApiError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.0))
+        }
+    }
 }
 
 impl From<sqlx::Error> for ApiError {
@@ -26,13 +96,79 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+impl From<PaymentBatchError> for ApiError {
+    fn from(err: PaymentBatchError) -> Self {
+        match err {
+            PaymentBatchError::InvalidTransition { .. }
+            | PaymentBatchError::VersionConflict { .. }
+            | PaymentBatchError::PaymentsChanged { .. } => ApiError::Conflict(err.to_string()),
+            PaymentBatchError::Db(e) => e.into(),
+        }
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        if let ApiError::TooManyRequests {
+            message,
+            retry_after_secs,
+        } = self
+        {
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                Json(json!({
+                    "error": message,
+                })),
+            )
+                .into_response();
+        }
+
+        if let ApiError::ServiceUnavailable {
+            code,
+            message,
+            retry_after_secs,
+        } = self
+        {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                Json(json!({
+                    "error": message,
+                    "code": code,
+                })),
+            )
+                .into_response();
+        }
+
+        if let ApiError::Validation(errors) = self {
+            let message = match errors.as_slice() {
+                [error] => error.message.clone(),
+                _ => format!("Request has {} invalid fields", errors.len()),
+            };
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": message,
+                    "errors": errors,
+                })),
+            )
+                .into_response();
+        }
+
         let (status, error_message) = match self {
             ApiError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ApiError::DbError(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, "Invalid request".to_string()),
+            ApiError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            ApiError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            ApiError::TooManyRequests { message, .. } => (StatusCode::TOO_MANY_REQUESTS, message),
+            ApiError::ServiceUnavailable { message, .. } => (StatusCode::SERVICE_UNAVAILABLE, message),
         };
 
         let body = Json(json!({
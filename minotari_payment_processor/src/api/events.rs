@@ -0,0 +1,93 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::error::ApiError,
+    db::event::{Event, EventType},
+};
+
+const DEFAULT_EVENTS_LIMIT: i64 = 100;
+const MAX_EVENTS_LIMIT: i64 = 1000;
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct EventsQuery {
+    /// Only events with a greater sequence number are returned. Defaults to 0 (from the beginning).
+    pub after_seq: Option<i64>,
+    /// Maximum number of events to return. Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EventResponse {
+    pub seq: i64,
+    pub event_type: EventType,
+    /// ID of the payment or payment batch the event refers to.
+    pub entity_id: String,
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<Event> for EventResponse {
+    type Error = ApiError;
+
+    fn try_from(event: Event) -> Result<Self, Self::Error> {
+        let payload = serde_json::from_str(&event.payload)
+            .map_err(|e| ApiError::InternalServerError(format!("Invalid payload for event {}: {}", event.seq, e)))?;
+        Ok(EventResponse {
+            seq: event.seq,
+            event_type: event.event_type,
+            entity_id: event.entity_id,
+            payload,
+            created_at: event.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EventsResponse {
+    pub events: Vec<EventResponse>,
+    /// Pass as `after_seq` to fetch the next page. Equals the request's `after_seq` if there are no new events.
+    pub last_seq: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "Events in sequence order", body = EventsResponse),
+        (status = 400, description = "Bad request (Invalid limit)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_events(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>, ApiError> {
+    let after_seq = query.after_seq.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_EVENTS_LIMIT);
+    if !(1..=MAX_EVENTS_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_EVENTS_LIMIT
+        )));
+    }
+
+    let mut conn = db_pool.acquire().await?;
+
+    let events = Event::find_after(&mut conn, after_seq, limit).await?;
+    let last_seq = events.last().map(|e| e.seq).unwrap_or(after_seq);
+    let events = events
+        .into_iter()
+        .map(EventResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(EventsResponse { events, last_seq }))
+}
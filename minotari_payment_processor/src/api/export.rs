@@ -0,0 +1,213 @@
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::stream;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::IntoParams;
+
+use crate::{
+    api::error::ApiError,
+    db::{
+        ledger_entry::LedgerEntry,
+        payment::{Payment, PaymentExportRow},
+    },
+};
+
+/// Rows fetched from the database per chunk of the response body.
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+const LEDGER_CSV_HEADER: &str =
+    "entry_id,journal_id,payment_id,entry_type,debit_account,credit_account,amount,created_at\n";
+
+const CSV_HEADER: &str = "payment_id,client_id,account_name,recipient_address,amount,status,payment_batch_id,batch_status,payref,mined_height,mined_timestamp,created_at,updated_at\n";
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ExportQuery {
+    /// Export format. Only `csv` is supported. Defaults to `csv`.
+    pub format: Option<String>,
+    /// Only rows created at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only rows created before this time.
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payments/export",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "CSV export of the payments, streamed in creation order", content_type = "text/csv", body = String),
+        (status = 400, description = "Bad request (Unsupported format or invalid time range)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_export_payments(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    validate_query(&query)?;
+
+    // The body is produced page by page, so only one page of rows is held in memory at a time.
+    let (from, to) = (query.from, query.to);
+    let pages = stream::try_unfold(Some(0i64), move |cursor| {
+        let db_pool = db_pool.clone();
+        async move {
+            let Some(after_row_id) = cursor else {
+                return Ok(None);
+            };
+
+            let mut conn = db_pool.acquire().await?;
+            let rows = Payment::find_for_export(&mut conn, from, to, after_row_id, EXPORT_PAGE_SIZE).await?;
+
+            let mut chunk = if after_row_id == 0 {
+                CSV_HEADER.to_string()
+            } else {
+                String::new()
+            };
+            if rows.is_empty() {
+                return Ok::<_, sqlx::Error>(if chunk.is_empty() { None } else { Some((chunk, None)) });
+            }
+
+            let next_cursor = if rows.len() < EXPORT_PAGE_SIZE as usize {
+                None
+            } else {
+                rows.last().map(|row| row.row_id)
+            };
+            for row in &rows {
+                write_csv_row(&mut chunk, row);
+            }
+            Ok(Some((chunk, next_cursor)))
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"payments.csv\""),
+        ],
+        Body::from_stream(pages),
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/ledger/export",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "CSV export of the double-entry ledger, streamed in entry order", content_type = "text/csv", body = String),
+        (status = 400, description = "Bad request (Unsupported format or invalid time range)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_export_ledger(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    validate_query(&query)?;
+
+    let (from, to) = (query.from, query.to);
+    let pages = stream::try_unfold(Some(0i64), move |cursor| {
+        let db_pool = db_pool.clone();
+        async move {
+            let Some(after_id) = cursor else {
+                return Ok(None);
+            };
+
+            let mut conn = db_pool.acquire().await?;
+            let entries = LedgerEntry::find_for_export(&mut conn, from, to, after_id, EXPORT_PAGE_SIZE).await?;
+
+            let mut chunk = if after_id == 0 {
+                LEDGER_CSV_HEADER.to_string()
+            } else {
+                String::new()
+            };
+            if entries.is_empty() {
+                return Ok::<_, sqlx::Error>(if chunk.is_empty() { None } else { Some((chunk, None)) });
+            }
+
+            let next_cursor = if entries.len() < EXPORT_PAGE_SIZE as usize {
+                None
+            } else {
+                entries.last().map(|entry| entry.id)
+            };
+            for entry in &entries {
+                write_ledger_csv_row(&mut chunk, entry);
+            }
+            Ok(Some((chunk, next_cursor)))
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"ledger.csv\""),
+        ],
+        Body::from_stream(pages),
+    )
+        .into_response())
+}
+
+fn validate_query(query: &ExportQuery) -> Result<(), ApiError> {
+    let format = query.format.as_deref().unwrap_or("csv");
+    if !format.eq_ignore_ascii_case("csv") {
+        return Err(ApiError::BadRequest(format!("Unsupported export format '{}'", format)));
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from >= to
+    {
+        return Err(ApiError::BadRequest("'from' must be before 'to'".to_string()));
+    }
+    Ok(())
+}
+
+fn write_csv_row(out: &mut String, row: &PaymentExportRow) {
+    let fields = [
+        row.id.clone(),
+        row.client_id.clone(),
+        row.account_name.clone(),
+        row.recipient_address.clone(),
+        row.amount.to_string(),
+        row.status.to_string(),
+        row.payment_batch_id.clone().unwrap_or_default(),
+        row.batch_status.clone().unwrap_or_default(),
+        row.payref.clone().unwrap_or_default(),
+        row.mined_height.map(|h| h.to_string()).unwrap_or_default(),
+        row.mined_timestamp.map(|t| t.to_string()).unwrap_or_default(),
+        row.created_at.to_rfc3339(),
+        row.updated_at.to_rfc3339(),
+    ];
+    let escaped: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+    out.push_str(&escaped.join(","));
+    out.push('\n');
+}
+
+fn write_ledger_csv_row(out: &mut String, entry: &LedgerEntry) {
+    let fields = [
+        entry.id.to_string(),
+        entry.journal_id.clone(),
+        entry.payment_id.clone().unwrap_or_default(),
+        entry.entry_type.to_string(),
+        entry.debit_account.clone(),
+        entry.credit_account.clone(),
+        entry.amount.to_string(),
+        entry.created_at.to_rfc3339(),
+    ];
+    let escaped: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+    out.push_str(&escaped.join(","));
+    out.push('\n');
+}
+
+/// Quotes a field if it contains a separator, quote or line break (RFC 4180).
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
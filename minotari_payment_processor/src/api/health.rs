@@ -0,0 +1,125 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use chrono::{TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::{AppState, error::ApiError},
+    workers::{chain_state::ChainTip, console_wallet_check::ConsoleWalletStatus, supervisor::WorkerStatus},
+};
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `true` if the database is reachable and the last console wallet check passed. The console wallet is not
+    /// checked in simulation mode, where it is not used, and in processes that don't run the console wallet check.
+    pub ready: bool,
+    pub database: bool,
+    /// Whether the service runs in simulation mode and pays nothing out.
+    pub simulation: bool,
+    /// Missing until the first console wallet check finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub console_wallet: Option<ConsoleWalletStatus>,
+    /// Most recently observed chain tip. Informational only, it does not affect `ready`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_tip: Option<ChainTip>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "Ready to process payments", body = ReadinessResponse),
+        (status = 503, description = "Not ready, see the failing checks", body = ReadinessResponse),
+    )
+)]
+pub async fn api_get_readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let database = sqlx::query("SELECT 1").execute(&state.db_pool).await.is_ok();
+    let console_wallet = state.console_wallet_health.current();
+    let simulation = state.env.simulation.is_some();
+    let wallet_checked = !simulation && state.env.runs_worker("console_wallet_check");
+    let ready = database && (!wallet_checked || console_wallet.as_ref().is_some_and(|status| status.ok));
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            database,
+            simulation,
+            console_wallet,
+            chain_tip: state.chain_state.current(),
+        }),
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct WorkerHealthQuery {
+    /// Only report this worker, e.g. `confirmation_checker`.
+    pub worker: Option<String>,
+    /// Answer `503` if a reported worker has not completed a successful cycle within this many seconds.
+    pub max_success_age_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WorkerHealthResponse {
+    /// `false` if a worker is stale according to `max_success_age_secs`. Always `true` without it.
+    pub healthy: bool,
+    pub workers: BTreeMap<String, WorkerStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/workers",
+    params(WorkerHealthQuery),
+    responses(
+        (status = 200, description = "Cycle statistics of the background workers", body = WorkerHealthResponse),
+        (status = 400, description = "Bad request (Too large max_success_age_secs)", body = ApiError),
+        (status = 404, description = "Unknown worker", body = ApiError),
+        (status = 503, description = "A worker has not completed a successful cycle within max_success_age_secs", body = WorkerHealthResponse),
+    )
+)]
+pub async fn api_get_worker_health(
+    State(state): State<AppState>,
+    Query(query): Query<WorkerHealthQuery>,
+) -> Result<(StatusCode, Json<WorkerHealthResponse>), ApiError> {
+    let workers: BTreeMap<String, WorkerStatus> = state
+        .worker_health
+        .snapshot()
+        .into_iter()
+        .filter(|(name, _)| query.worker.as_deref().is_none_or(|worker| worker == *name))
+        .map(|(name, status)| (name.to_string(), status))
+        .collect();
+    if let (Some(worker), true) = (&query.worker, workers.is_empty()) {
+        return Err(ApiError::NotFound(format!("Unknown worker '{}'", worker)));
+    }
+
+    let healthy = match query.max_success_age_secs {
+        Some(max_age_secs) => {
+            let oldest_allowed = TimeDelta::try_seconds(max_age_secs)
+                .and_then(|age| Utc::now().checked_sub_signed(age))
+                .ok_or_else(|| ApiError::BadRequest("max_success_age_secs is too large".to_string()))?;
+            workers.values().all(|status| {
+                status
+                    .last_success
+                    .is_some_and(|last_success| last_success >= oldest_allowed)
+            })
+        },
+        None => true,
+    };
+
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok((status, Json(WorkerHealthResponse { healthy, workers })))
+}
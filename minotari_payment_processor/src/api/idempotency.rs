@@ -0,0 +1,183 @@
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::{error, warn};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::{
+    api::{AppState, auth::ApiClient, error::ApiError},
+    db::idempotency_key::IdempotencyRecord,
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set on responses that were replayed from an earlier request with the same key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+/// Largest response that is stored for replay. Larger responses, and those of unknown size such as streams, are
+/// passed through without being stored.
+const MAX_STORED_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Makes POST requests that carry an `Idempotency-Key` header safe to retry.
+///
+/// The first request with a given key (per client and path) is executed and its response stored. Any later request
+/// of the same client with the same key receives the stored response, even if its body differs, instead of being
+/// executed again. Server errors and responses larger than `MAX_STORED_RESPONSE_BYTES` are not stored, so the
+/// request can be retried.
+pub async fn idempotency_layer(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let idempotency_key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => return next.run(request).await,
+        Some(value) => match value.to_str() {
+            Ok(key) if !key.trim().is_empty() && key.len() <= MAX_KEY_LENGTH => key.trim().to_string(),
+            _ => {
+                return ApiError::BadRequest(format!(
+                    "Idempotency-Key must be a non-empty ASCII string of at most {} characters",
+                    MAX_KEY_LENGTH
+                ))
+                .into_response();
+            },
+        },
+    };
+
+    let max_body_bytes = state.env.max_request_body_bytes;
+    match handle_idempotent_request(&state.db_writer, idempotency_key, max_body_bytes, request, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn handle_idempotent_request(
+    db_pool: &SqlitePool,
+    idempotency_key: String,
+    max_body_bytes: usize,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let request_path = request.uri().path().to_string();
+    // Clients may be limited to some accounts, so one client must never be handed the response stored for another.
+    let client_name = request
+        .extensions()
+        .get::<ApiClient>()
+        .and_then(|client| client.key_name.clone())
+        .unwrap_or_default();
+    let (parts, body) = request.into_parts();
+    // The request is buffered to hash it, so it is held to the limit of the `/v1` routes here already.
+    let body_bytes = to_bytes(body, max_body_bytes).await.map_err(|e| {
+        if std::error::Error::source(&e).is_some_and(|source| source.is::<http_body_util::LengthLimitError>()) {
+            ApiError::PayloadTooLarge(format!("Request body exceeds the limit of {} bytes", max_body_bytes))
+        } else {
+            ApiError::BadRequest(format!("Failed to read request body: {}", e))
+        }
+    })?;
+    let request_hash = hex::encode(Sha256::digest(&body_bytes));
+
+    let mut conn = db_pool.acquire().await?;
+
+    if !IdempotencyRecord::try_reserve(&mut conn, &idempotency_key, &request_path, &client_name, &request_hash).await? {
+        let record = IdempotencyRecord::find(&mut conn, &idempotency_key, &request_path, &client_name)
+            .await?
+            .ok_or_else(|| ApiError::Conflict("Idempotency-Key was released concurrently, retry".to_string()))?;
+        return replay(record, &request_hash);
+    }
+
+    // Do not hold a pooled connection while the handler runs.
+    drop(conn);
+
+    let response = next.run(Request::from_parts(parts, Body::from(body_bytes))).await;
+    let storable = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_STORED_RESPONSE_BYTES);
+    if !storable {
+        warn!(
+            target: LOG_TARGET,
+            "Response to {} with Idempotency-Key '{}' is too large or streamed to be stored. Releasing the key.",
+            request_path, idempotency_key
+        );
+        release(db_pool, &idempotency_key, &request_path, &client_name).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body_bytes = match to_bytes(body, MAX_STORED_RESPONSE_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            release(db_pool, &idempotency_key, &request_path, &client_name).await;
+            return Err(ApiError::InternalServerError(format!(
+                "Failed to read response body: {}",
+                e
+            )));
+        },
+    };
+
+    let mut conn = db_pool.acquire().await?;
+    match std::str::from_utf8(&body_bytes) {
+        Ok(body) if !parts.status.is_server_error() => {
+            IdempotencyRecord::complete(
+                &mut conn,
+                &idempotency_key,
+                &request_path,
+                &client_name,
+                i64::from(parts.status.as_u16()),
+                body,
+            )
+            .await?;
+        },
+        _ => IdempotencyRecord::release(&mut conn, &idempotency_key, &request_path, &client_name).await?,
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body_bytes)))
+}
+
+fn replay(record: IdempotencyRecord, request_hash: &str) -> Result<Response, ApiError> {
+    let (Some(status), Some(body)) = (record.response_status, record.response_body) else {
+        return Err(ApiError::Conflict(
+            "A request with this Idempotency-Key is still being processed".to_string(),
+        ));
+    };
+
+    if record.request_hash != request_hash {
+        warn!(
+            target: LOG_TARGET,
+            "Request to {} with Idempotency-Key '{}' has a different body than the original. Replaying the original response.",
+            record.request_path, record.idempotency_key
+        );
+    }
+
+    let status = u16::try_from(status)
+        .ok()
+        .and_then(|s| StatusCode::from_u16(s).ok())
+        .ok_or_else(|| ApiError::InternalServerError(format!("Stored response has invalid status {}", status)))?;
+
+    let mut response = (status, body).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    Ok(response)
+}
+
+async fn release(db_pool: &SqlitePool, idempotency_key: &str, request_path: &str, client_name: &str) {
+    let result = match db_pool.acquire().await {
+        Ok(mut conn) => IdempotencyRecord::release(&mut conn, idempotency_key, request_path, client_name).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!(
+            target: LOG_TARGET,
+            "Failed to release Idempotency-Key '{}' for {}: {:?}",
+            idempotency_key, request_path, e
+        );
+    }
+}
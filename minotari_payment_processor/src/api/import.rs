@@ -0,0 +1,547 @@
+use axum::{
+    Extension, Json,
+    body::{Body, BodyDataStream, Bytes},
+    extract::{Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+};
+use futures::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqliteConnection;
+use std::{convert::Infallible, fmt};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    amount::PaymentAmount,
+    api::{
+        API_ACTOR, AppState,
+        auth::ApiClient,
+        error::ApiError,
+        payments::{validate_amount, validate_memo, validate_recipient_address},
+    },
+    config::PaymentProcessorEnv,
+    db::{
+        audit_log::{AuditAction, AuditEntry},
+        payment::{Payment, PaymentPriority},
+    },
+};
+
+/// Payments created per database transaction.
+pub(crate) const IMPORT_CHUNK_SIZE: usize = 500;
+/// Request body limit of the import endpoint.
+pub const MAX_IMPORT_BODY_BYTES: usize = 64 * 1024 * 1024;
+/// Longest line accepted by the streamed import, which has no limit on the body as a whole.
+const MAX_STREAM_LINE_BYTES: usize = 64 * 1024;
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl From<String> for ImportFormat {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "csv" => ImportFormat::Csv,
+            "ndjson" => ImportFormat::Ndjson,
+            _ => panic!("Unknown ImportFormat: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportFormat::Csv => write!(f, "csv"),
+            ImportFormat::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct StreamImportQuery {
+    /// Account used for lines without an `account_name`.
+    pub account_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ImportQuery {
+    pub format: ImportFormat,
+    /// Account used for rows without an `account_name`.
+    pub account_name: Option<String>,
+}
+
+/// A row of an import file. CSV files need a header line with these column names.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ImportRow {
+    client_id: String,
+    account_name: Option<String>,
+    recipient_address: String,
+    amount: u64,
+    payment_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportRowStatus {
+    Created,
+    /// A payment with the same client_id already exists; nothing was changed.
+    Duplicate,
+    Invalid,
+}
+
+impl fmt::Display for ImportRowStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportRowStatus::Created => write!(f, "CREATED"),
+            ImportRowStatus::Duplicate => write!(f, "DUPLICATE"),
+            ImportRowStatus::Invalid => write!(f, "INVALID"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRowResult {
+    /// 1-based row number, not counting the CSV header.
+    pub row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    pub status: ImportRowStatus,
+    /// ID of the created or already existing payment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub total_rows: usize,
+    pub created: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub results: Vec<ImportRowResult>,
+}
+
+/// Last line of a streamed import response.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct ImportSummary {
+    pub total_rows: usize,
+    pub created: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+}
+
+pub(crate) struct ValidRow {
+    row: usize,
+    client_id: String,
+    account_name: String,
+    recipient_address: String,
+    amount: PaymentAmount,
+    payment_id: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/import",
+    params(ImportQuery),
+    request_body(content = String, description = "CSV (with header) or NDJSON file of payments", content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Per-row import report", body = ImportReport),
+        (status = 400, description = "Bad request (Unreadable file)", body = ApiError),
+        (status = 500, description = "Internal server error. Rows of chunks committed before the error are kept; re-submitting the file reports them as duplicates.", body = ApiError)
+    )
+)]
+pub async fn api_import_payments(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<Json<ImportReport>, ApiError> {
+    let rows = parse_rows(query.format, &body)?;
+    let total_rows = rows.len();
+    let (valid_rows, mut results) = validate_rows(
+        &state.env,
+        client.accounts.as_deref(),
+        query.account_name.as_deref(),
+        1,
+        rows,
+    );
+
+    for chunk in valid_rows.chunks(IMPORT_CHUNK_SIZE) {
+        let mut tx = state.db_writer.begin().await?;
+        results.extend(create_rows(&mut tx, chunk).await?);
+        tx.commit().await?;
+    }
+
+    results.sort_by_key(|result| result.row);
+    let count = |status: ImportRowStatus| results.iter().filter(|r| r.status == status).count();
+
+    Ok(Json(ImportReport {
+        total_rows,
+        created: count(ImportRowStatus::Created),
+        duplicates: count(ImportRowStatus::Duplicate),
+        invalid: count(ImportRowStatus::Invalid),
+        results,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/import/stream",
+    params(StreamImportQuery),
+    request_body(content = String, description = "NDJSON file of payments, one payment per line", content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Streamed NDJSON: one ImportRowResult per non-empty line, in line order, then an ImportSummary. If the import stops early, e.g. on a database error, the last line is an object with an `error` instead. Rows reported before it are kept.", content_type = "application/x-ndjson", body = String),
+        (status = 400, description = "Bad request (Content-Type is not application/x-ndjson)", body = ApiError)
+    )
+)]
+pub async fn api_import_payments_stream(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Query(query): Query<StreamImportQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.starts_with(NDJSON_CONTENT_TYPE) {
+        return Err(ApiError::BadRequest(format!(
+            "Content-Type must be {}",
+            NDJSON_CONTENT_TYPE
+        )));
+    }
+
+    // Results are sent as soon as the lines they belong to have been read, so neither the file nor the report is
+    // ever held in memory as a whole.
+    let import = StreamedImport {
+        state,
+        client,
+        default_account_name: query.account_name,
+        body: body.into_data_stream(),
+        buffer: Vec::new(),
+        summary: ImportSummary::default(),
+        finished: false,
+    };
+    let output = stream::unfold(import, |mut import| async move {
+        import
+            .next_output()
+            .await
+            .map(|chunk| (Ok::<_, Infallible>(chunk), import))
+    });
+
+    Ok(([(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)], Body::from_stream(output)).into_response())
+}
+
+/// A streamed import between two reads of its request body.
+struct StreamedImport {
+    state: AppState,
+    client: ApiClient,
+    default_account_name: Option<String>,
+    body: BodyDataStream,
+    /// Start of a line whose end has not been received yet.
+    buffer: Vec<u8>,
+    summary: ImportSummary,
+    finished: bool,
+}
+
+impl StreamedImport {
+    /// Reads the request body up to the next complete lines and returns the results of their rows. The summary
+    /// follows the results of the last line. Returns `None` once everything has been sent.
+    async fn next_output(&mut self) -> Option<Bytes> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            match self.body.next().await {
+                Some(Ok(data)) => {
+                    self.buffer.extend_from_slice(&data);
+                    let Some(end) = self.buffer.iter().rposition(|&byte| byte == b'\n') else {
+                        if self.buffer.len() > MAX_STREAM_LINE_BYTES {
+                            let error = format!(
+                                "Line {} is longer than {} bytes",
+                                self.summary.total_rows + 1,
+                                MAX_STREAM_LINE_BYTES
+                            );
+                            return Some(self.abort(Vec::new(), &error));
+                        }
+                        continue;
+                    };
+                    let lines: Vec<u8> = self.buffer.drain(..=end).collect();
+                    return Some(self.process_lines(&lines).await);
+                },
+                Some(Err(e)) => return Some(self.abort(Vec::new(), &format!("Failed to read request body: {}", e))),
+                None => {
+                    let lines = std::mem::take(&mut self.buffer);
+                    let mut output = self.process_lines(&lines).await.to_vec();
+                    if !self.finished {
+                        write_line(&mut output, &self.summary);
+                        self.finished = true;
+                    }
+                    return Some(output.into());
+                },
+            }
+        }
+    }
+
+    /// Validates and creates the rows of `lines` and returns their results, in line order.
+    async fn process_lines(&mut self, lines: &[u8]) -> Bytes {
+        let mut results = Vec::new();
+        let mut valid_rows = Vec::new();
+        for line in lines.split(|&byte| byte == b'\n') {
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            self.summary.total_rows += 1;
+            let row_number = self.summary.total_rows;
+            let parsed = std::str::from_utf8(line)
+                .map_err(|e| (None, format!("Line is not UTF-8: {}", e)))
+                .and_then(parse_ndjson_line)
+                .and_then(|row| {
+                    validate_row(
+                        &self.state.env,
+                        self.client.accounts.as_deref(),
+                        self.default_account_name.as_deref(),
+                        row_number,
+                        row,
+                    )
+                });
+            match parsed {
+                Ok(valid) => valid_rows.push(valid),
+                Err((client_id, error)) => results.push(ImportRowResult {
+                    row: row_number,
+                    client_id,
+                    status: ImportRowStatus::Invalid,
+                    payment_id: None,
+                    error: Some(error),
+                }),
+            }
+        }
+
+        let mut error = None;
+        for chunk in valid_rows.chunks(IMPORT_CHUNK_SIZE) {
+            match self.create_chunk(chunk).await {
+                Ok(created) => results.extend(created),
+                Err(e) => {
+                    error = Some(format!("Import stopped before row {}: {}", chunk[0].row, e));
+                    break;
+                },
+            }
+        }
+
+        results.sort_by_key(|result| result.row);
+        let mut output = Vec::new();
+        for result in &results {
+            match result.status {
+                ImportRowStatus::Created => self.summary.created += 1,
+                ImportRowStatus::Duplicate => self.summary.duplicates += 1,
+                ImportRowStatus::Invalid => self.summary.invalid += 1,
+            }
+            write_line(&mut output, result);
+        }
+        match error {
+            Some(error) => self.abort(output, &error),
+            None => output.into(),
+        }
+    }
+
+    async fn create_chunk(&self, rows: &[ValidRow]) -> Result<Vec<ImportRowResult>, sqlx::Error> {
+        let mut tx = self.state.db_writer.begin().await?;
+        let results = create_rows(&mut tx, rows).await?;
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    /// Ends the import with an error line after `output`.
+    fn abort(&mut self, mut output: Vec<u8>, error: &str) -> Bytes {
+        self.finished = true;
+        write_line(&mut output, &json!({ "error": error }));
+        output.into()
+    }
+}
+
+fn write_line(output: &mut Vec<u8>, value: &impl Serialize) {
+    serde_json::to_writer(&mut *output, value).expect("import results serialize to JSON");
+    output.push(b'\n');
+}
+
+/// Creates the payments of `rows`, which the caller does in one database transaction. Rows whose client_id is
+/// already used are reported as duplicates.
+pub(crate) async fn create_rows(
+    tx: &mut SqliteConnection,
+    rows: &[ValidRow],
+) -> Result<Vec<ImportRowResult>, sqlx::Error> {
+    let mut results = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        if let Some(existing) = Payment::get_by_client_id(&mut *tx, &row.client_id, &row.account_name).await? {
+            results.push(ImportRowResult {
+                row: row.row,
+                client_id: Some(row.client_id.clone()),
+                status: ImportRowStatus::Duplicate,
+                payment_id: Some(existing.id),
+                error: None,
+            });
+            continue;
+        }
+
+        let payment = Payment::create(
+            &mut *tx,
+            &row.client_id,
+            &row.account_name,
+            &row.recipient_address,
+            row.amount,
+            row.payment_id.clone(),
+            None,
+            None,
+            PaymentPriority::default(),
+        )
+        .await?;
+        AuditEntry::record(
+            &mut *tx,
+            API_ACTOR,
+            AuditAction::PaymentCreated,
+            &payment.id,
+            None,
+            Some(&payment.status.to_string()),
+            Some("imported"),
+        )
+        .await?;
+
+        results.push(ImportRowResult {
+            row: row.row,
+            client_id: Some(row.client_id.clone()),
+            status: ImportRowStatus::Created,
+            payment_id: Some(payment.id),
+            error: None,
+        });
+    }
+
+    Ok(results)
+}
+
+pub(crate) type ParsedRow = Result<ImportRow, (Option<String>, String)>;
+
+/// Reads the rows of an import file. Fails if the file as a whole is unreadable; unreadable rows are returned as
+/// errors.
+pub(crate) fn parse_rows(format: ImportFormat, body: &[u8]) -> Result<Vec<ParsedRow>, ApiError> {
+    match format {
+        ImportFormat::Csv => parse_csv(body),
+        ImportFormat::Ndjson => parse_ndjson(body),
+    }
+}
+
+fn parse_csv(body: &[u8]) -> Result<Vec<ParsedRow>, ApiError> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body);
+    reader
+        .headers()
+        .map_err(|e| ApiError::BadRequest(format!("Failed to read CSV header: {}", e)))?;
+
+    Ok(reader
+        .deserialize::<ImportRow>()
+        .map(|row| row.map_err(|e| (None, format!("Invalid CSV row: {}", e))))
+        .collect())
+}
+
+fn parse_ndjson(body: &[u8]) -> Result<Vec<ParsedRow>, ApiError> {
+    let body = std::str::from_utf8(body).map_err(|e| ApiError::BadRequest(format!("File is not UTF-8: {}", e)))?;
+
+    Ok(body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_ndjson_line)
+        .collect())
+}
+
+fn parse_ndjson_line(line: &str) -> ParsedRow {
+    serde_json::from_str::<ImportRow>(line).map_err(|e| (None, format!("Invalid JSON line: {}", e)))
+}
+
+/// Validates `rows`, numbered from `first_row_number`. Returns the valid rows and the results of the invalid ones.
+pub(crate) fn validate_rows(
+    env: &PaymentProcessorEnv,
+    allowed_accounts: Option<&[String]>,
+    default_account_name: Option<&str>,
+    first_row_number: usize,
+    rows: Vec<ParsedRow>,
+) -> (Vec<ValidRow>, Vec<ImportRowResult>) {
+    let mut valid_rows = Vec::with_capacity(rows.len());
+    let mut results = Vec::new();
+
+    for (idx, parsed) in rows.into_iter().enumerate() {
+        let row_number = first_row_number + idx;
+        match parsed.and_then(|row| validate_row(env, allowed_accounts, default_account_name, row_number, row)) {
+            Ok(valid) => valid_rows.push(valid),
+            Err((client_id, error)) => results.push(ImportRowResult {
+                row: row_number,
+                client_id,
+                status: ImportRowStatus::Invalid,
+                payment_id: None,
+                error: Some(error),
+            }),
+        }
+    }
+
+    (valid_rows, results)
+}
+
+/// Validates a row like a new payment. `allowed_accounts` are the accounts the submitting client may use, `None`
+/// for every account.
+fn validate_row(
+    env: &PaymentProcessorEnv,
+    allowed_accounts: Option<&[String]>,
+    default_account_name: Option<&str>,
+    row_number: usize,
+    row: ImportRow,
+) -> Result<ValidRow, (Option<String>, String)> {
+    let client_id = Some(row.client_id.clone());
+
+    if row.client_id.trim().is_empty() {
+        return Err((None, "client_id must not be empty".to_string()));
+    }
+
+    let account_name = row
+        .account_name
+        .filter(|name| !name.is_empty())
+        .or_else(|| default_account_name.map(String::from))
+        .ok_or_else(|| (client_id.clone(), "No account_name in row or query".to_string()))?;
+    if !env.accounts.current().contains_key(&account_name.to_lowercase()) {
+        return Err((
+            client_id,
+            format!("Account '{}' not found in configuration", account_name),
+        ));
+    }
+    if let Some(accounts) = allowed_accounts
+        && !accounts
+            .iter()
+            .any(|account| account.eq_ignore_ascii_case(&account_name))
+    {
+        return Err((
+            client_id,
+            format!("This API client may not use account '{}'", account_name),
+        ));
+    }
+
+    let amount = PaymentAmount::try_from(row.amount).map_err(|e| (client_id.clone(), e.to_string()))?;
+    validate_amount(amount, &env.payment_amount_limits_for(&account_name)).map_err(|e| (client_id.clone(), e))?;
+    let recipient_address =
+        validate_recipient_address(&row.recipient_address, env.tari_network).map_err(|e| (client_id.clone(), e))?;
+    let payment_id = row.payment_id.filter(|id| !id.is_empty());
+    if let Some(memo) = &payment_id {
+        validate_memo(memo).map_err(|e| (client_id.clone(), e))?;
+    }
+
+    Ok(ValidRow {
+        row: row_number,
+        client_id: row.client_id,
+        account_name,
+        recipient_address,
+        amount,
+        payment_id,
+    })
+}
@@ -0,0 +1,142 @@
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use utoipa::ToSchema;
+
+use crate::{
+    api::{
+        AppState,
+        auth::ApiClient,
+        error::ApiError,
+        import::{self, ImportFormat, ImportQuery, ImportRowResult, ImportRowStatus},
+    },
+    db::import_job::{ImportJob, ImportJobStatus},
+};
+
+/// Invalid rows listed in an import job response. `invalid` counts all of them.
+const MAX_REPORTED_ERRORS: i64 = 1000;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportJobResponse {
+    pub job_id: String,
+    pub status: ImportJobStatus,
+    pub format: ImportFormat,
+    pub total_rows: i64,
+    /// Rows validated and, if valid, created so far.
+    pub processed_rows: i64,
+    pub created: i64,
+    pub duplicates: i64,
+    pub invalid: i64,
+    /// The first 1000 invalid rows with the reason, in row order.
+    pub errors: Vec<ImportRowResult>,
+    /// Batches the created payments were placed in so far, oldest first. Filled in as the batch creator picks the
+    /// payments up, also after the job has completed.
+    pub batch_ids: Vec<String>,
+    /// Why the job failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+async fn load_import_job_response(conn: &mut SqliteConnection, job: ImportJob) -> Result<ImportJobResponse, ApiError> {
+    let errors = ImportJob::find_row_errors(conn, &job.id, MAX_REPORTED_ERRORS)
+        .await?
+        .into_iter()
+        .map(|row| ImportRowResult {
+            row: row.row_number as usize,
+            client_id: row.client_id,
+            status: ImportRowStatus::Invalid,
+            payment_id: row.payment_id,
+            error: row.error,
+        })
+        .collect();
+    let batch_ids = ImportJob::batch_ids(conn, &job.id).await?;
+
+    Ok(ImportJobResponse {
+        job_id: job.id,
+        status: job.status,
+        format: ImportFormat::from(job.format),
+        total_rows: job.total_rows,
+        processed_rows: job.processed_rows,
+        created: job.created,
+        duplicates: job.duplicates,
+        invalid: job.invalid,
+        errors,
+        batch_ids,
+        error_message: job.error_message,
+        created_at: job.created_at,
+        updated_at: job.updated_at,
+        finished_at: job.finished_at,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/import-jobs",
+    params(ImportQuery),
+    request_body(content = String, description = "CSV (with header) or NDJSON file of payments", content_type = "text/plain"),
+    responses(
+        (status = 202, description = "Import job queued", body = ImportJobResponse),
+        (status = 400, description = "Bad request (Unreadable file)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_create_import_job(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ImportJobResponse>), ApiError> {
+    // Unreadable files are rejected right away; the rows themselves are validated by the import job runner.
+    let total_rows = import::parse_rows(query.format, &body)?.len();
+
+    let mut conn = state.db_writer.acquire().await?;
+    let format = query.format.to_string();
+    let job = ImportJob::create(
+        &mut conn,
+        &format,
+        query.account_name.as_deref(),
+        client.accounts.as_deref(),
+        total_rows as i64,
+        &body,
+    )
+    .await?;
+    state.wakeups.import_job_runner.notify_one();
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(load_import_job_response(&mut conn, job).await?),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/import-jobs/{job_id}",
+    params(
+        ("job_id" = String, Path, description = "Job id returned when the file was submitted")
+    ),
+    responses(
+        (status = 200, description = "Progress and results of the import job", body = ImportJobResponse),
+        (status = 404, description = "Import job not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_import_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<ImportJobResponse>, ApiError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let job = ImportJob::find_by_id(&mut conn, &job_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Import job not found".to_string()))?;
+    Ok(Json(load_import_job_response(&mut conn, job).await?))
+}
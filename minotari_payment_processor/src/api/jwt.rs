@@ -0,0 +1,231 @@
+use anyhow::{Context, anyhow, bail};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+};
+use log::{info, warn};
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    api::auth::ApiClient,
+    config::{ApiRole, JwtConfig},
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+/// Signing keys are fetched again after this long, to pick up rotated keys.
+const JWKS_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// A token signed with an unknown key triggers a fetch, but at most this often, so forged tokens cannot flood the
+/// identity provider.
+const JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct CachedKeys {
+    keys: JwkSet,
+    /// Time of the last fetch attempt, successful or not.
+    checked_at: Option<Instant>,
+}
+
+impl CachedKeys {
+    fn is_fresh(&self) -> bool {
+        self.checked_at.is_some_and(|at| at.elapsed() < JWKS_MAX_AGE)
+    }
+
+    /// The key with ID `kid`. Tokens without a key ID are accepted if the set has a single key.
+    fn find(&self, kid: Option<&str>) -> Option<&Jwk> {
+        match kid {
+            Some(kid) => self.keys.find(kid),
+            None if self.keys.keys.len() == 1 => self.keys.keys.first(),
+            None => None,
+        }
+    }
+}
+
+/// Validates JWTs issued by `JWT_ISSUER` and maps their claims to a role and the accounts they may use.
+pub struct JwtValidator {
+    config: JwtConfig,
+    client: reqwest::Client,
+    keys: RwLock<CachedKeys>,
+    /// Held while the keys are fetched, so only one request waits for the identity provider at a time.
+    refresh: Mutex<()>,
+}
+
+impl JwtValidator {
+    pub fn new(config: JwtConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            keys: RwLock::new(CachedKeys {
+                keys: JwkSet { keys: Vec::new() },
+                checked_at: None,
+            }),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// Checks the signature, expiry, issuer and (if configured) audience of `token`. The token must be signed with an
+    /// algorithm of its key, not whichever one its header names.
+    pub async fn validate(&self, token: &str) -> Result<ApiClient, anyhow::Error> {
+        let header = decode_header(token).context("Malformed token")?;
+        let jwk = self.find_key(header.kid.as_deref()).await?;
+        let key = DecodingKey::from_jwk(&jwk).context("Unusable signing key")?;
+
+        let mut validation = Validation::default();
+        validation.algorithms = key_algorithms(&jwk)?;
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss"]);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = decode::<Map<String, Value>>(token, &key, &validation)?.claims;
+
+        let role = claim_values(&claims, &self.config.role_claim)
+            .iter()
+            .filter_map(|value| {
+                if self.config.role_mapping.is_empty() {
+                    value.parse::<ApiRole>().ok()
+                } else {
+                    self.config.role_mapping.get(value).copied()
+                }
+            })
+            .max()
+            .ok_or_else(|| anyhow!("The token grants no role in its '{}' claim", self.config.role_claim))?;
+        let accounts = self.config.accounts_claim.as_ref().and_then(|claim| {
+            let accounts: Vec<String> = claim_values(&claims, claim)
+                .into_iter()
+                .map(|account| account.to_lowercase())
+                .collect();
+            (!accounts.iter().any(|account| account == "*")).then_some(accounts)
+        });
+        let subject = claims.get("sub").and_then(Value::as_str).unwrap_or("-");
+
+        Ok(ApiClient {
+            key_name: Some(format!("jwt:{}", subject)),
+            role,
+            accounts,
+        })
+    }
+
+    async fn find_key(&self, kid: Option<&str>) -> Result<Jwk, anyhow::Error> {
+        let known = {
+            let cached = self.keys.read().await;
+            match cached.find(kid) {
+                Some(jwk) if cached.is_fresh() => return Ok(jwk.clone()),
+                known => known.cloned(),
+            }
+        };
+
+        // The keys are fetched without holding `keys`, so requests with a cached key are never held up by a slow
+        // identity provider. While another request refreshes stale keys, a known key is used as is.
+        let _refresh = match (self.refresh.try_lock(), known) {
+            (Ok(guard), _) => guard,
+            (Err(_), Some(jwk)) => return Ok(jwk),
+            (Err(_), None) => self.refresh.lock().await,
+        };
+        // Another request may have fetched the keys while this one waited for the lock.
+        let due = self
+            .keys
+            .read()
+            .await
+            .checked_at
+            .is_none_or(|at| at.elapsed() >= JWKS_MIN_REFRESH_INTERVAL);
+        if due {
+            let fetched = self.fetch_keys().await;
+            let mut cached = self.keys.write().await;
+            cached.checked_at = Some(Instant::now());
+            match fetched {
+                Ok(keys) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Fetched {} signing keys from {}.",
+                        keys.keys.len(),
+                        self.config.jwks_url
+                    );
+                    cached.keys = keys;
+                },
+                // The previous keys stay in use until a fetch succeeds.
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Failed to fetch signing keys from {}: {:#}",
+                    self.config.jwks_url,
+                    e
+                ),
+            }
+        }
+        self.keys
+            .read()
+            .await
+            .find(kid)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown signing key {}", kid.unwrap_or("(no key ID)")))
+    }
+
+    async fn fetch_keys(&self) -> Result<JwkSet, anyhow::Error> {
+        let keys = self
+            .client
+            .get(&self.config.jwks_url)
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        Ok(keys)
+    }
+}
+
+/// The algorithms a token signed with `jwk` may use: the key's `alg` if it has one, otherwise the signature algorithms
+/// of its key type. Keys from a JWKS are public keys, so symmetric (HMAC) keys are never accepted.
+fn key_algorithms(jwk: &Jwk) -> Result<Vec<Algorithm>, anyhow::Error> {
+    if let Some(alg) = &jwk.common.key_algorithm {
+        let alg = match alg {
+            KeyAlgorithm::RS256 => Algorithm::RS256,
+            KeyAlgorithm::RS384 => Algorithm::RS384,
+            KeyAlgorithm::RS512 => Algorithm::RS512,
+            KeyAlgorithm::PS256 => Algorithm::PS256,
+            KeyAlgorithm::PS384 => Algorithm::PS384,
+            KeyAlgorithm::PS512 => Algorithm::PS512,
+            KeyAlgorithm::ES256 => Algorithm::ES256,
+            KeyAlgorithm::ES384 => Algorithm::ES384,
+            KeyAlgorithm::EdDSA => Algorithm::EdDSA,
+            other => bail!("Signing key algorithm {:?} is not accepted", other),
+        };
+        return Ok(vec![alg]);
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Ok(vec![
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ]),
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Ok(vec![Algorithm::ES256]),
+            EllipticCurve::P384 => Ok(vec![Algorithm::ES384]),
+            ref curve => bail!("Signing key curve {:?} is not accepted", curve),
+        },
+        AlgorithmParameters::OctetKeyPair(params) if params.curve == EllipticCurve::Ed25519 => {
+            Ok(vec![Algorithm::EdDSA])
+        },
+        _ => bail!("Signing key type is not accepted"),
+    }
+}
+
+/// The values of a claim holding a string or a list of strings. Strings are split at whitespace, as in the `scope`
+/// claim of OAuth2 access tokens.
+fn claim_values(claims: &Map<String, Value>, claim: &str) -> Vec<String> {
+    let strings: Vec<&str> = match claims.get(claim) {
+        Some(Value::String(value)) => vec![value.as_str()],
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    strings
+        .into_iter()
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
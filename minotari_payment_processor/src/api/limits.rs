@@ -0,0 +1,68 @@
+use axum::{
+    BoxError, Router,
+    error_handling::HandleErrorLayer,
+    extract::Request,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use log::warn;
+use std::time::Duration;
+use tower::{ServiceBuilder, limit::GlobalConcurrencyLimitLayer, load_shed::LoadShedLayer};
+
+use crate::api::{AppState, error::ApiError};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api::limits";
+
+/// Clients are asked to retry after this long when a request was shed or timed out.
+const RETRY_AFTER_SECS: u64 = 1;
+
+/// Fails requests that take longer than `timeout` with `503 Service Unavailable`.
+///
+/// The handler is dropped when the timeout fires, which rolls back its open database transaction. Applied inside
+/// the idempotency layer, so a timed out request releases its Idempotency-Key and can be retried.
+pub fn with_timeout(routes: Router<AppState>, timeout: Duration) -> Router<AppState> {
+    routes.route_layer(middleware::from_fn(move |request: Request, next: Next| {
+        timeout_layer(timeout, request, next)
+    }))
+}
+
+async fn timeout_layer(timeout: Duration, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(
+                target: LOG_TARGET,
+                "{} {} timed out after {}s", method, path, timeout.as_secs()
+            );
+            ApiError::ServiceUnavailable {
+                code: "REQUEST_TIMEOUT",
+                message: format!("Request did not complete within {}s", timeout.as_secs()),
+                retry_after_secs: RETRY_AFTER_SECS,
+            }
+            .into_response()
+        },
+    }
+}
+
+/// Counts the requests of `routes` against `limit`, shared by every group it is applied to. Requests arriving while
+/// the limit is reached are rejected right away with `503 Service Unavailable` instead of queueing for a database
+/// connection.
+pub fn with_concurrency_limit(routes: Router<AppState>, limit: &GlobalConcurrencyLimitLayer) -> Router<AppState> {
+    routes.route_layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(overloaded))
+            .layer(LoadShedLayer::new())
+            .layer(limit.clone()),
+    )
+}
+
+async fn overloaded(error: BoxError) -> ApiError {
+    warn!(target: LOG_TARGET, "Shedding request: {}", error);
+    ApiError::ServiceUnavailable {
+        code: "OVERLOADED",
+        message: "Too many requests in flight, try again shortly".to_string(),
+        retry_after_secs: RETRY_AFTER_SECS,
+    }
+}
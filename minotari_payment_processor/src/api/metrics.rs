@@ -0,0 +1,99 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use std::fmt::Write;
+
+use crate::{api::AppState, workers::supervisor::WorkerStatus};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Worker metrics in the Prometheus text format", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn api_get_metrics(State(state): State<AppState>) -> Response {
+    let workers = state.worker_health.snapshot();
+    let mut body = String::new();
+
+    write_worker_metric(
+        &mut body,
+        "worker_running",
+        "gauge",
+        "Whether the worker task is alive.",
+        &workers,
+        |status| Some(u64::from(status.running) as f64),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_restarts_total",
+        "counter",
+        "Restarts of the worker after a panic.",
+        &workers,
+        |status| Some(status.restart_count as f64),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_cycles_total",
+        "counter",
+        "Completed worker cycles, successful or not.",
+        &workers,
+        |status| Some(status.cycles as f64),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_last_cycle_duration_seconds",
+        "gauge",
+        "Duration of the last completed cycle.",
+        &workers,
+        |status| status.last_cycle_duration_ms.map(|ms| ms as f64 / 1000.0),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_items_processed_total",
+        "counter",
+        "Items (e.g. batches or accounts) handled by the worker.",
+        &workers,
+        |status| Some(status.items_processed as f64),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_consecutive_failures",
+        "gauge",
+        "Cycles in a row that reported an error.",
+        &workers,
+        |status| Some(status.consecutive_failures as f64),
+    );
+    write_worker_metric(
+        &mut body,
+        "worker_last_success_timestamp_seconds",
+        "gauge",
+        "Unix time of the last cycle that completed without an error.",
+        &workers,
+        |status| status.last_success.map(|time| time.timestamp_millis() as f64 / 1000.0),
+    );
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response()
+}
+
+/// Appends one metric with a sample per worker. Workers without a value, e.g. before their first cycle, are skipped.
+fn write_worker_metric<'a>(
+    body: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    workers: impl IntoIterator<Item = (&'a &'static str, &'a WorkerStatus)>,
+    value: impl Fn(&WorkerStatus) -> Option<f64>,
+) {
+    let _ = writeln!(body, "# HELP payment_processor_{} {}", name, help);
+    let _ = writeln!(body, "# TYPE payment_processor_{} {}", name, metric_type);
+    for (worker, status) in workers {
+        if let Some(value) = value(status) {
+            let _ = writeln!(body, "payment_processor_{}{{worker=\"{}\"}} {}", name, worker, value);
+        }
+    }
+}
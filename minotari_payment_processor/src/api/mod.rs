@@ -1,22 +1,78 @@
 use axum::{
     Router,
-    extract::FromRef,
-    routing::{get, post},
+    extract::{DefaultBodyLimit, FromRef, Request},
+    middleware::{self, Next},
+    routing::{get, patch, post},
 };
+use minotari_client::apis::configuration::Configuration as MinotariConfiguration;
 use sqlx::SqlitePool;
+use std::{sync::Arc, time::Duration};
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower_http::compression::CompressionLayer;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::config::PaymentProcessorEnv;
+use crate::{
+    api::{jwt::JwtValidator, rate_limit::RateLimiter},
+    config::{ApiDocsMode, ApiRole, PaymentProcessorEnv},
+    workers::{
+        chain_state::ChainState, console_wallet_check::ConsoleWalletHealth, supervisor::WorkerHealth,
+        wakeup::WorkerWakeups,
+    },
+};
 
+mod access_log;
+mod accounts;
+mod admin;
+mod allowlist;
+mod audit;
+mod auth;
+mod body_limit;
+mod cors;
+mod dashboard;
 mod error;
+mod events;
+mod export;
+mod health;
+mod idempotency;
+pub(crate) mod import;
+mod import_jobs;
+mod jwt;
+mod limits;
+mod metrics;
+mod payment_groups;
 mod payments;
+mod rate_limit;
+mod stats;
 mod version;
 
+/// Actor recorded in the audit log for changes made through the HTTP API.
+pub(crate) const API_ACTOR: &str = "api";
+
+/// Timeout of payment imports and database backups, which work through a whole file or database before answering.
+const LONG_REQUEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Room for the response after the longest wait a client can ask for.
+const WAIT_REQUEST_TIMEOUT: Duration = Duration::from_secs(payments::MAX_WAIT_TIMEOUT.as_secs() + 10);
+
 #[derive(Clone)]
 pub struct AppState {
     pub db_pool: SqlitePool,
+    /// Single-connection pool the mutating endpoints write through, see `db::connect_writer`.
+    pub db_writer: SqlitePool,
     pub env: PaymentProcessorEnv,
+    pub worker_health: WorkerHealth,
+    /// Payment Receiver API client configuration.
+    pub client_config: Arc<MinotariConfiguration>,
+    /// Wakes workers before their next scheduled cycle.
+    pub wakeups: WorkerWakeups,
+    /// Result of the most recent console wallet check.
+    pub console_wallet_health: ConsoleWalletHealth,
+    /// Most recently observed chain tip.
+    pub chain_state: ChainState,
+    /// Limits payment creation per client. `None` if rate limiting is disabled.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// `None` if JWTs are not accepted.
+    pub jwt_validator: Option<Arc<JwtValidator>>,
 }
 
 impl FromRef<AppState> for SqlitePool {
@@ -25,26 +81,126 @@ impl FromRef<AppState> for SqlitePool {
     }
 }
 
+/// Extracts the writer pool of the `AppState`, for handlers that change the database.
+#[derive(Clone)]
+pub struct DbWriter(pub SqlitePool);
+
+impl FromRef<AppState> for DbWriter {
+    fn from_ref(state: &AppState) -> Self {
+        DbWriter(state.db_writer.clone())
+    }
+}
+
 #[derive(OpenApi)]
 #[openapi(
     paths(
         version::api_get_version,
+        health::api_get_readiness,
+        health::api_get_worker_health,
+        metrics::api_get_metrics,
         payments::api_create_payment,
         payments::api_create_payment_batch,
+        payments::api_append_batch_payments,
+        payments::api_estimate_payment_batch,
+        payment_groups::api_get_payment_group,
+        payments::api_list_batch_payments,
         payments::api_get_payment,
+        payments::api_amend_payment,
+        payments::api_wait_for_payment,
+        payments::api_list_payments,
+        export::api_export_payments,
+        export::api_export_ledger,
+        import::api_import_payments,
+        import::api_import_payments_stream,
+        import_jobs::api_create_import_job,
+        import_jobs::api_get_import_job,
         payments::api_cancel_payment,
+        payments::api_detach_payment,
+        payments::api_resubmit_payment,
+        payments::api_refund_payment,
+        payments::api_list_refunds,
+        admin::api_list_batches_needing_review,
+        admin::api_resolve_batch_confirmed,
+        admin::api_resolve_batch_failed,
+        admin::api_force_confirm_batch,
+        admin::api_force_fail_batch,
+        admin::api_reload_accounts,
+        admin::api_backup_database,
+        admin::api_list_signing_attempts,
+        admin::api_get_unsigned_batch,
+        admin::api_upload_signed_batch,
+        admin::api_list_batches,
+        admin::api_list_stuck_batches,
+        admin::api_get_pause_status,
+        admin::api_pause,
+        admin::api_resume,
+        admin::api_pause_account,
+        admin::api_resume_account,
+        events::api_list_events,
+        audit::api_list_audit_entries,
+        accounts::api_list_accounts,
+        accounts::api_get_account_balance,
+        accounts::api_create_account,
+        accounts::api_disable_account,
+        accounts::api_enable_account,
+        stats::api_get_stats,
     ),
     components(
         schemas(
             version::ServiceVersion,
+            health::ReadinessResponse,
+            health::WorkerHealthResponse,
+            crate::workers::supervisor::WorkerStatus,
+            crate::workers::console_wallet_check::ConsoleWalletStatus,
+            crate::workers::chain_state::ChainTip,
             payments::PaymentRequest,
             payments::BulkPaymentRequest,
             payments::BulkPaymentItem,
+            payments::AppendPaymentsRequest,
+            payments::AmendPaymentRequest,
             payments::BulkPaymentResponse,
+            payment_groups::PaymentGroupResponse,
+            payment_groups::PaymentGroupStatus,
+            payment_groups::PaymentGroupStatusSummary,
+            payments::BatchEstimateResponse,
             payments::PaymentResponse,
+            payments::PaymentListResponse,
             payments::PaymentCancelResponse,
+            payments::RefundRequest,
+            payments::RefundResponse,
+            import::ImportFormat,
+            import::ImportRowStatus,
+            import::ImportRowResult,
+            import::ImportReport,
+            import::ImportSummary,
+            import_jobs::ImportJobResponse,
+            crate::db::import_job::ImportJobStatus,
+            admin::PaymentBatchResponse,
+            admin::ResolveFailedRequest,
+            admin::ForceConfirmRequest,
+            admin::ForceFailRequest,
+            admin::ReloadAccountsResponse,
+            admin::PauseStatusResponse,
+            admin::SigningAttemptResponse,
+            accounts::AccountResponse,
+            accounts::LastConfirmedBatch,
+            accounts::AccountBalanceResponse,
+            accounts::CreateAccountRequest,
+            accounts::StoredAccountResponse,
+            events::EventResponse,
+            events::EventsResponse,
+            audit::AuditEntryResponse,
+            stats::StatsResponse,
+            stats::PaymentStatusStats,
+            stats::BatchStatusStats,
+            stats::AccountStats,
+            crate::db::event::EventType,
+            crate::db::audit_log::AuditAction,
             crate::db::payment::PaymentStatus,
+            crate::db::payment::PaymentPriority,
+            crate::db::payment_batch::PaymentBatchStatus,
             error::ApiError,
+            error::FieldError,
         )
     ),
     tags(
@@ -53,15 +209,239 @@ impl FromRef<AppState> for SqlitePool {
 )]
 pub struct ApiDoc;
 
-pub fn create_router(db_pool: SqlitePool, env: PaymentProcessorEnv) -> Router {
-    let app_state = AppState { db_pool, env };
+pub fn create_router(
+    db_pool: SqlitePool,
+    db_writer: SqlitePool,
+    env: PaymentProcessorEnv,
+    worker_health: WorkerHealth,
+    client_config: Arc<MinotariConfiguration>,
+    wakeups: WorkerWakeups,
+    console_wallet_health: ConsoleWalletHealth,
+    chain_state: ChainState,
+) -> Router {
+    let rate_limiter = env.rate_limit.map(|config| Arc::new(RateLimiter::new(config)));
+    let jwt_validator = env.jwt.clone().map(|config| Arc::new(JwtValidator::new(config)));
+    let api_docs = env.api_docs;
+    let max_request_body_bytes = env.max_request_body_bytes;
+    let request_timeout = env.request_timeout;
+    let concurrency_limit = env.max_concurrent_requests.map(GlobalConcurrencyLimitLayer::new);
+    let cors = env.cors.clone();
+    let app_state = AppState {
+        db_pool,
+        db_writer,
+        env,
+        worker_health,
+        client_config,
+        wakeups,
+        console_wallet_health,
+        chain_state,
+        rate_limiter,
+        jwt_validator,
+    };
 
-    Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
-        .route("/health/version", get(version::api_get_version))
+    let payment_creation_routes = Router::new()
         .route("/v1/payments", post(payments::api_create_payment))
         .route("/v1/payment-batches", post(payments::api_create_payment_batch))
+        .route("/v1/payments/{payment_id}/refund", post(payments::api_refund_payment))
+        .route(
+            "/v1/payments/{payment_id}/resubmit",
+            post(payments::api_resubmit_payment),
+        )
+        .route(
+            "/v1/payment-batches/{batch_id}/payments",
+            post(payments::api_append_batch_payments),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            rate_limit::rate_limit_layer,
+        ));
+
+    let docs_routes: Router<AppState> = match api_docs {
+        ApiDocsMode::Disabled => Router::new(),
+        ApiDocsMode::Public => {
+            Router::new().merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
+        },
+        ApiDocsMode::Protected => Router::new()
+            .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
+            .layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                allowlist::allowlist_layer,
+            )),
+    };
+
+    let read_routes = Router::new()
+        .route(
+            "/v1/payment-batches/estimate",
+            post(payments::api_estimate_payment_batch),
+        )
+        .route(
+            "/v1/payment-batches/{batch_id}/payments",
+            get(payments::api_list_batch_payments),
+        )
+        .route("/v1/payments/export", get(export::api_export_payments))
+        .route("/v1/ledger/export", get(export::api_export_ledger))
+        .route(
+            "/v1/payment-groups/{group_id}",
+            get(payment_groups::api_get_payment_group),
+        )
+        .route("/v1/payments", get(payments::api_list_payments))
         .route("/v1/payments/{payment_id}", get(payments::api_get_payment))
+        .route("/v1/payments/{payment_id}/refunds", get(payments::api_list_refunds))
+        .route("/v1/import-jobs/{job_id}", get(import_jobs::api_get_import_job))
+        .route("/v1/events", get(events::api_list_events))
+        .route("/v1/audit", get(audit::api_list_audit_entries))
+        .route("/v1/stats", get(stats::api_get_stats))
+        .route("/v1/accounts", get(accounts::api_list_accounts))
+        .route("/v1/accounts/{name}/balance", get(accounts::api_get_account_balance))
+        .route("/v1/admin/payment-batches", get(admin::api_list_batches))
+        .route("/v1/admin/payment-batches/stuck", get(admin::api_list_stuck_batches))
+        .route(
+            "/v1/admin/payment-batches/needs-review",
+            get(admin::api_list_batches_needing_review),
+        )
+        .route(
+            "/v1/admin/payment-batches/{batch_id}/signing-attempts",
+            get(admin::api_list_signing_attempts),
+        )
+        .route("/v1/admin/pause", get(admin::api_get_pause_status));
+
+    // Waiting clients hold no database connection between their checks, so they are not counted against the
+    // concurrency limit.
+    let wait_routes = Router::new().route("/v1/payments/{payment_id}/wait", get(payments::api_wait_for_payment));
+
+    let payment_routes = Router::new()
+        .merge(payment_creation_routes)
         .route("/v1/payments/{payment_id}/cancel", post(payments::api_cancel_payment))
-        .with_state(app_state)
+        .route("/v1/payments/{payment_id}/detach", post(payments::api_detach_payment))
+        .route("/v1/payments/{payment_id}", patch(payments::api_amend_payment));
+
+    let import_routes = Router::new()
+        .route(
+            "/v1/payments/import",
+            post(import::api_import_payments).layer(DefaultBodyLimit::max(import::MAX_IMPORT_BODY_BYTES)),
+        )
+        .route(
+            "/v1/import-jobs",
+            post(import_jobs::api_create_import_job).layer(DefaultBodyLimit::max(import::MAX_IMPORT_BODY_BYTES)),
+        );
+
+    // Streamed in both directions, so there is no body the idempotency store could buffer or replay. `guard` rather
+    // than `protect` keeps the route out of the idempotency layer. Its rows are idempotent through their client_id
+    // anyway.
+    let import_stream_routes =
+        Router::new().route("/v1/payments/import/stream", post(import::api_import_payments_stream));
+
+    let admin_routes = Router::new()
+        .route("/v1/accounts", post(accounts::api_create_account))
+        .route("/v1/accounts/{name}/disable", post(accounts::api_disable_account))
+        .route("/v1/accounts/{name}/enable", post(accounts::api_enable_account))
+        .route(
+            "/v1/admin/payment-batches/{batch_id}/resolve-confirmed",
+            post(admin::api_resolve_batch_confirmed),
+        )
+        .route(
+            "/v1/admin/payment-batches/{batch_id}/resolve-failed",
+            post(admin::api_resolve_batch_failed),
+        )
+        .route(
+            "/v1/admin/payment-batches/{batch_id}/force-confirm",
+            post(admin::api_force_confirm_batch),
+        )
+        .route(
+            "/v1/admin/payment-batches/{batch_id}/force-fail",
+            post(admin::api_force_fail_batch),
+        )
+        .route(
+            "/v1/payment-batches/{batch_id}/unsigned",
+            get(admin::api_get_unsigned_batch),
+        )
+        .route(
+            "/v1/payment-batches/{batch_id}/signed",
+            post(admin::api_upload_signed_batch),
+        )
+        .route("/v1/admin/reload-accounts", post(admin::api_reload_accounts))
+        .route("/v1/admin/pause", post(admin::api_pause))
+        .route("/v1/admin/resume", post(admin::api_resume))
+        .route("/v1/admin/accounts/{name}/pause", post(admin::api_pause_account))
+        .route("/v1/admin/accounts/{name}/resume", post(admin::api_resume_account));
+
+    let backup_routes = Router::new().route("/v1/admin/backup", get(admin::api_backup_database));
+
+    let limit = concurrency_limit.as_ref();
+    let long_timeout = LONG_REQUEST_TIMEOUT.max(request_timeout);
+    let wait_timeout = WAIT_REQUEST_TIMEOUT.max(request_timeout);
+    let v1_routes = Router::new()
+        .merge(protect(read_routes, &app_state, ApiRole::ReadOnly, request_timeout, limit))
+        .merge(protect(wait_routes, &app_state, ApiRole::ReadOnly, wait_timeout, None))
+        .merge(protect(payment_routes, &app_state, ApiRole::PaymentCreator, request_timeout, limit))
+        .merge(protect(import_routes, &app_state, ApiRole::PaymentCreator, long_timeout, limit))
+        .merge(guard(import_stream_routes, ApiRole::PaymentCreator, limit))
+        .merge(protect(admin_routes, &app_state, ApiRole::Admin, request_timeout, limit))
+        .merge(protect(backup_routes, &app_state, ApiRole::Admin, long_timeout, limit))
+        // Payment imports keep their own, larger limit.
+        .layer(DefaultBodyLimit::max(max_request_body_bytes))
+        .layer(middleware::from_fn_with_state(app_state.clone(), auth::auth_layer))
+        // Outermost, so rejected clients do not reach the idempotency store.
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            allowlist::allowlist_layer,
+        ));
+
+    // The page itself carries no data; the API calls it makes are authenticated like any other.
+    let dashboard_routes = Router::new()
+        .route("/dashboard", get(dashboard::api_get_dashboard))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            allowlist::allowlist_layer,
+        ));
+
+    let router = Router::new()
+        .merge(docs_routes)
+        .route("/health/version", get(version::api_get_version))
+        .route("/health/ready", get(health::api_get_readiness))
+        .route("/health/workers", get(health::api_get_worker_health))
+        .route("/metrics", get(metrics::api_get_metrics))
+        .merge(v1_routes)
+        .merge(dashboard_routes)
+        .layer(middleware::from_fn(body_limit::payload_too_large_layer))
+        .layer(middleware::from_fn(access_log::access_log_layer))
+        // Only applied if the client sends a matching Accept-Encoding.
+        .layer(CompressionLayer::new())
+        .with_state(app_state);
+
+    // Outermost, so preflight requests, which carry no credentials, are answered before authentication.
+    match &cors {
+        Some(config) => router.layer(cors::cors_layer(config)),
+        None => router,
+    }
+}
+
+/// Requires `role` for every route of `routes` and bounds how long and how many of their requests run.
+///
+/// From the outside in, a request passes the role check, the concurrency limit (if any), the idempotency store and
+/// the timeout. A request rejected by the role check or shed by the limit neither reserves its Idempotency-Key nor
+/// has its rejection replayed later, and one that times out releases its key again.
+fn protect(
+    routes: Router<AppState>,
+    state: &AppState,
+    role: ApiRole,
+    timeout: Duration,
+    limit: Option<&GlobalConcurrencyLimitLayer>,
+) -> Router<AppState> {
+    let routes = limits::with_timeout(routes, timeout).route_layer(middleware::from_fn_with_state(
+        state.clone(),
+        idempotency::idempotency_layer,
+    ));
+    guard(routes, role, limit)
+}
+
+/// Requires `role` for every route of `routes` and counts their requests against the concurrency limit, if any.
+fn guard(routes: Router<AppState>, role: ApiRole, limit: Option<&GlobalConcurrencyLimitLayer>) -> Router<AppState> {
+    let routes = match limit {
+        Some(limit) => limits::with_concurrency_limit(routes, limit),
+        None => routes,
+    };
+    routes.route_layer(middleware::from_fn(move |request: Request, next: Next| {
+        auth::require_role(role, request, next)
+    }))
 }
@@ -0,0 +1,115 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
+
+use crate::{
+    api::error::ApiError,
+    db::{
+        payment::PaymentStatus,
+        payment_group::{PaymentGroup, PaymentGroupStatusCount},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentGroupStatus {
+    /// Some payments are not CONFIRMED, FAILED or CANCELLED yet.
+    InProgress,
+    /// Every payment was confirmed (or settled).
+    Confirmed,
+    /// Every payment is final, but some failed or were cancelled.
+    CompletedWithFailures,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PaymentGroupStatusSummary {
+    pub status: PaymentStatus,
+    pub payment_count: i64,
+    /// Sum of the amounts, in µT.
+    pub total_amount: i64,
+}
+
+impl From<PaymentGroupStatusCount> for PaymentGroupStatusSummary {
+    fn from(count: PaymentGroupStatusCount) -> Self {
+        PaymentGroupStatusSummary {
+            status: count.status,
+            payment_count: count.payment_count,
+            total_amount: count.total_amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PaymentGroupResponse {
+    pub group_id: String,
+    pub account_name: String,
+    pub status: PaymentGroupStatus,
+    pub payment_count: i64,
+    /// Sum of all amounts, in µT.
+    pub total_amount: i64,
+    /// Payments per status. Statuses without payments are omitted.
+    pub statuses: Vec<PaymentGroupStatusSummary>,
+    /// Batches created for the group's payments so far, oldest first.
+    pub batch_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Aggregates the current state of the group's payments.
+pub(super) async fn load_payment_group_response(
+    conn: &mut SqliteConnection,
+    group: PaymentGroup,
+) -> Result<PaymentGroupResponse, ApiError> {
+    let statuses: Vec<PaymentGroupStatusSummary> = PaymentGroup::status_counts(conn, &group.id)
+        .await?
+        .into_iter()
+        .map(PaymentGroupStatusSummary::from)
+        .collect();
+    let batch_ids = PaymentGroup::batch_ids(conn, &group.id).await?;
+
+    let status = if !statuses.iter().all(|summary| summary.status.is_final()) {
+        PaymentGroupStatus::InProgress
+    } else if statuses.iter().all(|summary| summary.status.is_paid()) {
+        PaymentGroupStatus::Confirmed
+    } else {
+        PaymentGroupStatus::CompletedWithFailures
+    };
+
+    Ok(PaymentGroupResponse {
+        group_id: group.id,
+        account_name: group.account_name,
+        status,
+        payment_count: statuses.iter().map(|summary| summary.payment_count).sum(),
+        total_amount: statuses.iter().map(|summary| summary.total_amount).sum(),
+        statuses,
+        batch_ids,
+        created_at: group.created_at,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payment-groups/{group_id}",
+    params(
+        ("group_id" = String, Path, description = "Group id returned when the bulk request was split")
+    ),
+    responses(
+        (status = 200, description = "Aggregate status of the group's payments", body = PaymentGroupResponse),
+        (status = 404, description = "Payment group not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_payment_group(
+    State(db_pool): State<SqlitePool>,
+    Path(group_id): Path<String>,
+) -> Result<Json<PaymentGroupResponse>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+    let group = PaymentGroup::find_by_id(&mut conn, &group_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment group not found".to_string()))?;
+    Ok(Json(load_payment_group_response(&mut conn, group).await?))
+}
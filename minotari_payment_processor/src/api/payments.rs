@@ -1,38 +1,129 @@
 use axum::{
-    Json,
-    extract::{Path, State},
+    Extension, Json,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
-use utoipa::ToSchema;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool, Transaction};
+use std::collections::HashMap;
+use tari_common::configuration::Network;
+use tari_common_types::tari_address::{TariAddress, TariAddressFeatures};
+use tari_transaction_components::transaction_components::{MemoField, memo_field::TxType};
+use tokio::time::{self, Duration};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
     MAX_BATCH_SIZE,
-    api::{AppState, error::ApiError},
+    amount::PaymentAmount,
+    api::{
+        API_ACTOR, AppState, DbWriter,
+        auth::ApiClient,
+        error::{ApiError, ValidationErrors},
+        payment_groups::load_payment_group_response,
+    },
+    config::{PaymentAmountLimits, PaymentProcessorEnv},
     db::{
-        payment::{Payment, PaymentStatus},
-        payment_batch::PaymentBatch,
+        audit_log::{AuditAction, AuditEntry},
+        payment::{Payment, PaymentPriority, PaymentStatus},
+        payment_batch::{BatchPayload, PaymentBatch},
+        payment_group::PaymentGroup,
+        payment_tag::PaymentTag,
+        refund::Refund,
     },
+    payment_receiver,
+    workers::unsigned_tx_creator,
 };
 
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const MAX_LIST_LIMIT: i64 = 1000;
+const MAX_TAG_LENGTH: usize = 64;
+const MAX_TAGS_PER_PAYMENT: usize = 16;
+/// Memos are stored in the encrypted data of the recipient's output, which has little room to spare.
+const MAX_MEMO_BYTES: usize = 256;
+/// Largest bulk request accepted with `split`.
+const MAX_SPLIT_REQUEST_SIZE: usize = 10_000;
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const MAX_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+const WAIT_RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct PaymentRequest {
     pub client_id: String, // Idempotency key
     pub account_name: String,
     pub recipient_address: String,
-    pub amount: i64,
+    #[schema(value_type = u64, minimum = 1)]
+    pub amount: PaymentAmount,
+    pub payment_id: Option<String>, // Payment Memo
+    /// If the payment has not been batched by this time, it is cancelled automatically.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Defaults to NORMAL. Higher priority payments are batched first.
+    #[serde(default)]
+    pub priority: PaymentPriority,
+    /// Labels for grouping payments, e.g. the payout run they belong to.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Address a refund of this payment is sent to when the refund request does not name one.
+    pub refund_address: Option<String>,
+}
+
+/// Fields of a `RECEIVED` payment to change. Omitted fields keep their value.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AmendPaymentRequest {
+    pub recipient_address: Option<String>,
+    #[schema(value_type = Option<u64>, minimum = 1)]
+    pub amount: Option<PaymentAmount>,
     pub payment_id: Option<String>, // Payment Memo
 }
 
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RefundRequest {
+    pub client_id: String, // Idempotency key
+    /// Defaults to the `refund_address` given when the original payment was created.
+    pub recipient_address: Option<String>,
+    /// Defaults to the full amount of the original payment.
+    #[schema(value_type = Option<u64>, minimum = 1)]
+    pub amount: Option<PaymentAmount>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RefundResponse {
+    /// The payment that carries the refund.
+    pub refund_payment_id: String,
+    pub original_payment_id: String,
+    /// Status of the refund payment.
+    pub status: PaymentStatus,
+    pub recipient_address: String,
+    #[schema(value_type = u64, minimum = 1)]
+    pub amount: PaymentAmount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Refund> for RefundResponse {
+    fn from(refund: Refund) -> Self {
+        RefundResponse {
+            refund_payment_id: refund.refund_payment_id,
+            original_payment_id: refund.original_payment_id,
+            status: refund.status,
+            recipient_address: refund.recipient_address,
+            amount: refund.amount,
+            reason: refund.reason,
+            created_at: refund.created_at,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct BulkPaymentItem {
     pub client_id: String, // Idempotency key
     pub recipient_address: String,
-    pub amount: i64,
+    #[schema(value_type = u64, minimum = 1)]
+    pub amount: PaymentAmount,
     pub payment_id: Option<String>, // Payment Memo
 }
 
@@ -40,6 +131,24 @@ pub struct BulkPaymentItem {
 pub struct BulkPaymentRequest {
     pub account_name: String,
     pub items: Vec<BulkPaymentItem>,
+    /// Labels attached to every payment of the batch.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Queue the payments instead of creating a single batch, and let the batch creator split them into batches of
+    /// up to 100 payments. Allows up to 10000 items. The response is then a payment group, see
+    /// `GET /v1/payment-groups/{group_id}`.
+    #[serde(default)]
+    pub split: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AppendPaymentsRequest {
+    /// Account of the batch.
+    pub account_name: String,
+    pub items: Vec<BulkPaymentItem>,
+    /// Labels attached to every appended payment.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, ToSchema)]
@@ -50,6 +159,54 @@ pub struct BulkPaymentResponse {
     pub payments: Vec<PaymentResponse>,
 }
 
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ListPaymentsQuery {
+    pub account_name: Option<String>,
+    pub status: Option<PaymentStatus>,
+    /// Only payments carrying this tag.
+    pub tag: Option<String>,
+    /// Continue after this payment, i.e. the `next_cursor` of the previous page.
+    pub after: Option<String>,
+    /// Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PaymentListResponse {
+    pub payments: Vec<PaymentResponse>,
+    /// Pass as `after` to fetch the next page. Absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct EstimateQuery {
+    /// Number of UTXOs expected to fund the batch. The Payment Receiver only selects UTXOs when funds are
+    /// locked, so the estimate cannot determine it on its own. Defaults to 1.
+    pub input_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchEstimateResponse {
+    pub account_name: String,
+    pub payment_count: usize,
+    /// Sum of the payment amounts, in µT.
+    pub total_amount: u64,
+    /// Number of UTXOs the estimate assumes.
+    pub input_count: usize,
+    /// Number of transactions needed, including consolidation transactions.
+    pub step_count: usize,
+    /// Whether a consolidation cycle runs before the payments are sent.
+    pub requires_consolidation: bool,
+    /// Estimated total fee of all transactions, in µT.
+    pub estimated_fee: u64,
+    /// Amount that would be locked, i.e. the payment total plus the account's fee buffer, in µT.
+    pub amount_to_lock: u64,
+    /// Spendable balance reported by the Payment Receiver, in µT.
+    pub available_balance: i64,
+    pub sufficient_funds: bool,
+}
+
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct PaymentResponse {
     pub payment_id: String,
@@ -57,28 +214,72 @@ pub struct PaymentResponse {
     pub client_id: String,
     pub account_name: String,
     pub recipient_address: String,
-    pub amount: i64,
+    #[schema(value_type = u64, minimum = 1)]
+    pub amount: PaymentAmount,
+    pub priority: PaymentPriority,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payref: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failure_reason: Option<String>,
+    /// The batch the payment was added to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+    /// Tari transaction id of the transaction paying the recipient, once it has been signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<u64>,
+    /// Hex encoded public nonce of the kernel excess signature, once the transaction has been signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel_excess_public_nonce: Option<String>,
+    /// Hex encoded kernel excess signature, once the transaction has been signed. Together with the public nonce
+    /// it identifies the transaction on a block explorer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel_excess_signature: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mined_height: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mined_header_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mined_timestamp: Option<i64>,
+    /// Confirmations of the paying transaction as last observed by the confirmation checker, once it is mined.
+    /// Updated every confirmation checker cycle until the payment is confirmed, or settled if settlement is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<i64>,
+    /// Chain tip height at which `confirmations` was observed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tip_height: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Set if this payment is a refund of another payment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_of: Option<String>,
+    /// Set if this payment resubmits a failed payment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resubmitted_from: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl PaymentResponse {
     pub fn from_payment_and_batch(payment: Payment, payment_batch: Option<PaymentBatch>) -> Self {
-        let (mined_height, mined_header_hash, mined_timestamp) = if let Some(batch) = payment_batch {
-            (batch.mined_height, batch.mined_header_hash, batch.mined_timestamp)
-        } else {
-            (None, None, None)
-        };
+        let transaction = payment_batch
+            .as_ref()
+            .and_then(|batch| batch.signed_tx_json.as_deref())
+            .and_then(|json| BatchPayload::from_json(json).ok())
+            .and_then(|payload| payload.payout_transaction(&payment.id).ok().flatten());
+        let (mined_height, mined_header_hash, mined_timestamp, confirmations, tip_height) =
+            if let Some(batch) = payment_batch {
+                (
+                    batch.mined_height,
+                    batch.mined_header_hash,
+                    batch.mined_timestamp,
+                    batch.confirmations,
+                    batch.tip_height,
+                )
+            } else {
+                (None, None, None, None, None)
+            };
 
         PaymentResponse {
             payment_id: payment.id,
@@ -87,15 +288,41 @@ impl PaymentResponse {
             account_name: payment.account_name,
             recipient_address: payment.recipient_address,
             amount: payment.amount,
+            priority: payment.priority,
             payref: payment.payref,
             failure_reason: payment.failure_reason,
+            batch_id: payment.payment_batch_id,
+            tx_id: transaction.as_ref().map(|tx| tx.tx_id.as_u64()),
+            kernel_excess_public_nonce: transaction.as_ref().map(|tx| tx.excess_public_nonce.clone()),
+            kernel_excess_signature: transaction.map(|tx| tx.excess_signature),
             mined_height,
             mined_header_hash,
             mined_timestamp,
+            confirmations,
+            tip_height,
+            expires_at: payment.expires_at,
+            tags: Vec::new(),
+            refund_of: None,
+            resubmitted_from: None,
             created_at: payment.created_at,
             updated_at: payment.updated_at,
         }
     }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_refund_of(mut self, refund_of: Option<String>) -> Self {
+        self.refund_of = refund_of;
+        self
+    }
+
+    pub fn with_resubmitted_from(mut self, resubmitted_from: Option<String>) -> Self {
+        self.resubmitted_from = resubmitted_from;
+        self
+    }
 }
 
 impl From<Payment> for PaymentResponse {
@@ -117,48 +344,114 @@ pub struct PaymentCancelResponse {
     responses(
         (status = 202, description = "Payment request accepted for processing", body = PaymentResponse),
         (status = 200, description = "Payment request already exists (idempotent)", body = PaymentResponse),
-        (status = 400, description = "Bad request (Invalid amount or Account not found)", body = ApiError),
-        (status = 500, description = "Internal server error", body = ApiError)
+        (status = 422, description = "Invalid fields, all listed in `errors`", body = ApiError),
+        (status = 429, description = "Rate limit exceeded, retry after the time in the Retry-After header", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError),
+        (status = 503, description = "Backlog too large (code BACKLOG_FULL), retry after the time in the Retry-After header", body = ApiError)
     )
 )]
 pub async fn api_create_payment(
     State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
     Json(request): Json<PaymentRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
-    if !state.env.accounts.contains_key(&request.account_name.to_lowercase()) {
-        return Err(ApiError::BadRequest(format!(
-            "Account '{}' not found in configuration",
-            request.account_name
-        )));
+    client.check_account(&request.account_name)?;
+    let mut errors = ValidationErrors::default();
+    if !state
+        .env
+        .accounts
+        .current()
+        .contains_key(&request.account_name.to_lowercase())
+    {
+        errors.add(
+            "account_name",
+            format!("Account '{}' not found in configuration", request.account_name),
+        );
     }
-
-    if request.amount <= 0 {
-        return Err(ApiError::BadRequest("Amount must be positive".to_string()));
+    errors.check(
+        "amount",
+        validate_amount(
+            request.amount,
+            &state.env.payment_amount_limits_for(&request.account_name),
+        ),
+    );
+    if let Some(memo) = &request.payment_id {
+        errors.check("payment_id", validate_memo(memo));
     }
+    let tags = errors.check("tags", normalize_tags(&request.tags));
+    let recipient_address = errors.check(
+        "recipient_address",
+        validate_recipient_address(&request.recipient_address, state.env.tari_network),
+    );
+    let refund_address = match request.refund_address.as_deref() {
+        Some(address) => errors.check(
+            "refund_address",
+            validate_recipient_address(address, state.env.tari_network).map(Some),
+        ),
+        None => Some(None),
+    };
+    if let Some(expires_at) = request.expires_at
+        && expires_at <= Utc::now()
+    {
+        errors.add("expires_at", "expires_at must be in the future");
+    }
+    errors.into_result()?;
+    let (Some(tags), Some(recipient_address), Some(refund_address)) = (tags, recipient_address, refund_address) else {
+        unreachable!("validation failures are returned above");
+    };
 
-    let mut transaction = state.db_pool.begin().await?;
+    let mut transaction = state.db_writer.begin().await?;
 
     if let Some(existing_payment) =
         Payment::get_by_client_id(&mut transaction, &request.client_id, &request.account_name).await?
     {
+        let existing_tags = PaymentTag::find_by_payment_id(&mut transaction, &existing_payment.id).await?;
         transaction.commit().await?;
-        return Ok((StatusCode::OK, Json(PaymentResponse::from(existing_payment))));
+        return Ok((
+            StatusCode::OK,
+            Json(PaymentResponse::from(existing_payment).with_tags(existing_tags)),
+        ));
     }
 
+    check_backlog(&mut transaction, &state.env).await?;
+
     let new_payment = Payment::create(
         &mut transaction,
         &request.client_id,
         &request.account_name,
-        &request.recipient_address,
+        &recipient_address,
         request.amount,
         request.payment_id,
         None,
+        request.expires_at,
+        request.priority,
+    )
+    .await?;
+    PaymentTag::add(&mut transaction, &new_payment.id, &tags).await?;
+    if let Some(refund_address) = &refund_address {
+        Payment::set_refund_address(&mut transaction, &new_payment.id, refund_address).await?;
+    }
+    AuditEntry::record(
+        &mut transaction,
+        API_ACTOR,
+        AuditAction::PaymentCreated,
+        &new_payment.id,
+        None,
+        Some(&new_payment.status.to_string()),
+        None,
     )
     .await?;
 
     transaction.commit().await?;
 
-    Ok((StatusCode::ACCEPTED, Json(PaymentResponse::from(new_payment))))
+    if new_payment.priority == PaymentPriority::High && state.env.batch_creator_dedicated_high_priority_batches {
+        state.wakeups.batch_creator.notify_one();
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(PaymentResponse::from(new_payment).with_tags(tags)),
+    ))
 }
 
 #[utoipa::path(
@@ -166,48 +459,32 @@ pub async fn api_create_payment(
     path = "/v1/payment-batches",
     request_body = BulkPaymentRequest,
     responses(
-        (status = 202, description = "Bulk payment batch created successfully", body = BulkPaymentResponse),
-        (status = 200, description = "Bulk payment batch already exists (idempotent)", body = BulkPaymentResponse),
-        (status = 400, description = "Bad request (Account not found, limits exceeded, or duplicate payments)", body = ApiError),
-        (status = 500, description = "Internal server error", body = ApiError)
+        (status = 202, description = "Bulk payment batch created successfully, or a payment group if `split` was requested", body = BulkPaymentResponse),
+        (status = 200, description = "Bulk payment batch or payment group already exists (idempotent)", body = BulkPaymentResponse),
+        (status = 400, description = "Bad request (Payments already exist outside this batch)", body = ApiError),
+        (status = 422, description = "Invalid fields of the request or its items, all listed in `errors`", body = ApiError),
+        (status = 429, description = "Rate limit exceeded, retry after the time in the Retry-After header", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError),
+        (status = 503, description = "Backlog too large (code BACKLOG_FULL), retry after the time in the Retry-After header", body = ApiError)
     )
 )]
 pub async fn api_create_payment_batch(
     State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
     Json(request): Json<BulkPaymentRequest>,
-) -> Result<impl IntoResponse, ApiError> {
-    if !state.env.accounts.contains_key(&request.account_name.to_lowercase()) {
-        return Err(ApiError::BadRequest(format!(
-            "Account '{}' not found in configuration",
-            request.account_name
-        )));
-    }
-
-    if request.items.is_empty() {
-        return Err(ApiError::BadRequest("Batch cannot be empty".to_string()));
-    }
-
-    if request.items.len() > MAX_BATCH_SIZE {
-        return Err(ApiError::BadRequest(format!(
-            "Batch size exceeds limit of {}",
-            MAX_BATCH_SIZE
-        )));
-    }
-
-    for (idx, item) in request.items.iter().enumerate() {
-        if item.amount <= 0 {
-            return Err(ApiError::BadRequest(format!(
-                "Item at index {} has invalid amount",
-                idx
-            )));
-        }
-    }
+) -> Result<Response, ApiError> {
+    client.check_account(&request.account_name)?;
+    let (recipient_addresses, tags) = validate_bulk_request(&state.env, &request)?;
 
-    let mut tx = state.db_pool.begin().await?;
+    let mut tx = state.db_writer.begin().await?;
 
     let item_client_ids: Vec<String> = request.items.iter().map(|i| i.client_id.clone()).collect();
     let existing_payments = Payment::find_by_client_ids(&mut tx, &item_client_ids, &request.account_name).await?;
 
+    if request.split {
+        return create_payment_group(&state, tx, request, recipient_addresses, tags, existing_payments).await;
+    }
+
     if existing_payments.len() == request.items.len() {
         let first_batch_id = existing_payments[0].payment_batch_id.clone();
 
@@ -218,8 +495,15 @@ pub async fn api_create_payment_batch(
                 .await?
                 .ok_or_else(|| ApiError::InternalServerError("Referenced batch not found".to_string()))?;
 
-            let response_payments: Vec<PaymentResponse> =
-                existing_payments.into_iter().map(PaymentResponse::from).collect();
+            let existing_ids: Vec<String> = existing_payments.iter().map(|p| p.id.clone()).collect();
+            let mut existing_tags = PaymentTag::find_by_payment_ids(&mut tx, &existing_ids).await?;
+            let response_payments: Vec<PaymentResponse> = existing_payments
+                .into_iter()
+                .map(|p| {
+                    let tags = existing_tags.remove(&p.id).unwrap_or_default();
+                    PaymentResponse::from(p).with_tags(tags)
+                })
+                .collect();
 
             let response = BulkPaymentResponse {
                 batch_id: batch.id,
@@ -229,7 +513,7 @@ pub async fn api_create_payment_batch(
             };
 
             tx.commit().await?;
-            return Ok((StatusCode::OK, Json(response)));
+            return Ok((StatusCode::OK, Json(response)).into_response());
         } else {
             return Err(ApiError::BadRequest(
                 "Duplicate payments found, but they do not form a single consistent batch.".to_string(),
@@ -245,18 +529,33 @@ pub async fn api_create_payment_batch(
         )));
     }
 
+    check_backlog(&mut tx, &state.env).await?;
+
     let mut created_payments = Vec::new();
     let mut payment_ids_for_batch = Vec::new();
 
-    for item in request.items {
+    for (item, recipient_address) in request.items.into_iter().zip(recipient_addresses) {
         let new_payment = Payment::create(
             &mut tx,
             &item.client_id,
             &request.account_name,
-            &item.recipient_address,
+            &recipient_address,
             item.amount,
             item.payment_id,
             None,
+            None,
+            PaymentPriority::default(),
+        )
+        .await?;
+        PaymentTag::add(&mut tx, &new_payment.id, &tags).await?;
+        AuditEntry::record(
+            &mut tx,
+            API_ACTOR,
+            AuditAction::PaymentCreated,
+            &new_payment.id,
+            None,
+            Some(&new_payment.status.to_string()),
+            None,
         )
         .await?;
 
@@ -268,19 +567,26 @@ pub async fn api_create_payment_batch(
 
     let batch = PaymentBatch::create_with_payments(
         &mut tx,
+        API_ACTOR,
         &request.account_name,
         &pr_idempotency_key,
         &payment_ids_for_batch,
+        None,
+        state.env.required_confirmations_for(&request.account_name),
     )
     .await?;
 
     tx.commit().await?;
+    state.wakeups.unsigned_tx_creator.notify_one();
 
     for p in &mut created_payments {
         p.status = PaymentStatus::Batched;
         p.payment_batch_id = Some(batch.id.clone());
     }
-    let response_payments: Vec<PaymentResponse> = created_payments.into_iter().map(PaymentResponse::from).collect();
+    let response_payments: Vec<PaymentResponse> = created_payments
+        .into_iter()
+        .map(|p| PaymentResponse::from(p).with_tags(tags.clone()))
+        .collect();
 
     let response = BulkPaymentResponse {
         batch_id: batch.id,
@@ -289,7 +595,299 @@ pub async fn api_create_payment_batch(
         payments: response_payments,
     };
 
-    Ok((StatusCode::ACCEPTED, Json(response)))
+    Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payment-batches/{batch_id}/payments",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    request_body = AppendPaymentsRequest,
+    responses(
+        (status = 202, description = "Payments created and added to the batch. Only the added payments are listed.", body = BulkPaymentResponse),
+        (status = 200, description = "Payments were already added to this batch (idempotent)", body = BulkPaymentResponse),
+        (status = 400, description = "Bad request (Payments already exist outside this batch, or the batch belongs to another account)", body = ApiError),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 409, description = "The batch no longer accepts payments, as creating its transaction has started", body = ApiError),
+        (status = 422, description = "Invalid fields of the request or its items, or the batch would exceed its size limit", body = ApiError),
+        (status = 429, description = "Rate limit exceeded, retry after the time in the Retry-After header", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError),
+        (status = 503, description = "Backlog too large (code BACKLOG_FULL), retry after the time in the Retry-After header", body = ApiError)
+    )
+)]
+pub async fn api_append_batch_payments(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Path(batch_id): Path<String>,
+    Json(request): Json<AppendPaymentsRequest>,
+) -> Result<Response, ApiError> {
+    client.check_account(&request.account_name)?;
+    let (recipient_addresses, tags) = validate_bulk_items(
+        &state.env,
+        &request.account_name,
+        &request.items,
+        &request.tags,
+        MAX_BATCH_SIZE,
+    )?;
+
+    let mut tx = state.db_writer.begin().await?;
+
+    let batch = PaymentBatch::find_by_id(&mut tx, &batch_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment batch not found".to_string()))?;
+    if !batch.account_name.eq_ignore_ascii_case(&request.account_name) {
+        return Err(ApiError::BadRequest(format!(
+            "Batch belongs to account '{}'",
+            batch.account_name
+        )));
+    }
+
+    let item_client_ids: Vec<String> = request.items.iter().map(|i| i.client_id.clone()).collect();
+    let existing_payments = Payment::find_by_client_ids(&mut tx, &item_client_ids, &request.account_name).await?;
+    if !existing_payments.is_empty() {
+        let all_in_batch = existing_payments
+            .iter()
+            .all(|p| p.payment_batch_id.as_deref() == Some(batch.id.as_str()));
+        if existing_payments.len() != request.items.len() {
+            return Err(ApiError::BadRequest(format!(
+                "Request contains {} duplicate client_ids (out of {}). Partial appends are not allowed.",
+                existing_payments.len(),
+                request.items.len()
+            )));
+        }
+        if !all_in_batch {
+            return Err(ApiError::BadRequest(
+                "Duplicate payments found, but they do not belong to this batch.".to_string(),
+            ));
+        }
+
+        let existing_ids: Vec<String> = existing_payments.iter().map(|p| p.id.clone()).collect();
+        let mut existing_tags = PaymentTag::find_by_payment_ids(&mut tx, &existing_ids).await?;
+        let payments = existing_payments
+            .into_iter()
+            .map(|p| {
+                let tags = existing_tags.remove(&p.id).unwrap_or_default();
+                PaymentResponse::from(p).with_tags(tags)
+            })
+            .collect();
+        tx.commit().await?;
+        let response = BulkPaymentResponse {
+            batch_id: batch.id,
+            account_name: batch.account_name,
+            status: batch.status.to_string(),
+            payments,
+        };
+        return Ok((StatusCode::OK, Json(response)).into_response());
+    }
+
+    let batch_size = Payment::find_by_batch_id(&mut tx, &batch.id).await?.len();
+    if batch_size + request.items.len() > MAX_BATCH_SIZE {
+        let mut errors = ValidationErrors::default();
+        errors.add(
+            "items",
+            format!(
+                "Batch holds {} payments, adding {} exceeds the limit of {}",
+                batch_size,
+                request.items.len(),
+                MAX_BATCH_SIZE
+            ),
+        );
+        errors.into_result()?;
+    }
+
+    check_backlog(&mut tx, &state.env).await?;
+
+    let mut created_payments = Vec::with_capacity(request.items.len());
+    for (item, recipient_address) in request.items.into_iter().zip(recipient_addresses) {
+        let new_payment = Payment::create(
+            &mut tx,
+            &item.client_id,
+            &request.account_name,
+            &recipient_address,
+            item.amount,
+            item.payment_id,
+            None,
+            None,
+            PaymentPriority::default(),
+        )
+        .await?;
+        PaymentTag::add(&mut tx, &new_payment.id, &tags).await?;
+        AuditEntry::record(
+            &mut tx,
+            API_ACTOR,
+            AuditAction::PaymentCreated,
+            &new_payment.id,
+            None,
+            Some(&new_payment.status.to_string()),
+            None,
+        )
+        .await?;
+        created_payments.push(new_payment);
+    }
+
+    let payment_ids: Vec<String> = created_payments.iter().map(|p| p.id.clone()).collect();
+    if !PaymentBatch::append_payments(&mut tx, API_ACTOR, &batch.id, &payment_ids).await? {
+        return Err(ApiError::Conflict(format!(
+            "Batch {} no longer accepts payments, as creating its transaction has started",
+            batch.id
+        )));
+    }
+
+    tx.commit().await?;
+
+    for p in &mut created_payments {
+        p.status = PaymentStatus::Batched;
+        p.payment_batch_id = Some(batch.id.clone());
+    }
+    let response = BulkPaymentResponse {
+        batch_id: batch.id,
+        account_name: batch.account_name,
+        status: batch.status.to_string(),
+        payments: created_payments
+            .into_iter()
+            .map(|p| PaymentResponse::from(p).with_tags(tags.clone()))
+            .collect(),
+    };
+
+    Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+}
+
+/// Creates the payments of a `split` bulk request as a group without batching them, so the batch creator can
+/// spread them over as many batches as needed. Repeating the request returns the existing group.
+async fn create_payment_group(
+    state: &AppState,
+    mut tx: Transaction<'_, Sqlite>,
+    request: BulkPaymentRequest,
+    recipient_addresses: Vec<String>,
+    tags: Vec<String>,
+    existing_payments: Vec<Payment>,
+) -> Result<Response, ApiError> {
+    if !existing_payments.is_empty() {
+        let existing_ids: Vec<String> = existing_payments.iter().map(|p| p.id.clone()).collect();
+        let group_ids = PaymentGroup::find_group_ids_by_payment_ids(&mut tx, &existing_ids).await?;
+        let group_id = existing_ids
+            .first()
+            .and_then(|id| group_ids.get(id))
+            .filter(|group_id| existing_ids.iter().all(|id| group_ids.get(id) == Some(*group_id)));
+
+        return match (existing_payments.len() == request.items.len(), group_id) {
+            (true, Some(group_id)) => {
+                let group = PaymentGroup::find_by_id(&mut tx, group_id)
+                    .await?
+                    .ok_or_else(|| ApiError::InternalServerError("Referenced payment group not found".to_string()))?;
+                let response = load_payment_group_response(&mut tx, group).await?;
+                tx.commit().await?;
+                Ok((StatusCode::OK, Json(response)).into_response())
+            },
+            (true, None) => Err(ApiError::BadRequest(
+                "Duplicate payments found, but they do not form a single consistent payment group.".to_string(),
+            )),
+            (false, _) => Err(ApiError::BadRequest(format!(
+                "Request contains {} duplicate client_ids (out of {}). Partial payment groups are not allowed.",
+                existing_payments.len(),
+                request.items.len()
+            ))),
+        };
+    }
+
+    check_backlog(&mut tx, &state.env).await?;
+
+    let mut payment_ids = Vec::with_capacity(request.items.len());
+    for (item, recipient_address) in request.items.into_iter().zip(recipient_addresses) {
+        let new_payment = Payment::create(
+            &mut tx,
+            &item.client_id,
+            &request.account_name,
+            &recipient_address,
+            item.amount,
+            item.payment_id,
+            None,
+            None,
+            PaymentPriority::default(),
+        )
+        .await?;
+        PaymentTag::add(&mut tx, &new_payment.id, &tags).await?;
+        AuditEntry::record(
+            &mut tx,
+            API_ACTOR,
+            AuditAction::PaymentCreated,
+            &new_payment.id,
+            None,
+            Some(&new_payment.status.to_string()),
+            None,
+        )
+        .await?;
+        payment_ids.push(new_payment.id);
+    }
+
+    let group = PaymentGroup::create(&mut tx, &request.account_name, &payment_ids).await?;
+    let response = load_payment_group_response(&mut tx, group).await?;
+
+    tx.commit().await?;
+    state.wakeups.batch_creator.notify_one();
+
+    Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payment-batches/estimate",
+    params(EstimateQuery),
+    request_body = BulkPaymentRequest,
+    responses(
+        (status = 200, description = "Estimated cost of the batch. Nothing is created or locked.", body = BatchEstimateResponse),
+        (status = 422, description = "Invalid fields of the request or its items, all listed in `errors`", body = ApiError),
+        (status = 502, description = "Payment Receiver request failed", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_estimate_payment_batch(
+    State(state): State<AppState>,
+    Query(query): Query<EstimateQuery>,
+    Json(request): Json<BulkPaymentRequest>,
+) -> Result<Json<BatchEstimateResponse>, ApiError> {
+    if request.split {
+        return Err(ApiError::field(
+            "split",
+            "Estimates are only available for requests forming a single batch",
+        ));
+    }
+    validate_bulk_request(&state.env, &request)?;
+
+    let accounts = state.env.accounts.current();
+    let account = accounts
+        .get(&request.account_name.to_lowercase())
+        .ok_or_else(|| ApiError::InternalServerError("Validated account disappeared".to_string()))?;
+
+    let total_amount = PaymentAmount::checked_sum(request.items.iter().map(|i| i.amount))
+        .map_err(|e| ApiError::BadRequest(format!("Batch total is invalid: {}", e)))?;
+    let amount_to_lock = total_amount
+        .checked_add(account.fee_buffer_amount.max(0) as u64)
+        .ok_or_else(|| ApiError::BadRequest("Batch amount to lock overflows".to_string()))?;
+
+    let input_count = query.input_count.unwrap_or(1).max(1);
+    let estimate =
+        unsigned_tx_creator::estimate_batch(input_count, request.items.len(), state.env.max_input_count_per_tx)
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to estimate fees: {}", e)))?;
+
+    let balance = payment_receiver::get_balance(&state.client_config, &state.env.network_retry, &account.name)
+        .await
+        .map_err(|e| ApiError::BadGateway(format!("Failed to fetch balance from Payment Receiver: {}", e)))?;
+
+    Ok(Json(BatchEstimateResponse {
+        account_name: account.name.clone(),
+        payment_count: request.items.len(),
+        total_amount,
+        input_count,
+        step_count: estimate.step_count,
+        requires_consolidation: estimate.requires_consolidation,
+        estimated_fee: estimate.fee.as_u64(),
+        amount_to_lock,
+        available_balance: balance.available,
+        sufficient_funds: balance.available >= 0 && balance.available as u64 >= amount_to_lock,
+    }))
 }
 
 #[utoipa::path(
@@ -309,34 +907,571 @@ pub async fn api_get_payment(
     Path(payment_id): Path<String>,
 ) -> Result<Json<PaymentResponse>, ApiError> {
     let mut conn = db_pool.acquire().await?;
+    Ok(Json(load_payment_response(&mut conn, &payment_id).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/payments/{payment_id}",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the payment")
+    ),
+    request_body = AmendPaymentRequest,
+    responses(
+        (status = 200, description = "Payment amended", body = PaymentResponse),
+        (status = 400, description = "Bad request (Nothing to amend, payment is no longer RECEIVED, or the amount of a refund was changed)", body = ApiError),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 409, description = "The payment was batched or cancelled while it was amended", body = ApiError),
+        (status = 422, description = "Invalid fields, all listed in `errors`", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_amend_payment(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Path(payment_id): Path<String>,
+    Json(request): Json<AmendPaymentRequest>,
+) -> Result<Json<PaymentResponse>, ApiError> {
+    let mut conn = state.db_writer.acquire().await?;
 
-    let (payment, payment_batch) = Payment::get_by_id_with_batch_info(&mut conn, &payment_id)
+    let payment = Payment::get_by_id(&mut conn, &payment_id)
         .await?
         .ok_or_else(|| ApiError::NotFound("Payment not found".to_string()))?;
+    client.check_account(&payment.account_name)?;
+
+    if request.recipient_address.is_none() && request.amount.is_none() && request.payment_id.is_none() {
+        return Err(ApiError::BadRequest(
+            "Nothing to amend, set recipient_address, amount or payment_id".to_string(),
+        ));
+    }
+    let mut errors = ValidationErrors::default();
+    let recipient_address = match request.recipient_address.as_deref() {
+        Some(address) => errors.check(
+            "recipient_address",
+            validate_recipient_address(address, state.env.tari_network).map(Some),
+        ),
+        None => Some(None),
+    };
+    if let Some(amount) = request.amount {
+        errors.check(
+            "amount",
+            validate_amount(amount, &state.env.payment_amount_limits_for(&payment.account_name)),
+        );
+    }
+    if let Some(memo) = &request.payment_id {
+        errors.check("payment_id", validate_memo(memo));
+    }
+    errors.into_result()?;
+    let Some(recipient_address) = recipient_address else {
+        unreachable!("validation failures are returned above");
+    };
+
+    if !matches!(payment.status, PaymentStatus::Received) {
+        return Err(ApiError::BadRequest(format!(
+            "Payment is in status {}, only RECEIVED payments can be amended",
+            payment.status
+        )));
+    }
+    // The amounts of refunds are checked against the refundable amount of the payment they refund.
+    if request.amount.is_some()
+        && Refund::find_by_refund_payment_id(&mut conn, &payment_id)
+            .await?
+            .is_some()
+    {
+        return Err(ApiError::BadRequest(
+            "The amount of a refund cannot be amended".to_string(),
+        ));
+    }
+
+    Payment::amend_received(
+        &mut conn,
+        API_ACTOR,
+        &payment_id,
+        recipient_address.as_deref(),
+        request.amount,
+        request.payment_id.as_deref(),
+    )
+    .await?
+    .ok_or_else(|| ApiError::Conflict("Payment was batched or cancelled while it was amended".to_string()))?;
+
+    Ok(Json(load_payment_response(&mut conn, &payment_id).await?))
+}
 
-    Ok(Json(PaymentResponse::from_payment_and_batch(payment, payment_batch)))
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct WaitQuery {
+    /// How long to wait for a final status, e.g. `30s`, `2m` or plain seconds. Defaults to 30 seconds, at most
+    /// 2 minutes.
+    pub timeout: Option<String>,
 }
 
 #[utoipa::path(
-    post,
-    path = "/v1/payments/{payment_id}/cancel",
+    get,
+    path = "/v1/payments/{payment_id}/wait",
     params(
-        ("payment_id" = String, Path, description = "Unique identifier of the payment")
+        ("payment_id" = String, Path, description = "Unique identifier of the payment"),
+        WaitQuery
     ),
     responses(
-        (status = 200, description = "Payment cancelled successfully", body = PaymentCancelResponse),
-        (status = 400, description = "Bad request (Payment cannot be cancelled in current state)", body = ApiError),
+        (status = 200, description = "The payment once it is CONFIRMED, FAILED or CANCELLED, or its current state when the timeout elapsed first", body = PaymentResponse),
+        (status = 422, description = "Invalid timeout", body = ApiError),
         (status = 404, description = "Payment not found", body = ApiError),
         (status = 500, description = "Internal server error", body = ApiError)
     )
 )]
-pub async fn api_cancel_payment(
+pub async fn api_wait_for_payment(
     State(db_pool): State<SqlitePool>,
     Path(payment_id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
+    Query(query): Query<WaitQuery>,
+) -> Result<Json<PaymentResponse>, ApiError> {
+    let timeout = match query.timeout.as_deref() {
+        Some(timeout) => parse_wait_timeout(timeout).map_err(|message| ApiError::field("timeout", message))?,
+        None => DEFAULT_WAIT_TIMEOUT,
+    };
+    let deadline = time::Instant::now() + timeout;
+
+    loop {
+        let status_changed = Payment::status_changed();
+        let response = {
+            let mut conn = db_pool.acquire().await?;
+            load_payment_response(&mut conn, &payment_id).await?
+        };
+        if response.status.is_final() || time::Instant::now() >= deadline {
+            return Ok(Json(response));
+        }
+
+        // Status updates signal before their transaction commits, so re-read periodically as well.
+        tokio::select! {
+            _ = status_changed => {},
+            _ = time::sleep(WAIT_RECHECK_INTERVAL) => {},
+            _ = time::sleep_until(deadline) => {},
+        }
+    }
+}
+
+/// Parses `<n>`, `<n>s` or `<n>m` into a duration of at most `MAX_WAIT_TIMEOUT`.
+fn parse_wait_timeout(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let (number, unit_secs) = if let Some(minutes) = raw.strip_suffix('m') {
+        (minutes, 60)
+    } else {
+        (raw.strip_suffix('s').unwrap_or(raw), 1)
+    };
+    let secs = number
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid timeout '{}', expected e.g. '30s' or '2m'", raw))?
+        .saturating_mul(unit_secs);
+    let timeout = Duration::from_secs(secs);
+    if timeout > MAX_WAIT_TIMEOUT {
+        return Err(format!(
+            "Timeout must be at most {} seconds",
+            MAX_WAIT_TIMEOUT.as_secs()
+        ));
+    }
+    Ok(timeout)
+}
+
+async fn load_payment_response(conn: &mut SqliteConnection, payment_id: &str) -> Result<PaymentResponse, ApiError> {
+    let (payment, payment_batch) = Payment::get_by_id_with_batch_info(conn, payment_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment not found".to_string()))?;
+    let tags = PaymentTag::find_by_payment_id(conn, payment_id).await?;
+    let refund_of = Refund::find_by_refund_payment_id(conn, payment_id)
+        .await?
+        .map(|refund| refund.original_payment_id);
+    let resubmitted_from = Payment::find_resubmitted_from(conn, payment_id).await?;
+
+    Ok(PaymentResponse::from_payment_and_batch(payment, payment_batch)
+        .with_tags(tags)
+        .with_refund_of(refund_of)
+        .with_resubmitted_from(resubmitted_from))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/{payment_id}/refund",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the payment to refund")
+    ),
+    request_body = RefundRequest,
+    responses(
+        (status = 202, description = "Refund payment accepted for processing", body = RefundResponse),
+        (status = 200, description = "Refund already exists (idempotent)", body = RefundResponse),
+        (status = 400, description = "Bad request (Payment not confirmed or amount exceeds the refundable amount)", body = ApiError),
+        (status = 422, description = "Invalid recipient address or amount, or no refund address", body = ApiError),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 409, description = "client_id is already used by a payment that is not a refund of this payment", body = ApiError),
+        (status = 429, description = "Rate limit exceeded, retry after the time in the Retry-After header", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_refund_payment(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Path(payment_id): Path<String>,
+    Json(request): Json<RefundRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_writer.begin().await?;
+
+    let original = Payment::get_by_id(&mut tx, &payment_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment not found".to_string()))?;
+    client.check_account(&original.account_name)?;
+
+    if let Some(existing) = Payment::get_by_client_id(&mut tx, &request.client_id, &original.account_name).await? {
+        let refund = Refund::find_by_refund_payment_id(&mut tx, &existing.id)
+            .await?
+            .filter(|refund| refund.original_payment_id == original.id)
+            .ok_or_else(|| {
+                ApiError::Conflict(format!(
+                    "client_id '{}' is already used by a payment that is not a refund of this payment",
+                    request.client_id
+                ))
+            })?;
+        tx.commit().await?;
+        return Ok((StatusCode::OK, Json(RefundResponse::from(refund))));
+    }
+
+    if Refund::find_by_refund_payment_id(&mut tx, &original.id)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::BadRequest("A refund cannot be refunded".to_string()));
+    }
+    if !original.status.is_paid() {
+        return Err(ApiError::BadRequest(format!(
+            "Payment is in status {}, only confirmed payments can be refunded",
+            original.status
+        )));
+    }
+    if !state
+        .env
+        .accounts
+        .current()
+        .contains_key(&original.account_name.to_lowercase())
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Account '{}' not found in configuration",
+            original.account_name
+        )));
+    }
+
+    let recipient_address = match request.recipient_address {
+        Some(address) => Some(address),
+        None => Payment::find_refund_address(&mut tx, &original.id).await?,
+    }
+    .ok_or_else(|| {
+        ApiError::field(
+            "recipient_address",
+            "recipient_address is required, the payment was created without a refund_address",
+        )
+    })?;
+
+    let mut errors = ValidationErrors::default();
+    let recipient_address = errors.check(
+        "recipient_address",
+        validate_recipient_address(&recipient_address, state.env.tari_network),
+    );
+    let amount = request.amount.unwrap_or(original.amount);
+    errors.check(
+        "amount",
+        validate_amount(amount, &state.env.payment_amount_limits_for(&original.account_name)),
+    );
+    errors.into_result()?;
+    let Some(recipient_address) = recipient_address else {
+        unreachable!("validation failures are returned above");
+    };
+    let refunded = Refund::refunded_total(&mut tx, &original.id).await?;
+    let refundable = original.amount.as_i64().saturating_sub(refunded);
+    if amount.as_i64() > refundable {
+        return Err(ApiError::BadRequest(format!(
+            "Amount {} exceeds the refundable amount of {} ({} already refunded)",
+            amount, refundable, refunded
+        )));
+    }
+
+    let refund_payment = Payment::create(
+        &mut tx,
+        &request.client_id,
+        &original.account_name,
+        &recipient_address,
+        amount,
+        None,
+        None,
+        None,
+        PaymentPriority::default(),
+    )
+    .await?;
+    Refund::create(&mut tx, &refund_payment.id, &original.id, request.reason.as_deref()).await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::PaymentCreated,
+        &refund_payment.id,
+        None,
+        Some(&refund_payment.status.to_string()),
+        Some(&format!("Refund of payment {}", original.id)),
+    )
+    .await?;
+    let refund = Refund::find_by_refund_payment_id(&mut tx, &refund_payment.id)
+        .await?
+        .ok_or_else(|| ApiError::InternalServerError("Created refund not found".to_string()))?;
+
+    tx.commit().await?;
+
+    Ok((StatusCode::ACCEPTED, Json(RefundResponse::from(refund))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/{payment_id}/resubmit",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the failed payment")
+    ),
+    responses(
+        (status = 202, description = "New payment created from the failed one", body = PaymentResponse),
+        (status = 200, description = "The payment was already resubmitted (idempotent)", body = PaymentResponse),
+        (status = 400, description = "Bad request (Payment has not failed, or is a refund)", body = ApiError),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 409, description = "The derived client_id is already used by a payment that is not a resubmission of this payment", body = ApiError),
+        (status = 429, description = "Rate limit exceeded, retry after the time in the Retry-After header", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError),
+        (status = 503, description = "Backlog too large (code BACKLOG_FULL), retry after the time in the Retry-After header", body = ApiError)
+    )
+)]
+pub async fn api_resubmit_payment(
+    State(state): State<AppState>,
+    Extension(client): Extension<ApiClient>,
+    Path(payment_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_writer.begin().await?;
+
+    let original = Payment::get_by_id(&mut tx, &payment_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment not found".to_string()))?;
+    client.check_account(&original.account_name)?;
+
+    // Derived from the original, so repeating the request finds the resubmission instead of paying twice.
+    let client_id = format!("{}:resubmit:{}", original.client_id, original.id);
+    if let Some(existing) = Payment::get_by_client_id(&mut tx, &client_id, &original.account_name).await? {
+        if Payment::find_resubmitted_from(&mut tx, &existing.id).await?.as_deref() != Some(original.id.as_str()) {
+            return Err(ApiError::Conflict(format!(
+                "client_id '{}' is already used by a payment that is not a resubmission of this payment",
+                client_id
+            )));
+        }
+        let response = load_payment_response(&mut tx, &existing.id).await?;
+        tx.commit().await?;
+        return Ok((StatusCode::OK, Json(response)));
+    }
+
+    if !matches!(original.status, PaymentStatus::Failed) {
+        return Err(ApiError::BadRequest(format!(
+            "Payment is in status {}, only failed payments can be resubmitted",
+            original.status
+        )));
+    }
+    // The refund would no longer count against the refundable amount of the payment it refunds.
+    if Refund::find_by_refund_payment_id(&mut tx, &original.id)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::BadRequest(
+            "A failed refund cannot be resubmitted, request a new refund instead".to_string(),
+        ));
+    }
+    if !state
+        .env
+        .accounts
+        .current()
+        .contains_key(&original.account_name.to_lowercase())
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Account '{}' not found in configuration",
+            original.account_name
+        )));
+    }
+
+    check_backlog(&mut tx, &state.env).await?;
+
+    let new_payment = Payment::create(
+        &mut tx,
+        &client_id,
+        &original.account_name,
+        &original.recipient_address,
+        original.amount,
+        original.payment_id.clone(),
+        None,
+        None,
+        original.priority,
+    )
+    .await?;
+    Payment::set_resubmitted_from(&mut tx, &new_payment.id, &original.id).await?;
+    if let Some(refund_address) = Payment::find_refund_address(&mut tx, &original.id).await? {
+        Payment::set_refund_address(&mut tx, &new_payment.id, &refund_address).await?;
+    }
+    let tags = PaymentTag::find_by_payment_id(&mut tx, &original.id).await?;
+    PaymentTag::add(&mut tx, &new_payment.id, &tags).await?;
+    AuditEntry::record(
+        &mut tx,
+        API_ACTOR,
+        AuditAction::PaymentCreated,
+        &new_payment.id,
+        None,
+        Some(&new_payment.status.to_string()),
+        Some(&format!("Resubmission of payment {}", original.id)),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if new_payment.priority == PaymentPriority::High && state.env.batch_creator_dedicated_high_priority_batches {
+        state.wakeups.batch_creator.notify_one();
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(
+            PaymentResponse::from(new_payment)
+                .with_tags(tags)
+                .with_resubmitted_from(Some(original.id)),
+        ),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payments/{payment_id}/refunds",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the original payment")
+    ),
+    responses(
+        (status = 200, description = "Refunds of the payment, oldest first", body = Vec<RefundResponse>),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_refunds(
+    State(db_pool): State<SqlitePool>,
+    Path(payment_id): Path<String>,
+) -> Result<Json<Vec<RefundResponse>>, ApiError> {
     let mut conn = db_pool.acquire().await?;
 
-    match Payment::cancel_single_payment(&mut conn, &payment_id).await {
+    if Payment::get_by_id(&mut conn, &payment_id).await?.is_none() {
+        return Err(ApiError::NotFound("Payment not found".to_string()));
+    }
+    let refunds = Refund::find_by_original_payment_id(&mut conn, &payment_id).await?;
+
+    Ok(Json(refunds.into_iter().map(RefundResponse::from).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payment-batches/{batch_id}/payments",
+    params(
+        ("batch_id" = String, Path, description = "Unique identifier of the payment batch")
+    ),
+    responses(
+        (status = 200, description = "Every payment of the batch, including failed and cancelled ones", body = Vec<PaymentResponse>),
+        (status = 404, description = "Batch not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_batch_payments(
+    State(db_pool): State<SqlitePool>,
+    Path(batch_id): Path<String>,
+) -> Result<Json<Vec<PaymentResponse>>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    let batch = PaymentBatch::find_by_id(&mut conn, &batch_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Payment batch not found".to_string()))?;
+    let payments = Payment::find_all_by_batch_id(&mut conn, &batch_id).await?;
+
+    let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
+    let mut tags = PaymentTag::find_by_payment_ids(&mut conn, &payment_ids).await?;
+
+    let payments = payments
+        .into_iter()
+        .map(|p| {
+            let payment_tags = tags.remove(&p.id).unwrap_or_default();
+            PaymentResponse::from_payment_and_batch(p, Some(batch.clone())).with_tags(payment_tags)
+        })
+        .collect();
+
+    Ok(Json(payments))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/payments",
+    params(ListPaymentsQuery),
+    responses(
+        (status = 200, description = "Payments matching the filters, in creation order", body = PaymentListResponse),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_list_payments(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<ListPaymentsQuery>,
+) -> Result<Json<PaymentListResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+    let mut conn = db_pool.acquire().await?;
+
+    let payments = Payment::list(
+        &mut conn,
+        query.account_name.as_deref(),
+        query.status,
+        query.tag.as_deref(),
+        query.after.as_deref(),
+        limit,
+    )
+    .await?;
+
+    let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
+    let mut tags = PaymentTag::find_by_payment_ids(&mut conn, &payment_ids).await?;
+
+    let next_cursor = if payments.len() as i64 == limit {
+        payments.last().map(|p| p.id.clone())
+    } else {
+        None
+    };
+    let payments = payments
+        .into_iter()
+        .map(|p| {
+            let payment_tags = tags.remove(&p.id).unwrap_or_default();
+            PaymentResponse::from(p).with_tags(payment_tags)
+        })
+        .collect();
+
+    Ok(Json(PaymentListResponse { payments, next_cursor }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/{payment_id}/cancel",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the payment")
+    ),
+    responses(
+        (status = 200, description = "Payment cancelled successfully", body = PaymentCancelResponse),
+        (status = 400, description = "Bad request (Payment cannot be cancelled in current state)", body = ApiError),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_cancel_payment(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Extension(client): Extension<ApiClient>,
+    Path(payment_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    if let Some(payment) = Payment::get_by_id(&mut conn, &payment_id).await? {
+        client.check_account(&payment.account_name)?;
+    }
+
+    match Payment::cancel_single_payment(&mut conn, API_ACTOR, &payment_id).await {
         Ok(status) => Ok((StatusCode::OK, Json(PaymentCancelResponse { payment_id, status }))),
         Err(e) => {
             let err_msg = e.to_string();
@@ -348,3 +1483,236 @@ pub async fn api_cancel_payment(
         },
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/v1/payments/{payment_id}/detach",
+    params(
+        ("payment_id" = String, Path, description = "Unique identifier of the payment")
+    ),
+    responses(
+        (status = 200, description = "Payment taken out of its batch and returned to RECEIVED", body = PaymentResponse),
+        (status = 400, description = "Bad request (Payment is not batched, or its batch is too far along)", body = ApiError),
+        (status = 404, description = "Payment not found", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_detach_payment(
+    State(DbWriter(db_pool)): State<DbWriter>,
+    Extension(client): Extension<ApiClient>,
+    Path(payment_id): Path<String>,
+) -> Result<Json<PaymentResponse>, ApiError> {
+    let mut conn = db_pool.acquire().await?;
+
+    if let Some(payment) = Payment::get_by_id(&mut conn, &payment_id).await? {
+        client.check_account(&payment.account_name)?;
+    }
+
+    match Payment::detach_from_batch(&mut conn, API_ACTOR, &payment_id).await {
+        Ok(payment) => {
+            let tags = PaymentTag::find_by_payment_id(&mut conn, &payment_id).await?;
+            Ok(Json(PaymentResponse::from(payment).with_tags(tags)))
+        },
+        Err(e) => {
+            let err_msg = e.to_string();
+            if err_msg.contains("Payment not found") {
+                Err(ApiError::NotFound(err_msg))
+            } else {
+                Err(ApiError::BadRequest(err_msg))
+            }
+        },
+    }
+}
+
+/// Rejects new payments while the backlog exceeds the configured limits, so an upstream flood cannot overwhelm
+/// the pipeline.
+async fn check_backlog(conn: &mut SqliteConnection, env: &PaymentProcessorEnv) -> Result<(), ApiError> {
+    let Some(backpressure) = env.backpressure else {
+        return Ok(());
+    };
+    let overloaded = |message: String| ApiError::ServiceUnavailable {
+        code: "BACKLOG_FULL",
+        message,
+        retry_after_secs: backpressure.retry_after_secs,
+    };
+
+    if let Some(max_pending_payments) = backpressure.max_pending_payments {
+        let pending_payments = Payment::count_pending(conn).await?;
+        if pending_payments >= max_pending_payments {
+            return Err(overloaded(format!(
+                "{} payments are waiting to be batched (limit {}). Retry later.",
+                pending_payments, max_pending_payments
+            )));
+        }
+    }
+    if let Some(max_in_flight_batches) = backpressure.max_in_flight_batches {
+        let in_flight_batches = PaymentBatch::count_in_flight(conn).await?;
+        if in_flight_batches >= max_in_flight_batches {
+            return Err(overloaded(format!(
+                "{} batches are being processed (limit {}). Retry later.",
+                in_flight_batches, max_in_flight_batches
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the account, size and items of a bulk request. Returns the normalized recipient addresses, in item order.
+/// Validates the whole request and reports every invalid field, so a large batch can be fixed in one pass. Returns
+/// the recipient addresses in Base58 form, in item order, and the normalized tags.
+fn validate_bulk_request(
+    env: &PaymentProcessorEnv,
+    request: &BulkPaymentRequest,
+) -> Result<(Vec<String>, Vec<String>), ApiError> {
+    let max_items = if request.split {
+        MAX_SPLIT_REQUEST_SIZE
+    } else {
+        MAX_BATCH_SIZE
+    };
+    validate_bulk_items(env, &request.account_name, &request.items, &request.tags, max_items)
+}
+
+/// Validates the account, tags and items of a bulk request. Returns the recipient addresses of the items in Base58
+/// form and the normalized tags.
+fn validate_bulk_items(
+    env: &PaymentProcessorEnv,
+    account_name: &str,
+    items: &[BulkPaymentItem],
+    tags: &[String],
+    max_items: usize,
+) -> Result<(Vec<String>, Vec<String>), ApiError> {
+    let mut errors = ValidationErrors::default();
+    if !env.accounts.current().contains_key(&account_name.to_lowercase()) {
+        errors.add(
+            "account_name",
+            format!("Account '{}' not found in configuration", account_name),
+        );
+    }
+    if items.is_empty() {
+        errors.add("items", "Batch cannot be empty");
+    }
+    if items.len() > max_items {
+        errors.add("items", format!("Batch size exceeds limit of {}", max_items));
+    }
+    let tags = errors.check("tags", normalize_tags(tags));
+
+    let amount_limits = env.payment_amount_limits_for(account_name);
+    let mut first_index_of_client_id = HashMap::with_capacity(items.len());
+    let mut recipient_addresses = Vec::with_capacity(items.len());
+    for (idx, item) in items.iter().enumerate() {
+        let first = *first_index_of_client_id.entry(item.client_id.as_str()).or_insert(idx);
+        if first != idx {
+            errors.add(
+                format!("items[{}].client_id", idx),
+                format!("Duplicate of items[{}].client_id", first),
+            );
+        }
+        errors.check(
+            format!("items[{}].amount", idx),
+            validate_amount(item.amount, &amount_limits),
+        );
+        if let Some(memo) = &item.payment_id {
+            errors.check(format!("items[{}].payment_id", idx), validate_memo(memo));
+        }
+        if let Some(address) = errors.check(
+            format!("items[{}].recipient_address", idx),
+            validate_recipient_address(&item.recipient_address, env.tari_network),
+        ) {
+            recipient_addresses.push(address);
+        }
+    }
+
+    errors.into_result()?;
+    Ok((recipient_addresses, tags.unwrap_or_default()))
+}
+
+/// Parses a Base58 or emoji recipient address and checks that it can receive one-sided payments on `network`.
+/// Returns the address in Base58 form, which is what gets stored and later used to build the transaction.
+pub(super) fn validate_recipient_address(address: &str, network: Network) -> Result<String, String> {
+    let address = address.trim();
+    let parsed = TariAddress::from_base58(address)
+        .or_else(|_| TariAddress::from_emoji_string(address))
+        .map_err(|e| format!("Invalid recipient address '{}': {}", address, e))?;
+
+    if parsed.network() != network {
+        return Err(format!(
+            "Recipient address is for network {}, but this processor runs on {}",
+            parsed.network(),
+            network
+        ));
+    }
+
+    if !parsed.features().contains(TariAddressFeatures::ONE_SIDED) {
+        return Err("Recipient address does not support one-sided payments".to_string());
+    }
+
+    Ok(parsed.to_base58())
+}
+
+/// Checks a memo up front, so it can't fail the whole batch once its transaction is built. Memos must be non-empty,
+/// at most `MAX_MEMO_BYTES` long in UTF-8, free of control characters, and accepted by `MemoField`.
+pub(super) fn validate_memo(memo: &str) -> Result<(), String> {
+    if memo.is_empty() {
+        return Err("Memo must not be empty, omit it instead".to_string());
+    }
+    if memo.len() > MAX_MEMO_BYTES {
+        return Err(format!(
+            "Memo is {} bytes long, at most {} bytes are allowed",
+            memo.len(),
+            MAX_MEMO_BYTES
+        ));
+    }
+    if memo.chars().any(char::is_control) {
+        return Err("Memo must not contain control characters".to_string());
+    }
+    MemoField::new_open_from_string(memo, TxType::PaymentToOther).map_err(|e| format!("Invalid memo: {}", e))?;
+    Ok(())
+}
+
+/// Trims, sorts and de-duplicates tags. Tags must be non-empty, at most `MAX_TAG_LENGTH` characters long and
+/// consist of ASCII letters, digits, `-`, `_`, `.` and `:`.
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() || tag.len() > MAX_TAG_LENGTH {
+            return Err(format!("Tags must be between 1 and {} characters long", MAX_TAG_LENGTH));
+        }
+        if !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+        {
+            return Err(format!(
+                "Tag '{}' contains invalid characters, allowed are letters, digits, '-', '_', '.' and ':'",
+                tag
+            ));
+        }
+        normalized.push(tag.to_string());
+    }
+    normalized.sort();
+    normalized.dedup();
+
+    if normalized.len() > MAX_TAGS_PER_PAYMENT {
+        return Err(format!("At most {} tags per payment are allowed", MAX_TAGS_PER_PAYMENT));
+    }
+    Ok(normalized)
+}
+
+/// Rejects dust and amounts above the account's maximum. Positivity and the `i64` bound are already enforced when
+/// deserializing `PaymentAmount`.
+pub(super) fn validate_amount(amount: PaymentAmount, limits: &PaymentAmountLimits) -> Result<(), String> {
+    if amount.as_u64() < limits.min {
+        return Err(format!(
+            "Amount {} is below the minimum payment amount of {}",
+            amount, limits.min
+        ));
+    }
+    if let Some(max) = limits.max.filter(|max| amount.as_u64() > *max) {
+        return Err(format!(
+            "Amount {} exceeds the maximum payment amount of {}",
+            amount, max
+        ));
+    }
+    Ok(())
+}
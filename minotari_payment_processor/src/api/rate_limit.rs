@@ -0,0 +1,102 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::warn;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    api::{AppState, error::ApiError},
+    config::RateLimitConfig,
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::api";
+
+/// Above this many tracked clients, clients whose bucket has fully refilled are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// In-memory token bucket rate limiter keyed by client.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token from the client's bucket. If the bucket is empty, returns how long the client has to wait.
+    pub fn check(&self, client: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let burst = f64::from(self.config.burst);
+        let rate = self.config.requests_per_second;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: burst,
+            last_refill: now,
+        });
+
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() * rate).min(burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+/// Rejects requests of clients that exceeded the configured rate with `429 Too Many Requests`.
+/// Clients are identified by their source IP.
+pub async fn rate_limit_layer(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(rate_limiter) = &state.rate_limiter else {
+        return next.run(request).await;
+    };
+
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    if let Err(wait) = rate_limiter.check(&client) {
+        warn!(
+            target: LOG_TARGET,
+            "Rate limit exceeded by {} on {} {}.",
+            client,
+            request.method(),
+            request.uri().path()
+        );
+        return ApiError::TooManyRequests {
+            message: "Rate limit exceeded".to_string(),
+            retry_after_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,132 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    api::error::ApiError,
+    db::{
+        payment::{Payment, PaymentStatus},
+        payment_batch::{PaymentBatch, PaymentBatchStatus},
+    },
+};
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct StatsQuery {
+    /// Only payments and batches created at or after this time (RFC 3339).
+    pub from: Option<DateTime<Utc>>,
+    /// Only payments and batches created before this time (RFC 3339).
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PaymentStatusStats {
+    pub status: PaymentStatus,
+    pub payment_count: i64,
+    /// Sum of the payment amounts, in µT.
+    pub total_amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchStatusStats {
+    pub status: PaymentBatchStatus,
+    pub batch_count: i64,
+    /// Sum of the amounts of the batches' payments, in µT.
+    pub total_amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountStats {
+    pub account_name: String,
+    pub payment_count: i64,
+    /// Sum of the payment amounts, in µT.
+    pub total_amount: i64,
+    pub by_status: Vec<PaymentStatusStats>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StatsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    pub payments_by_status: Vec<PaymentStatusStats>,
+    pub batches_by_status: Vec<BatchStatusStats>,
+    pub accounts: Vec<AccountStats>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/stats",
+    params(StatsQuery),
+    responses(
+        (status = 200, description = "Payment and batch totals for the time window", body = StatsResponse),
+        (status = 400, description = "Bad request (from is not before to)", body = ApiError),
+        (status = 500, description = "Internal server error", body = ApiError)
+    )
+)]
+pub async fn api_get_stats(
+    State(db_pool): State<SqlitePool>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from >= to
+    {
+        return Err(ApiError::BadRequest("from must be before to".to_string()));
+    }
+
+    let mut conn = db_pool.acquire().await?;
+    let payment_aggregates = Payment::aggregate_by_account_and_status(&mut conn, query.from, query.to).await?;
+    let batch_aggregates = PaymentBatch::aggregate_by_status(&mut conn, query.from, query.to).await?;
+
+    // Keyed by the status string, so both roll-ups come out in a stable order.
+    let mut by_status: BTreeMap<String, PaymentStatusStats> = BTreeMap::new();
+    let mut by_account: BTreeMap<String, AccountStats> = BTreeMap::new();
+    for aggregate in payment_aggregates {
+        let status_stats = by_status
+            .entry(aggregate.status.to_string())
+            .or_insert_with(|| PaymentStatusStats {
+                status: aggregate.status.clone(),
+                payment_count: 0,
+                total_amount: 0,
+            });
+        status_stats.payment_count += aggregate.payment_count;
+        status_stats.total_amount += aggregate.total_amount;
+
+        let account_stats = by_account
+            .entry(aggregate.account_name.clone())
+            .or_insert_with(|| AccountStats {
+                account_name: aggregate.account_name.clone(),
+                payment_count: 0,
+                total_amount: 0,
+                by_status: Vec::new(),
+            });
+        account_stats.payment_count += aggregate.payment_count;
+        account_stats.total_amount += aggregate.total_amount;
+        account_stats.by_status.push(PaymentStatusStats {
+            status: aggregate.status,
+            payment_count: aggregate.payment_count,
+            total_amount: aggregate.total_amount,
+        });
+    }
+
+    Ok(Json(StatsResponse {
+        from: query.from,
+        to: query.to,
+        payments_by_status: by_status.into_values().collect(),
+        batches_by_status: batch_aggregates
+            .into_iter()
+            .map(|aggregate| BatchStatusStats {
+                status: aggregate.status,
+                batch_count: aggregate.batch_count,
+                total_amount: aggregate.total_amount,
+            })
+            .collect(),
+        accounts: by_account.into_values().collect(),
+    }))
+}
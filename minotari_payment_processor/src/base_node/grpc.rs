@@ -0,0 +1,122 @@
+use anyhow::{Context, anyhow};
+use minotari_app_grpc::tari_rpc::{
+    self, SubmitTransactionResult, TransactionLocation, base_node_client::BaseNodeClient,
+};
+use tari_transaction_components::rpc::models::TxLocation;
+use tari_transaction_components::transaction_components::Transaction;
+use tonic::transport::{Channel, Endpoint};
+use url::Url;
+
+use crate::base_node::{TipInfo, TxStatus, TxSubmission};
+
+/// Client for the base node's gRPC interface, for nodes that don't expose the HTTP wallet interface.
+#[derive(Clone)]
+pub struct GrpcClient {
+    client: BaseNodeClient<Channel>,
+}
+
+impl GrpcClient {
+    /// Creates a client for a `grpc://host:port` URL. The connection is established on first use.
+    pub fn new(url: &Url) -> anyhow::Result<Self> {
+        let host = url.host_str().ok_or_else(|| anyhow!("gRPC URL {} has no host", url))?;
+        let port = url.port().ok_or_else(|| anyhow!("gRPC URL {} has no port", url))?;
+        let channel = Endpoint::from_shared(format!("http://{}:{}", host, port))
+            .with_context(|| format!("Invalid gRPC endpoint: {}", url))?
+            .connect_lazy();
+        Ok(Self {
+            client: BaseNodeClient::new(channel),
+        })
+    }
+
+    pub async fn submit_transaction(&self, tx: Transaction) -> anyhow::Result<TxSubmission> {
+        let transaction =
+            tari_rpc::Transaction::try_from(tx).map_err(|e| anyhow!("Failed to convert transaction: {}", e))?;
+        let response = self
+            .client
+            .clone()
+            .submit_transaction(tari_rpc::SubmitTransactionRequest {
+                transaction: Some(transaction),
+            })
+            .await?
+            .into_inner();
+
+        let result = SubmitTransactionResult::try_from(response.result).unwrap_or(SubmitTransactionResult::None);
+        Ok(match result {
+            SubmitTransactionResult::Accepted | SubmitTransactionResult::AlreadyMined => TxSubmission {
+                accepted: true,
+                rejection_reason: String::new(),
+            },
+            other => TxSubmission {
+                accepted: false,
+                rejection_reason: other.as_str_name().to_string(),
+            },
+        })
+    }
+
+    /// Looks up a transaction by its kernel's excess signature. The gRPC transaction state doesn't include the block,
+    /// so mined transactions are also searched by kernel to fill in the mined height, header hash and timestamp.
+    pub async fn transaction_query(
+        &self,
+        excess_public_nonce: Vec<u8>,
+        excess_sig: Vec<u8>,
+    ) -> anyhow::Result<TxStatus> {
+        let signature = tari_rpc::Signature {
+            public_nonce: excess_public_nonce,
+            signature: excess_sig,
+        };
+        let response = self
+            .client
+            .clone()
+            .transaction_state(tari_rpc::TransactionStateRequest {
+                excess_sig: Some(signature.clone()),
+            })
+            .await?
+            .into_inner();
+
+        let location = match TransactionLocation::try_from(response.result).unwrap_or(TransactionLocation::Unknown) {
+            TransactionLocation::Mined => TxLocation::Mined,
+            TransactionLocation::Mempool => TxLocation::InMempool,
+            TransactionLocation::NotStored => TxLocation::NotStored,
+            TransactionLocation::Unknown => TxLocation::None,
+        };
+        if !matches!(location, TxLocation::Mined) {
+            return Ok(TxStatus {
+                location,
+                mined_height: None,
+                mined_header_hash: None,
+                mined_timestamp: None,
+            });
+        }
+
+        let mut blocks = self
+            .client
+            .clone()
+            .search_kernels(tari_rpc::SearchKernelsRequest {
+                signatures: vec![signature],
+            })
+            .await?
+            .into_inner();
+        let header = blocks
+            .message()
+            .await?
+            .and_then(|historical| historical.block)
+            .and_then(|block| block.header)
+            .ok_or_else(|| anyhow!("Base node reported the transaction as mined but returned no block"))?;
+
+        Ok(TxStatus {
+            location,
+            mined_height: Some(header.height),
+            mined_header_hash: Some(header.hash),
+            mined_timestamp: Some(header.timestamp),
+        })
+    }
+
+    pub async fn get_tip_info(&self) -> anyhow::Result<TipInfo> {
+        let response = self.client.clone().get_tip_info(tari_rpc::Empty {}).await?.into_inner();
+        let metadata = response.metadata.ok_or_else(|| anyhow!("Tip info missing metadata"))?;
+        Ok(TipInfo {
+            best_block_height: metadata.best_block_height,
+            best_block_hash: metadata.best_block_hash,
+        })
+    }
+}
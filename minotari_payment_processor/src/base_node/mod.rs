@@ -0,0 +1,406 @@
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use log::{info, warn};
+use minotari_node_wallet_client::BaseNodeWalletClient;
+use minotari_node_wallet_client::http::Client;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use tari_transaction_components::rpc::models::TxLocation;
+use tari_transaction_components::transaction_components::Transaction;
+use tari_utilities::ByteArray;
+use tokio::time;
+use url::Url;
+
+use crate::base_node::grpc::GrpcClient;
+use crate::config::NetworkRetry;
+use crate::simulation::{SimulatedBaseNode, SimulatedTransaction};
+
+mod grpc;
+
+const LOG_TARGET: &str = "minotari_payment_processor::base_node";
+
+/// Public nonce and signature of the excess signature of a transaction's first kernel, by which base nodes look the
+/// transaction up.
+pub type KernelSignature = (Vec<u8>, Vec<u8>);
+
+pub fn kernel_signature(tx: &Transaction) -> anyhow::Result<KernelSignature> {
+    let kernel = tx
+        .body
+        .kernels()
+        .first()
+        .ok_or_else(|| anyhow!("Transaction has no kernels"))?;
+    Ok((
+        kernel.excess_sig.get_compressed_public_nonce().to_vec(),
+        kernel.excess_sig.get_signature().to_vec(),
+    ))
+}
+
+/// Outcome of submitting a transaction to a base node.
+#[derive(Debug, Clone)]
+pub struct TxSubmission {
+    pub accepted: bool,
+    /// Empty if the transaction was accepted.
+    pub rejection_reason: String,
+}
+
+/// Where a transaction is, and the block it was mined in, if any.
+#[derive(Debug, Clone)]
+pub struct TxStatus {
+    pub location: TxLocation,
+    pub mined_height: Option<u64>,
+    pub mined_header_hash: Option<Vec<u8>>,
+    pub mined_timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TipInfo {
+    pub best_block_height: u64,
+    pub best_block_hash: Vec<u8>,
+}
+
+/// Spent state of an output, identified by its hash.
+#[derive(Debug, Clone)]
+pub struct OutputSpendInfo {
+    pub output_hash: Vec<u8>,
+    /// Height of the block the output was spent in, if it was spent.
+    pub spent_at_height: Option<u64>,
+}
+
+/// Client for one base node, speaking either its HTTP wallet interface or its gRPC interface. URLs with the
+/// `grpc` scheme use gRPC, all others HTTP.
+#[derive(Clone)]
+pub enum NodeClient {
+    Http(Client),
+    Grpc(GrpcClient),
+    /// Base node of simulation mode.
+    Simulated(SimulatedBaseNode),
+    /// In-process base node for tests.
+    #[cfg(feature = "testing")]
+    Mock(crate::testing::base_node::MockBaseNode),
+}
+
+impl NodeClient {
+    pub fn new(url: &Url) -> anyhow::Result<Self> {
+        match url.scheme() {
+            "grpc" => Ok(NodeClient::Grpc(GrpcClient::new(url)?)),
+            "http" | "https" => Ok(NodeClient::Http(Client::new(url.clone(), url.clone()))),
+            scheme => Err(anyhow!("Unsupported base node URL scheme '{}' in {}", scheme, url)),
+        }
+    }
+
+    pub async fn submit_transaction(&self, tx: Transaction) -> anyhow::Result<TxSubmission> {
+        match self {
+            NodeClient::Http(client) => {
+                let response = client.submit_transaction(tx).await?;
+                Ok(TxSubmission {
+                    accepted: response.accepted,
+                    rejection_reason: if response.accepted {
+                        String::new()
+                    } else {
+                        response.rejection_reason.to_string()
+                    },
+                })
+            },
+            NodeClient::Grpc(client) => client.submit_transaction(tx).await,
+            NodeClient::Simulated(node) => node.submit(kernel_signature(&tx)?).await,
+            #[cfg(feature = "testing")]
+            NodeClient::Mock(node) => node.submit_transaction(tx).await,
+        }
+    }
+
+    /// Submits a transaction signed in simulation mode. Only the simulated base node (and the mock of the `testing`
+    /// feature) accepts them.
+    pub async fn submit_simulated_transaction(&self, tx: SimulatedTransaction) -> anyhow::Result<TxSubmission> {
+        match self {
+            NodeClient::Simulated(node) => node.submit(tx.kernel_signature()?).await,
+            #[cfg(feature = "testing")]
+            NodeClient::Mock(node) => node.submit_simulated_transaction(tx).await,
+            _ => Err(anyhow!(
+                "Simulated transactions can only be submitted to the simulated base node"
+            )),
+        }
+    }
+
+    /// Looks up a transaction by the public nonce and signature of its kernel's excess signature.
+    pub async fn transaction_query(
+        &self,
+        excess_public_nonce: Vec<u8>,
+        excess_sig: Vec<u8>,
+    ) -> anyhow::Result<TxStatus> {
+        match self {
+            NodeClient::Http(client) => {
+                let response = client.transaction_query(excess_public_nonce, excess_sig).await?;
+                Ok(TxStatus {
+                    location: response.location,
+                    mined_height: response.mined_height,
+                    mined_header_hash: response.mined_header_hash,
+                    mined_timestamp: response.mined_timestamp,
+                })
+            },
+            NodeClient::Grpc(client) => client.transaction_query(excess_public_nonce, excess_sig).await,
+            NodeClient::Simulated(node) => node.transaction_query((excess_public_nonce, excess_sig)).await,
+            #[cfg(feature = "testing")]
+            NodeClient::Mock(node) => node.transaction_query(excess_public_nonce, excess_sig).await,
+        }
+    }
+
+    pub async fn get_tip_info(&self) -> anyhow::Result<TipInfo> {
+        match self {
+            NodeClient::Http(client) => {
+                let metadata = client
+                    .get_tip_info()
+                    .await?
+                    .metadata
+                    .ok_or_else(|| anyhow!("Tip info missing metadata"))?;
+                Ok(TipInfo {
+                    best_block_height: metadata.best_block_height(),
+                    best_block_hash: metadata.best_block_hash().to_vec(),
+                })
+            },
+            NodeClient::Grpc(client) => client.get_tip_info().await,
+            NodeClient::Simulated(node) => node.get_tip_info().await,
+            #[cfg(feature = "testing")]
+            NodeClient::Mock(node) => node.get_tip_info().await,
+        }
+    }
+
+    /// Returns the spent state of the given outputs as of the current tip. `None` if the node's interface can't
+    /// tell, which is the case for gRPC.
+    pub async fn spent_outputs(&self, output_hashes: Vec<Vec<u8>>) -> anyhow::Result<Option<Vec<OutputSpendInfo>>> {
+        match self {
+            NodeClient::Http(client) => {
+                let tip_hash = self.get_tip_info().await?.best_block_hash;
+                let response = client.get_utxos_deleted_info(output_hashes, tip_hash).await?;
+                Ok(Some(
+                    response
+                        .data
+                        .into_iter()
+                        .map(|info| OutputSpendInfo {
+                            output_hash: info.output_hash,
+                            spent_at_height: info.spent_in_header.map(|(height, _)| height),
+                        })
+                        .collect(),
+                ))
+            },
+            NodeClient::Grpc(_) | NodeClient::Simulated(_) => Ok(None),
+            #[cfg(feature = "testing")]
+            NodeClient::Mock(node) => node.spent_outputs(output_hashes).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BaseNodeHealth {
+    pub url: String,
+    pub consecutive_failures: u64,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+struct BaseNode {
+    client: NodeClient,
+    health: RwLock<BaseNodeHealth>,
+}
+
+impl BaseNode {
+    fn record_success(&self) {
+        let mut health = self.health.write().unwrap_or_else(|e| e.into_inner());
+        health.consecutive_failures = 0;
+        health.last_success_at = Some(Utc::now());
+    }
+
+    fn record_failure(&self, error: &str) {
+        let mut health = self.health.write().unwrap_or_else(|e| e.into_inner());
+        health.consecutive_failures += 1;
+        health.total_failures += 1;
+        health.last_error = Some(error.to_string());
+        health.last_failure_at = Some(Utc::now());
+    }
+
+    fn url(&self) -> String {
+        self.health.read().unwrap_or_else(|e| e.into_inner()).url.clone()
+    }
+}
+
+struct Inner {
+    nodes: Vec<BaseNode>,
+    active: AtomicUsize,
+    retry: NetworkRetry,
+}
+
+/// Base node client that spreads calls over several configured nodes.
+///
+/// Calls go to the currently active node. When a call fails, the next node in the list is tried and,
+/// if it succeeds, becomes the active node. Every call is limited to the network timeout, and when all nodes fail the
+/// round is retried with backoff. Per-node health is tracked for reporting.
+#[derive(Clone)]
+pub struct FailoverBaseNodeClient {
+    inner: Arc<Inner>,
+}
+
+impl FailoverBaseNodeClient {
+    pub fn new(urls: &[String], retry: NetworkRetry) -> anyhow::Result<Self> {
+        let clients = urls
+            .iter()
+            .map(|url| {
+                let parsed = Url::parse(url).with_context(|| format!("Invalid base node URL: {}", url))?;
+                Ok((url.clone(), NodeClient::new(&parsed)?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Self::from_clients(clients, retry)
+    }
+
+    /// Creates a client for already constructed node clients, each labelled with its URL for health reporting.
+    pub fn from_clients(clients: Vec<(String, NodeClient)>, retry: NetworkRetry) -> anyhow::Result<Self> {
+        if clients.is_empty() {
+            return Err(anyhow!("At least one base node URL must be configured"));
+        }
+
+        let nodes = clients
+            .into_iter()
+            .map(|(url, client)| BaseNode {
+                client,
+                health: RwLock::new(BaseNodeHealth {
+                    url,
+                    ..Default::default()
+                }),
+            })
+            .collect();
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                nodes,
+                active: AtomicUsize::new(0),
+                retry,
+            }),
+        })
+    }
+
+    /// Executes `operation` against the active base node, failing over to the remaining nodes in turn. If every node
+    /// fails, all of them are tried again after a backoff, up to the configured number of attempts.
+    pub async fn call<T, F, Fut>(&self, operation_name: &str, operation: F) -> anyhow::Result<T>
+    where
+        F: Fn(NodeClient) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let retry = &self.inner.retry;
+        let mut attempt = 1;
+        loop {
+            match self.call_nodes(operation_name, &operation).await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < retry.attempts => {
+                    let delay = retry.backoff_delay(attempt);
+                    warn!(
+                        target: LOG_TARGET,
+                        "{:#} (attempt {} of {}). Retrying in {:?}.",
+                        e,
+                        attempt,
+                        retry.attempts,
+                        delay
+                    );
+                    time::sleep(delay).await;
+                    attempt += 1;
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// One round of [`Self::call`]: the active node first, then the remaining nodes in turn.
+    async fn call_nodes<T, F, Fut>(&self, operation_name: &str, operation: &F) -> anyhow::Result<T>
+    where
+        F: Fn(NodeClient) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let nodes = &self.inner.nodes;
+        let start = self.inner.active.load(Ordering::Relaxed) % nodes.len();
+        let mut last_error = None;
+
+        for offset in 0..nodes.len() {
+            let index = (start + offset) % nodes.len();
+            let node = &nodes[index];
+
+            match self.with_timeout(operation(node.client.clone())).await {
+                Ok(value) => {
+                    node.record_success();
+                    if index != start {
+                        self.inner.active.store(index, Ordering::Relaxed);
+                        info!(target: LOG_TARGET, "Switched active base node to {}.", node.url());
+                    }
+                    return Ok(value);
+                },
+                Err(e) => {
+                    node.record_failure(&e.to_string());
+                    warn!(
+                        target: LOG_TARGET,
+                        "Base node {} failed '{}': {}. Trying next node.",
+                        node.url(),
+                        operation_name,
+                        e
+                    );
+                    last_error = Some(e);
+                },
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| anyhow!("No base nodes configured"))
+            .context(format!("All {} base nodes failed '{}'", nodes.len(), operation_name)))
+    }
+
+    /// Executes `operation` against every configured base node concurrently and returns each node's URL and result,
+    /// in configuration order. Does not change the active node, and failed calls are not retried.
+    pub async fn call_all<T, F, Fut>(&self, operation_name: &str, operation: F) -> Vec<(String, anyhow::Result<T>)>
+    where
+        F: Fn(NodeClient) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let calls = self.inner.nodes.iter().map(|node| {
+            let call = self.with_timeout(operation(node.client.clone()));
+            async move {
+                let result = match call.await {
+                    Ok(value) => {
+                        node.record_success();
+                        Ok(value)
+                    },
+                    Err(e) => {
+                        node.record_failure(&e.to_string());
+                        warn!(
+                            target: LOG_TARGET,
+                            "Base node {} failed '{}': {}.",
+                            node.url(),
+                            operation_name,
+                            e
+                        );
+                        Err(e)
+                    },
+                };
+                (node.url(), result)
+            }
+        });
+        join_all(calls).await
+    }
+
+    /// Fails the call if it doesn't complete within the network timeout.
+    async fn with_timeout<T>(&self, call: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+        let timeout = self.inner.retry.timeout;
+        time::timeout(timeout, call)
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("Timed out after {:?}", timeout)))
+    }
+
+    /// Returns the health of every configured base node, in configuration order.
+    pub fn health(&self) -> Vec<BaseNodeHealth> {
+        self.inner
+            .nodes
+            .iter()
+            .map(|node| node.health.read().unwrap_or_else(|e| e.into_inner()).clone())
+            .collect()
+    }
+}
@@ -1,7 +1,21 @@
 use anyhow::Context;
-use config::{Config, Environment};
+use axum::http::{HeaderName, HeaderValue, Method};
+use chrono::{DateTime, Utc};
+use config::{Config, Environment, File};
+use ipnet::IpNet;
+use log::{error, warn};
+use rand::Rng;
 use serde::Deserialize;
-use std::{collections::HashMap, str::FromStr};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use tari_common::configuration::Network;
 use tari_common_types::{
     tari_address::{TariAddress, TariAddressFeatures},
@@ -14,40 +28,614 @@ use tari_crypto::{
 };
 use tari_utilities::ByteArray;
 
+use crate::MAX_BATCH_SIZE;
+use crate::db::account::StoredAccount;
+use crate::encryption::EncryptionKey;
+use crate::secrets::{self, SecretString};
+
+const LOG_TARGET: &str = "minotari_payment_processor::config";
+
 #[derive(Debug, Clone)]
 pub struct PaymentReceiverAccount {
     pub name: String,
     pub view_key: RistrettoSecretKey,
     pub public_spend_key: CompressedKey<RistrettoPublicKey>,
     pub address: TariAddress,
+    /// Extra amount locked on top of the payment total to cover fees (including split cycle fees).
+    pub fee_buffer_amount: i64,
+    /// Confirmations required before batches of this account are considered final.
+    pub required_confirmations: u64,
+    /// Which of the locked UTXOs are spent by a batch.
+    pub coin_selection: CoinSelectionStrategy,
+    /// Largest fee the transactions of a batch may pay.
+    pub max_fee: MaxFee,
+    pub payment_amount_limits: PaymentAmountLimits,
+    /// Outputs split off the change of payout transactions.
+    pub change_split: ChangeSplit,
+    pub source: AccountSource,
+}
+
+/// Where an account is defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountSource {
+    /// The config file or environment.
+    Config,
+    /// The `accounts` table, created through `POST /v1/accounts`.
+    Database,
+}
+
+/// Chooses the inputs of a payout transaction among the UTXOs the Payment Receiver locked for the batch.
+/// Locked UTXOs that are not selected stay locked until the Payment Receiver releases the lock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoinSelectionStrategy {
+    /// Spend everything that was locked.
+    #[default]
+    All,
+    /// Take the largest UTXOs until the payments and fee are covered.
+    LargestFirst,
+    /// Take UTXOs in the order the Payment Receiver returned them, which is treated as their age.
+    OldestFirst,
+    /// Use as few inputs as possible and, among those, the combination with the least excess.
+    MinimizeInputs,
+    /// Use the combination whose excess over the payments and fee, i.e. the change, is smallest.
+    MinimizeChange,
+}
+
+impl FromStr for CoinSelectionStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "all" => Ok(CoinSelectionStrategy::All),
+            "largest-first" => Ok(CoinSelectionStrategy::LargestFirst),
+            "oldest-first" => Ok(CoinSelectionStrategy::OldestFirst),
+            "minimize-inputs" => Ok(CoinSelectionStrategy::MinimizeInputs),
+            "minimize-change" => Ok(CoinSelectionStrategy::MinimizeChange),
+            _ => Err(anyhow::anyhow!(
+                "Unknown coin selection strategy '{}', expected one of: all, largest-first, oldest-first, \
+                 minimize-inputs, minimize-change",
+                s
+            )),
+        }
+    }
+}
+
+/// Whether the Swagger UI and `/openapi.json` are served, and to whom.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiDocsMode {
+    /// Not served, so the API surface is not advertised.
+    #[default]
+    Disabled,
+    /// Served to every client.
+    Public,
+    /// Served only to clients allowed to call the `/v1` routes.
+    Protected,
+}
+
+impl FromStr for ApiDocsMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "disabled" | "false" => Ok(ApiDocsMode::Disabled),
+            "public" | "true" => Ok(ApiDocsMode::Public),
+            "protected" => Ok(ApiDocsMode::Protected),
+            _ => Err(anyhow::anyhow!(
+                "Unknown API docs mode '{}', expected one of: disabled, public, protected",
+                s
+            )),
+        }
+    }
+}
+
+/// What an API key may do. Each role includes the permissions of the roles before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiRole {
+    /// Query payments, batches, accounts and statistics, e.g. for monitoring.
+    ReadOnly,
+    /// Also create, cancel and refund payments.
+    PaymentCreator,
+    /// Also manage accounts, resolve batches, pause processing and take backups.
+    Admin,
+}
+
+impl FromStr for ApiRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "read-only" => Ok(ApiRole::ReadOnly),
+            "payment-creator" => Ok(ApiRole::PaymentCreator),
+            "admin" => Ok(ApiRole::Admin),
+            _ => Err(anyhow::anyhow!(
+                "Unknown API role '{}', expected one of: read-only, payment-creator, admin",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiRole::ReadOnly => write!(f, "read-only"),
+            ApiRole::PaymentCreator => write!(f, "payment-creator"),
+            ApiRole::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// A key clients present as `Authorization: Bearer <key>`. Only the SHA-256 of the key is kept in memory.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub name: String,
+    pub key_hash: [u8; 32],
+    pub role: ApiRole,
+    /// Accounts the key may create and cancel payments for. `None` allows every account.
+    pub accounts: Option<Vec<String>>,
+}
+
+impl ApiKey {
+    pub fn hash(key: &str) -> [u8; 32] {
+        Sha256::digest(key.as_bytes()).into()
+    }
+}
+
+/// Bearer tokens (JWTs) issued by an identity provider, accepted next to the static API keys.
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    /// Required `iss` claim.
+    pub issuer: String,
+    /// JSON Web Key Set with the provider's signing keys.
+    pub jwks_url: String,
+    /// Required `aud` claim. `None` does not check the audience.
+    pub audience: Option<String>,
+    /// Claim holding the roles (a string or a list of strings), e.g. `roles` or `scope`.
+    pub role_claim: String,
+    /// Maps claim values to roles. If empty, the values must be role names.
+    pub role_mapping: HashMap<String, ApiRole>,
+    /// Claim listing the accounts a token may use, `*` for all. `None` allows every account.
+    pub accounts_claim: Option<String>,
+}
+
+/// Upper bound on the fee of a batch. If both limits are set, the lower one applies; if neither is, any fee is
+/// accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaxFee {
+    pub micro_minotari: Option<u64>,
+    /// Percentage of the batch's payment total. Ignored for batches without payments, e.g. UTXO consolidations.
+    pub percent: Option<f64>,
+}
+
+impl MaxFee {
+    /// The largest fee in µT of a batch paying `payment_total` µT, or `None` if the fee is not limited.
+    pub fn cap_for(&self, payment_total: u64) -> Option<u64> {
+        let percent_cap = self
+            .percent
+            .filter(|_| payment_total > 0)
+            .map(|percent| (payment_total as f64 * percent / 100.0) as u64);
+        match (self.micro_minotari, percent_cap) {
+            (Some(absolute), Some(relative)) => Some(absolute.min(relative)),
+            (absolute, relative) => absolute.or(relative),
+        }
+    }
+
+    /// Overrides the limits of `self` that are given, keeping the others.
+    fn with_overrides(self, micro_minotari: Option<u64>, percent: Option<f64>, context: &str) -> anyhow::Result<Self> {
+        if let Some(percent) = percent {
+            anyhow::ensure!(
+                percent > 0.0 && percent <= 100.0,
+                "Invalid max_fee_percent {} for {}, expected a value above 0 and at most 100",
+                percent,
+                context
+            );
+        }
+        Ok(MaxFee {
+            micro_minotari: micro_minotari.or(self.micro_minotari),
+            percent: percent.or(self.percent),
+        })
+    }
+}
+
+/// Range of accepted payment amounts in µT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaymentAmountLimits {
+    /// Smaller amounts are rejected as dust.
+    pub min: u64,
+    /// `None` accepts any amount up to the `i64` bound of `PaymentAmount`.
+    pub max: Option<u64>,
+}
+
+impl PaymentAmountLimits {
+    /// Overrides the limits of `self` that are given, keeping the others.
+    fn with_overrides(self, min: Option<u64>, max: Option<u64>, context: &str) -> anyhow::Result<Self> {
+        let limits = PaymentAmountLimits {
+            min: min.unwrap_or(self.min),
+            max: max.or(self.max),
+        };
+        if let Some(max) = limits.max {
+            anyhow::ensure!(
+                limits.min <= max,
+                "Invalid payment amount limits for {}: min_payment_amount {} exceeds max_payment_amount {}",
+                context,
+                limits.min,
+                max
+            );
+        }
+        Ok(limits)
+    }
+}
+
+/// Splits part of the change of a payout transaction into separate outputs, so the wallet keeps several spendable
+/// UTXOs for concurrent batches instead of a single large change output. Disabled if `count` is 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeSplit {
+    /// Most outputs split off per transaction.
+    pub count: usize,
+    /// Value of each split output, in µT.
+    pub denomination: u64,
+}
+
+impl ChangeSplit {
+    /// Overrides the settings of `self` that are given, keeping the others.
+    fn with_overrides(self, count: Option<usize>, denomination: Option<u64>, context: &str) -> anyhow::Result<Self> {
+        let change_split = ChangeSplit {
+            count: count.unwrap_or(self.count),
+            denomination: denomination.unwrap_or(self.denomination),
+        };
+        anyhow::ensure!(
+            change_split.count == 0 || change_split.denomination > 0,
+            "Invalid change split for {}: change_split_count is set, but change_split_denomination is missing",
+            context
+        );
+        Ok(change_split)
+    }
+}
+
+/// Controls how often and how quickly a failing batch is retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Number of failed attempts after which a batch is marked as FAILED.
+    pub max_retries: i64,
+    pub backoff_base_secs: u64,
+    pub backoff_max_secs: u64,
+}
+
+impl RetryPolicy {
+    /// Exponential backoff (`base * 2^retry_count`, capped at the maximum) with jitter,
+    /// so the returned delay lies between half and the full backoff.
+    pub fn backoff_delay(&self, retry_count: i64) -> Duration {
+        let exponent = retry_count.clamp(0, 32) as u32;
+        let backoff = self
+            .backoff_base_secs
+            .saturating_mul(2u64.saturating_pow(exponent))
+            .min(self.backoff_max_secs);
+        let half = backoff / 2;
+        Duration::from_secs(half + rand::rng().random_range(0..=backoff - half))
+    }
+}
+
+/// How long the batch creator lets payments of an account accumulate before batching them.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchingWindow {
+    /// Fewer payments are only batched once the oldest of them has waited `max_wait`.
+    pub min_batch_size: usize,
+    pub max_wait: Duration,
+}
+
+impl BatchingWindow {
+    /// Whether payments of an account, the oldest created at `oldest`, should be batched now.
+    pub fn is_ready(&self, payment_count: usize, oldest: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        payment_count >= self.min_batch_size
+            || now.signed_duration_since(oldest).to_std().unwrap_or_default() >= self.max_wait
+    }
+}
+
+/// How long the broadcaster waits for the transactions of a split cycle to show up in the mempool before it gives up
+/// on the cycle.
+#[derive(Debug, Clone, Copy)]
+pub struct MempoolCheck {
+    /// Number of times each transaction is looked up before the cycle fails.
+    pub retries: usize,
+    /// Delay between two lookups of a transaction.
+    pub delay: Duration,
+    /// Overall limit for verifying all transactions of the cycle, regardless of the retries left.
+    pub deadline: Duration,
+}
+
+/// Retries of calls to the base nodes and the Payment Receiver that failed with a transient error, such as a
+/// connection failure, a timeout or a `5xx` response, before the failure is passed on to the caller.
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkRetry {
+    /// Number of attempts, including the first one.
+    pub attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Time limit of a single attempt.
+    pub timeout: Duration,
+}
+
+impl NetworkRetry {
+    /// Exponential backoff (`base * 2^(attempt - 1)`, capped at the maximum) with full jitter, so concurrent callers
+    /// don't retry in lockstep.
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        backoff.mul_f64(rand::rng().random_range(0.0..=1.0))
+    }
+}
+
+/// Settings of simulation mode, in which steps get deterministic fake signatures instead of being signed by the
+/// console wallet, and are submitted to a simulated base node instead of the configured ones.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationConfig {
+    /// How long the simulated base node takes to accept a submitted transaction.
+    pub broadcast_delay: Duration,
+    /// How long an accepted transaction waits in the simulated mempool before it can be mined.
+    pub mining_delay: Duration,
+    /// Time between two simulated blocks.
+    pub block_time: Duration,
+}
+
+/// Token bucket limits for the payment creation endpoints, applied per client.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    /// Sustained number of requests allowed per second.
+    pub requests_per_second: f64,
+    /// Number of requests a client may send in a burst before being limited.
+    pub burst: u32,
+}
+
+/// Backlog limits above which the API rejects new payments with `503 Service Unavailable`.
+#[derive(Debug, Clone, Copy)]
+pub struct BackpressureConfig {
+    /// Maximum number of RECEIVED payments waiting to be batched.
+    pub max_pending_payments: Option<i64>,
+    /// Maximum number of batches being processed by the pipeline.
+    pub max_in_flight_batches: Option<i64>,
+    /// Value of the `Retry-After` header sent with rejections.
+    pub retry_after_secs: u64,
+}
+
+/// Certificate and key for serving the API over TLS.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// PEM file with the server certificate chain.
+    pub cert_path: String,
+    /// PEM file with the server private key.
+    pub key_path: String,
+    /// PEM file with the CAs trusted to issue client certificates. If set, clients must present a certificate
+    /// issued by one of them (mTLS).
+    pub client_ca_path: Option<String>,
+}
+
+/// Cross-origin requests allowed from browsers, e.g. a dashboard served from another origin.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API. `None` allows every origin.
+    pub allowed_origins: Option<Vec<HeaderValue>>,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    /// How long browsers may cache the answer to a preflight request.
+    pub max_age: Duration,
+}
+
+/// Outgoing chat webhook (Slack, Discord, Telegram, ...) that critical events are posted to.
+#[derive(Debug, Clone)]
+pub struct ChatWebhookConfig {
+    pub url: String,
+    /// Event types that are posted, e.g. `BATCH_FAILED`.
+    pub events: Vec<String>,
+    /// JSON request body. `{message}` is replaced by the rendered message as a JSON string.
+    pub body_template: String,
+    /// Replaces the built-in message of every event. `{event_type}`, `{entity_id}`, `{seq}`, `{created_at}` and the
+    /// top-level fields of the event payload are substituted.
+    pub message_template: Option<String>,
+}
+
+/// Connection pool size and pragmas of the SQLite database.
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseConfig {
+    /// Size of the pool that the API reads through. Writes use a single connection, see `db::connect_writer`.
+    pub max_connections: u32,
+    /// How long a connection waits for a lock held by another connection before failing with SQLITE_BUSY.
+    pub busy_timeout: Duration,
+    pub journal_mode: SqliteJournalMode,
+    pub synchronous: SqliteSynchronous,
+    /// Apply pending migrations when the database is opened. Otherwise they are applied with `migrate up`.
+    pub auto_migrate: bool,
+}
+
+/// Settings of the proactive UTXO consolidation worker.
+#[derive(Debug, Clone, Copy)]
+pub struct ConsolidationConfig {
+    /// First UTC hour (0-23) of the daily window in which consolidation runs.
+    pub quiet_hours_start: u32,
+    /// UTC hour (0-23) at which the window ends, exclusive. May be smaller than the start for windows that span
+    /// midnight.
+    pub quiet_hours_end: u32,
+    /// Consolidation only starts if locking the available balance yields at least this many UTXOs.
+    pub min_inputs: usize,
+    /// How long the Payment Receiver keeps the locked UTXOs reserved.
+    pub lock_secs: i64,
+}
+
+impl ConsolidationConfig {
+    pub fn is_quiet_hour(&self, hour: u32) -> bool {
+        if self.quiet_hours_start <= self.quiet_hours_end {
+            (self.quiet_hours_start..self.quiet_hours_end).contains(&hour)
+        } else {
+            hour >= self.quiet_hours_start || hour < self.quiet_hours_end
+        }
+    }
+}
+
+/// Payment receiver accounts keyed by lower-cased name, shared by the API and the workers.
+///
+/// Clones share the same accounts. `replace` swaps them for all holders at once; readers keep the snapshot returned
+/// by `current` until they ask again.
+#[derive(Debug, Clone, Default)]
+pub struct SharedAccounts(Arc<RwLock<Arc<HashMap<String, PaymentReceiverAccount>>>>);
+
+impl SharedAccounts {
+    pub fn new(accounts: HashMap<String, PaymentReceiverAccount>) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(accounts))))
+    }
+
+    pub fn current(&self) -> Arc<HashMap<String, PaymentReceiverAccount>> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn replace(&self, accounts: Arc<HashMap<String, PaymentReceiverAccount>>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = accounts;
+    }
+}
+
+/// Retry policies with per-account overrides.
+#[derive(Debug, Clone)]
+pub struct RetryPolicies {
+    pub default: RetryPolicy,
+    /// Keyed by lower-cased account name.
+    pub accounts: HashMap<String, RetryPolicy>,
+}
+
+impl RetryPolicies {
+    pub fn for_account(&self, account_name: &str) -> &RetryPolicy {
+        self.accounts.get(&account_name.to_lowercase()).unwrap_or(&self.default)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PaymentProcessorEnv {
     pub tari_network: Network,
     pub database_url: String,
+    pub database: DatabaseConfig,
     pub payment_receiver: String,
-    pub base_node: String,
+    pub base_nodes: Vec<String>,
     pub console_wallet_path: String,
     pub console_wallet_base_path: String,
-    pub console_wallet_password: String,
+    /// Empty until prompted for if `console_wallet_password_prompt` is set.
+    pub console_wallet_password: SecretString,
+    /// Ask for the console wallet password on the terminal when the service starts.
+    pub console_wallet_password_prompt: bool,
     pub listen_ip: String,
     pub listen_port: u16,
+    /// Whether this process serves the HTTP API.
+    pub run_api: bool,
+    /// Workers this process runs. `None` runs every worker enabled by the configuration.
+    pub workers: Option<Vec<String>>,
+    /// Path of the log4rs configuration file.
+    pub log_config_path: String,
     pub batch_creator_sleep_secs: Option<u64>,
+    pub batch_creator_dedicated_high_priority_batches: bool,
+    pub batching_window: BatchingWindow,
     pub unsigned_tx_creator_sleep_secs: Option<u64>,
+    pub unsigned_tx_creator_max_concurrency: Option<usize>,
     pub transaction_signer_sleep_secs: Option<u64>,
+    pub transaction_signer_max_parallelism: Option<usize>,
+    pub transaction_signer_timeout_secs: Option<u64>,
+    pub console_wallet_check_sleep_secs: Option<u64>,
     pub broadcaster_sleep_secs: Option<u64>,
     pub confirmation_checker_sleep_secs: Option<u64>,
-    pub confirmation_checker_required_confirmations: Option<u64>,
+    /// How often the shared chain tip is refreshed.
+    pub chain_state_sleep_secs: Option<u64>,
+    /// Consecutive checks without finding a transaction on the base node before it is re-submitted.
+    pub confirmation_checker_rebroadcast_after_checks: Option<u32>,
+    /// Depth at which confirmed batches become SETTLED. `None` keeps them CONFIRMED.
+    pub settlement_confirmations: Option<u64>,
+    pub maintenance_sleep_secs: Option<u64>,
+    pub reconciliation_sleep_secs: Option<u64>,
+    /// Discrepancy in µT above which a reconciliation report raises an alert event.
+    pub reconciliation_discrepancy_threshold: Option<i64>,
+    pub balance_monitor_sleep_secs: Option<u64>,
+    /// Balance in µT an account should keep on top of its pending payments before a LOW_BALANCE event is recorded.
+    pub low_balance_reserve: Option<i64>,
+    pub consolidation_sleep_secs: Option<u64>,
+    /// `None` disables proactive UTXO consolidation.
+    pub consolidation: Option<ConsolidationConfig>,
+    pub chat_notifier_sleep_secs: Option<u64>,
+    pub import_job_runner_sleep_secs: Option<u64>,
+    /// `None` disables chat notifications.
+    pub chat_webhook: Option<ChatWebhookConfig>,
+    /// Default for accounts without their own `REQUIRED_CONFIRMATIONS`.
+    pub confirmation_checker_required_confirmations: u64,
     pub max_input_count_per_tx: usize,
-    pub accounts: HashMap<String, PaymentReceiverAccount>,
+    /// Maximum number of batches a pipeline worker picks up per cycle.
+    pub worker_batches_per_cycle: i64,
+    /// Accepted payment amounts of accounts without their own `MIN_PAYMENT_AMOUNT` or `MAX_PAYMENT_AMOUNT`,
+    /// including accounts created through the API.
+    pub default_payment_amount_limits: PaymentAmountLimits,
+    /// Largest fee in µT a signed transaction may pay before the broadcaster refuses to submit it.
+    pub max_transaction_fee: u64,
+    /// Whether the broadcaster submits transactions to every configured base node instead of only the active one.
+    pub broadcast_to_all_base_nodes: bool,
+    pub mempool_check: MempoolCheck,
+    pub network_retry: NetworkRetry,
+    /// `None` signs with the console wallet and broadcasts to the configured base nodes.
+    pub simulation: Option<SimulationConfig>,
+    pub retry_policies: RetryPolicies,
+    /// `None` disables rate limiting.
+    pub rate_limit: Option<RateLimitConfig>,
+    /// `None` accepts payments regardless of the backlog.
+    pub backpressure: Option<BackpressureConfig>,
+    /// `None` serves plain HTTP.
+    pub tls: Option<TlsConfig>,
+    /// `None` does not answer cross-origin requests, so browsers block them.
+    pub cors: Option<CorsConfig>,
+    /// Largest request body accepted by the `/v1` routes, except payment imports.
+    pub max_request_body_bytes: usize,
+    /// Time a `/v1` request may take before it fails. Waits, imports and backups get more time, see `api::protect`.
+    pub request_timeout: Duration,
+    /// Most `/v1` requests handled at once; further requests are rejected. `None` does not limit them.
+    pub max_concurrent_requests: Option<usize>,
+    /// Networks allowed to call the `/v1` routes. `None` allows every client.
+    pub api_allowed_networks: Option<Vec<IpNet>>,
+    /// Keys accepted on the `/v1` routes. If empty and `jwt` is `None`, no key is required.
+    pub api_keys: Vec<ApiKey>,
+    /// `None` does not accept JWTs.
+    pub jwt: Option<JwtConfig>,
+    pub api_docs: ApiDocsMode,
+    pub accounts: SharedAccounts,
+    /// Fee buffer of accounts without their own `FEE_BUFFER_AMOUNT`, including accounts created through the API.
+    pub default_fee_buffer_amount: i64,
+    /// Coin selection of accounts without their own `COIN_SELECTION`, including accounts created through the API.
+    pub default_coin_selection: CoinSelectionStrategy,
+    /// Fee limit of accounts without their own `MAX_FEE_MICRO_MINOTARI` or `MAX_FEE_PERCENT`, including accounts
+    /// created through the API.
+    pub default_max_fee: MaxFee,
+    /// Change split of accounts without their own `CHANGE_SPLIT_COUNT` or `CHANGE_SPLIT_DENOMINATION`, including
+    /// accounts created through the API.
+    pub default_change_split: ChangeSplit,
+    /// Encrypts the view keys of accounts created through the API. `None` disables runtime account management.
+    pub accounts_encryption_key: Option<EncryptionKey>,
+    /// Config file the configuration was loaded from, re-read by `reload_accounts`.
+    pub config_file: Option<PathBuf>,
 }
 
 #[derive(Deserialize)]
 struct RawAccount {
     name: String,
-    view_key: String,
+    view_key: Option<String>,
+    view_key_file: Option<String>,
     public_spend_key: String,
+    fee_buffer_amount: Option<i64>,
+    max_retries: Option<i64>,
+    required_confirmations: Option<u64>,
+    coin_selection: Option<String>,
+    max_fee_micro_minotari: Option<u64>,
+    max_fee_percent: Option<f64>,
+    min_payment_amount: Option<u64>,
+    max_payment_amount: Option<u64>,
+    change_split_count: Option<usize>,
+    change_split_denomination: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RawApiKey {
+    key: Option<String>,
+    key_file: Option<String>,
+    role: String,
+    accounts: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -55,22 +643,110 @@ struct RawSettings {
     #[serde(default = "default_network_str")]
     tari_network: String,
     database_url: String,
+    database_max_connections: Option<u32>,
+    database_busy_timeout_ms: Option<u64>,
+    database_journal_mode: Option<String>,
+    database_synchronous: Option<String>,
+    database_auto_migrate: Option<bool>,
     payment_receiver: String,
     base_node: String,
     console_wallet_path: String,
     console_wallet_base_path: String,
-    console_wallet_password: String,
+    console_wallet_password: Option<String>,
+    console_wallet_password_file: Option<String>,
+    console_wallet_password_prompt: Option<bool>,
     #[serde(default = "default_ip")]
     listen_ip: String,
     #[serde(default = "default_port")]
     listen_port: u16,
+    run_api: Option<bool>,
+    workers: Option<String>,
+    #[serde(default = "default_log_config_path")]
+    log_config_path: String,
     batch_creator_sleep_secs: Option<u64>,
+    batch_creator_dedicated_high_priority_batches: Option<bool>,
+    batch_creator_min_batch_size: Option<usize>,
+    batch_creator_max_wait_secs: Option<u64>,
     unsigned_tx_creator_sleep_secs: Option<u64>,
+    unsigned_tx_creator_max_concurrency: Option<usize>,
     transaction_signer_sleep_secs: Option<u64>,
+    transaction_signer_max_parallelism: Option<usize>,
+    transaction_signer_timeout_secs: Option<u64>,
+    console_wallet_check_sleep_secs: Option<u64>,
     broadcaster_sleep_secs: Option<u64>,
     confirmation_checker_sleep_secs: Option<u64>,
+    chain_state_sleep_secs: Option<u64>,
+    confirmation_checker_rebroadcast_after_checks: Option<u32>,
+    settlement_confirmations: Option<u64>,
+    maintenance_sleep_secs: Option<u64>,
+    reconciliation_sleep_secs: Option<u64>,
+    reconciliation_discrepancy_threshold: Option<i64>,
+    balance_monitor_sleep_secs: Option<u64>,
+    low_balance_reserve: Option<i64>,
+    consolidation_sleep_secs: Option<u64>,
+    consolidation_quiet_hours: Option<String>,
+    consolidation_min_inputs: Option<usize>,
+    consolidation_lock_secs: Option<i64>,
     confirmation_checker_required_confirmations: Option<u64>,
     max_input_count_per_tx: Option<usize>,
+    worker_batches_per_cycle: Option<i64>,
+    min_payment_amount: Option<u64>,
+    max_payment_amount: Option<u64>,
+    max_transaction_fee: Option<u64>,
+    broadcast_to_all_base_nodes: Option<bool>,
+    mempool_check_retries: Option<usize>,
+    mempool_check_delay_secs: Option<u64>,
+    mempool_check_deadline_secs: Option<u64>,
+    network_retry_attempts: Option<u32>,
+    network_retry_base_delay_ms: Option<u64>,
+    network_retry_max_delay_ms: Option<u64>,
+    network_timeout_secs: Option<u64>,
+    simulation_mode: Option<bool>,
+    simulation_broadcast_delay_ms: Option<u64>,
+    simulation_mining_delay_secs: Option<u64>,
+    simulation_block_time_secs: Option<u64>,
+    fee_buffer_amount: Option<i64>,
+    coin_selection: Option<String>,
+    max_fee_micro_minotari: Option<u64>,
+    max_fee_percent: Option<f64>,
+    change_split_count: Option<usize>,
+    change_split_denomination: Option<u64>,
+    max_retries: Option<i64>,
+    retry_backoff_base_secs: Option<u64>,
+    retry_backoff_max_secs: Option<u64>,
+    rate_limit_requests_per_second: Option<f64>,
+    rate_limit_burst: Option<u32>,
+    max_pending_payments: Option<i64>,
+    max_in_flight_batches: Option<i64>,
+    backpressure_retry_after_secs: Option<u64>,
+    accounts_encryption_key: Option<String>,
+    accounts_encryption_key_file: Option<String>,
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+    tls_client_ca_path: Option<String>,
+    cors_allowed_origins: Option<String>,
+    cors_allowed_methods: Option<String>,
+    cors_allowed_headers: Option<String>,
+    cors_max_age_secs: Option<u64>,
+    chat_notifier_sleep_secs: Option<u64>,
+    import_job_runner_sleep_secs: Option<u64>,
+    chat_webhook_url: Option<String>,
+    chat_webhook_events: Option<String>,
+    chat_webhook_body_template: Option<String>,
+    chat_webhook_message_template: Option<String>,
+    max_request_body_bytes: Option<usize>,
+    request_timeout_secs: Option<u64>,
+    max_concurrent_requests: Option<usize>,
+    api_allowed_networks: Option<String>,
+    api_docs: Option<String>,
+    #[serde(default)]
+    api_keys: HashMap<String, RawApiKey>,
+    jwt_issuer: Option<String>,
+    jwt_jwks_url: Option<String>,
+    jwt_audience: Option<String>,
+    jwt_role_claim: Option<String>,
+    jwt_role_mapping: Option<String>,
+    jwt_accounts_claim: Option<String>,
     #[serde(default)]
     accounts: HashMap<String, RawAccount>,
 }
@@ -81,22 +757,196 @@ fn default_ip() -> String {
 fn default_port() -> u16 {
     9145
 }
+fn default_log_config_path() -> String {
+    "log4rs.yml".to_string()
+}
 fn default_network_str() -> String {
     "MainNet".to_string()
 }
 
+/// Environment variable naming the config file, used when `--config` is not given.
+pub const CONFIG_FILE_ENV: &str = "CONFIG_FILE";
+
+const DEFAULT_FEE_BUFFER_AMOUNT: i64 = 200_000;
+const DEFAULT_MIN_PAYMENT_AMOUNT: u64 = 1;
+const DEFAULT_MAX_TRANSACTION_FEE: u64 = 1_000_000;
+const DEFAULT_MEMPOOL_CHECK_RETRIES: usize = 10;
+const DEFAULT_MEMPOOL_CHECK_DELAY_SECS: u64 = 2;
+const DEFAULT_MEMPOOL_CHECK_DEADLINE_SECS: u64 = 120;
+const DEFAULT_NETWORK_RETRY_ATTEMPTS: u32 = 3;
+const DEFAULT_NETWORK_RETRY_BASE_DELAY_MS: u64 = 500;
+const DEFAULT_NETWORK_RETRY_MAX_DELAY_MS: u64 = 10_000;
+const DEFAULT_NETWORK_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_SIMULATION_BROADCAST_DELAY_MS: u64 = 500;
+const DEFAULT_SIMULATION_MINING_DELAY_SECS: u64 = 60;
+const DEFAULT_SIMULATION_BLOCK_TIME_SECS: u64 = 120;
+const DEFAULT_MAX_RETRIES: i64 = 10;
+const DEFAULT_BATCH_CREATOR_MAX_WAIT_SECS: u64 = 60 * 60; // 1 hour
+const DEFAULT_REQUIRED_CONFIRMATIONS: u64 = 10;
+const DEFAULT_RETRY_BACKOFF_BASE_SECS: u64 = 15;
+const DEFAULT_RETRY_BACKOFF_MAX_SECS: u64 = 60 * 60; // 1 hour
+const DEFAULT_CONSOLIDATION_MIN_INPUTS: usize = 20;
+const DEFAULT_CONSOLIDATION_LOCK_SECS: i64 = 60 * 60; // 1 hour
+const DEFAULT_DATABASE_MAX_CONNECTIONS: u32 = 5;
+const DEFAULT_DATABASE_BUSY_TIMEOUT_MS: u64 = 5_000;
+const DEFAULT_BACKPRESSURE_RETRY_AFTER_SECS: u64 = 60;
+/// Room for a bulk request of 10,000 items with long memos.
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 8 * 1024 * 1024;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Well above what the database pool serves at once, so only a real pile-up is shed.
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 64;
+/// Shorter keys could be guessed.
+const MIN_API_KEY_LENGTH: usize = 32;
+const DEFAULT_JWT_ROLE_CLAIM: &str = "roles";
+const DEFAULT_CORS_ALLOWED_METHODS: &str = "GET,POST";
+const DEFAULT_CORS_ALLOWED_HEADERS: &str = "authorization,content-type,idempotency-key";
+const DEFAULT_CORS_MAX_AGE_SECS: u64 = 60 * 60; // 1 hour
+const DEFAULT_CHAT_WEBHOOK_EVENTS: &str = "BATCH_FAILED,RECONCILIATION_DISCREPANCY,LOW_BALANCE";
+/// Understood by Slack and Mattermost incoming webhooks.
+const DEFAULT_CHAT_WEBHOOK_BODY_TEMPLATE: &str = r#"{"text": {message}}"#;
+
 impl PaymentProcessorEnv {
-    pub fn load() -> anyhow::Result<Self> {
+    /// Loads the configuration from the optional config file (`config_file`, or the file named by `CONFIG_FILE`),
+    /// with environment variables taking precedence over its values. The file format (TOML, YAML, JSON, ...) is
+    /// derived from the file extension.
+    pub fn load(config_file: Option<&Path>) -> anyhow::Result<Self> {
+        let config_file = config_file
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_FILE_ENV).map(PathBuf::from));
+
+        let mut builder = Config::builder();
+        if let Some(path) = &config_file {
+            builder = builder.add_source(File::from(path.as_path()));
+        }
         // For nested HashMaps (accounts), it supports "ACCOUNTS__KEY__FIELD" syntax.
-        let s = Config::builder()
+        let s = builder
             .add_source(Environment::default().separator("__"))
-            .build()?;
+            .build()
+            .with_context(|| match &config_file {
+                Some(path) => format!("Failed to read config file {}", path.display()),
+                None => "Failed to read configuration".to_string(),
+            })?;
 
         let raw: RawSettings = s
             .try_deserialize()
-            .context("Failed to read configuration from environment variables")?;
+            .context("Failed to read configuration from config file and environment variables")?;
+
+        let mut env = Self::try_from(raw)?;
+        env.config_file = config_file;
+        Ok(env)
+    }
+
+    /// Whether `WORKERS` selects the worker for this process.
+    pub fn runs_worker(&self, name: &str) -> bool {
+        self.workers
+            .as_ref()
+            .is_none_or(|workers| workers.iter().any(|worker| worker == name))
+    }
+
+    /// Re-reads the configuration and swaps in the accounts it defines. Everything else, including per-account
+    /// retry policies, keeps its value until the next restart. Accounts created through the API are kept unless the
+    /// configuration now defines an account of the same name. Returns the number of accounts now configured.
+    ///
+    /// The process environment cannot change while the service runs, so new accounts have to be added to the
+    /// config file.
+    pub fn reload_accounts(&self) -> anyhow::Result<usize> {
+        let reloaded = Self::load(self.config_file.as_deref())?;
+        let mut accounts = (*reloaded.accounts.current()).clone();
+        for (key, account) in self.accounts.current().iter() {
+            if account.source == AccountSource::Database {
+                accounts.entry(key.clone()).or_insert_with(|| account.clone());
+            }
+        }
+        let count = accounts.len();
+        self.accounts.replace(Arc::new(accounts));
+        Ok(count)
+    }
 
-        Self::try_from(raw)
+    /// Replaces the accounts created through the API with the enabled ones in the `accounts` table. Accounts from
+    /// the configuration take precedence over stored accounts of the same name. Stored accounts whose view key
+    /// cannot be decrypted are skipped. Returns the number of stored accounts loaded.
+    pub async fn load_stored_accounts(&self, conn: &mut sqlx::SqliteConnection) -> anyhow::Result<usize> {
+        let Some(encryption_key) = &self.accounts_encryption_key else {
+            return Ok(0);
+        };
+        let stored_accounts = StoredAccount::find_enabled(conn).await?;
+
+        let mut accounts: HashMap<_, _> = self
+            .accounts
+            .current()
+            .iter()
+            .filter(|(_, account)| account.source == AccountSource::Config)
+            .map(|(key, account)| (key.clone(), account.clone()))
+            .collect();
+        let mut loaded = 0;
+        for stored in stored_accounts {
+            if accounts.contains_key(&stored.name) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Stored account '{}' is shadowed by the configured account of the same name.",
+                    stored.display_name
+                );
+                continue;
+            }
+            match self.account_from_stored(&stored, encryption_key) {
+                Ok(account) => {
+                    accounts.insert(stored.name, account);
+                    loaded += 1;
+                },
+                Err(e) => error!(
+                    target: LOG_TARGET,
+                    "Failed to load stored account '{}': {:#}",
+                    stored.display_name,
+                    e
+                ),
+            }
+        }
+        self.accounts.replace(Arc::new(accounts));
+        Ok(loaded)
+    }
+
+    pub fn account_from_stored(
+        &self,
+        stored: &StoredAccount,
+        encryption_key: &EncryptionKey,
+    ) -> anyhow::Result<PaymentReceiverAccount> {
+        let view_key_hex = String::from_utf8(encryption_key.decrypt(&stored.encrypted_view_key)?)?;
+        let view_key = parse_view_key(&view_key_hex).context("Failed to parse view_key")?;
+        let public_spend_key =
+            parse_public_spend_key(&stored.public_spend_key).context("Failed to parse public_spend_key")?;
+        let address = derive_address(&view_key, &public_spend_key, self.tari_network)?;
+
+        Ok(PaymentReceiverAccount {
+            name: stored.display_name.clone(),
+            view_key,
+            public_spend_key,
+            address,
+            fee_buffer_amount: self.default_fee_buffer_amount,
+            required_confirmations: self.confirmation_checker_required_confirmations,
+            coin_selection: self.default_coin_selection,
+            max_fee: self.default_max_fee,
+            payment_amount_limits: self.default_payment_amount_limits,
+            change_split: self.default_change_split,
+            source: AccountSource::Database,
+        })
+    }
+
+    /// Confirmations required for new batches of `account_name`.
+    pub fn required_confirmations_for(&self, account_name: &str) -> u64 {
+        self.accounts
+            .current()
+            .get(&account_name.to_lowercase())
+            .map(|account| account.required_confirmations)
+            .unwrap_or(self.confirmation_checker_required_confirmations)
+    }
+
+    /// Accepted payment amounts of `account_name`.
+    pub fn payment_amount_limits_for(&self, account_name: &str) -> PaymentAmountLimits {
+        self.accounts
+            .current()
+            .get(&account_name.to_lowercase())
+            .map(|account| account.payment_amount_limits)
+            .unwrap_or(self.default_payment_amount_limits)
     }
 }
 
@@ -107,9 +957,285 @@ impl TryFrom<RawSettings> for PaymentProcessorEnv {
         let tari_network = Network::from_str(&raw.tari_network)
             .context(format!("Failed to parse tari_network: {}", raw.tari_network))?;
 
+        let default_retry_policy = RetryPolicy {
+            max_retries: raw.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            backoff_base_secs: raw.retry_backoff_base_secs.unwrap_or(DEFAULT_RETRY_BACKOFF_BASE_SECS),
+            backoff_max_secs: raw.retry_backoff_max_secs.unwrap_or(DEFAULT_RETRY_BACKOFF_MAX_SECS),
+        };
+        let default_fee_buffer_amount = raw.fee_buffer_amount.unwrap_or(DEFAULT_FEE_BUFFER_AMOUNT);
+        let default_coin_selection = raw
+            .coin_selection
+            .as_deref()
+            .map(CoinSelectionStrategy::from_str)
+            .transpose()?
+            .unwrap_or_default();
+        let default_max_fee =
+            MaxFee::default().with_overrides(raw.max_fee_micro_minotari, raw.max_fee_percent, "the default")?;
+        let default_payment_amount_limits = PaymentAmountLimits {
+            min: DEFAULT_MIN_PAYMENT_AMOUNT,
+            max: None,
+        }
+        .with_overrides(raw.min_payment_amount, raw.max_payment_amount, "the default")?;
+        let default_change_split = ChangeSplit::default().with_overrides(
+            raw.change_split_count,
+            raw.change_split_denomination,
+            "the default",
+        )?;
+        let default_required_confirmations = raw
+            .confirmation_checker_required_confirmations
+            .unwrap_or(DEFAULT_REQUIRED_CONFIRMATIONS);
+
+        let rate_limit = match raw.rate_limit_requests_per_second {
+            Some(requests_per_second) if requests_per_second > 0.0 => Some(RateLimitConfig {
+                requests_per_second,
+                burst: raw.rate_limit_burst.unwrap_or(requests_per_second.ceil() as u32).max(1),
+            }),
+            _ => None,
+        };
+
+        let backpressure = match (raw.max_pending_payments, raw.max_in_flight_batches) {
+            (None, None) => None,
+            (max_pending_payments, max_in_flight_batches) => Some(BackpressureConfig {
+                max_pending_payments,
+                max_in_flight_batches,
+                retry_after_secs: raw
+                    .backpressure_retry_after_secs
+                    .unwrap_or(DEFAULT_BACKPRESSURE_RETRY_AFTER_SECS),
+            }),
+        };
+
+        let tls = match (raw.tls_cert_path, raw.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                cert_path,
+                key_path,
+                client_ca_path: raw.tls_client_ca_path,
+            }),
+            (None, None) if raw.tls_client_ca_path.is_some() => {
+                return Err(anyhow::anyhow!(
+                    "tls_client_ca_path requires tls_cert_path and tls_key_path"
+                ));
+            },
+            (None, None) => None,
+            _ => return Err(anyhow::anyhow!("tls_cert_path and tls_key_path must be set together")),
+        };
+
+        let cors = match raw.cors_allowed_origins.filter(|origins| !origins.trim().is_empty()) {
+            Some(origins) => {
+                let origins = split_list(&origins);
+                let allowed_origins = if origins.iter().any(|origin| origin == "*") {
+                    None
+                } else {
+                    Some(
+                        origins
+                            .iter()
+                            .map(|origin| {
+                                HeaderValue::from_str(origin.trim_end_matches('/'))
+                                    .context(format!("Invalid origin in cors_allowed_origins: '{}'", origin))
+                            })
+                            .collect::<anyhow::Result<Vec<_>>>()?,
+                    )
+                };
+                let allowed_methods = split_list(
+                    raw.cors_allowed_methods
+                        .as_deref()
+                        .unwrap_or(DEFAULT_CORS_ALLOWED_METHODS),
+                )
+                .iter()
+                .map(|method| {
+                    Method::from_str(&method.to_uppercase())
+                        .context(format!("Invalid method in cors_allowed_methods: '{}'", method))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+                let allowed_headers = split_list(
+                    raw.cors_allowed_headers
+                        .as_deref()
+                        .unwrap_or(DEFAULT_CORS_ALLOWED_HEADERS),
+                )
+                .iter()
+                .map(|name| {
+                    HeaderName::from_str(name).context(format!("Invalid header in cors_allowed_headers: '{}'", name))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+                Some(CorsConfig {
+                    allowed_origins,
+                    allowed_methods,
+                    allowed_headers,
+                    max_age: Duration::from_secs(raw.cors_max_age_secs.unwrap_or(DEFAULT_CORS_MAX_AGE_SECS)),
+                })
+            },
+            None => None,
+        };
+
+        let chat_webhook = match raw.chat_webhook_url.filter(|url| !url.trim().is_empty()) {
+            Some(url) => {
+                let body_template = raw
+                    .chat_webhook_body_template
+                    .unwrap_or_else(|| DEFAULT_CHAT_WEBHOOK_BODY_TEMPLATE.to_string());
+                if !body_template.contains("{message}") {
+                    return Err(anyhow::anyhow!("chat_webhook_body_template must contain {{message}}"));
+                }
+                let events = raw
+                    .chat_webhook_events
+                    .as_deref()
+                    .unwrap_or(DEFAULT_CHAT_WEBHOOK_EVENTS)
+                    .split(',')
+                    .map(|event| event.trim().to_uppercase())
+                    .filter(|event| !event.is_empty())
+                    .collect();
+                Some(ChatWebhookConfig {
+                    url,
+                    events,
+                    body_template,
+                    message_template: raw.chat_webhook_message_template,
+                })
+            },
+            None => None,
+        };
+
+        let network_retry = NetworkRetry {
+            attempts: raw
+                .network_retry_attempts
+                .unwrap_or(DEFAULT_NETWORK_RETRY_ATTEMPTS)
+                .max(1),
+            base_delay: Duration::from_millis(
+                raw.network_retry_base_delay_ms
+                    .unwrap_or(DEFAULT_NETWORK_RETRY_BASE_DELAY_MS),
+            ),
+            max_delay: Duration::from_millis(
+                raw.network_retry_max_delay_ms
+                    .unwrap_or(DEFAULT_NETWORK_RETRY_MAX_DELAY_MS),
+            ),
+            timeout: Duration::from_secs(raw.network_timeout_secs.unwrap_or(DEFAULT_NETWORK_TIMEOUT_SECS).max(1)),
+        };
+
+        let simulation = if raw.simulation_mode.unwrap_or(false) {
+            let broadcast_delay = Duration::from_millis(
+                raw.simulation_broadcast_delay_ms
+                    .unwrap_or(DEFAULT_SIMULATION_BROADCAST_DELAY_MS),
+            );
+            if broadcast_delay >= network_retry.timeout {
+                return Err(anyhow::anyhow!(
+                    "simulation_broadcast_delay_ms must be shorter than network_timeout_secs"
+                ));
+            }
+            Some(SimulationConfig {
+                broadcast_delay,
+                mining_delay: Duration::from_secs(
+                    raw.simulation_mining_delay_secs
+                        .unwrap_or(DEFAULT_SIMULATION_MINING_DELAY_SECS),
+                ),
+                block_time: Duration::from_secs(
+                    raw.simulation_block_time_secs
+                        .unwrap_or(DEFAULT_SIMULATION_BLOCK_TIME_SECS)
+                        .max(1),
+                ),
+            })
+        } else {
+            None
+        };
+
+        let database = DatabaseConfig {
+            max_connections: raw
+                .database_max_connections
+                .unwrap_or(DEFAULT_DATABASE_MAX_CONNECTIONS)
+                .max(1),
+            busy_timeout: Duration::from_millis(
+                raw.database_busy_timeout_ms.unwrap_or(DEFAULT_DATABASE_BUSY_TIMEOUT_MS),
+            ),
+            journal_mode: match raw.database_journal_mode.as_deref() {
+                Some(mode) => SqliteJournalMode::from_str(mode)
+                    .map_err(|_| anyhow::anyhow!("Invalid database_journal_mode '{}'", mode))?,
+                None => SqliteJournalMode::Wal,
+            },
+            synchronous: match raw.database_synchronous.as_deref() {
+                Some(mode) => SqliteSynchronous::from_str(mode)
+                    .map_err(|_| anyhow::anyhow!("Invalid database_synchronous '{}'", mode))?,
+                None => SqliteSynchronous::Normal,
+            },
+            auto_migrate: raw.database_auto_migrate.unwrap_or(true),
+        };
+
+        let consolidation = match raw.consolidation_quiet_hours.as_deref() {
+            Some(quiet_hours) => {
+                let (quiet_hours_start, quiet_hours_end) = parse_quiet_hours(quiet_hours)?;
+                Some(ConsolidationConfig {
+                    quiet_hours_start,
+                    quiet_hours_end,
+                    min_inputs: raw
+                        .consolidation_min_inputs
+                        .unwrap_or(DEFAULT_CONSOLIDATION_MIN_INPUTS)
+                        .max(2),
+                    lock_secs: raw
+                        .consolidation_lock_secs
+                        .unwrap_or(DEFAULT_CONSOLIDATION_LOCK_SECS)
+                        .max(1),
+                })
+            },
+            None => None,
+        };
+
+        let mut api_keys = Vec::new();
+        for (name, raw_key) in raw.api_keys {
+            let key = secrets::resolve(&format!("api_keys.{}.key", name), raw_key.key, raw_key.key_file)?;
+            if key.len() < MIN_API_KEY_LENGTH {
+                return Err(anyhow::anyhow!(
+                    "API key '{}' must be at least {} characters long",
+                    name,
+                    MIN_API_KEY_LENGTH
+                ));
+            }
+            let role = ApiRole::from_str(&raw_key.role).context(format!("Invalid role for API key '{}'", name))?;
+            let key_hash = ApiKey::hash(&key);
+            if let Some(duplicate) = api_keys.iter().find(|other| other.key_hash == key_hash) {
+                return Err(anyhow::anyhow!(
+                    "API keys '{}' and '{}' are the same key",
+                    duplicate.name,
+                    name
+                ));
+            }
+            let accounts = raw_key.accounts.as_deref().and_then(parse_account_scope);
+            api_keys.push(ApiKey {
+                name,
+                key_hash,
+                role,
+                accounts,
+            });
+        }
+        api_keys.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let jwt = match raw.jwt_issuer.filter(|issuer| !issuer.trim().is_empty()) {
+            Some(issuer) => {
+                let jwks_url = raw
+                    .jwt_jwks_url
+                    .filter(|url| !url.trim().is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("jwt_jwks_url must be set if jwt_issuer is set"))?;
+                let role_mapping = raw
+                    .jwt_role_mapping
+                    .as_deref()
+                    .map(parse_role_mapping)
+                    .transpose()?
+                    .unwrap_or_default();
+                Some(JwtConfig {
+                    issuer,
+                    jwks_url,
+                    audience: raw.jwt_audience.filter(|audience| !audience.is_empty()),
+                    role_claim: raw.jwt_role_claim.unwrap_or_else(|| DEFAULT_JWT_ROLE_CLAIM.to_string()),
+                    role_mapping,
+                    accounts_claim: raw.jwt_accounts_claim.filter(|claim| !claim.is_empty()),
+                })
+            },
+            None => None,
+        };
+
         let mut accounts = HashMap::new();
+        let mut account_retry_policies = HashMap::new();
         for (_key, raw_acc) in raw.accounts {
-            let view_key = parse_view_key(&raw_acc.view_key)
+            let raw_view_key = secrets::resolve(
+                &format!("accounts.{}.view_key", raw_acc.name),
+                raw_acc.view_key,
+                raw_acc.view_key_file,
+            )?;
+            let view_key = parse_view_key(&raw_view_key)
                 .context(format!("Failed to parse view_key for account '{}'", raw_acc.name))?;
 
             let public_spend_key = parse_public_spend_key(&raw_acc.public_spend_key).context(format!(
@@ -117,13 +1243,39 @@ impl TryFrom<RawSettings> for PaymentProcessorEnv {
                 raw_acc.name
             ))?;
 
-            let address = TariAddress::new_dual_address(
-                CompressedPublicKey::new_from_pk(RistrettoPublicKey::from_secret_key(&view_key)),
-                public_spend_key.clone(),
-                tari_network,
-                TariAddressFeatures::create_one_sided_only(),
-                None,
+            let address = derive_address(&view_key, &public_spend_key, tari_network)?;
+
+            let coin_selection = match raw_acc.coin_selection.as_deref() {
+                Some(strategy) => CoinSelectionStrategy::from_str(strategy)
+                    .context(format!("Invalid coin_selection for account '{}'", raw_acc.name))?,
+                None => default_coin_selection,
+            };
+
+            let max_fee = default_max_fee.with_overrides(
+                raw_acc.max_fee_micro_minotari,
+                raw_acc.max_fee_percent,
+                &format!("account '{}'", raw_acc.name),
+            )?;
+            let payment_amount_limits = default_payment_amount_limits.with_overrides(
+                raw_acc.min_payment_amount,
+                raw_acc.max_payment_amount,
+                &format!("account '{}'", raw_acc.name),
             )?;
+            let change_split = default_change_split.with_overrides(
+                raw_acc.change_split_count,
+                raw_acc.change_split_denomination,
+                &format!("account '{}'", raw_acc.name),
+            )?;
+
+            if let Some(max_retries) = raw_acc.max_retries {
+                account_retry_policies.insert(
+                    raw_acc.name.to_lowercase(),
+                    RetryPolicy {
+                        max_retries,
+                        ..default_retry_policy
+                    },
+                );
+            }
 
             accounts.insert(
                 raw_acc.name.clone().to_lowercase(),
@@ -132,39 +1284,260 @@ impl TryFrom<RawSettings> for PaymentProcessorEnv {
                     view_key,
                     public_spend_key,
                     address,
+                    fee_buffer_amount: raw_acc.fee_buffer_amount.unwrap_or(default_fee_buffer_amount),
+                    required_confirmations: raw_acc.required_confirmations.unwrap_or(default_required_confirmations),
+                    coin_selection,
+                    max_fee,
+                    payment_amount_limits,
+                    change_split,
+                    source: AccountSource::Config,
                 },
             );
         }
 
+        let accounts_encryption_key = match (raw.accounts_encryption_key, raw.accounts_encryption_key_file) {
+            (None, None) => None,
+            (value, file) => {
+                let key_hex = secrets::resolve("accounts_encryption_key", value, file)?;
+                Some(EncryptionKey::from_hex(&key_hex).context("Invalid accounts_encryption_key")?)
+            },
+        };
+
+        // With the prompt, the password is only asked for by `serve`, so reloads and CLI commands never block.
+        let console_wallet_password_prompt = raw.console_wallet_password_prompt.unwrap_or(false);
+        let console_wallet_password = if console_wallet_password_prompt {
+            SecretString::default()
+        } else {
+            secrets::resolve(
+                "console_wallet_password",
+                raw.console_wallet_password,
+                raw.console_wallet_password_file,
+            )?
+            .into()
+        };
+
         Ok(Self {
             tari_network,
             database_url: raw.database_url,
+            database,
             payment_receiver: raw.payment_receiver,
-            base_node: raw.base_node,
+            base_nodes: parse_base_nodes(&raw.base_node)?,
             console_wallet_path: raw.console_wallet_path,
             console_wallet_base_path: raw.console_wallet_base_path,
-            console_wallet_password: raw.console_wallet_password,
+            console_wallet_password,
+            console_wallet_password_prompt,
             listen_ip: raw.listen_ip,
             listen_port: raw.listen_port,
+            run_api: raw.run_api.unwrap_or(true),
+            workers: raw.workers.as_deref().map(parse_workers),
+            log_config_path: raw.log_config_path,
             batch_creator_sleep_secs: raw.batch_creator_sleep_secs,
+            batch_creator_dedicated_high_priority_batches: raw
+                .batch_creator_dedicated_high_priority_batches
+                .unwrap_or(false),
+            batching_window: BatchingWindow {
+                min_batch_size: raw.batch_creator_min_batch_size.unwrap_or(1).clamp(1, MAX_BATCH_SIZE),
+                max_wait: Duration::from_secs(
+                    raw.batch_creator_max_wait_secs
+                        .unwrap_or(DEFAULT_BATCH_CREATOR_MAX_WAIT_SECS),
+                ),
+            },
             unsigned_tx_creator_sleep_secs: raw.unsigned_tx_creator_sleep_secs,
+            unsigned_tx_creator_max_concurrency: raw.unsigned_tx_creator_max_concurrency,
             transaction_signer_sleep_secs: raw.transaction_signer_sleep_secs,
+            transaction_signer_max_parallelism: raw.transaction_signer_max_parallelism,
+            transaction_signer_timeout_secs: raw.transaction_signer_timeout_secs,
+            console_wallet_check_sleep_secs: raw.console_wallet_check_sleep_secs,
             broadcaster_sleep_secs: raw.broadcaster_sleep_secs,
             confirmation_checker_sleep_secs: raw.confirmation_checker_sleep_secs,
-            confirmation_checker_required_confirmations: raw.confirmation_checker_required_confirmations,
+            chain_state_sleep_secs: raw.chain_state_sleep_secs,
+            confirmation_checker_rebroadcast_after_checks: raw.confirmation_checker_rebroadcast_after_checks,
+            settlement_confirmations: raw.settlement_confirmations.filter(|&depth| depth > 0),
+            maintenance_sleep_secs: raw.maintenance_sleep_secs,
+            reconciliation_sleep_secs: raw.reconciliation_sleep_secs,
+            reconciliation_discrepancy_threshold: raw.reconciliation_discrepancy_threshold,
+            balance_monitor_sleep_secs: raw.balance_monitor_sleep_secs,
+            low_balance_reserve: raw.low_balance_reserve,
+            consolidation_sleep_secs: raw.consolidation_sleep_secs,
+            consolidation,
+            chat_notifier_sleep_secs: raw.chat_notifier_sleep_secs,
+            import_job_runner_sleep_secs: raw.import_job_runner_sleep_secs,
+            chat_webhook,
+            confirmation_checker_required_confirmations: default_required_confirmations,
             max_input_count_per_tx: raw.max_input_count_per_tx.unwrap_or(400).min(400),
-            accounts,
+            worker_batches_per_cycle: raw.worker_batches_per_cycle.unwrap_or(100).max(1),
+            max_transaction_fee: raw.max_transaction_fee.unwrap_or(DEFAULT_MAX_TRANSACTION_FEE),
+            broadcast_to_all_base_nodes: raw.broadcast_to_all_base_nodes.unwrap_or(false),
+            mempool_check: MempoolCheck {
+                retries: raw
+                    .mempool_check_retries
+                    .unwrap_or(DEFAULT_MEMPOOL_CHECK_RETRIES)
+                    .max(1),
+                delay: Duration::from_secs(raw.mempool_check_delay_secs.unwrap_or(DEFAULT_MEMPOOL_CHECK_DELAY_SECS)),
+                deadline: Duration::from_secs(
+                    raw.mempool_check_deadline_secs
+                        .unwrap_or(DEFAULT_MEMPOOL_CHECK_DEADLINE_SECS),
+                ),
+            },
+            network_retry,
+            simulation,
+            retry_policies: RetryPolicies {
+                default: default_retry_policy,
+                accounts: account_retry_policies,
+            },
+            rate_limit,
+            backpressure,
+            tls,
+            cors,
+            max_request_body_bytes: raw
+                .max_request_body_bytes
+                .unwrap_or(DEFAULT_MAX_REQUEST_BODY_BYTES)
+                .max(1),
+            request_timeout: Duration::from_secs(
+                raw.request_timeout_secs.unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS).max(1),
+            ),
+            // 0 turns the limit off.
+            max_concurrent_requests: Some(raw.max_concurrent_requests.unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS))
+                .filter(|&limit| limit > 0),
+            api_allowed_networks: raw.api_allowed_networks.as_deref().map(parse_networks).transpose()?,
+            api_keys,
+            jwt,
+            api_docs: raw
+                .api_docs
+                .as_deref()
+                .map(ApiDocsMode::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+            accounts: SharedAccounts::new(accounts),
+            default_fee_buffer_amount,
+            default_coin_selection,
+            default_max_fee,
+            default_payment_amount_limits,
+            default_change_split,
+            accounts_encryption_key,
+            config_file: None,
+        })
+    }
+}
+
+/// Parses a comma-separated list of networks in CIDR notation. A plain IP address allows that single address.
+fn parse_networks(raw: &str) -> anyhow::Result<Vec<IpNet>> {
+    let networks = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|network| !network.is_empty())
+        .map(|network| {
+            IpNet::from_str(network)
+                .or_else(|_| IpAddr::from_str(network).map(IpNet::from))
+                .map_err(|_| anyhow::anyhow!("Invalid network in api_allowed_networks: '{}'", network))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if networks.is_empty() {
+        return Err(anyhow::anyhow!(
+            "api_allowed_networks must contain at least one network"
+        ));
+    }
+    Ok(networks)
+}
+
+/// Splits a comma-separated list, dropping empty entries.
+fn split_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parses a comma-separated list of account names. `*` allows every account and returns `None`.
+fn parse_account_scope(raw: &str) -> Option<Vec<String>> {
+    let accounts: Vec<String> = raw
+        .split(',')
+        .map(|account| account.trim().to_lowercase())
+        .filter(|account| !account.is_empty())
+        .collect();
+    if accounts.iter().any(|account| account == "*") {
+        None
+    } else {
+        Some(accounts)
+    }
+}
+
+/// Parses a comma-separated list of `<claim value>=<role>` pairs.
+fn parse_role_mapping(raw: &str) -> anyhow::Result<HashMap<String, ApiRole>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (value, role) = pair.rsplit_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid entry in jwt_role_mapping: '{}', expected <value>=<role>", pair)
+            })?;
+            Ok((value.trim().to_string(), ApiRole::from_str(role)?))
         })
+        .collect()
+}
+
+/// Parses a comma-separated list of worker names. `none` selects no worker.
+fn parse_workers(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty() && name != "none")
+        .collect()
+}
+
+/// Parses a `<start>-<end>` range of UTC hours, e.g. `1-5` or `22-4`.
+fn parse_quiet_hours(raw: &str) -> anyhow::Result<(u32, u32)> {
+    let invalid = || anyhow::anyhow!("Invalid consolidation_quiet_hours '{}', expected e.g. '1-5'", raw);
+    let (start, end) = raw.split_once('-').ok_or_else(invalid)?;
+    let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+    let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+
+    if start > 23 || end > 23 || start == end {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+/// Parses a comma-separated list of base node URLs, in order of preference.
+fn parse_base_nodes(raw: &str) -> anyhow::Result<Vec<String>> {
+    let base_nodes: Vec<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(String::from)
+        .collect();
+
+    if base_nodes.is_empty() {
+        return Err(anyhow::anyhow!("base_node must contain at least one URL"));
     }
+
+    Ok(base_nodes)
+}
+
+/// The one-sided address of an account.
+pub fn derive_address(
+    view_key: &RistrettoSecretKey,
+    public_spend_key: &CompressedKey<RistrettoPublicKey>,
+    network: Network,
+) -> anyhow::Result<TariAddress> {
+    let address = TariAddress::new_dual_address(
+        CompressedPublicKey::new_from_pk(RistrettoPublicKey::from_secret_key(view_key)),
+        public_spend_key.clone(),
+        network,
+        TariAddressFeatures::create_one_sided_only(),
+        None,
+    )?;
+    Ok(address)
 }
 
-fn parse_view_key(view_key_hex: &str) -> anyhow::Result<RistrettoSecretKey> {
+pub fn parse_view_key(view_key_hex: &str) -> anyhow::Result<RistrettoSecretKey> {
     let view_key_bytes = hex::decode(view_key_hex)?;
     let view_key = RistrettoSecretKey::from_canonical_bytes(&view_key_bytes).map_err(|e| anyhow::anyhow!(e))?;
     Ok(view_key)
 }
 
-fn parse_public_spend_key(public_spend_key_hex: &str) -> anyhow::Result<CompressedKey<RistrettoPublicKey>> {
+pub fn parse_public_spend_key(public_spend_key_hex: &str) -> anyhow::Result<CompressedKey<RistrettoPublicKey>> {
     let spend_key_bytes = hex::decode(public_spend_key_hex)?;
     let spend_key =
         CompressedKey::<RistrettoPublicKey>::from_canonical_bytes(&spend_key_bytes).map_err(|e| anyhow::anyhow!(e))?;
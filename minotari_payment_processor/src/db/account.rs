@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+
+/// An account created through the API. Its view key is encrypted with the configured `ACCOUNTS_ENCRYPTION_KEY`.
+#[derive(Debug, Clone, FromRow)]
+pub struct StoredAccount {
+    /// Lower-cased name.
+    pub name: String,
+    pub display_name: String,
+    pub encrypted_view_key: String,
+    pub public_spend_key: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StoredAccount {
+    pub async fn create(
+        pool: &mut SqliteConnection,
+        display_name: &str,
+        encrypted_view_key: &str,
+        public_spend_key: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let name = display_name.to_lowercase();
+        sqlx::query_as!(
+            StoredAccount,
+            r#"
+            INSERT INTO accounts (name, display_name, encrypted_view_key, public_spend_key)
+            VALUES (?, ?, ?, ?)
+            RETURNING
+                name,
+                display_name,
+                encrypted_view_key,
+                public_spend_key,
+                enabled as "enabled: bool",
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            "#,
+            name,
+            display_name,
+            encrypted_view_key,
+            public_spend_key,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_name(pool: &mut SqliteConnection, name: &str) -> Result<Option<Self>, sqlx::Error> {
+        let name = name.to_lowercase();
+        sqlx::query_as!(
+            StoredAccount,
+            r#"
+            SELECT
+                name,
+                display_name,
+                encrypted_view_key,
+                public_spend_key,
+                enabled as "enabled: bool",
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM accounts
+            WHERE name = ?
+            "#,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_enabled(pool: &mut SqliteConnection) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            StoredAccount,
+            r#"
+            SELECT
+                name,
+                display_name,
+                encrypted_view_key,
+                public_spend_key,
+                enabled as "enabled: bool",
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM accounts
+            WHERE enabled = TRUE
+            ORDER BY name
+            "#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns the updated account, or `None` if it does not exist.
+    pub async fn set_enabled(
+        pool: &mut SqliteConnection,
+        name: &str,
+        enabled: bool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let name = name.to_lowercase();
+        sqlx::query_as!(
+            StoredAccount,
+            r#"
+            UPDATE accounts
+            SET enabled = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE name = ?
+            RETURNING
+                name,
+                display_name,
+                encrypted_view_key,
+                public_spend_key,
+                enabled as "enabled: bool",
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            "#,
+            enabled,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
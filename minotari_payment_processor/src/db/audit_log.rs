@@ -0,0 +1,460 @@
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, FromRow, SqliteConnection};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// Log target every audit entry is also written to, so it can be routed to a dedicated file.
+pub const AUDIT_LOG_TARGET: &str = "audit";
+/// Actor of changes made by the workers.
+pub const SYSTEM_ACTOR: &str = "system";
+/// Entries read per query while verifying the hash chain.
+const VERIFY_PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditAction {
+    PaymentCreated,
+    PaymentCancelled,
+    PaymentExpired,
+    PaymentDetached,
+    PaymentAmended,
+    BatchCreated,
+    BatchPaymentsAdded,
+    BatchStatusChanged,
+    BatchRetryRequested,
+    BatchResolvedConfirmed,
+    BatchResolvedFailed,
+    BatchForceConfirmed,
+    BatchForceFailed,
+    BatchSignatureUploaded,
+    ProcessingPaused,
+    ProcessingResumed,
+    AccountCreated,
+    AccountEnabled,
+    AccountDisabled,
+}
+
+impl From<String> for AuditAction {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "PAYMENT_CREATED" => AuditAction::PaymentCreated,
+            "PAYMENT_CANCELLED" => AuditAction::PaymentCancelled,
+            "PAYMENT_EXPIRED" => AuditAction::PaymentExpired,
+            "PAYMENT_DETACHED" => AuditAction::PaymentDetached,
+            "PAYMENT_AMENDED" => AuditAction::PaymentAmended,
+            "BATCH_CREATED" => AuditAction::BatchCreated,
+            "BATCH_PAYMENTS_ADDED" => AuditAction::BatchPaymentsAdded,
+            "BATCH_STATUS_CHANGED" => AuditAction::BatchStatusChanged,
+            "BATCH_RETRY_REQUESTED" => AuditAction::BatchRetryRequested,
+            "BATCH_RESOLVED_CONFIRMED" => AuditAction::BatchResolvedConfirmed,
+            "BATCH_RESOLVED_FAILED" => AuditAction::BatchResolvedFailed,
+            "BATCH_FORCE_CONFIRMED" => AuditAction::BatchForceConfirmed,
+            "BATCH_FORCE_FAILED" => AuditAction::BatchForceFailed,
+            "BATCH_SIGNATURE_UPLOADED" => AuditAction::BatchSignatureUploaded,
+            "PROCESSING_PAUSED" => AuditAction::ProcessingPaused,
+            "PROCESSING_RESUMED" => AuditAction::ProcessingResumed,
+            "ACCOUNT_CREATED" => AuditAction::AccountCreated,
+            "ACCOUNT_ENABLED" => AuditAction::AccountEnabled,
+            "ACCOUNT_DISABLED" => AuditAction::AccountDisabled,
+            _ => panic!("Unknown AuditAction: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditAction::PaymentCreated => write!(f, "PAYMENT_CREATED"),
+            AuditAction::PaymentCancelled => write!(f, "PAYMENT_CANCELLED"),
+            AuditAction::PaymentExpired => write!(f, "PAYMENT_EXPIRED"),
+            AuditAction::PaymentDetached => write!(f, "PAYMENT_DETACHED"),
+            AuditAction::PaymentAmended => write!(f, "PAYMENT_AMENDED"),
+            AuditAction::BatchCreated => write!(f, "BATCH_CREATED"),
+            AuditAction::BatchPaymentsAdded => write!(f, "BATCH_PAYMENTS_ADDED"),
+            AuditAction::BatchStatusChanged => write!(f, "BATCH_STATUS_CHANGED"),
+            AuditAction::BatchRetryRequested => write!(f, "BATCH_RETRY_REQUESTED"),
+            AuditAction::BatchResolvedConfirmed => write!(f, "BATCH_RESOLVED_CONFIRMED"),
+            AuditAction::BatchResolvedFailed => write!(f, "BATCH_RESOLVED_FAILED"),
+            AuditAction::BatchForceConfirmed => write!(f, "BATCH_FORCE_CONFIRMED"),
+            AuditAction::BatchForceFailed => write!(f, "BATCH_FORCE_FAILED"),
+            AuditAction::BatchSignatureUploaded => write!(f, "BATCH_SIGNATURE_UPLOADED"),
+            AuditAction::ProcessingPaused => write!(f, "PROCESSING_PAUSED"),
+            AuditAction::ProcessingResumed => write!(f, "PROCESSING_RESUMED"),
+            AuditAction::AccountCreated => write!(f, "ACCOUNT_CREATED"),
+            AuditAction::AccountEnabled => write!(f, "ACCOUNT_ENABLED"),
+            AuditAction::AccountDisabled => write!(f, "ACCOUNT_DISABLED"),
+        }
+    }
+}
+
+/// An entry of the `audit_log` table.
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_id: String,
+    pub before_status: Option<String>,
+    pub after_status: Option<String>,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    /// Persists an audit entry and writes it to the `audit` log target. Call it with the connection/transaction
+    /// that performs the change, so the entry is only persisted if the change is committed.
+    ///
+    /// The entry is chained to the one before it, see `verify_chain`. The previous entry is read after the insert,
+    /// while this connection holds SQLite's write lock, so concurrent writers (also in other processes) cannot
+    /// chain two entries to the same predecessor.
+    pub async fn record(
+        pool: &mut SqliteConnection,
+        actor: &str,
+        action: AuditAction,
+        entity_id: &str,
+        before_status: Option<&str>,
+        after_status: Option<&str>,
+        details: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let action_str = action.to_string();
+        let mut tx = pool.begin().await?;
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO audit_log (actor, action, entity_id, before_status, after_status, details)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!", created_at as "created_at!: String"
+            "#,
+            actor,
+            action_str,
+            entity_id,
+            before_status,
+            after_status,
+            details,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let prev_hash = sqlx::query_scalar!(
+            "SELECT entry_hash FROM audit_log WHERE id < ? ORDER BY id DESC LIMIT 1",
+            inserted.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        let entry = ChainedEntry {
+            id: inserted.id,
+            actor: actor.to_string(),
+            action: action_str,
+            entity_id: entity_id.to_string(),
+            before_status: before_status.map(str::to_string),
+            after_status: after_status.map(str::to_string),
+            details: details.map(str::to_string),
+            created_at: inserted.created_at,
+            prev_hash,
+            entry_hash: None,
+        };
+        let entry_hash = entry.compute_hash();
+        sqlx::query!(
+            "UPDATE audit_log SET prev_hash = ?, entry_hash = ? WHERE id = ?",
+            entry.prev_hash,
+            entry_hash,
+            entry.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!(
+            target: AUDIT_LOG_TARGET,
+            "actor={} action={} entity_id={} before={} after={} details={}",
+            actor,
+            action,
+            entity_id,
+            before_status.unwrap_or("-"),
+            after_status.unwrap_or("-"),
+            details.unwrap_or("-")
+        );
+        Ok(())
+    }
+
+    /// Returns up to `limit` entries with an ID greater than `after_id`, oldest first, optionally only those of
+    /// a single payment or batch.
+    pub async fn find(
+        pool: &mut SqliteConnection,
+        entity_id: Option<&str>,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditEntry,
+            r#"
+            SELECT
+                id as "id!",
+                actor,
+                action,
+                entity_id,
+                before_status,
+                after_status,
+                details,
+                created_at as "created_at: DateTime<Utc>"
+            FROM audit_log
+            WHERE id > ? AND (? IS NULL OR entity_id = ?)
+            ORDER BY id
+            LIMIT ?
+            "#,
+            after_id,
+            entity_id,
+            entity_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Whether an entry with this `entry_hash` exists. If the chain is intact, every entry up to it is unchanged.
+    pub async fn contains_hash(conn: &mut SqliteConnection, entry_hash: &str) -> Result<bool, sqlx::Error> {
+        let found = sqlx::query_scalar!("SELECT id FROM audit_log WHERE entry_hash = ?", entry_hash)
+            .fetch_optional(conn)
+            .await?;
+        Ok(found.is_some())
+    }
+
+    /// Walks the whole audit log, oldest first, and checks that every entry still matches its hash and links to
+    /// the entry before it. An entry that was changed, removed or inserted after the fact breaks the chain.
+    pub async fn verify_chain(conn: &mut SqliteConnection) -> Result<AuditChainReport, sqlx::Error> {
+        let mut report = AuditChainReport::default();
+        let mut after_id = 0i64;
+        loop {
+            let entries = sqlx::query_as!(
+                ChainedEntry,
+                r#"
+                SELECT
+                    id as "id!",
+                    actor,
+                    action,
+                    entity_id,
+                    before_status,
+                    after_status,
+                    details,
+                    created_at as "created_at!: String",
+                    prev_hash,
+                    entry_hash
+                FROM audit_log
+                WHERE id > ?
+                ORDER BY id
+                LIMIT ?
+                "#,
+                after_id,
+                VERIFY_PAGE_SIZE
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+            let Some(last) = entries.last() else {
+                return Ok(report);
+            };
+            after_id = last.id;
+
+            for entry in &entries {
+                if let Some(reason) = report.check(entry) {
+                    report.broken = Some(AuditChainBreak { id: entry.id, reason });
+                    return Ok(report);
+                }
+            }
+        }
+    }
+}
+
+/// Result of `AuditEntry::verify_chain`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditChainReport {
+    /// Entries whose hash and link to the previous entry were checked.
+    pub verified: u64,
+    /// Entries written before the hash chain was introduced, which cannot be checked.
+    pub unchained: u64,
+    /// Hash of the newest verified entry. Removing the newest entries does not break the chain, so auditors should
+    /// keep this hash and check that later verifications still contain it.
+    pub head_hash: Option<String>,
+    /// The first entry that breaks the chain. Verification stops there.
+    pub broken: Option<AuditChainBreak>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditChainBreak {
+    pub id: i64,
+    pub reason: String,
+}
+
+/// An audit log row as stored, including its position in the hash chain.
+struct ChainedEntry {
+    id: i64,
+    actor: String,
+    action: String,
+    entity_id: String,
+    before_status: Option<String>,
+    after_status: Option<String>,
+    details: Option<String>,
+    /// Kept as stored, so the hash does not depend on how timestamps are parsed and formatted.
+    created_at: String,
+    prev_hash: Option<String>,
+    entry_hash: Option<String>,
+}
+
+impl ChainedEntry {
+    /// Hex-encoded SHA-256 of the entry's contents and `prev_hash`. Every field is length-prefixed, so moving text
+    /// from one field to the next changes the hash.
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.id.to_be_bytes());
+        for field in [
+            Some(self.actor.as_str()),
+            Some(self.action.as_str()),
+            Some(self.entity_id.as_str()),
+            self.before_status.as_deref(),
+            self.after_status.as_deref(),
+            self.details.as_deref(),
+            Some(self.created_at.as_str()),
+            self.prev_hash.as_deref(),
+        ] {
+            match field {
+                Some(value) => {
+                    hasher.update([1u8]);
+                    hasher.update((value.len() as u64).to_be_bytes());
+                    hasher.update(value.as_bytes());
+                },
+                None => hasher.update([0u8]),
+            }
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl AuditChainReport {
+    /// Checks the next entry of the chain and returns why it breaks the chain, if it does.
+    fn check(&mut self, entry: &ChainedEntry) -> Option<String> {
+        let Some(entry_hash) = &entry.entry_hash else {
+            if self.verified > 0 {
+                return Some("The entry has no hash, but entries before it have one".to_string());
+            }
+            self.unchained += 1;
+            return None;
+        };
+        if entry.prev_hash != self.head_hash {
+            return Some(format!(
+                "The previous hash {} does not match the entry before it ({}). An entry was removed or inserted.",
+                entry.prev_hash.as_deref().unwrap_or("-"),
+                self.head_hash.as_deref().unwrap_or("-")
+            ));
+        }
+        if entry.compute_hash() != *entry_hash {
+            return Some("The contents of the entry do not match its hash. The entry was modified.".to_string());
+        }
+        self.verified += 1;
+        self.head_hash = Some(entry_hash.clone());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` entries chained like `AuditEntry::record` chains them.
+    fn chain(count: i64) -> Vec<ChainedEntry> {
+        let mut prev_hash = None;
+        (1..=count)
+            .map(|id| {
+                let mut entry = ChainedEntry {
+                    id,
+                    actor: SYSTEM_ACTOR.to_string(),
+                    action: AuditAction::BatchStatusChanged.to_string(),
+                    entity_id: format!("batch-{}", id),
+                    before_status: Some("AWAITING_BROADCAST".to_string()),
+                    after_status: Some("BROADCASTING".to_string()),
+                    details: None,
+                    created_at: "2025-12-19 04:00:00".to_string(),
+                    prev_hash: prev_hash.clone(),
+                    entry_hash: None,
+                };
+                entry.entry_hash = Some(entry.compute_hash());
+                prev_hash = entry.entry_hash.clone();
+                entry
+            })
+            .collect()
+    }
+
+    /// Checks `entries` like `AuditEntry::verify_chain` does.
+    fn verify(entries: &[ChainedEntry]) -> AuditChainReport {
+        let mut report = AuditChainReport::default();
+        for entry in entries {
+            if let Some(reason) = report.check(entry) {
+                report.broken = Some(AuditChainBreak { id: entry.id, reason });
+                break;
+            }
+        }
+        report
+    }
+
+    #[test]
+    fn intact_chain_is_verified() {
+        let entries = chain(3);
+        let report = verify(&entries);
+        assert!(report.broken.is_none());
+        assert_eq!(report.verified, 3);
+        assert_eq!(report.head_hash, entries[2].entry_hash);
+    }
+
+    #[test]
+    fn modified_entry_breaks_the_chain() {
+        let mut entries = chain(3);
+        entries[1].after_status = Some("CONFIRMED".to_string());
+        let report = verify(&entries);
+        assert_eq!(report.broken.map(|broken| broken.id), Some(2));
+        assert_eq!(report.verified, 1);
+    }
+
+    #[test]
+    fn text_moved_between_fields_breaks_the_chain() {
+        let mut entries = chain(1);
+        entries[0].before_status = Some("AWAITING_BROADCASTB".to_string());
+        entries[0].after_status = Some("ROADCASTING".to_string());
+        assert_eq!(verify(&entries).broken.map(|broken| broken.id), Some(1));
+    }
+
+    #[test]
+    fn removed_entry_breaks_the_chain() {
+        let mut entries = chain(3);
+        entries.remove(1);
+        let report = verify(&entries);
+        assert_eq!(report.broken.map(|broken| broken.id), Some(3));
+    }
+
+    #[test]
+    fn rehashed_entry_still_breaks_the_link_of_its_successor() {
+        let mut entries = chain(3);
+        entries[1].details = Some("forged".to_string());
+        entries[1].entry_hash = Some(entries[1].compute_hash());
+        assert_eq!(verify(&entries).broken.map(|broken| broken.id), Some(3));
+    }
+
+    #[test]
+    fn unchained_entries_are_only_accepted_before_the_chain() {
+        // Entries written before the chain was introduced have no hashes, so the first chained one links to nothing.
+        let mut entries = chain(3);
+        for entry in &mut entries[..2] {
+            entry.prev_hash = None;
+            entry.entry_hash = None;
+        }
+        entries[2].prev_hash = None;
+        entries[2].entry_hash = Some(entries[2].compute_hash());
+        let report = verify(&entries);
+        assert!(report.broken.is_none());
+        assert_eq!((report.unchained, report.verified), (2, 1));
+
+        let mut stripped = chain(2);
+        stripped[1].entry_hash = None;
+        assert_eq!(verify(&stripped).broken.map(|broken| broken.id), Some(2));
+    }
+}
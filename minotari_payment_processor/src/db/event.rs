@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqliteConnection};
+use std::fmt;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    PaymentCreated,
+    PaymentExpired,
+    BatchConfirmed,
+    /// A confirmed batch reached `SETTLEMENT_CONFIRMATIONS`, the final, reorg-safe signal.
+    BatchSettled,
+    BatchFailed,
+    ReconciliationDiscrepancy,
+    /// An account's available balance no longer covers its payments waiting for funds plus the reserve.
+    LowBalance,
+}
+
+impl From<String> for EventType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "PAYMENT_CREATED" => EventType::PaymentCreated,
+            "PAYMENT_EXPIRED" => EventType::PaymentExpired,
+            "BATCH_CONFIRMED" => EventType::BatchConfirmed,
+            "BATCH_SETTLED" => EventType::BatchSettled,
+            "BATCH_FAILED" => EventType::BatchFailed,
+            "RECONCILIATION_DISCREPANCY" => EventType::ReconciliationDiscrepancy,
+            "LOW_BALANCE" => EventType::LowBalance,
+            _ => panic!("Unknown EventType: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventType::PaymentCreated => write!(f, "PAYMENT_CREATED"),
+            EventType::PaymentExpired => write!(f, "PAYMENT_EXPIRED"),
+            EventType::BatchConfirmed => write!(f, "BATCH_CONFIRMED"),
+            EventType::BatchSettled => write!(f, "BATCH_SETTLED"),
+            EventType::BatchFailed => write!(f, "BATCH_FAILED"),
+            EventType::ReconciliationDiscrepancy => write!(f, "RECONCILIATION_DISCREPANCY"),
+            EventType::LowBalance => write!(f, "LOW_BALANCE"),
+        }
+    }
+}
+
+/// A domain event from the `events` outbox.
+#[derive(Debug, Clone, FromRow)]
+pub struct Event {
+    pub seq: i64,
+    pub event_type: EventType,
+    pub entity_id: String,
+    /// JSON encoded event details.
+    pub payload: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Event {
+    /// Appends an event to the outbox. Call it with the connection/transaction that performs the state change,
+    /// so the event is only visible if the change is committed.
+    pub async fn record(
+        pool: &mut SqliteConnection,
+        event_type: EventType,
+        entity_id: &str,
+        payload: Value,
+    ) -> Result<(), sqlx::Error> {
+        let event_type = event_type.to_string();
+        let payload = payload.to_string();
+        sqlx::query!(
+            r#"
+            INSERT INTO events (event_type, entity_id, payload)
+            VALUES (?, ?, ?)
+            "#,
+            event_type,
+            entity_id,
+            payload,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The sequence number of the latest event, `0` if there are none.
+    pub async fn last_seq(pool: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT COALESCE(MAX(seq), 0) as "seq!: i64" FROM events"#)
+            .fetch_one(pool)
+            .await
+    }
+
+    /// Returns up to `limit` events with a sequence number greater than `after_seq`, in sequence order.
+    pub async fn find_after(pool: &mut SqliteConnection, after_seq: i64, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+                seq as "seq!",
+                event_type,
+                entity_id,
+                payload,
+                created_at as "created_at: DateTime<Utc>"
+            FROM events
+            WHERE seq > ?
+            ORDER BY seq
+            LIMIT ?
+            "#,
+            after_seq,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+
+/// A request made with an `Idempotency-Key` header and, once it completed, its response.
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyRecord {
+    pub idempotency_key: String,
+    pub request_path: String,
+    /// API key name, or `jwt:<subject>`, of the client that made the request. Empty without authentication.
+    pub client_name: String,
+    pub request_hash: String,
+    pub response_status: Option<i64>,
+    pub response_body: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    pub async fn find(
+        pool: &mut SqliteConnection,
+        idempotency_key: &str,
+        request_path: &str,
+        client_name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            IdempotencyRecord,
+            r#"
+            SELECT
+                idempotency_key,
+                request_path,
+                client_name,
+                request_hash,
+                response_status,
+                response_body,
+                created_at as "created_at: DateTime<Utc>"
+            FROM idempotency_keys
+            WHERE idempotency_key = ? AND request_path = ? AND client_name = ?
+            "#,
+            idempotency_key,
+            request_path,
+            client_name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Claims the key for a new request. Returns `false` if the client already used the key for this path.
+    pub async fn try_reserve(
+        pool: &mut SqliteConnection,
+        idempotency_key: &str,
+        request_path: &str,
+        client_name: &str,
+        request_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (idempotency_key, request_path, client_name, request_hash)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (idempotency_key, request_path, client_name) DO NOTHING
+            "#,
+            idempotency_key,
+            request_path,
+            client_name,
+            request_hash
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Stores the response of the request that reserved the key.
+    pub async fn complete(
+        pool: &mut SqliteConnection,
+        idempotency_key: &str,
+        request_path: &str,
+        client_name: &str,
+        response_status: i64,
+        response_body: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET response_status = ?, response_body = ?
+            WHERE idempotency_key = ? AND request_path = ? AND client_name = ?
+            "#,
+            response_status,
+            response_body,
+            idempotency_key,
+            request_path,
+            client_name
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Frees the key so that a retry executes the request again.
+    pub async fn release(
+        pool: &mut SqliteConnection,
+        idempotency_key: &str,
+        request_path: &str,
+        client_name: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE idempotency_key = ? AND request_path = ? AND client_name = ?
+            "#,
+            idempotency_key,
+            request_path,
+            client_name
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes completed keys older than `retention_secs` and reservations that never completed (e.g. because the
+    /// process stopped mid-request) older than `abandoned_secs`. Returns the number of deleted keys.
+    pub async fn delete_expired(
+        pool: &mut SqliteConnection,
+        retention_secs: i64,
+        abandoned_secs: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE created_at < datetime('now', '-' || ? || ' seconds')
+               OR (response_status IS NULL AND created_at < datetime('now', '-' || ? || ' seconds'))
+            "#,
+            retention_secs,
+            abandoned_secs
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,351 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::fmt;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportJobStatus {
+    /// Waiting for the import job runner.
+    Queued,
+    /// Partly imported. A job interrupted by a restart continues after its last processed row.
+    Running,
+    Completed,
+    /// The file could not be read. Rows processed before are kept.
+    Failed,
+}
+
+impl From<String> for ImportJobStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "QUEUED" => ImportJobStatus::Queued,
+            "RUNNING" => ImportJobStatus::Running,
+            "COMPLETED" => ImportJobStatus::Completed,
+            "FAILED" => ImportJobStatus::Failed,
+            _ => panic!("Unknown ImportJobStatus: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for ImportJobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportJobStatus::Queued => write!(f, "QUEUED"),
+            ImportJobStatus::Running => write!(f, "RUNNING"),
+            ImportJobStatus::Completed => write!(f, "COMPLETED"),
+            ImportJobStatus::Failed => write!(f, "FAILED"),
+        }
+    }
+}
+
+/// A payment file imported in the background, see `workers::import_job_runner`. The file itself is only loaded with
+/// `ImportJob::content`.
+#[derive(Debug, Clone, FromRow)]
+pub struct ImportJob {
+    pub id: String,
+    pub status: ImportJobStatus,
+    /// `csv` or `ndjson`.
+    pub format: String,
+    /// Account used for rows without an `account_name`.
+    pub default_account_name: Option<String>,
+    /// JSON array of the accounts the submitting client may use. `None` allows every account.
+    pub allowed_accounts: Option<String>,
+    pub total_rows: i64,
+    pub processed_rows: i64,
+    pub created: i64,
+    pub duplicates: i64,
+    pub invalid: i64,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of one processed row of an import job.
+#[derive(Debug, Clone, FromRow)]
+pub struct ImportJobRow {
+    /// 1-based row number, not counting the CSV header.
+    pub row_number: i64,
+    pub client_id: Option<String>,
+    /// `CREATED`, `DUPLICATE` or `INVALID`.
+    pub status: String,
+    pub payment_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Rows of a chunk and how many of them were created, duplicates or invalid.
+#[derive(Debug, Clone, Default)]
+pub struct ImportJobProgress {
+    pub rows: Vec<ImportJobRow>,
+    pub created: i64,
+    pub duplicates: i64,
+    pub invalid: i64,
+}
+
+impl ImportJob {
+    pub async fn create(
+        pool: &mut SqliteConnection,
+        format: &str,
+        default_account_name: Option<&str>,
+        allowed_accounts: Option<&[String]>,
+        total_rows: i64,
+        content: &[u8],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let allowed_accounts = allowed_accounts
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        sqlx::query_as!(
+            ImportJob,
+            r#"
+            INSERT INTO import_jobs (id, format, default_account_name, allowed_accounts, total_rows, content)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING
+                id as "id!",
+                status,
+                format,
+                default_account_name,
+                allowed_accounts,
+                total_rows,
+                processed_rows,
+                created,
+                duplicates,
+                invalid,
+                error_message,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                finished_at as "finished_at: DateTime<Utc>"
+            "#,
+            id,
+            format,
+            default_account_name,
+            allowed_accounts,
+            total_rows,
+            content
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &mut SqliteConnection, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ImportJob,
+            r#"
+            SELECT
+                id,
+                status,
+                format,
+                default_account_name,
+                allowed_accounts,
+                total_rows,
+                processed_rows,
+                created,
+                duplicates,
+                invalid,
+                error_message,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                finished_at as "finished_at: DateTime<Utc>"
+            FROM import_jobs
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The oldest job that is queued or was interrupted while running.
+    pub async fn find_next_pending(pool: &mut SqliteConnection) -> Result<Option<Self>, sqlx::Error> {
+        let queued = ImportJobStatus::Queued.to_string();
+        let running = ImportJobStatus::Running.to_string();
+        sqlx::query_as!(
+            ImportJob,
+            r#"
+            SELECT
+                id,
+                status,
+                format,
+                default_account_name,
+                allowed_accounts,
+                total_rows,
+                processed_rows,
+                created,
+                duplicates,
+                invalid,
+                error_message,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                finished_at as "finished_at: DateTime<Utc>"
+            FROM import_jobs
+            WHERE status IN (?, ?)
+            ORDER BY created_at, id
+            LIMIT 1
+            "#,
+            queued,
+            running
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The uploaded file. `None` once the job has finished.
+    pub async fn content(pool: &mut SqliteConnection, id: &str) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        let content = sqlx::query_scalar!(r#"SELECT content FROM import_jobs WHERE id = ?"#, id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(content.flatten())
+    }
+
+    /// Decodes `allowed_accounts`.
+    pub fn allowed_accounts(&self) -> Result<Option<Vec<String>>, serde_json::Error> {
+        self.allowed_accounts.as_deref().map(serde_json::from_str).transpose()
+    }
+
+    pub async fn mark_running(pool: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+        let status = ImportJobStatus::Running.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE import_jobs
+            SET status = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            status,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stores the rows of a processed chunk and adds them to the job's counts. Called in the transaction that
+    /// created the chunk's payments, so a job interrupted by a restart continues right after its last stored row.
+    pub async fn record_progress(
+        pool: &mut SqliteConnection,
+        id: &str,
+        progress: &ImportJobProgress,
+    ) -> Result<(), sqlx::Error> {
+        for row in &progress.rows {
+            sqlx::query!(
+                r#"
+                INSERT INTO import_job_rows (job_id, row_number, client_id, status, payment_id, error)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+                id,
+                row.row_number,
+                row.client_id,
+                row.status,
+                row.payment_id,
+                row.error
+            )
+            .execute(&mut *pool)
+            .await?;
+        }
+
+        let processed_rows = progress.rows.len() as i64;
+        sqlx::query!(
+            r#"
+            UPDATE import_jobs
+            SET processed_rows = processed_rows + ?,
+                created = created + ?,
+                duplicates = duplicates + ?,
+                invalid = invalid + ?,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            processed_rows,
+            progress.created,
+            progress.duplicates,
+            progress.invalid,
+            id
+        )
+        .execute(&mut *pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Finishes the job and drops its file.
+    pub async fn complete(pool: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+        let status = ImportJobStatus::Completed.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE import_jobs
+            SET status = ?, content = NULL, updated_at = CURRENT_TIMESTAMP, finished_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            status,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gives up on the job and drops its file. The rows processed so far are kept.
+    pub async fn fail(pool: &mut SqliteConnection, id: &str, error_message: &str) -> Result<(), sqlx::Error> {
+        let status = ImportJobStatus::Failed.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE import_jobs
+            SET status = ?,
+                error_message = ?,
+                content = NULL,
+                updated_at = CURRENT_TIMESTAMP,
+                finished_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            status,
+            error_message,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The first `limit` invalid rows of the job, in row order.
+    pub async fn find_row_errors(
+        pool: &mut SqliteConnection,
+        id: &str,
+        limit: i64,
+    ) -> Result<Vec<ImportJobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ImportJobRow,
+            r#"
+            SELECT row_number, client_id, status, payment_id, error
+            FROM import_job_rows
+            WHERE job_id = ? AND error IS NOT NULL
+            ORDER BY row_number
+            LIMIT ?
+            "#,
+            id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The batches the payments created by the job were placed in so far, oldest first. Payments the job reported
+    /// as duplicates were not created by it and are left out.
+    pub async fn batch_ids(pool: &mut SqliteConnection, id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT pb.id
+            FROM payment_batches pb
+            WHERE pb.id IN (
+                SELECT p.payment_batch_id
+                FROM import_job_rows r
+                JOIN payments p ON p.id = r.payment_id
+                WHERE r.job_id = ? AND r.status = 'CREATED'
+            )
+            ORDER BY pb.created_at, pb.id
+            "#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
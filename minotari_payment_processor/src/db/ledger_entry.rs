@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::fmt;
+
+use crate::db::payment::Payment;
+use crate::db::payment_batch::{BatchPayload, PaymentBatch};
+
+/// Ledger account credited with the network fees paid by the hot wallets.
+pub const NETWORK_FEES_ACCOUNT: &str = "network_fees";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LedgerEntryType {
+    Payout,
+    NetworkFee,
+}
+
+impl From<String> for LedgerEntryType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "PAYOUT" => LedgerEntryType::Payout,
+            "NETWORK_FEE" => LedgerEntryType::NetworkFee,
+            _ => panic!("Unknown LedgerEntryType: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for LedgerEntryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LedgerEntryType::Payout => write!(f, "PAYOUT"),
+            LedgerEntryType::NetworkFee => write!(f, "NETWORK_FEE"),
+        }
+    }
+}
+
+/// A double-entry ledger row: `amount` µT debited to `debit_account` and credited to `credit_account`.
+#[derive(Debug, Clone, FromRow)]
+pub struct LedgerEntry {
+    pub id: i64,
+    /// The confirmed batch the entry belongs to.
+    pub journal_id: String,
+    /// The payment paid out, `None` for fee entries.
+    pub payment_id: Option<String>,
+    pub entry_type: LedgerEntryType,
+    pub debit_account: String,
+    pub credit_account: String,
+    pub amount: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LedgerEntry {
+    /// Writes the journal of a batch that has just been confirmed: one PAYOUT entry per payment (debit the
+    /// account's hot wallet, credit the recipient) and, if the signed transactions can be read, one NETWORK_FEE
+    /// entry (debit the hot wallet, credit `network_fees`) with the sum of their kernel fees.
+    ///
+    /// Call it in the transaction that marks the batch as confirmed. A batch that already has a journal is left
+    /// untouched.
+    pub async fn record_batch_confirmed(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        let existing = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM ledger_entries WHERE journal_id = ?"#,
+            batch_id
+        )
+        .fetch_one(&mut *pool)
+        .await?;
+        if existing > 0 {
+            return Ok(());
+        }
+
+        let batch = PaymentBatch::find_by_id(&mut *pool, batch_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let hot_wallet = hot_wallet_account(&batch.account_name);
+
+        for payment in Payment::find_by_batch_id(&mut *pool, batch_id).await? {
+            Self::insert(
+                &mut *pool,
+                batch_id,
+                Some(&payment.id),
+                LedgerEntryType::Payout,
+                &hot_wallet,
+                &recipient_account(&payment.recipient_address),
+                payment.amount.as_i64(),
+            )
+            .await?;
+        }
+
+        let fee = batch
+            .signed_tx_json
+            .as_deref()
+            .and_then(|json| BatchPayload::from_json(json).ok())
+            .and_then(|payload| payload.total_fee().ok())
+            .filter(|fee| *fee > 0);
+        if let Some(fee) = fee {
+            Self::insert(
+                &mut *pool,
+                batch_id,
+                None,
+                LedgerEntryType::NetworkFee,
+                &hot_wallet,
+                NETWORK_FEES_ACCOUNT,
+                i64::try_from(fee).unwrap_or(i64::MAX),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert(
+        pool: &mut SqliteConnection,
+        journal_id: &str,
+        payment_id: Option<&str>,
+        entry_type: LedgerEntryType,
+        debit_account: &str,
+        credit_account: &str,
+        amount: i64,
+    ) -> Result<(), sqlx::Error> {
+        let entry_type = entry_type.to_string();
+        sqlx::query!(
+            r#"
+            INSERT INTO ledger_entries (journal_id, payment_id, entry_type, debit_account, credit_account, amount)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            journal_id,
+            payment_id,
+            entry_type,
+            debit_account,
+            credit_account,
+            amount,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` entries with an id greater than `after_id`, created within the optional time range,
+    /// in id order.
+    pub async fn find_for_export(
+        pool: &mut SqliteConnection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            LedgerEntry,
+            r#"
+            SELECT
+                id as "id!",
+                journal_id,
+                payment_id,
+                entry_type,
+                debit_account,
+                credit_account,
+                amount,
+                created_at as "created_at: DateTime<Utc>"
+            FROM ledger_entries
+            WHERE id > ?
+              AND (? IS NULL OR created_at >= datetime(?))
+              AND (? IS NULL OR created_at < datetime(?))
+            ORDER BY id
+            LIMIT ?
+            "#,
+            after_id,
+            from,
+            from,
+            to,
+            to,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+fn hot_wallet_account(account_name: &str) -> String {
+    format!("hot_wallet:{}", account_name)
+}
+
+fn recipient_account(address: &str) -> String {
+    format!("recipient:{}", address)
+}
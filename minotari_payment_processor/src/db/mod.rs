@@ -1,12 +1,122 @@
+pub mod account;
+pub mod audit_log;
+pub mod event;
+pub mod idempotency_key;
+pub mod import_job;
+pub mod ledger_entry;
 pub mod payment;
 pub mod payment_batch;
+pub mod payment_group;
+pub mod payment_tag;
+pub mod postgres_copy;
+pub mod reconciliation_report;
+pub mod refund;
+pub mod setting;
+pub mod signing_attempt;
+pub mod superseded_pr_lock;
 
-use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use anyhow::bail;
+use sqlx::{
+    SqliteConnection, SqlitePool,
+    migrate::{Migrate, Migration, Migrator},
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
 
-pub async fn init_db(db_url: &str) -> Result<SqlitePool, anyhow::Error> {
-    let pool = SqlitePoolOptions::new().max_connections(5).connect(db_url).await?;
+use crate::config::DatabaseConfig;
 
-    // Run migrations
-    sqlx::migrate!("../migrations").run(&pool).await?;
+/// The migrations in `migrations/`, embedded in the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// Opens the connection pool and prepares the schema, see `connect` and `prepare_schema`.
+pub async fn init_db(db_url: &str, config: &DatabaseConfig) -> Result<SqlitePool, anyhow::Error> {
+    let pool = connect(db_url, config).await?;
+    prepare_schema(&pool, config).await?;
     Ok(pool)
 }
+
+/// Opens the connection pool with the configured pragmas, without touching the schema.
+///
+/// In WAL mode readers no longer block the writer, and with the busy timeout a connection that finds the database
+/// locked by another writer waits for it instead of failing with SQLITE_BUSY right away.
+pub async fn connect(db_url: &str, config: &DatabaseConfig) -> Result<SqlitePool, anyhow::Error> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect_with(connect_options(db_url, config)?)
+        .await?;
+    Ok(pool)
+}
+
+/// Opens the pool that the workers and the mutating API endpoints write through, with the same pragmas as `connect`.
+///
+/// SQLite allows a single writer at a time. A transaction that starts reading and then writes cannot wait for a
+/// concurrent writer, and fails with SQLITE_BUSY regardless of the busy timeout. With a single connection, writes
+/// queue up in the pool instead. Connections from this pool must therefore never be held across network calls or
+/// while acquiring another one.
+pub async fn connect_writer(db_url: &str, config: &DatabaseConfig) -> Result<SqlitePool, anyhow::Error> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(connect_options(db_url, config)?)
+        .await?;
+    Ok(pool)
+}
+
+fn connect_options(db_url: &str, config: &DatabaseConfig) -> Result<SqliteConnectOptions, anyhow::Error> {
+    Ok(SqliteConnectOptions::from_str(db_url)?
+        .busy_timeout(config.busy_timeout)
+        .journal_mode(config.journal_mode)
+        .synchronous(config.synchronous))
+}
+
+/// Applies pending migrations. With `auto_migrate` disabled, fails instead if any are pending, so the schema is
+/// only changed through the `migrate` command.
+pub async fn prepare_schema(pool: &SqlitePool, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    if config.auto_migrate {
+        return migrate(pool).await;
+    }
+    let pending = pending_migrations(&mut *pool.acquire().await?).await?;
+    if !pending.is_empty() {
+        bail!(
+            "The database schema is {} migration(s) behind and DATABASE_AUTO_MIGRATE is disabled. Apply them with \
+             `migrate up` first.",
+            pending.len()
+        );
+    }
+    Ok(())
+}
+
+/// Applies pending migrations, e.g. to a pool opened by a service that embeds the processor.
+pub async fn migrate(pool: &SqlitePool) -> Result<(), anyhow::Error> {
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+/// Writes a consistent copy of the database to `path` with `VACUUM INTO`, while the service keeps running.
+///
+/// The copy is a snapshot of one read transaction, so unlike copying the database file it never mixes pages from
+/// before and after a concurrent write, and it includes the contents of the WAL. `path` must not exist or be an empty
+/// file.
+pub async fn backup(pool: &SqlitePool, path: &Path) -> Result<(), anyhow::Error> {
+    let Some(path) = path.to_str() else {
+        bail!("Backup path {} is not valid UTF-8", path.display());
+    };
+    sqlx::query("VACUUM INTO ?").bind(path).execute(pool).await?;
+    Ok(())
+}
+
+/// Migrations that have not been applied to the database yet, oldest first.
+pub async fn pending_migrations(conn: &mut SqliteConnection) -> Result<Vec<&'static Migration>, anyhow::Error> {
+    conn.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration() && !applied.contains(&migration.version))
+        .collect())
+}
@@ -1,29 +1,57 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::Connection;
 use sqlx::{FromRow, SqliteConnection};
 use std::fmt;
+use tokio::sync::Notify;
+use tokio::sync::futures::Notified;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::amount::PaymentAmount;
+use crate::db::audit_log::{AuditAction, AuditEntry, SYSTEM_ACTOR};
+use crate::db::event::{Event, EventType};
 use crate::db::payment_batch::{PaymentBatch, PaymentBatchStatus};
 
+/// Signalled whenever payment statuses are updated. Most updates run inside a transaction that commits after the
+/// signal, so listeners must re-read the payment and should not rely on being woken after the commit.
+static STATUS_CHANGES: Notify = Notify::const_new();
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PaymentStatus {
     Received,
     Batched,
     Confirmed,
+    /// Confirmed and buried under `SETTLEMENT_CONFIRMATIONS` blocks, so a reorg is no longer expected to undo it.
+    Settled,
     Failed,
     Cancelled,
 }
 
+impl PaymentStatus {
+    /// Whether a payment may no longer change its status.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Confirmed | PaymentStatus::Settled | PaymentStatus::Failed | PaymentStatus::Cancelled
+        )
+    }
+
+    /// Whether the payment was paid out, i.e. is CONFIRMED or SETTLED.
+    pub fn is_paid(&self) -> bool {
+        matches!(self, PaymentStatus::Confirmed | PaymentStatus::Settled)
+    }
+}
+
 impl From<String> for PaymentStatus {
     fn from(s: String) -> Self {
         match s.as_str() {
             "RECEIVED" => PaymentStatus::Received,
             "BATCHED" => PaymentStatus::Batched,
             "CONFIRMED" => PaymentStatus::Confirmed,
+            "SETTLED" => PaymentStatus::Settled,
             "FAILED" => PaymentStatus::Failed,
             "CANCELLED" => PaymentStatus::Cancelled,
             _ => panic!("Unknown PaymentStatus: {}", s),
@@ -37,12 +65,93 @@ impl fmt::Display for PaymentStatus {
             PaymentStatus::Received => write!(f, "RECEIVED"),
             PaymentStatus::Batched => write!(f, "BATCHED"),
             PaymentStatus::Confirmed => write!(f, "CONFIRMED"),
+            PaymentStatus::Settled => write!(f, "SETTLED"),
             PaymentStatus::Failed => write!(f, "FAILED"),
             PaymentStatus::Cancelled => write!(f, "CANCELLED"),
         }
     }
 }
 
+/// Determines the order in which received payments are batched. Stored as an integer so it can be sorted on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl PaymentPriority {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            PaymentPriority::Low => 0,
+            PaymentPriority::Normal => 1,
+            PaymentPriority::High => 2,
+        }
+    }
+}
+
+impl From<i64> for PaymentPriority {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => PaymentPriority::Low,
+            1 => PaymentPriority::Normal,
+            2 => PaymentPriority::High,
+            _ => panic!("Unknown PaymentPriority: {}", value),
+        }
+    }
+}
+
+/// Aggregate of an account's payments that are waiting to be batched.
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct PendingSummary {
+    pub payment_count: i64,
+    /// Sum of the pending amounts, in µT.
+    pub total_amount: i64,
+}
+
+/// Receivable payments of one account that go into the same batch, how many there are and when the oldest was
+/// created.
+#[derive(Debug, Clone, FromRow)]
+pub struct ReceivableGroup {
+    pub account_name: String,
+    /// Whether the group holds the HIGH priority payments that are batched on their own.
+    pub dedicated: bool,
+    pub payment_count: i64,
+    pub oldest_created_at: DateTime<Utc>,
+}
+
+/// Count and amount total of the payments of one account in one status.
+#[derive(Debug, Clone, FromRow)]
+pub struct PaymentAggregate {
+    pub account_name: String,
+    pub status: PaymentStatus,
+    pub payment_count: i64,
+    /// Sum of the amounts, in µT.
+    pub total_amount: i64,
+}
+
+/// A payment together with the on-chain details of its batch, as exported for reconciliation.
+#[derive(Debug, Clone, FromRow)]
+pub struct PaymentExportRow {
+    /// SQLite rowid, used as the pagination cursor.
+    pub row_id: i64,
+    pub id: String,
+    pub client_id: String,
+    pub account_name: String,
+    pub recipient_address: String,
+    pub amount: PaymentAmount,
+    pub status: PaymentStatus,
+    pub payment_batch_id: Option<String>,
+    pub batch_status: Option<String>,
+    pub payref: Option<String>,
+    pub mined_height: Option<i64>,
+    pub mined_timestamp: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct Payment {
     pub id: String,
@@ -51,33 +160,46 @@ pub struct Payment {
     pub status: PaymentStatus,
     pub payment_batch_id: Option<String>,
     pub recipient_address: String,
-    pub amount: i64,
+    pub amount: PaymentAmount,
     pub payment_id: Option<String>,
     pub payref: Option<String>,
     pub failure_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// RECEIVED payments that were not batched by this time are cancelled automatically.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub priority: PaymentPriority,
 }
 
 impl Payment {
-    /// Creates a new payment record in the database.
+    /// Resolves on the next payment status update. Create it before reading the payment, so an update in between is
+    /// not missed.
+    pub fn status_changed() -> Notified<'static> {
+        STATUS_CHANGES.notified()
+    }
+
+    /// Creates a new payment record in the database and records a PAYMENT_CREATED event.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         pool: &mut SqliteConnection,
         client_id: &str,
         account_name: &str,
         recipient_address: &str,
-        amount: i64,
+        amount: PaymentAmount,
         payment_id: Option<String>,
         payref: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+        priority: PaymentPriority,
     ) -> Result<Self, sqlx::Error> {
         let id = Uuid::new_v4().to_string();
         let status = PaymentStatus::Received.to_string();
+        let priority = priority.as_i64();
 
-        sqlx::query_as!(
+        let payment = sqlx::query_as!(
             Payment,
             r#"
-            INSERT INTO payments (id, client_id, account_name, status, recipient_address, amount, payment_id, payref)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO payments (id, client_id, account_name, status, recipient_address, amount, payment_id, payref, expires_at, priority)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, datetime(?), ?)
             RETURNING
                 id,
                 client_id,
@@ -85,12 +207,14 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 payref,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
-                updated_at as "updated_at: DateTime<Utc>"
+                updated_at as "updated_at: DateTime<Utc>",
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             "#,
             id,
             client_id,
@@ -99,10 +223,27 @@ impl Payment {
             recipient_address,
             amount,
             payment_id,
-            payref
+            payref,
+            expires_at,
+            priority
         )
-        .fetch_one(pool)
-        .await
+        .fetch_one(&mut *pool)
+        .await?;
+
+        Event::record(
+            pool,
+            EventType::PaymentCreated,
+            &payment.id,
+            json!({
+                "client_id": payment.client_id,
+                "account_name": payment.account_name,
+                "recipient_address": payment.recipient_address,
+                "amount": payment.amount,
+            }),
+        )
+        .await?;
+
+        Ok(payment)
     }
 
     /// Retrieves a payment by its ID.
@@ -117,12 +258,14 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>",
-                payref
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             FROM payments
             WHERE id = ?
             "#,
@@ -132,6 +275,54 @@ impl Payment {
         .await
     }
 
+    /// Stores the address the payment can be refunded to.
+    pub async fn set_refund_address(
+        pool: &mut SqliteConnection,
+        id: &str,
+        refund_address: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE payments SET refund_address = ?, version = version + 1 WHERE id = ?",
+            refund_address,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Links a payment to the failed payment it was resubmitted from.
+    pub async fn set_resubmitted_from(
+        pool: &mut SqliteConnection,
+        id: &str,
+        original_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE payments SET resubmitted_from = ?, version = version + 1 WHERE id = ?",
+            original_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the ID of the failed payment this payment was resubmitted from, if any.
+    pub async fn find_resubmitted_from(pool: &mut SqliteConnection, id: &str) -> Result<Option<String>, sqlx::Error> {
+        let resubmitted_from = sqlx::query_scalar!("SELECT resubmitted_from FROM payments WHERE id = ?", id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(resubmitted_from.flatten())
+    }
+
+    /// Returns the refund address supplied when the payment was created, if any.
+    pub async fn find_refund_address(pool: &mut SqliteConnection, id: &str) -> Result<Option<String>, sqlx::Error> {
+        let refund_address = sqlx::query_scalar!("SELECT refund_address FROM payments WHERE id = ?", id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(refund_address.flatten())
+    }
+
     /// Retrieves a payment by client_id and account_name for idempotency checks.
     pub async fn get_by_client_id(
         pool: &mut SqliteConnection,
@@ -148,12 +339,14 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>",
-                payref
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             FROM payments
             WHERE client_id = ? AND account_name = ?
             "#,
@@ -187,12 +380,14 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>",
-                payref
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             FROM payments
             WHERE account_name = ?
               AND client_id IN (SELECT value FROM json_each(?))
@@ -204,8 +399,47 @@ impl Payment {
         .await
     }
 
-    /// Finds payments with status 'RECEIVED' for batching.
-    pub async fn find_receivable_payments(pool: &mut SqliteConnection, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+    /// Groups the payments with status 'RECEIVED' of the accounts not in `excluded_accounts` by account and, with
+    /// `dedicated_high_priority`, by whether they are HIGH priority.
+    pub async fn find_receivable_groups(
+        pool: &mut SqliteConnection,
+        excluded_accounts: &[String],
+        dedicated_high_priority: bool,
+    ) -> Result<Vec<ReceivableGroup>, sqlx::Error> {
+        let excluded_accounts =
+            serde_json::to_string(excluded_accounts).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        let priority_high = PaymentPriority::High.as_i64();
+        sqlx::query_as!(
+            ReceivableGroup,
+            r#"
+            SELECT
+                account_name,
+                (? AND priority = ?) as "dedicated!: bool",
+                COUNT(*) as "payment_count!: i64",
+                MIN(created_at) as "oldest_created_at!: DateTime<Utc>"
+            FROM payments
+            WHERE status = 'RECEIVED'
+              AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+              AND LOWER(account_name) NOT IN (SELECT value FROM json_each(?))
+            GROUP BY account_name, 2
+            ORDER BY 2 DESC, MIN(created_at) ASC
+            "#,
+            dedicated_high_priority,
+            priority_high,
+            excluded_accounts
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Finds up to `limit` payments of `group` for batching, highest priority first, then oldest first.
+    pub async fn find_receivable_payments(
+        pool: &mut SqliteConnection,
+        group: &ReceivableGroup,
+        dedicated_high_priority: bool,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let priority_high = PaymentPriority::High.as_i64();
         sqlx::query_as!(
             Payment,
             r#"
@@ -216,22 +450,212 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>",
-                payref
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             FROM payments
             WHERE status = 'RECEIVED'
+              AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+              AND account_name = ?
+              AND (? AND priority = ?) = ?
+            ORDER BY priority DESC, created_at ASC
             LIMIT ?
             "#,
+            group.account_name,
+            dedicated_high_priority,
+            priority_high,
+            group.dedicated,
             limit
         )
         .fetch_all(pool)
         .await
     }
 
+    /// Cancels RECEIVED payments whose `expires_at` has passed, records a PAYMENT_EXPIRED event for each
+    /// and returns them.
+    pub async fn cancel_expired_payments(pool: &mut SqliteConnection) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let status_cancelled = PaymentStatus::Cancelled.to_string();
+        let status_received = PaymentStatus::Received.to_string();
+        let payments = sqlx::query_as!(
+            Payment,
+            r#"
+            UPDATE payments
+            SET status = ?, failure_reason = 'Payment expired before it was batched', updated_at = CURRENT_TIMESTAMP,
+                version = version + 1
+            WHERE status = ?
+              AND expires_at IS NOT NULL
+              AND expires_at <= CURRENT_TIMESTAMP
+            RETURNING
+                id,
+                client_id,
+                account_name,
+                status,
+                payment_batch_id,
+                recipient_address,
+                amount as "amount: PaymentAmount",
+                payment_id,
+                failure_reason,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
+            "#,
+            status_cancelled,
+            status_received,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for payment in &payments {
+            Event::record(
+                &mut tx,
+                EventType::PaymentExpired,
+                &payment.id,
+                json!({
+                    "client_id": payment.client_id,
+                    "account_name": payment.account_name,
+                    "expires_at": payment.expires_at,
+                }),
+            )
+            .await?;
+            AuditEntry::record(
+                &mut tx,
+                SYSTEM_ACTOR,
+                AuditAction::PaymentExpired,
+                &payment.id,
+                Some(&status_received),
+                Some(&status_cancelled),
+                None,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        STATUS_CHANGES.notify_waiters();
+        Ok(payments)
+    }
+
+    /// Counts the RECEIVED payments of all accounts.
+    pub async fn count_pending(pool: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+        let status_received = PaymentStatus::Received.to_string();
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM payments
+            WHERE status = ?
+            "#,
+            status_received,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Summarizes the RECEIVED payments of an account.
+    pub async fn pending_summary(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+    ) -> Result<PendingSummary, sqlx::Error> {
+        let status_received = PaymentStatus::Received.to_string();
+        sqlx::query_as!(
+            PendingSummary,
+            r#"
+            SELECT
+                COUNT(*) as "payment_count!: i64",
+                COALESCE(SUM(amount), 0) as "total_amount!: i64"
+            FROM payments
+            WHERE status = ? AND LOWER(account_name) = LOWER(?)
+            "#,
+            status_received,
+            account_name,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Summarizes the payments of an account the Payment Receiver has not locked funds for yet: RECEIVED payments and
+    /// the payments of PENDING_BATCHING batches.
+    pub async fn unfunded_summary(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+    ) -> Result<PendingSummary, sqlx::Error> {
+        let status_received = PaymentStatus::Received.to_string();
+        let status_batched = PaymentStatus::Batched.to_string();
+        let batch_status_pending = PaymentBatchStatus::PendingBatching.to_string();
+        sqlx::query_as!(
+            PendingSummary,
+            r#"
+            SELECT
+                COUNT(*) as "payment_count!: i64",
+                COALESCE(SUM(p.amount), 0) as "total_amount!: i64"
+            FROM payments p
+            LEFT JOIN payment_batches pb ON pb.id = p.payment_batch_id
+            WHERE LOWER(p.account_name) = LOWER(?)
+              AND (p.status = ? OR (p.status = ? AND pb.status = ?))
+            "#,
+            account_name,
+            status_received,
+            status_batched,
+            batch_status_pending,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Sums the amounts of all CONFIRMED and SETTLED payments of an account, in µT.
+    pub async fn confirmed_total(pool: &mut SqliteConnection, account_name: &str) -> Result<i64, sqlx::Error> {
+        let status_confirmed = PaymentStatus::Confirmed.to_string();
+        let status_settled = PaymentStatus::Settled.to_string();
+        sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "total!: i64"
+            FROM payments
+            WHERE status IN (?, ?) AND LOWER(account_name) = LOWER(?)
+            "#,
+            status_confirmed,
+            status_settled,
+            account_name,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Counts and sums the payments created within `[from, to)`, grouped by account and status.
+    pub async fn aggregate_by_account_and_status(
+        pool: &mut SqliteConnection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PaymentAggregate>, sqlx::Error> {
+        sqlx::query_as!(
+            PaymentAggregate,
+            r#"
+            SELECT
+                account_name,
+                status,
+                COUNT(*) as "payment_count!: i64",
+                COALESCE(SUM(amount), 0) as "total_amount!: i64"
+            FROM payments
+            WHERE (? IS NULL OR created_at >= datetime(?))
+              AND (? IS NULL OR created_at < datetime(?))
+            GROUP BY account_name, status
+            ORDER BY account_name, status
+            "#,
+            from,
+            from,
+            to,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Generic function to update payment status and optional fields.
     async fn update_payment_status(
         pool: &mut SqliteConnection,
@@ -249,7 +673,8 @@ impl Payment {
                 status = ?, 
                 payment_batch_id = COALESCE(?, payment_batch_id), 
                 failure_reason = ?, 
-                updated_at = CURRENT_TIMESTAMP
+                updated_at = CURRENT_TIMESTAMP,
+                version = version + 1
             WHERE id IN (SELECT value FROM json_each(?))
             "#,
             status,
@@ -259,6 +684,7 @@ impl Payment {
         )
         .execute(pool)
         .await?;
+        STATUS_CHANGES.notify_waiters();
         Ok(())
     }
 
@@ -281,7 +707,7 @@ impl Payment {
         sqlx::query!(
             r#"
             UPDATE payments
-              SET status = ?, payref = ?, updated_at = CURRENT_TIMESTAMP
+              SET status = ?, payref = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
             WHERE id = ?
             "#,
             status,
@@ -290,6 +716,7 @@ impl Payment {
         )
         .execute(pool)
         .await?;
+        STATUS_CHANGES.notify_waiters();
         Ok(())
     }
 
@@ -307,8 +734,10 @@ impl Payment {
         Self::update_payment_status(pool, &[payment_id.to_string()], PaymentStatus::Cancelled, None, None).await
     }
 
+    /// Cancels a payment on behalf of `actor`, removing it from its batch if the batch has not been signed yet.
     pub async fn cancel_single_payment(
         pool: &mut SqliteConnection,
+        actor: &str,
         payment_id: &str,
     ) -> Result<PaymentStatus, anyhow::Error> {
         let mut tx = pool.begin().await?;
@@ -322,14 +751,21 @@ impl Payment {
                 PaymentBatchStatus::PendingBatching | PaymentBatchStatus::AwaitingSignature => {},
                 _ => return Err(anyhow::anyhow!("Batch is too far along to cancel payment")),
             }
-        } else if matches!(
-            payment.status,
-            PaymentStatus::Confirmed | PaymentStatus::Failed | PaymentStatus::Cancelled
-        ) {
+        } else if payment.status.is_final() {
             return Err(anyhow::anyhow!("Payment is already in final state"));
         }
 
         Self::update_to_cancelled(&mut tx, payment_id).await?;
+        AuditEntry::record(
+            &mut tx,
+            actor,
+            AuditAction::PaymentCancelled,
+            payment_id,
+            Some(&payment.status.to_string()),
+            Some(&PaymentStatus::Cancelled.to_string()),
+            None,
+        )
+        .await?;
 
         if let Some(batch) = batch_opt {
             let remaining = Self::find_by_batch_id(&mut tx, &batch.id).await?;
@@ -344,6 +780,144 @@ impl Payment {
         Ok(PaymentStatus::Cancelled)
     }
 
+    /// Changes the recipient, amount or memo of a payment that has not been batched yet, on behalf of `actor`. Fields
+    /// passed as `None` keep their value. Returns `None`, changing nothing, if the payment is not 'RECEIVED' (any
+    /// more).
+    pub async fn amend_received(
+        pool: &mut SqliteConnection,
+        actor: &str,
+        payment_id: &str,
+        recipient_address: Option<&str>,
+        amount: Option<PaymentAmount>,
+        memo: Option<&str>,
+    ) -> Result<Option<Payment>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let status_received = PaymentStatus::Received.to_string();
+
+        let Some(before) = Self::get_by_id(&mut tx, payment_id).await? else {
+            return Ok(None);
+        };
+        let result = sqlx::query!(
+            r#"
+            UPDATE payments
+            SET recipient_address = COALESCE(?, recipient_address),
+                amount = COALESCE(?, amount),
+                payment_id = COALESCE(?, payment_id),
+                updated_at = CURRENT_TIMESTAMP,
+                version = version + 1
+            WHERE id = ? AND status = ?
+            "#,
+            recipient_address,
+            amount,
+            memo,
+            payment_id,
+            status_received,
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let after = Self::get_by_id(&mut tx, payment_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let mut changes = Vec::new();
+        if before.recipient_address != after.recipient_address {
+            changes.push(format!(
+                "recipient_address: {} -> {}",
+                before.recipient_address, after.recipient_address
+            ));
+        }
+        if before.amount != after.amount {
+            changes.push(format!("amount: {} -> {}", before.amount, after.amount));
+        }
+        if before.payment_id != after.payment_id {
+            changes.push(format!(
+                "memo: {} -> {}",
+                before.payment_id.as_deref().unwrap_or("-"),
+                after.payment_id.as_deref().unwrap_or("-")
+            ));
+        }
+        AuditEntry::record(
+            &mut tx,
+            actor,
+            AuditAction::PaymentAmended,
+            payment_id,
+            Some(&status_received),
+            Some(&status_received),
+            Some(&changes.join(", ")),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(after))
+    }
+
+    /// Takes a payment out of its batch on behalf of `actor` and returns it to 'RECEIVED', so the batch creator
+    /// batches it again. Only possible while the batch has not been signed; the batch then prepares its transaction
+    /// anew, or is cancelled if the payment was its last one.
+    pub async fn detach_from_batch(
+        pool: &mut SqliteConnection,
+        actor: &str,
+        payment_id: &str,
+    ) -> Result<Payment, anyhow::Error> {
+        let mut tx = pool.begin().await?;
+
+        let (payment, batch_opt) = Self::get_by_id_with_batch_info(&mut tx, payment_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Payment not found"))?;
+        let Some(batch) = batch_opt else {
+            return Err(anyhow::anyhow!("Payment is not in a batch"));
+        };
+        if !matches!(payment.status, PaymentStatus::Batched) {
+            return Err(anyhow::anyhow!(
+                "Payment is in status {} and cannot be detached",
+                payment.status
+            ));
+        }
+        match batch.status {
+            PaymentBatchStatus::PendingBatching | PaymentBatchStatus::AwaitingSignature => {},
+            _ => return Err(anyhow::anyhow!("Batch is too far along to detach payment")),
+        }
+
+        let status_received = PaymentStatus::Received.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE payments
+            SET status = ?, payment_batch_id = NULL, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id = ?
+            "#,
+            status_received,
+            payment_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+        AuditEntry::record(
+            &mut tx,
+            actor,
+            AuditAction::PaymentDetached,
+            payment_id,
+            Some(&payment.status.to_string()),
+            Some(&status_received),
+            Some(&format!("batch: {}", batch.id)),
+        )
+        .await?;
+
+        let remaining = Self::find_by_batch_id(&mut tx, &batch.id).await?;
+        if remaining.is_empty() {
+            PaymentBatch::cancel_batch_internal(&mut tx, &batch.id).await?;
+        } else {
+            PaymentBatch::recalc_batch_after_modification(&mut tx, &batch.id).await?;
+        }
+
+        let payment = Self::get_by_id(&mut tx, payment_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Payment not found"))?;
+        tx.commit().await?;
+        Ok(payment)
+    }
+
     /// Updates the status of all payments in a batch to 'FAILED' with a reason.
     pub async fn fail_payments_in_batch(
         pool: &mut SqliteConnection,
@@ -354,7 +928,7 @@ impl Payment {
         sqlx::query!(
             r#"
             UPDATE payments
-            SET status = ?, failure_reason = ?, updated_at = CURRENT_TIMESTAMP
+            SET status = ?, failure_reason = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
             WHERE payment_batch_id = ?
             "#,
             status_failed,
@@ -363,6 +937,47 @@ impl Payment {
         )
         .execute(pool)
         .await?;
+        STATUS_CHANGES.notify_waiters();
+        Ok(())
+    }
+
+    /// Updates the status of all non-cancelled payments in a batch to 'CONFIRMED'.
+    pub async fn confirm_payments_in_batch(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        let status_confirmed = PaymentStatus::Confirmed.to_string();
+        let status_cancelled = PaymentStatus::Cancelled.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE payments
+            SET status = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE payment_batch_id = ? AND status != ?
+            "#,
+            status_confirmed,
+            batch_id,
+            status_cancelled,
+        )
+        .execute(pool)
+        .await?;
+        STATUS_CHANGES.notify_waiters();
+        Ok(())
+    }
+
+    /// Updates the status of all 'CONFIRMED' payments in a batch to 'SETTLED'.
+    pub async fn settle_payments_in_batch(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        let status_settled = PaymentStatus::Settled.to_string();
+        let status_confirmed = PaymentStatus::Confirmed.to_string();
+        sqlx::query!(
+            r#"
+            UPDATE payments
+            SET status = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE payment_batch_id = ? AND status = ?
+            "#,
+            status_settled,
+            batch_id,
+            status_confirmed,
+        )
+        .execute(pool)
+        .await?;
+        STATUS_CHANGES.notify_waiters();
         Ok(())
     }
 
@@ -380,12 +995,14 @@ impl Payment {
                 status,
                 payment_batch_id,
                 recipient_address,
-                amount,
+                amount as "amount: PaymentAmount",
                 payment_id,
                 failure_reason,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>",
-                payref
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
             FROM payments
             WHERE payment_batch_id = ?
               AND status NOT IN (?, ?)
@@ -399,6 +1016,133 @@ impl Payment {
         .await
     }
 
+    /// Finds every payment ever added to a payment batch, including failed and cancelled ones.
+    pub async fn find_all_by_batch_id(pool: &mut SqliteConnection, batch_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                id,
+                client_id,
+                account_name,
+                status,
+                payment_batch_id,
+                recipient_address,
+                amount as "amount: PaymentAmount",
+                payment_id,
+                failure_reason,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
+            FROM payments
+            WHERE payment_batch_id = ?
+            ORDER BY id
+            "#,
+            batch_id,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns up to `limit` payments matching the optional filters, in creation order, starting after the payment
+    /// `after_payment_id`. Meant to be called repeatedly with the last returned payment ID to page through results.
+    pub async fn list(
+        pool: &mut SqliteConnection,
+        account_name: Option<&str>,
+        status: Option<PaymentStatus>,
+        tag: Option<&str>,
+        after_payment_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status = status.map(|s| s.to_string());
+        sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT
+                id,
+                client_id,
+                account_name,
+                status,
+                payment_batch_id,
+                recipient_address,
+                amount as "amount: PaymentAmount",
+                payment_id,
+                failure_reason,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>",
+                payref,
+                expires_at as "expires_at?: DateTime<Utc>",
+                priority
+            FROM payments
+            WHERE (? IS NULL OR account_name = ?)
+              AND (? IS NULL OR status = ?)
+              AND (? IS NULL OR id IN (SELECT payment_id FROM payment_tags WHERE tag = ?))
+              AND (? IS NULL OR rowid > (SELECT rowid FROM payments WHERE id = ?))
+            ORDER BY rowid
+            LIMIT ?
+            "#,
+            account_name,
+            account_name,
+            status,
+            status,
+            tag,
+            tag,
+            after_payment_id,
+            after_payment_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns up to `limit` payments created within `[from, to)` with a rowid greater than `after_row_id`,
+    /// in rowid order. Meant to be called repeatedly with the last returned `row_id` to page through large exports.
+    pub async fn find_for_export(
+        pool: &mut SqliteConnection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after_row_id: i64,
+        limit: i64,
+    ) -> Result<Vec<PaymentExportRow>, sqlx::Error> {
+        sqlx::query_as!(
+            PaymentExportRow,
+            r#"
+            SELECT
+                p.rowid as "row_id!: i64",
+                p.id,
+                p.client_id,
+                p.account_name,
+                p.recipient_address,
+                p.amount as "amount: PaymentAmount",
+                p.status,
+                p.payment_batch_id,
+                pb.status as "batch_status?",
+                p.payref,
+                pb.mined_height as "mined_height?",
+                pb.mined_timestamp as "mined_timestamp?",
+                p.created_at as "created_at: DateTime<Utc>",
+                p.updated_at as "updated_at: DateTime<Utc>"
+            FROM payments p
+            LEFT JOIN payment_batches pb ON p.payment_batch_id = pb.id
+            WHERE p.rowid > ?
+              AND (? IS NULL OR p.created_at >= datetime(?))
+              AND (? IS NULL OR p.created_at < datetime(?))
+            ORDER BY p.rowid
+            LIMIT ?
+            "#,
+            after_row_id,
+            from,
+            from,
+            to,
+            to,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Retrieves a payment by its ID, joining with payment_batches for more details.
     pub async fn get_by_id_with_batch_info(
         pool: &mut SqliteConnection,
@@ -414,12 +1158,14 @@ impl Payment {
                 p.status,
                 p.payment_batch_id,
                 p.recipient_address,
-                p.amount,
+                p.amount as "amount: PaymentAmount",
                 p.payment_id,
                 p.failure_reason,
                 p.created_at as "created_at: DateTime<Utc>",
                 p.updated_at as "updated_at: DateTime<Utc>",
                 p.payref,
+                p.expires_at as "expires_at?: DateTime<Utc>",
+                p.priority,
                 pb.id as batch_id,
                 pb.account_name as batch_account_name,
                 pb.status as batch_status,
@@ -432,6 +1178,10 @@ impl Payment {
                 pb.mined_height as batch_mined_height,
                 pb.mined_header_hash as batch_mined_header_hash,
                 pb.mined_timestamp as batch_mined_timestamp,
+                pb.required_confirmations as batch_required_confirmations,
+                pb.confirmations as batch_confirmations,
+                pb.tip_height as batch_tip_height,
+                pb.version as batch_version,
                 pb.created_at as "batch_created_at: DateTime<Utc>",
                 pb.updated_at as "batch_updated_at: DateTime<Utc>"
             FROM payments p
@@ -457,6 +1207,8 @@ impl Payment {
                     created_at: row.created_at,
                     updated_at: row.updated_at,
                     payref: row.payref,
+                    expires_at: row.expires_at,
+                    priority: row.priority.into(),
                 };
                 let batch_id = row.batch_id.clone();
                 let payment_batch = batch_id.map(|_| PaymentBatch {
@@ -472,6 +1224,10 @@ impl Payment {
                     mined_height: row.batch_mined_height,
                     mined_header_hash: row.batch_mined_header_hash,
                     mined_timestamp: row.batch_mined_timestamp,
+                    required_confirmations: row.batch_required_confirmations,
+                    confirmations: row.batch_confirmations,
+                    tip_height: row.batch_tip_height,
+                    version: row.batch_version.unwrap(),
                     created_at: row.batch_created_at.unwrap(),
                     updated_at: row.batch_updated_at.unwrap(),
                 });
@@ -490,12 +1246,14 @@ struct PaymentWithBatch {
     status: String,
     payment_batch_id: Option<String>,
     recipient_address: String,
-    amount: i64,
+    amount: PaymentAmount,
     payment_id: Option<String>,
     failure_reason: Option<String>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     payref: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    priority: i64,
     batch_id: Option<String>,
     batch_account_name: Option<String>,
     batch_status: Option<String>,
@@ -508,6 +1266,10 @@ struct PaymentWithBatch {
     batch_mined_height: Option<i64>,
     batch_mined_header_hash: Option<String>,
     batch_mined_timestamp: Option<i64>,
+    batch_required_confirmations: Option<i64>,
+    batch_confirmations: Option<i64>,
+    batch_tip_height: Option<i64>,
+    batch_version: Option<i64>,
     batch_created_at: Option<DateTime<Utc>>,
     batch_updated_at: Option<DateTime<Utc>>,
 }
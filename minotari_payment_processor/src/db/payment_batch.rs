@@ -1,15 +1,28 @@
 use anyhow::Context;
 use chrono::{DateTime, Utc};
+use log::warn;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::{Connection, FromRow, SqliteConnection};
 use std::fmt;
+use std::str::FromStr;
 use tari_common_types::transaction::TxId;
+use tari_common_types::types::FixedHash;
+use tari_transaction_components::offline_signing::models::SignedOneSidedTransactionResult;
+use thiserror::Error;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::base_node::{KernelSignature, kernel_signature};
+use crate::config::RetryPolicy;
+use crate::db::audit_log::{AuditAction, AuditEntry, SYSTEM_ACTOR};
+use crate::db::event::{Event, EventType};
+use crate::db::ledger_entry::LedgerEntry;
 use crate::db::payment::{Payment, PaymentStatus};
+use crate::db::superseded_pr_lock::SupersededPrLock;
+use crate::simulation::SimulatedTransaction;
 
-const MAX_RETRIES: i64 = 10;
+const LOG_TARGET: &str = "minotari_payment_processor::db::payment_batch";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -18,6 +31,8 @@ pub enum StepPayload {
     Unsigned(String),
     /// The payload returned by the Console Wallet, ready for Broadcast.
     Signed(String),
+    /// Signed with a fake signature in simulation mode. Only the simulated base node accepts it.
+    Simulated(SimulatedTransaction),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +43,109 @@ pub struct TransactionStep {
     pub is_consolidation: bool,
     pub payload: StepPayload,
     pub tx_id: TxId,
+    /// What the step was built to spend and pay, checked against the signed transaction before broadcast.
+    /// Missing on steps prepared by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<StepExpectation>,
+    /// Payments paid by the step, in the order of its recipients. Empty for consolidation steps and for steps
+    /// prepared by older versions, which are the only payout step of their batch and pay all of its payments in the
+    /// order of `Payment::find_by_batch_id`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payment_ids: Vec<String>,
+}
+
+/// Recorded when the unsigned transaction of a step is prepared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepExpectation {
+    /// Sum of the inputs spent by the step, in µT.
+    pub input_total: u64,
+    /// Recipients other than the sending account. Empty for consolidation steps.
+    pub recipients: Vec<ExpectedRecipient>,
+    /// Outputs split off the change and sent back to the sending account after the recipients, in µT.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub change_outputs: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedRecipient {
+    pub address: String,
+    /// In µT.
+    pub amount: u64,
+}
+
+impl TransactionStep {
+    /// The signed transaction of the step. `None` while the step is unsigned.
+    pub fn transaction(&self) -> anyhow::Result<Option<StepTransaction>> {
+        match &self.payload {
+            StepPayload::Unsigned(_) => Ok(None),
+            StepPayload::Signed(signed_json) => {
+                let signed_tx = SignedOneSidedTransactionResult::from_json(signed_json)
+                    .with_context(|| format!("Failed to parse signed transaction of step {}", self.step_index))?;
+                Ok(Some(StepTransaction::Signed(Box::new(signed_tx))))
+            },
+            StepPayload::Simulated(simulated) => Ok(Some(StepTransaction::Simulated(simulated.clone()))),
+        }
+    }
+}
+
+/// The transaction of a signed step.
+#[derive(Debug, Clone)]
+pub enum StepTransaction {
+    Signed(Box<SignedOneSidedTransactionResult>),
+    Simulated(SimulatedTransaction),
+}
+
+impl StepTransaction {
+    pub fn kernel_signature(&self) -> anyhow::Result<KernelSignature> {
+        match self {
+            StepTransaction::Signed(signed_tx) => kernel_signature(&signed_tx.signed_transaction.transaction),
+            StepTransaction::Simulated(simulated) => simulated.kernel_signature(),
+        }
+    }
+
+    /// Sum of the kernel fees, in µT.
+    pub fn fee(&self) -> u64 {
+        match self {
+            StepTransaction::Signed(signed_tx) => signed_tx
+                .signed_transaction
+                .transaction
+                .body
+                .kernels()
+                .iter()
+                .fold(0u64, |total, kernel| total.saturating_add(kernel.fee.as_u64())),
+            StepTransaction::Simulated(_) => 0,
+        }
+    }
+
+    /// Hashes of the outputs sent to the recipients, followed by those split off the change.
+    pub fn sent_hashes(&self) -> anyhow::Result<Vec<FixedHash>> {
+        match self {
+            StepTransaction::Signed(signed_tx) => Ok(signed_tx.signed_transaction.sent_hashes.clone()),
+            StepTransaction::Simulated(simulated) => simulated
+                .sent_hashes
+                .iter()
+                .map(|hash| {
+                    let bytes = hex::decode(hash).context("Invalid simulated sent hash")?;
+                    FixedHash::try_from(bytes).context("Invalid simulated sent hash")
+                })
+                .collect(),
+        }
+    }
+
+    /// Hashes of the outputs the transaction spends. Simulated transactions spend none.
+    pub fn input_hashes(&self) -> Vec<Vec<u8>> {
+        match self {
+            StepTransaction::Signed(signed_tx) => signed_tx
+                .signed_transaction
+                .transaction
+                .body
+                .inputs()
+                .iter()
+                .map(|input| input.output_hash().to_vec())
+                .collect(),
+            StepTransaction::Simulated(_) => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +153,16 @@ pub struct BatchPayload {
     pub steps: Vec<TransactionStep>,
 }
 
+/// Identifies a signed transaction on chain, e.g. for looking it up on a block explorer.
+#[derive(Debug, Clone)]
+pub struct TransactionIdentifiers {
+    pub tx_id: TxId,
+    /// Hex encoded public nonce of the excess signature of the transaction's first kernel.
+    pub excess_public_nonce: String,
+    /// Hex encoded excess signature of the transaction's first kernel.
+    pub excess_signature: String,
+}
+
 impl BatchPayload {
     pub fn from_json(json: &str) -> anyhow::Result<Self> {
         serde_json::from_str(json).context("Failed to deserialize BatchPayload")
@@ -43,9 +171,49 @@ impl BatchPayload {
     pub fn to_json(&self) -> anyhow::Result<String> {
         serde_json::to_string(self).context("Failed to serialize BatchPayload")
     }
+
+    /// Steps of the final cycle that pay the recipients. Empty while the payload holds the consolidation steps of a
+    /// split cycle.
+    pub fn payout_steps(&self) -> impl Iterator<Item = &TransactionStep> {
+        self.steps.iter().filter(|step| !step.is_consolidation)
+    }
+
+    /// Identifiers of the signed step that pays `payment_id`. `None` while the payload only holds the
+    /// consolidation steps of a split cycle or has not been signed yet.
+    pub fn payout_transaction(&self, payment_id: &str) -> anyhow::Result<Option<TransactionIdentifiers>> {
+        let Some(step) = self
+            .payout_steps()
+            .find(|step| step.payment_ids.is_empty() || step.payment_ids.iter().any(|id| id == payment_id))
+        else {
+            return Ok(None);
+        };
+        let Some(transaction) = step.transaction()? else {
+            return Ok(None);
+        };
+        let Ok((excess_public_nonce, excess_signature)) = transaction.kernel_signature() else {
+            return Ok(None);
+        };
+
+        Ok(Some(TransactionIdentifiers {
+            tx_id: step.tx_id,
+            excess_public_nonce: hex::encode(excess_public_nonce),
+            excess_signature: hex::encode(excess_signature),
+        }))
+    }
+
+    /// Sums the kernel fees of the signed steps, in µT. Unsigned steps are skipped.
+    pub fn total_fee(&self) -> anyhow::Result<u64> {
+        let mut total = 0u64;
+        for step in &self.steps {
+            if let Some(transaction) = step.transaction()? {
+                total = total.saturating_add(transaction.fee());
+            }
+        }
+        Ok(total)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PaymentBatchStatus {
     PendingBatching,
@@ -55,24 +223,89 @@ pub enum PaymentBatchStatus {
     Broadcasting,
     AwaitingConfirmation,
     Confirmed,
+    /// Confirmed and buried under `SETTLEMENT_CONFIRMATIONS` blocks.
+    Settled,
     Failed,
     Cancelled,
+    /// Retries were exhausted after a broadcast was attempted, so the funds may already be on chain.
+    /// The batch must be resolved manually as confirmed or failed.
+    NeedsReview,
+}
+
+impl PaymentBatchStatus {
+    /// Whether a batch in this status may already have had a transaction submitted to the network.
+    pub fn is_post_broadcast(&self) -> bool {
+        matches!(
+            self,
+            PaymentBatchStatus::AwaitingBroadcast
+                | PaymentBatchStatus::Broadcasting
+                | PaymentBatchStatus::AwaitingConfirmation
+        )
+    }
+
+    /// Whether a batch may no longer change its status.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PaymentBatchStatus::Confirmed
+                | PaymentBatchStatus::Settled
+                | PaymentBatchStatus::Failed
+                | PaymentBatchStatus::Cancelled
+        )
+    }
+
+    /// The transition table of the batch state machine. Non-final statuses may also be set again, e.g. when a
+    /// failed broadcast is reverted to 'AWAITING_BROADCAST' before the batch was marked as 'BROADCASTING'.
+    pub fn can_transition_to(&self, next: &PaymentBatchStatus) -> bool {
+        use PaymentBatchStatus::*;
+
+        if self == next {
+            return !self.is_final();
+        }
+        match self {
+            PendingBatching => matches!(next, AwaitingSignature | Failed | Cancelled),
+            // Taking a payment out of an unsigned batch sends it back to be prepared anew.
+            AwaitingSignature => matches!(next, SigningInProgress | PendingBatching | Failed | Cancelled),
+            SigningInProgress => matches!(next, AwaitingSignature | AwaitingBroadcast | Failed),
+            AwaitingBroadcast => matches!(next, Broadcasting | NeedsReview),
+            // A broadcast split cycle loops back to 'PENDING_BATCHING' for the next cycle.
+            Broadcasting => matches!(
+                next,
+                AwaitingBroadcast | AwaitingConfirmation | PendingBatching | NeedsReview
+            ),
+            AwaitingConfirmation => matches!(next, Confirmed | NeedsReview),
+            NeedsReview => matches!(next, Confirmed | Failed),
+            // The only move out of a final status: a confirmed batch settles once it is deep enough.
+            Confirmed => matches!(next, Settled),
+            Settled | Failed | Cancelled => false,
+        }
+    }
+}
+
+impl FromStr for PaymentBatchStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PENDING_BATCHING" => Ok(PaymentBatchStatus::PendingBatching),
+            "AWAITING_SIGNATURE" => Ok(PaymentBatchStatus::AwaitingSignature),
+            "SIGNING_IN_PROGRESS" => Ok(PaymentBatchStatus::SigningInProgress),
+            "AWAITING_BROADCAST" => Ok(PaymentBatchStatus::AwaitingBroadcast),
+            "BROADCASTING" => Ok(PaymentBatchStatus::Broadcasting),
+            "AWAITING_CONFIRMATION" => Ok(PaymentBatchStatus::AwaitingConfirmation),
+            "CONFIRMED" => Ok(PaymentBatchStatus::Confirmed),
+            "SETTLED" => Ok(PaymentBatchStatus::Settled),
+            "FAILED" => Ok(PaymentBatchStatus::Failed),
+            "CANCELLED" => Ok(PaymentBatchStatus::Cancelled),
+            "NEEDS_REVIEW" => Ok(PaymentBatchStatus::NeedsReview),
+            _ => Err(anyhow::anyhow!("Unknown PaymentBatchStatus: {}", s)),
+        }
+    }
 }
 
 impl From<String> for PaymentBatchStatus {
     fn from(s: String) -> Self {
-        match s.as_str() {
-            "PENDING_BATCHING" => PaymentBatchStatus::PendingBatching,
-            "AWAITING_SIGNATURE" => PaymentBatchStatus::AwaitingSignature,
-            "SIGNING_IN_PROGRESS" => PaymentBatchStatus::SigningInProgress,
-            "AWAITING_BROADCAST" => PaymentBatchStatus::AwaitingBroadcast,
-            "BROADCASTING" => PaymentBatchStatus::Broadcasting,
-            "AWAITING_CONFIRMATION" => PaymentBatchStatus::AwaitingConfirmation,
-            "CONFIRMED" => PaymentBatchStatus::Confirmed,
-            "FAILED" => PaymentBatchStatus::Failed,
-            "CANCELLED" => PaymentBatchStatus::Cancelled,
-            _ => panic!("Unknown PaymentBatchStatus: {}", s),
-        }
+        s.parse().unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -86,12 +319,49 @@ impl fmt::Display for PaymentBatchStatus {
             PaymentBatchStatus::Broadcasting => write!(f, "BROADCASTING"),
             PaymentBatchStatus::AwaitingConfirmation => write!(f, "AWAITING_CONFIRMATION"),
             PaymentBatchStatus::Confirmed => write!(f, "CONFIRMED"),
+            PaymentBatchStatus::Settled => write!(f, "SETTLED"),
             PaymentBatchStatus::Failed => write!(f, "FAILED"),
             PaymentBatchStatus::Cancelled => write!(f, "CANCELLED"),
+            PaymentBatchStatus::NeedsReview => write!(f, "NEEDS_REVIEW"),
         }
     }
 }
 
+#[derive(Debug, Error)]
+pub enum PaymentBatchError {
+    /// The batch is not in a status from which the requested one may be reached, e.g. a worker acting on a batch
+    /// another worker or an operator has already moved on.
+    #[error("Invalid status transition of batch {batch_id} from {from} to {to}")]
+    InvalidTransition {
+        batch_id: String,
+        from: PaymentBatchStatus,
+        to: PaymentBatchStatus,
+    },
+    /// The batch was updated by someone else since it was read.
+    #[error("Batch {batch_id} was modified concurrently (expected version {expected_version})")]
+    VersionConflict { batch_id: String, expected_version: i64 },
+    /// Some of the payments to be batched were cancelled, amended or batched by someone else since they were read.
+    #[error(
+        "Only {batched} of {expected} payments could be added to batch {batch_id}, the others changed concurrently"
+    )]
+    PaymentsChanged {
+        batch_id: String,
+        expected: usize,
+        batched: u64,
+    },
+    #[error(transparent)]
+    Db(#[from] sqlx::Error),
+}
+
+/// Count of the batches in one status and the amount total of their payments.
+#[derive(Debug, Clone, FromRow)]
+pub struct BatchAggregate {
+    pub status: PaymentBatchStatus,
+    pub batch_count: i64,
+    /// Sum of the payment amounts, in µT.
+    pub total_amount: i64,
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct PaymentBatch {
     pub id: String,
@@ -106,10 +376,27 @@ pub struct PaymentBatch {
     pub mined_height: Option<i64>,
     pub mined_header_hash: Option<String>,
     pub mined_timestamp: Option<i64>,
+    /// Confirmations needed before the batch is CONFIRMED. NULL for batches created before it was tracked
+    /// per batch, which fall back to the global setting.
+    pub required_confirmations: Option<i64>,
+    /// Latest confirmation count of the batch's transactions observed by the confirmation checker.
+    pub confirmations: Option<i64>,
+    /// Chain tip height at which `confirmations` was observed.
+    pub tip_height: Option<i64>,
+    /// Incremented on every update, so concurrent changes of the same batch can be detected.
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Aggregate of an account's batches that have not reached a final status yet.
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct InFlightSummary {
+    pub batch_count: i64,
+    /// Sum of the amounts of the (non-cancelled) payments in those batches, in µT.
+    pub total_amount: i64,
+}
+
 #[derive(Debug, Default)]
 pub struct PaymentBatchUpdate<'a> {
     pub status: Option<PaymentBatchStatus>,
@@ -120,6 +407,13 @@ pub struct PaymentBatchUpdate<'a> {
     pub mined_height: Option<i64>,
     pub mined_header_hash: Option<&'a str>,
     pub mined_timestamp: Option<i64>,
+    /// Delays the next processing attempt by this many seconds (used together with a retry count increment).
+    pub retry_delay_secs: Option<i64>,
+    /// Version of the batch the caller acted on. If unset, the version read right before the update is used.
+    pub expected_version: Option<i64>,
+    /// Skips the transition table, for an operator resolving a stuck batch by hand. Final statuses are still never
+    /// left.
+    pub force: bool,
 }
 
 impl PaymentBatch {
@@ -141,6 +435,10 @@ impl PaymentBatch {
                 mined_height,
                 mined_header_hash,
                 mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>"
             FROM payment_batches
@@ -153,21 +451,29 @@ impl PaymentBatch {
     }
 
     /// Creates a new payment batch and updates the associated payments.
+    /// `actor` is recorded in the audit log as the creator of the batch.
+    ///
+    /// The payments must still be 'RECEIVED' and unbatched or, if `source_batch_id` is given, still belong to that
+    /// batch. Otherwise nothing is created, so a payment cancelled or amended in the meantime is never paid.
     pub async fn create_with_payments(
         pool: &mut SqliteConnection,
+        actor: &str,
         account_name: &str,
         pr_idempotency_key: &str,
         payment_ids: &[String],
-    ) -> Result<Self, sqlx::Error> {
+        source_batch_id: Option<&str>,
+        required_confirmations: u64,
+    ) -> Result<Self, PaymentBatchError> {
         let mut tx = pool.begin().await?;
         let batch_id = Uuid::new_v4().to_string();
         let status = PaymentBatchStatus::PendingBatching.to_string();
+        let required_confirmations = required_confirmations as i64;
 
         let batch = sqlx::query_as!(
             PaymentBatch,
             r#"
-            INSERT INTO payment_batches (id, account_name, pr_idempotency_key, status)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO payment_batches (id, account_name, pr_idempotency_key, status, required_confirmations)
+            VALUES (?, ?, ?, ?, ?)
             RETURNING
                 id,
                 account_name,
@@ -181,42 +487,547 @@ impl PaymentBatch {
                 mined_height,
                 mined_header_hash,
                 mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>"
             "#,
             batch_id,
             account_name,
             pr_idempotency_key,
-            status
+            status,
+            required_confirmations
         )
         .fetch_one(&mut *tx)
         .await?;
 
         let json = serde_json::to_string(payment_ids).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
         let status_batched = PaymentStatus::Batched.to_string();
-        sqlx::query!(
+        let source_status = match source_batch_id {
+            Some(_) => PaymentStatus::Batched,
+            None => PaymentStatus::Received,
+        }
+        .to_string();
+        let result = sqlx::query!(
             r#"
             UPDATE payments
-            SET status = ?, payment_batch_id = ?, updated_at = CURRENT_TIMESTAMP
-            WHERE id IN (SELECT value FROM json_each(?))
+            SET status = ?, payment_batch_id = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id IN (SELECT value FROM json_each(?)) AND status = ? AND payment_batch_id IS ?
             "#,
             status_batched,
             batch_id,
             json,
+            source_status,
+            source_batch_id,
         )
         .execute(&mut *tx)
         .await?;
+        if result.rows_affected() < payment_ids.len() as u64 {
+            return Err(PaymentBatchError::PaymentsChanged {
+                batch_id,
+                expected: payment_ids.len(),
+                batched: result.rows_affected(),
+            });
+        }
+
+        AuditEntry::record(
+            &mut tx,
+            actor,
+            AuditAction::BatchCreated,
+            &batch.id,
+            None,
+            Some(&status),
+            Some(&format!("account: {}, payments: {}", account_name, payment_ids.len())),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(batch)
+    }
+
+    /// Creates a batch without payments that consolidates UTXOs of the account's wallet. The unsigned transaction
+    /// is already prepared, so the batch starts in 'AWAITING_SIGNATURE'.
+    pub async fn create_consolidation(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+        pr_idempotency_key: &str,
+        unsigned_tx_json: &str,
+        input_count: usize,
+        required_confirmations: u64,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let batch_id = Uuid::new_v4().to_string();
+        let status = PaymentBatchStatus::AwaitingSignature.to_string();
+        let required_confirmations = required_confirmations as i64;
+
+        let batch = sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            INSERT INTO payment_batches (id, account_name, pr_idempotency_key, status, unsigned_tx_json, required_confirmations)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            "#,
+            batch_id,
+            account_name,
+            pr_idempotency_key,
+            status,
+            unsigned_tx_json,
+            required_confirmations
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        AuditEntry::record(
+            &mut tx,
+            SYSTEM_ACTOR,
+            AuditAction::BatchCreated,
+            &batch.id,
+            None,
+            Some(&status),
+            Some(&format!(
+                "account: {}, consolidation of {} UTXOs",
+                account_name, input_count
+            )),
+        )
+        .await?;
 
         tx.commit().await?;
         Ok(batch)
     }
 
-    /// Finds payment batches by their status.
-    pub async fn find_by_status(
+    /// Finds payment batches by their status, skipping batches whose retry backoff has not elapsed yet.
+    pub async fn find_by_status(
+        pool: &mut SqliteConnection,
+        status: PaymentBatchStatus,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status = status.to_string();
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status = ?
+              AND (next_retry_at IS NULL OR next_retry_at <= CURRENT_TIMESTAMP)
+            ORDER BY created_at
+            "#,
+            status
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Like `find_by_status`, but returns at most `limit` batches for a single worker cycle. Batches are taken
+    /// round-robin by account, oldest first within each account, so a large backlog of one account cannot starve
+    /// the others. Batches of `excluded_accounts` (lower-cased) are skipped.
+    pub async fn find_by_status_fair(
+        pool: &mut SqliteConnection,
+        status: PaymentBatchStatus,
+        limit: i64,
+        excluded_accounts: &[String],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status = status.to_string();
+        let excluded_accounts =
+            serde_json::to_string(excluded_accounts).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status = ?
+              AND (next_retry_at IS NULL OR next_retry_at <= CURRENT_TIMESTAMP)
+              AND LOWER(account_name) NOT IN (SELECT value FROM json_each(?))
+            ORDER BY
+                ROW_NUMBER() OVER (PARTITION BY account_name ORDER BY created_at, id),
+                created_at,
+                id
+            LIMIT ?
+            "#,
+            status,
+            excluded_accounts,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Pages through the batches of a status in id order, returning at most `limit` batches after `after_id`. For
+    /// workers that have to visit every batch each cycle without loading them all at once.
+    pub async fn find_by_status_page(
+        pool: &mut SqliteConnection,
+        status: PaymentBatchStatus,
+        after_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status = status.to_string();
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status = ?
+              AND (next_retry_at IS NULL OR next_retry_at <= CURRENT_TIMESTAMP)
+              AND (? IS NULL OR id > ?)
+            ORDER BY id
+            LIMIT ?
+            "#,
+            status,
+            after_id,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Lists batches, newest first, optionally filtered by status and account.
+    pub async fn list(
+        pool: &mut SqliteConnection,
+        status: Option<PaymentBatchStatus>,
+        account_name: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status = status.map(|s| s.to_string());
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE (? IS NULL OR status = ?)
+              AND (? IS NULL OR LOWER(account_name) = LOWER(?))
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            status,
+            status,
+            account_name,
+            account_name,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Finds up to `limit` batches in the pipeline that have not changed since `updated_before`, least recently updated
+    /// first. Final batches and batches waiting for manual review are not stuck.
+    pub async fn find_stuck(
+        pool: &mut SqliteConnection,
+        updated_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let status_confirmed = PaymentBatchStatus::Confirmed.to_string();
+        let status_settled = PaymentBatchStatus::Settled.to_string();
+        let status_failed = PaymentBatchStatus::Failed.to_string();
+        let status_cancelled = PaymentBatchStatus::Cancelled.to_string();
+        let status_needs_review = PaymentBatchStatus::NeedsReview.to_string();
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status NOT IN (?, ?, ?, ?, ?)
+              AND updated_at < datetime(?)
+            ORDER BY updated_at, id
+            LIMIT ?
+            "#,
+            status_confirmed,
+            status_settled,
+            status_failed,
+            status_cancelled,
+            status_needs_review,
+            updated_before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Records that the unsigned transaction creator started working on a batch, which closes it to
+    /// `append_payments`. The time of the first start is kept.
+    pub async fn mark_tx_creation_started(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE payment_batches
+            SET tx_creation_started_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND tx_creation_started_at IS NULL
+            "#,
+            batch_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Adds payments to a batch in 'PENDING_BATCHING' that the unsigned transaction creator has not started on yet.
+    /// Returns `false`, changing nothing, if the batch no longer accepts payments. The payments must still be
+    /// 'RECEIVED' and unbatched, otherwise nothing is changed either.
+    pub async fn append_payments(
+        pool: &mut SqliteConnection,
+        actor: &str,
+        batch_id: &str,
+        payment_ids: &[String],
+    ) -> Result<bool, PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+        let status_pending = PaymentBatchStatus::PendingBatching.to_string();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE payment_batches
+            SET updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id = ? AND status = ? AND tx_creation_started_at IS NULL
+            "#,
+            batch_id,
+            status_pending,
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let json = serde_json::to_string(payment_ids).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        let status_batched = PaymentStatus::Batched.to_string();
+        let status_received = PaymentStatus::Received.to_string();
+        let result = sqlx::query!(
+            r#"
+            UPDATE payments
+            SET status = ?, payment_batch_id = ?, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id IN (SELECT value FROM json_each(?)) AND status = ? AND payment_batch_id IS NULL
+            "#,
+            status_batched,
+            batch_id,
+            json,
+            status_received,
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() < payment_ids.len() as u64 {
+            return Err(PaymentBatchError::PaymentsChanged {
+                batch_id: batch_id.to_string(),
+                expected: payment_ids.len(),
+                batched: result.rows_affected(),
+            });
+        }
+
+        AuditEntry::record(
+            &mut tx,
+            actor,
+            AuditAction::BatchPaymentsAdded,
+            batch_id,
+            None,
+            None,
+            Some(&format!("payments: {}", payment_ids.len())),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Resets the retry count and backoff of a batch, so the responsible worker picks it up on its next cycle.
+    pub async fn clear_retry_backoff(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE payment_batches
+            SET retry_count = 0, next_retry_at = NULL, updated_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id = ?
+            "#,
+            batch_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Counts the batches created within `[from, to)` by status, together with the amount total of their payments.
+    pub async fn aggregate_by_status(
+        pool: &mut SqliteConnection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<BatchAggregate>, sqlx::Error> {
+        sqlx::query_as!(
+            BatchAggregate,
+            r#"
+            SELECT
+                pb.status,
+                COUNT(DISTINCT pb.id) as "batch_count!: i64",
+                COALESCE(SUM(p.amount), 0) as "total_amount!: i64"
+            FROM payment_batches pb
+            LEFT JOIN payments p ON p.payment_batch_id = pb.id
+            WHERE (? IS NULL OR pb.created_at >= datetime(?))
+              AND (? IS NULL OR pb.created_at < datetime(?))
+            GROUP BY pb.status
+            ORDER BY pb.status
+            "#,
+            from,
+            from,
+            to,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Finds the most recently confirmed (or settled) batch of an account.
+    pub async fn find_last_confirmed(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let status_confirmed = PaymentBatchStatus::Confirmed.to_string();
+        let status_settled = PaymentBatchStatus::Settled.to_string();
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status IN (?, ?) AND LOWER(account_name) = LOWER(?)
+            ORDER BY updated_at DESC
+            LIMIT 1
+            "#,
+            status_confirmed,
+            status_settled,
+            account_name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Finds up to `limit` 'CONFIRMED' batches mined at or below `max_mined_height`, ordered by id and starting after
+    /// `after_id`. Batches resolved manually carry no mined height and are never returned.
+    pub async fn find_settleable_page(
         pool: &mut SqliteConnection,
-        status: PaymentBatchStatus,
+        max_mined_height: i64,
+        after_id: Option<&str>,
+        limit: i64,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        let status = status.to_string();
+        let status = PaymentBatchStatus::Confirmed.to_string();
         sqlx::query_as!(
             PaymentBatch,
             r#"
@@ -233,24 +1044,68 @@ impl PaymentBatch {
                 mined_height,
                 mined_header_hash,
                 mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
                 created_at as "created_at: DateTime<Utc>",
                 updated_at as "updated_at: DateTime<Utc>"
             FROM payment_batches
             WHERE status = ?
-            ORDER BY created_at
+              AND mined_height IS NOT NULL
+              AND mined_height <= ?
+              AND (? IS NULL OR id > ?)
+            ORDER BY id
+            LIMIT ?
             "#,
-            status
+            status,
+            max_mined_height,
+            after_id,
+            after_id,
+            limit
         )
         .fetch_all(pool)
         .await
     }
 
+    /// Applies `update` to a batch. A status change is checked against the transition table, otherwise
+    /// `PaymentBatchError::InvalidTransition` is returned. The update is only applied if the batch still has the
+    /// expected version, otherwise `PaymentBatchError::VersionConflict` is returned and nothing is changed.
     async fn update_payment_batch_status(
         pool: &mut SqliteConnection,
         batch_id: &str,
         update: &PaymentBatchUpdate<'_>,
         increment_retry_count: bool,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), PaymentBatchError> {
+        // Status changes are checked and audited, so remember where the batch came from.
+        let current = sqlx::query!(
+            r#"SELECT status, version as "version!: i64" FROM payment_batches WHERE id = ?"#,
+            batch_id
+        )
+        .fetch_optional(&mut *pool)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+        let expected_version = update.expected_version.unwrap_or(current.version);
+        if expected_version != current.version {
+            return Err(Self::version_conflict(batch_id, expected_version));
+        }
+
+        let before_status = match &update.status {
+            Some(after) => {
+                let before = PaymentBatchStatus::from(current.status);
+                let allowed = if update.force {
+                    !before.is_final()
+                } else {
+                    before.can_transition_to(after)
+                };
+                if !allowed {
+                    return Err(Self::invalid_transition(batch_id, before, after.clone()));
+                }
+                Some(before)
+            },
+            None => None,
+        };
+
         let mut qb = sqlx::QueryBuilder::new("UPDATE payment_batches SET");
         let mut needs_comma = false;
 
@@ -263,9 +1118,9 @@ impl PaymentBatch {
             }
         };
 
-        // Always update the timestamp.
+        // Always update the timestamp and version.
         separator(&mut qb);
-        qb.push("updated_at = CURRENT_TIMESTAMP");
+        qb.push("updated_at = CURRENT_TIMESTAMP, version = version + 1");
 
         if let Some(status) = &update.status {
             separator(&mut qb);
@@ -307,37 +1162,118 @@ impl PaymentBatch {
         if increment_retry_count {
             separator(&mut qb);
             qb.push("retry_count = retry_count + 1");
+            if let Some(delay_secs) = update.retry_delay_secs {
+                separator(&mut qb);
+                qb.push("next_retry_at = datetime('now', '+' || ")
+                    .push_bind(delay_secs)
+                    .push(" || ' seconds')");
+            }
         } else if let Some(new_status) = &update.status
-            && !matches!(new_status, PaymentBatchStatus::Failed | PaymentBatchStatus::Cancelled)
+            && !matches!(
+                new_status,
+                PaymentBatchStatus::Failed | PaymentBatchStatus::Cancelled | PaymentBatchStatus::NeedsReview
+            )
         {
             separator(&mut qb);
-            qb.push("retry_count = 0");
+            qb.push("retry_count = 0, next_retry_at = NULL");
         }
 
-        qb.push(" WHERE id = ").push_bind(batch_id);
-        qb.build().execute(pool).await?;
+        qb.push(" WHERE id = ")
+            .push_bind(batch_id)
+            .push(" AND version = ")
+            .push_bind(expected_version);
+        let result = qb.build().execute(&mut *pool).await?;
+        if result.rows_affected() == 0 {
+            // Another connection updated the batch between the read and the update.
+            return Err(Self::version_conflict(batch_id, expected_version));
+        }
+
+        if let (Some(before), Some(after)) = (before_status, update.status.as_ref()) {
+            if &before != after {
+                AuditEntry::record(
+                    pool,
+                    SYSTEM_ACTOR,
+                    AuditAction::BatchStatusChanged,
+                    batch_id,
+                    Some(&before.to_string()),
+                    Some(&after.to_string()),
+                    update.error_message,
+                )
+                .await?;
+            }
+        }
 
         Ok(())
     }
 
-    /// Updates a payment batch to 'AWAITING_SIGNATURE' status with unsigned transaction details.
+    fn version_conflict(batch_id: &str, expected_version: i64) -> PaymentBatchError {
+        warn!(
+            target: LOG_TARGET,
+            "Rejected concurrent update of batch {} (expected version {}).",
+            batch_id, expected_version
+        );
+        PaymentBatchError::VersionConflict {
+            batch_id: batch_id.to_string(),
+            expected_version,
+        }
+    }
+
+    fn invalid_transition(batch_id: &str, from: PaymentBatchStatus, to: PaymentBatchStatus) -> PaymentBatchError {
+        warn!(
+            target: LOG_TARGET,
+            "Rejected status transition of batch {} from '{}' to '{}'.",
+            batch_id, from, to
+        );
+        PaymentBatchError::InvalidTransition {
+            batch_id: batch_id.to_string(),
+            from,
+            to,
+        }
+    }
+
+    /// Updates a payment batch to 'AWAITING_SIGNATURE' status with unsigned transaction details. With an
+    /// `expected_version`, the batch must not have changed since, e.g. by payments being cancelled or detached while
+    /// the transaction was prepared.
     pub async fn update_to_awaiting_signature(
         pool: &mut SqliteConnection,
         batch_id: &str,
         unsigned_tx_json: &str,
-    ) -> Result<(), sqlx::Error> {
+        expected_version: Option<i64>,
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::AwaitingSignature),
             unsigned_tx_json: Some(unsigned_tx_json),
+            expected_version,
             ..Default::default()
         };
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
     }
 
-    /// Updates a payment batch to 'SIGNING_IN_PROGRESS' status.
-    pub async fn update_to_signing_in_progress(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+    /// Updates a payment batch to 'SIGNING_IN_PROGRESS' status, if it is still at `expected_version`.
+    pub async fn update_to_signing_in_progress(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        expected_version: i64,
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::SigningInProgress),
+            expected_version: Some(expected_version),
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(pool, batch_id, &update, false).await
+    }
+
+    /// Moves a batch that a previous process left in a transient status (e.g. 'SIGNING_IN_PROGRESS') to `status`,
+    /// keeping the transaction details. `reason` is stored as the batch's error message.
+    pub async fn recover_to_status(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        status: PaymentBatchStatus,
+        reason: &str,
+    ) -> Result<(), PaymentBatchError> {
+        let update = PaymentBatchUpdate {
+            status: Some(status),
+            error_message: Some(reason),
             ..Default::default()
         };
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
@@ -349,7 +1285,7 @@ impl PaymentBatch {
         batch_id: &str,
         signed_tx_json: &str,
         intermediate_context_json: Option<&str>,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::AwaitingBroadcast),
             signed_tx_json: Some(signed_tx_json),
@@ -359,22 +1295,48 @@ impl PaymentBatch {
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
     }
 
-    /// Updates a payment batch to 'AWAITING_BROADCAST' status for retry.
+    /// Updates a payment batch to 'AWAITING_BROADCAST' status for retry after `retry_delay`, or to
+    /// 'NEEDS_REVIEW' if max retries reached. Returns the new status.
     pub async fn update_to_awaiting_broadcast_for_retry(
         pool: &mut SqliteConnection,
         batch_id: &str,
-    ) -> Result<(), sqlx::Error> {
-        let update = PaymentBatchUpdate {
-            status: Some(PaymentBatchStatus::AwaitingBroadcast),
-            ..Default::default()
+        error_message: &str,
+        retry_policy: &RetryPolicy,
+    ) -> Result<PaymentBatchStatus, PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let batch = Self::find_by_id(&mut tx, batch_id)
+            .await?
+            .ok_or_else(|| sqlx::Error::RowNotFound)?;
+
+        let new_status = if batch.retry_count + 1 >= retry_policy.max_retries {
+            Self::update_to_needs_review(&mut tx, batch_id, error_message).await?;
+            PaymentBatchStatus::NeedsReview
+        } else {
+            let retry_delay = retry_policy.backoff_delay(batch.retry_count);
+            let update = PaymentBatchUpdate {
+                status: Some(PaymentBatchStatus::AwaitingBroadcast),
+                error_message: Some(error_message),
+                retry_delay_secs: Some(retry_delay.as_secs() as i64),
+                ..Default::default()
+            };
+            Self::update_payment_batch_status(&mut tx, batch_id, &update, true).await?;
+            PaymentBatchStatus::AwaitingBroadcast
         };
-        Self::update_payment_batch_status(pool, batch_id, &update, true).await
+
+        tx.commit().await?;
+        Ok(new_status)
     }
 
-    /// Updates a payment batch to 'BROADCASTING' status.
-    pub async fn update_to_broadcasting(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+    /// Updates a payment batch to 'BROADCASTING' status, if it is still at `expected_version`.
+    pub async fn update_to_broadcasting(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        expected_version: i64,
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::Broadcasting),
+            expected_version: Some(expected_version),
             ..Default::default()
         };
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
@@ -384,7 +1346,7 @@ impl PaymentBatch {
     pub async fn update_to_awaiting_confirmation(
         pool: &mut SqliteConnection,
         batch_id: &str,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::AwaitingConfirmation),
             intermediate_context_json: Some(""),
@@ -393,7 +1355,10 @@ impl PaymentBatch {
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
     }
 
-    pub async fn reset_to_pending_batching(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+    pub async fn reset_to_pending_batching(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+    ) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::PendingBatching),
             ..Default::default()
@@ -401,22 +1366,40 @@ impl PaymentBatch {
         Self::update_payment_batch_status(pool, batch_id, &update, false).await
     }
 
-    /// Updates a payment batch to 'CONFIRMED' status.
+    /// Updates a payment batch to 'CONFIRMED' status and writes its ledger journal.
     pub async fn update_to_confirmed(
         pool: &mut SqliteConnection,
         batch_id: &str,
         mined_height: u64,
         mined_header_hash: Vec<u8>,
         mined_timestamp: u64,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let mined_header_hash = hex::encode(mined_header_hash);
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::Confirmed),
             mined_height: Some(mined_height as i64),
-            mined_header_hash: Some(&hex::encode(mined_header_hash)),
+            mined_header_hash: Some(&mined_header_hash),
             mined_timestamp: Some(mined_timestamp as i64),
             ..Default::default()
         };
-        Self::update_payment_batch_status(pool, batch_id, &update, false).await
+        Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
+        LedgerEntry::record_batch_confirmed(&mut tx, batch_id).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchConfirmed,
+            batch_id,
+            json!({
+                "mined_height": mined_height,
+                "mined_header_hash": mined_header_hash,
+                "mined_timestamp": mined_timestamp,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
     }
 
     /// Updates a payment batch to 'FAILED' status with an error message.
@@ -424,7 +1407,7 @@ impl PaymentBatch {
         pool: &mut SqliteConnection,
         batch_id: &str,
         error_message: &str,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), PaymentBatchError> {
         let mut tx = pool.begin().await?;
 
         let update = PaymentBatchUpdate {
@@ -434,24 +1417,201 @@ impl PaymentBatch {
         };
         Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
         Payment::fail_payments_in_batch(&mut tx, batch_id, error_message).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchFailed,
+            batch_id,
+            json!({ "error_message": error_message }),
+        )
+        .await?;
 
         tx.commit().await?;
         Ok(())
     }
 
-    /// Increments the retry count for a payment batch, or sets to FAILED if max retries reached.
-    pub async fn increment_retry_count(
+    /// Updates a payment batch to 'NEEDS_REVIEW' status. Its payments are left untouched, as the
+    /// transaction may have been mined.
+    pub async fn update_to_needs_review(
         pool: &mut SqliteConnection,
         batch_id: &str,
         error_message: &str,
+    ) -> Result<(), PaymentBatchError> {
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::NeedsReview),
+            error_message: Some(error_message),
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(pool, batch_id, &update, false).await
+    }
+
+    /// Stores the latest observed confirmation count. This is progress information rather than a state change, so
+    /// neither the version nor `updated_at` is touched.
+    pub async fn record_confirmations(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        confirmations: u64,
+        tip_height: u64,
     ) -> Result<(), sqlx::Error> {
+        let confirmations = confirmations as i64;
+        let tip_height = tip_height as i64;
+        sqlx::query!(
+            r#"
+            UPDATE payment_batches
+            SET confirmations = ?, tip_height = ?
+            WHERE id = ?
+            "#,
+            confirmations,
+            tip_height,
+            batch_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Updates a 'CONFIRMED' batch and its confirmed payments to 'SETTLED'.
+    pub async fn update_to_settled(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        mined_height: u64,
+        confirmations: u64,
+    ) -> Result<(), PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::Settled),
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
+        Payment::settle_payments_in_batch(&mut tx, batch_id).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchSettled,
+            batch_id,
+            json!({
+                "mined_height": mined_height,
+                "confirmations": confirmations,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Manually resolves a 'NEEDS_REVIEW' batch as 'CONFIRMED', confirming all of its payments.
+    pub async fn resolve_as_confirmed(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::Confirmed),
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
+        Payment::confirm_payments_in_batch(&mut tx, batch_id).await?;
+        LedgerEntry::record_batch_confirmed(&mut tx, batch_id).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchConfirmed,
+            batch_id,
+            json!({ "resolved_manually": true }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Marks a batch whose transaction an operator found on chain as 'CONFIRMED' at the given block, bypassing the
+    /// transition table, and confirms its payments. Fails with a `VersionConflict` if the batch changed since it was
+    /// read at `expected_version`.
+    pub async fn force_to_confirmed(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        expected_version: i64,
+        mined_height: u64,
+        mined_header_hash: &str,
+        reason: &str,
+    ) -> Result<(), PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::Confirmed),
+            mined_height: Some(mined_height as i64),
+            mined_header_hash: Some(mined_header_hash),
+            expected_version: Some(expected_version),
+            force: true,
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
+        Payment::confirm_payments_in_batch(&mut tx, batch_id).await?;
+        LedgerEntry::record_batch_confirmed(&mut tx, batch_id).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchConfirmed,
+            batch_id,
+            json!({
+                "mined_height": mined_height,
+                "mined_header_hash": mined_header_hash,
+                "resolved_manually": true,
+                "reason": reason,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Marks a batch as 'FAILED' with `reason`, bypassing the transition table, and fails its payments. Fails with
+    /// a `VersionConflict` if the batch changed since it was read at `expected_version`.
+    pub async fn force_to_failed(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        expected_version: i64,
+        reason: &str,
+    ) -> Result<(), PaymentBatchError> {
+        let mut tx = pool.begin().await?;
+
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::Failed),
+            error_message: Some(reason),
+            expected_version: Some(expected_version),
+            force: true,
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
+        Payment::fail_payments_in_batch(&mut tx, batch_id, reason).await?;
+        Event::record(
+            &mut tx,
+            EventType::BatchFailed,
+            batch_id,
+            json!({ "error_message": reason, "resolved_manually": true }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Increments the retry count for a payment batch and schedules the next attempt using the policy's
+    /// backoff. If max retries reached, sets to NEEDS_REVIEW when a broadcast may have happened,
+    /// otherwise to FAILED.
+    pub async fn increment_retry_count(
+        pool: &mut SqliteConnection,
+        batch_id: &str,
+        error_message: &str,
+        retry_policy: &RetryPolicy,
+    ) -> Result<(), PaymentBatchError> {
         let mut tx = pool.begin().await?;
 
         let batch = Self::find_by_id(&mut tx, batch_id)
             .await?
             .ok_or_else(|| sqlx::Error::RowNotFound)?;
 
-        if batch.retry_count + 1 >= MAX_RETRIES {
+        if batch.retry_count + 1 >= retry_policy.max_retries && batch.status.is_post_broadcast() {
+            Self::update_to_needs_review(&mut tx, batch_id, error_message).await?;
+        } else if batch.retry_count + 1 >= retry_policy.max_retries {
             let status_failed = PaymentBatchStatus::Failed;
             let update = PaymentBatchUpdate {
                 status: Some(status_failed),
@@ -460,9 +1620,18 @@ impl PaymentBatch {
             };
             Self::update_payment_batch_status(&mut tx, batch_id, &update, false).await?;
             Payment::fail_payments_in_batch(&mut tx, batch_id, error_message).await?;
+            Event::record(
+                &mut tx,
+                EventType::BatchFailed,
+                batch_id,
+                json!({ "error_message": error_message, "retry_count": batch.retry_count + 1 }),
+            )
+            .await?;
         } else {
-            // No fields to update other than incrementing retry_count.
-            let update = PaymentBatchUpdate::default();
+            let update = PaymentBatchUpdate {
+                retry_delay_secs: Some(retry_policy.backoff_delay(batch.retry_count).as_secs() as i64),
+                ..Default::default()
+            };
             Self::update_payment_batch_status(&mut tx, batch_id, &update, true).await?;
         }
 
@@ -470,8 +1639,121 @@ impl PaymentBatch {
         Ok(())
     }
 
+    /// Counts the batches of all accounts that are still moving through the pipeline. NEEDS_REVIEW batches are
+    /// not counted, as they wait for an operator.
+    pub async fn count_in_flight(pool: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+        let status_confirmed = PaymentBatchStatus::Confirmed.to_string();
+        let status_settled = PaymentBatchStatus::Settled.to_string();
+        let status_failed = PaymentBatchStatus::Failed.to_string();
+        let status_cancelled = PaymentBatchStatus::Cancelled.to_string();
+        let status_needs_review = PaymentBatchStatus::NeedsReview.to_string();
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM payment_batches
+            WHERE status NOT IN (?, ?, ?, ?, ?)
+            "#,
+            status_confirmed,
+            status_settled,
+            status_failed,
+            status_cancelled,
+            status_needs_review,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Summarizes the batches of `account_name` that are still being processed (including NEEDS_REVIEW ones,
+    /// as their funds may still be spent).
+    pub async fn in_flight_summary(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+    ) -> Result<InFlightSummary, sqlx::Error> {
+        let status_confirmed = PaymentBatchStatus::Confirmed.to_string();
+        let status_settled = PaymentBatchStatus::Settled.to_string();
+        let status_failed = PaymentBatchStatus::Failed.to_string();
+        let status_cancelled = PaymentBatchStatus::Cancelled.to_string();
+        let payment_status_batched = PaymentStatus::Batched.to_string();
+        sqlx::query_as!(
+            InFlightSummary,
+            r#"
+            SELECT
+                COUNT(DISTINCT pb.id) as "batch_count!: i64",
+                COALESCE(SUM(p.amount), 0) as "total_amount!: i64"
+            FROM payment_batches pb
+            LEFT JOIN payments p ON p.payment_batch_id = pb.id AND p.status = ?
+            WHERE LOWER(pb.account_name) = LOWER(?)
+              AND pb.status NOT IN (?, ?, ?, ?)
+            "#,
+            payment_status_batched,
+            account_name,
+            status_confirmed,
+            status_settled,
+            status_failed,
+            status_cancelled,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Finds up to `limit` 'FAILED' and 'CANCELLED' batches whose Payment Receiver lock has not been released yet,
+    /// oldest first.
+    pub async fn find_with_unreleased_funds(pool: &mut SqliteConnection, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        let status_failed = PaymentBatchStatus::Failed.to_string();
+        let status_cancelled = PaymentBatchStatus::Cancelled.to_string();
+        sqlx::query_as!(
+            PaymentBatch,
+            r#"
+            SELECT
+                id,
+                account_name,
+                status,
+                pr_idempotency_key,
+                unsigned_tx_json,
+                signed_tx_json,
+                error_message,
+                retry_count,
+                intermediate_context_json,
+                mined_height,
+                mined_header_hash,
+                mined_timestamp,
+                required_confirmations,
+                confirmations,
+                tip_height,
+                version,
+                created_at as "created_at: DateTime<Utc>",
+                updated_at as "updated_at: DateTime<Utc>"
+            FROM payment_batches
+            WHERE status IN (?, ?)
+              AND pr_funds_released_at IS NULL
+            ORDER BY updated_at
+            LIMIT ?
+            "#,
+            status_failed,
+            status_cancelled,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Records that the Payment Receiver lock of a batch has been released.
+    pub async fn mark_funds_released(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE payment_batches
+            SET pr_funds_released_at = CURRENT_TIMESTAMP, version = version + 1
+            WHERE id = ?
+            "#,
+            batch_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     // Internal helper used by Payment::cancel_single_payment
-    pub async fn cancel_batch_internal(tx: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+    pub async fn cancel_batch_internal(tx: &mut SqliteConnection, batch_id: &str) -> Result<(), PaymentBatchError> {
         let update = PaymentBatchUpdate {
             status: Some(PaymentBatchStatus::Cancelled),
             ..Default::default()
@@ -479,22 +1761,34 @@ impl PaymentBatch {
         Self::update_payment_batch_status(tx, batch_id, &update, false).await
     }
 
-    /// Used when a payment is removed/cancelled from an active batch.
+    /// Used when a payment is removed/cancelled from an active batch. Sends the batch back to 'PENDING_BATCHING' and
+    /// drops its unsigned transaction and split-cycle context. The batch gets a new Payment Receiver idempotency key,
+    /// as the funds locked under the old one were sized for the old payments; the old lock is recorded for the
+    /// maintenance worker to release.
     pub async fn recalc_batch_after_modification(
         pool: &mut SqliteConnection,
         batch_id: &str,
-    ) -> Result<(), sqlx::Error> {
-        let status_pending_batching = PaymentBatchStatus::PendingBatching.to_string();
+    ) -> Result<(), PaymentBatchError> {
+        let update = PaymentBatchUpdate {
+            status: Some(PaymentBatchStatus::PendingBatching),
+            ..Default::default()
+        };
+        Self::update_payment_batch_status(pool, batch_id, &update, false).await?;
+        SupersededPrLock::record_for_batch(pool, batch_id).await?;
+
+        let pr_idempotency_key = Uuid::new_v4().to_string();
         sqlx::query!(
             r#"
             UPDATE payment_batches
-            SET status = ?,
-                unsigned_tx_json = NULL,
+            SET unsigned_tx_json = NULL,
                 signed_tx_json = NULL,
-                updated_at = CURRENT_TIMESTAMP
+                intermediate_context_json = NULL,
+                tx_creation_started_at = NULL,
+                pr_idempotency_key = ?,
+                version = version + 1
             WHERE id = ?
             "#,
-            status_pending_batching,
+            pr_idempotency_key,
             batch_id
         )
         .execute(pool)
@@ -503,3 +1797,88 @@ impl PaymentBatch {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PaymentBatchStatus::{self, *};
+
+    const ALL: [PaymentBatchStatus; 11] = [
+        PendingBatching,
+        AwaitingSignature,
+        SigningInProgress,
+        AwaitingBroadcast,
+        Broadcasting,
+        AwaitingConfirmation,
+        Confirmed,
+        Settled,
+        Failed,
+        Cancelled,
+        NeedsReview,
+    ];
+
+    #[test]
+    fn lifecycle_transitions_are_allowed() {
+        let path = [
+            PendingBatching,
+            AwaitingSignature,
+            SigningInProgress,
+            AwaitingBroadcast,
+            Broadcasting,
+            AwaitingConfirmation,
+            Confirmed,
+            Settled,
+        ];
+        for pair in path.windows(2) {
+            assert!(pair[0].can_transition_to(&pair[1]), "{:?} -> {:?}", pair[0], pair[1]);
+        }
+        // A split cycle loops back, a detached payment sends an unsigned batch back, and review resolves either way.
+        assert!(Broadcasting.can_transition_to(&PendingBatching));
+        assert!(AwaitingSignature.can_transition_to(&PendingBatching));
+        assert!(NeedsReview.can_transition_to(&Confirmed));
+        assert!(NeedsReview.can_transition_to(&Failed));
+    }
+
+    #[test]
+    fn transitions_skipping_or_reverting_steps_are_forbidden() {
+        for (from, to) in [
+            (PendingBatching, SigningInProgress),
+            (PendingBatching, Broadcasting),
+            (AwaitingSignature, AwaitingBroadcast),
+            (SigningInProgress, PendingBatching),
+            (SigningInProgress, Broadcasting),
+            (AwaitingBroadcast, PendingBatching),
+            (AwaitingBroadcast, Failed),
+            (Broadcasting, Failed),
+            (AwaitingConfirmation, Failed),
+            (AwaitingConfirmation, PendingBatching),
+            (NeedsReview, AwaitingBroadcast),
+        ] {
+            assert!(!from.can_transition_to(&to), "{:?} -> {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn once_broadcast_a_batch_cannot_be_cancelled_or_failed_directly() {
+        for from in ALL.iter().filter(|status| status.is_post_broadcast()) {
+            assert!(!from.can_transition_to(&Cancelled), "{:?} -> CANCELLED", from);
+            assert!(!from.can_transition_to(&Failed), "{:?} -> FAILED", from);
+        }
+    }
+
+    #[test]
+    fn non_final_statuses_may_be_set_again() {
+        for status in ALL.iter().filter(|status| !status.is_final()) {
+            assert!(status.can_transition_to(status), "{:?} -> {:?}", status, status);
+        }
+    }
+
+    #[test]
+    fn final_statuses_are_only_left_to_settle() {
+        for from in ALL.iter().filter(|status| status.is_final()) {
+            for to in &ALL {
+                let allowed = *from == Confirmed && *to == Settled;
+                assert_eq!(from.can_transition_to(to), allowed, "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::payment::PaymentStatus;
+
+/// Payments created by one bulk request in split mode. They are batched like single payments, so a group may end
+/// up in several batches.
+#[derive(Debug, Clone, FromRow)]
+pub struct PaymentGroup {
+    pub id: String,
+    pub account_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Number and total amount of a group's payments in one status.
+#[derive(Debug, Clone, FromRow)]
+pub struct PaymentGroupStatusCount {
+    pub status: PaymentStatus,
+    pub payment_count: i64,
+    /// Sum of the amounts, in µT.
+    pub total_amount: i64,
+}
+
+impl PaymentGroup {
+    /// Creates a group of the given payments, which must belong to `account_name`.
+    pub async fn create(
+        pool: &mut SqliteConnection,
+        account_name: &str,
+        payment_ids: &[String],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let group = sqlx::query_as!(
+            PaymentGroup,
+            r#"
+            INSERT INTO payment_groups (id, account_name)
+            VALUES (?, ?)
+            RETURNING id, account_name, created_at as "created_at: DateTime<Utc>"
+            "#,
+            id,
+            account_name
+        )
+        .fetch_one(&mut *pool)
+        .await?;
+
+        let json = serde_json::to_string(payment_ids).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_group_payments (payment_id, group_id)
+            SELECT value, ? FROM json_each(?)
+            "#,
+            group.id,
+            json
+        )
+        .execute(&mut *pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    pub async fn find_by_id(pool: &mut SqliteConnection, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PaymentGroup,
+            r#"
+            SELECT id, account_name, created_at as "created_at: DateTime<Utc>"
+            FROM payment_groups
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Returns the group of each of the given payments. Payments outside any group are omitted.
+    pub async fn find_group_ids_by_payment_ids(
+        pool: &mut SqliteConnection,
+        payment_ids: &[String],
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        if payment_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let json = serde_json::to_string(payment_ids).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        let rows = sqlx::query!(
+            r#"
+            SELECT payment_id, group_id
+            FROM payment_group_payments
+            WHERE payment_id IN (SELECT value FROM json_each(?))
+            "#,
+            json
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.payment_id, row.group_id)).collect())
+    }
+
+    /// Counts and sums the payments of the group by status.
+    pub async fn status_counts(
+        pool: &mut SqliteConnection,
+        id: &str,
+    ) -> Result<Vec<PaymentGroupStatusCount>, sqlx::Error> {
+        sqlx::query_as!(
+            PaymentGroupStatusCount,
+            r#"
+            SELECT
+                p.status,
+                COUNT(*) as "payment_count!: i64",
+                COALESCE(SUM(p.amount), 0) as "total_amount!: i64"
+            FROM payment_group_payments g
+            JOIN payments p ON p.id = g.payment_id
+            WHERE g.group_id = ?
+            GROUP BY p.status
+            ORDER BY p.status
+            "#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The batches the group's payments were placed in so far, oldest first.
+    pub async fn batch_ids(pool: &mut SqliteConnection, id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT pb.id
+            FROM payment_batches pb
+            WHERE pb.id IN (
+                SELECT p.payment_batch_id
+                FROM payment_group_payments g
+                JOIN payments p ON p.id = g.payment_id
+                WHERE g.group_id = ?
+            )
+            ORDER BY pb.created_at, pb.id
+            "#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,70 @@
+use sqlx::SqliteConnection;
+use std::collections::HashMap;
+
+/// Labels attached to payments, stored in the `payment_tags` join table.
+pub struct PaymentTag;
+
+impl PaymentTag {
+    /// Attaches `tags` to a payment. Tags the payment already has are ignored.
+    pub async fn add(pool: &mut SqliteConnection, payment_id: &str, tags: &[String]) -> Result<(), sqlx::Error> {
+        for tag in tags {
+            sqlx::query!(
+                r#"
+                INSERT INTO payment_tags (payment_id, tag)
+                VALUES (?, ?)
+                ON CONFLICT (payment_id, tag) DO NOTHING
+                "#,
+                payment_id,
+                tag
+            )
+            .execute(&mut *pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the tags of a payment, sorted.
+    pub async fn find_by_payment_id(pool: &mut SqliteConnection, payment_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT tag
+            FROM payment_tags
+            WHERE payment_id = ?
+            ORDER BY tag
+            "#,
+            payment_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns the sorted tags of each of the given payments. Payments without tags are omitted.
+    pub async fn find_by_payment_ids(
+        pool: &mut SqliteConnection,
+        payment_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>, sqlx::Error> {
+        if payment_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let json = serde_json::to_string(payment_ids).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT payment_id, tag
+            FROM payment_tags
+            WHERE payment_id IN (SELECT value FROM json_each(?))
+            ORDER BY payment_id, tag
+            "#,
+            json
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.payment_id).or_default().push(row.tag);
+        }
+        Ok(tags)
+    }
+}
@@ -0,0 +1,467 @@
+use anyhow::{Context, bail};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+use sqlx::{
+    Connection, PgConnection, Postgres, QueryBuilder, Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef,
+    postgres::PgRow, sqlite::SqliteRow,
+};
+
+use crate::db::pending_migrations;
+
+/// Rows inserted into Postgres per query. Postgres allows up to 65535 bind parameters per query.
+const COPY_PAGE_SIZE: usize = 500;
+
+/// A table copied by `copy_to_postgres`.
+#[derive(Debug, Clone)]
+pub struct CopiedTable {
+    pub name: String,
+    pub rows: u64,
+    /// SHA-256 over the sorted hashes of all rows, so it does not depend on the order the rows are read in.
+    pub digest: String,
+}
+
+/// Copies every table of the SQLite database into an empty Postgres database, in a single Postgres transaction.
+///
+/// The tables are read in one SQLite read transaction, so the copy is a consistent snapshot while the service keeps
+/// running. Each table is created with the same columns and primary key; foreign keys, defaults, indexes and
+/// sequences are not. Integers and booleans become `BIGINT`, timestamps stay the text SQLite stores.
+///
+/// After copying a table, its rows are read back from Postgres and their count and digest compared with those read
+/// from SQLite. The transaction is only committed if every table matches, so a failed copy leaves Postgres untouched.
+pub async fn copy_to_postgres(pool: &SqlitePool, postgres_url: &str) -> Result<Vec<CopiedTable>, anyhow::Error> {
+    let pending = pending_migrations(&mut *pool.acquire().await?).await?;
+    if !pending.is_empty() {
+        bail!(
+            "The database schema is {} migration(s) behind. Apply them with `migrate up` before copying it.",
+            pending.len()
+        );
+    }
+
+    let mut sqlite = pool.begin().await?;
+    let tables = list_tables(&mut sqlite).await?;
+
+    let mut postgres = PgConnection::connect(postgres_url)
+        .await
+        .context("Failed to connect to Postgres")?;
+    let mut tx = postgres.begin().await?;
+    ensure_absent(&mut tx, &tables).await?;
+
+    let mut copied = Vec::with_capacity(tables.len());
+    for table in &tables {
+        sqlx::query(&table.create_statement()).execute(&mut *tx).await?;
+        let (rows, digest) = copy_rows(&mut sqlite, &mut tx, table)
+            .await
+            .with_context(|| format!("Failed to copy table {}", table.name))?
+            .finish();
+        let (copied_rows, copied_digest) = digest_rows(&mut tx, table).await?.finish();
+        if (rows, &digest) != (copied_rows, &copied_digest) {
+            bail!(
+                "Table {} does not match after copying: SQLite has {} rows with digest {}, Postgres {} rows with \
+                 digest {}. Nothing was committed.",
+                table.name,
+                rows,
+                digest,
+                copied_rows,
+                copied_digest
+            );
+        }
+        copied.push(CopiedTable {
+            name: table.name.clone(),
+            rows,
+            digest,
+        });
+    }
+
+    tx.commit().await?;
+    sqlite.rollback().await?;
+    Ok(copied)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+impl ColumnKind {
+    /// Maps a declared column type following SQLite's type affinity rules. Booleans are stored as integers, the
+    /// remaining NUMERIC columns hold timestamps as text.
+    fn from_declared(declared: &str) -> Option<Self> {
+        let declared = declared.to_ascii_uppercase();
+        let kind = if declared.contains("INT") || declared.contains("BOOL") {
+            ColumnKind::Integer
+        } else if ["CHAR", "CLOB", "TEXT"].iter().any(|t| declared.contains(t)) {
+            ColumnKind::Text
+        } else if declared.contains("BLOB") {
+            ColumnKind::Blob
+        } else if ["REAL", "FLOA", "DOUB"].iter().any(|t| declared.contains(t)) {
+            ColumnKind::Real
+        } else if declared.is_empty() {
+            return None;
+        } else {
+            ColumnKind::Text
+        };
+        Some(kind)
+    }
+
+    fn postgres_type(self) -> &'static str {
+        match self {
+            ColumnKind::Integer => "BIGINT",
+            ColumnKind::Real => "DOUBLE PRECISION",
+            ColumnKind::Text => "TEXT",
+            ColumnKind::Blob => "BYTEA",
+        }
+    }
+}
+
+struct Column {
+    name: String,
+    kind: ColumnKind,
+    not_null: bool,
+    /// Position in the primary key, starting at 1, or 0 if the column is not part of it.
+    primary_key: i64,
+}
+
+struct Table {
+    name: String,
+    columns: Vec<Column>,
+}
+
+impl Table {
+    fn column_list(&self) -> String {
+        self.columns
+            .iter()
+            .map(|column| quote(&column.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn create_statement(&self) -> String {
+        let mut definitions: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let not_null = if column.not_null { " NOT NULL" } else { "" };
+                format!("{} {}{}", quote(&column.name), column.kind.postgres_type(), not_null)
+            })
+            .collect();
+        let mut key: Vec<&Column> = self.columns.iter().filter(|column| column.primary_key > 0).collect();
+        key.sort_by_key(|column| column.primary_key);
+        if !key.is_empty() {
+            let key: Vec<String> = key.iter().map(|column| quote(&column.name)).collect();
+            definitions.push(format!("PRIMARY KEY ({})", key.join(", ")));
+        }
+        format!("CREATE TABLE {} ({})", quote(&self.name), definitions.join(", "))
+    }
+
+    fn select_statement(&self) -> String {
+        format!("SELECT {} FROM {}", self.column_list(), quote(&self.name))
+    }
+}
+
+/// A value as SQLite stores it.
+#[derive(Debug, Clone)]
+enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    fn fits(&self, kind: ColumnKind) -> bool {
+        matches!(
+            (self, kind),
+            (Value::Null, _)
+                | (Value::Integer(_), ColumnKind::Integer)
+                | (Value::Real(_), ColumnKind::Real)
+                | (Value::Text(_), ColumnKind::Text)
+                | (Value::Blob(_), ColumnKind::Blob)
+        )
+    }
+
+    /// Feeds a tag byte and the value, length-prefixed where its size varies, to `hasher`.
+    fn hash_into(&self, hasher: &mut Sha256) {
+        match self {
+            Value::Null => hasher.update([0u8]),
+            Value::Integer(value) => {
+                hasher.update([1u8]);
+                hasher.update(value.to_be_bytes());
+            },
+            Value::Real(value) => {
+                hasher.update([2u8]);
+                hasher.update(value.to_bits().to_be_bytes());
+            },
+            Value::Text(value) => {
+                hasher.update([3u8]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value.as_bytes());
+            },
+            Value::Blob(value) => {
+                hasher.update([4u8]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value);
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct TableDigest {
+    row_hashes: Vec<[u8; 32]>,
+}
+
+impl TableDigest {
+    fn add_row(&mut self, values: &[Value]) {
+        let mut hasher = Sha256::new();
+        for value in values {
+            value.hash_into(&mut hasher);
+        }
+        self.row_hashes.push(hasher.finalize().into());
+    }
+
+    /// Returns the number of rows and their digest.
+    fn finish(mut self) -> (u64, String) {
+        self.row_hashes.sort_unstable();
+        let mut hasher = Sha256::new();
+        for row_hash in &self.row_hashes {
+            hasher.update(row_hash);
+        }
+        (self.row_hashes.len() as u64, hex::encode(hasher.finalize()))
+    }
+}
+
+/// Tables with their columns in creation order, without SQLite's internal tables and the migration history.
+async fn list_tables(sqlite: &mut SqliteConnection) -> Result<Vec<Table>, anyhow::Error> {
+    let names: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT name FROM sqlite_master
+        WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'
+        ORDER BY rowid
+        "#,
+    )
+    .fetch_all(&mut *sqlite)
+    .await?;
+
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        let rows = sqlx::query(r#"SELECT name, type, "notnull", pk FROM pragma_table_info(?) ORDER BY cid"#)
+            .bind(&name)
+            .fetch_all(&mut *sqlite)
+            .await?;
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            let column_name: String = row.try_get("name")?;
+            let declared: String = row.try_get("type")?;
+            let Some(kind) = ColumnKind::from_declared(&declared) else {
+                bail!("Column {}.{} has no declared type", name, column_name);
+            };
+            columns.push(Column {
+                name: column_name,
+                kind,
+                not_null: row.try_get("notnull")?,
+                primary_key: row.try_get("pk")?,
+            });
+        }
+        tables.push(Table { name, columns });
+    }
+    Ok(tables)
+}
+
+/// Fails if any of the tables already exists in Postgres, so data is never merged into an earlier copy.
+async fn ensure_absent(postgres: &mut PgConnection, tables: &[Table]) -> Result<(), anyhow::Error> {
+    let names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+    let existing: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT table_name::text FROM information_schema.tables
+        WHERE table_schema = current_schema() AND table_name = ANY($1)
+        "#,
+    )
+    .bind(&names)
+    .fetch_all(postgres)
+    .await?;
+    if !existing.is_empty() {
+        bail!(
+            "Postgres already has the tables {}. Copy into an empty database.",
+            existing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Inserts the rows of `table` into Postgres and returns the digest of the rows read from SQLite.
+async fn copy_rows(
+    sqlite: &mut SqliteConnection,
+    postgres: &mut PgConnection,
+    table: &Table,
+) -> Result<TableDigest, anyhow::Error> {
+    let mut digest = TableDigest::default();
+    let mut page = Vec::with_capacity(COPY_PAGE_SIZE);
+    let sql = table.select_statement();
+    let mut rows = sqlx::query(&sql).fetch(sqlite);
+    while let Some(row) = rows.try_next().await? {
+        let mut values = Vec::with_capacity(table.columns.len());
+        for (index, column) in table.columns.iter().enumerate() {
+            let value = sqlite_value(&row, index)?;
+            if !value.fits(column.kind) {
+                bail!(
+                    "Column {}.{} holds {:?}, which does not fit its {} type in Postgres",
+                    table.name,
+                    column.name,
+                    value,
+                    column.kind.postgres_type()
+                );
+            }
+            values.push(value);
+        }
+        digest.add_row(&values);
+        page.push(values);
+        if page.len() == COPY_PAGE_SIZE {
+            insert_rows(postgres, table, std::mem::take(&mut page)).await?;
+        }
+    }
+    if !page.is_empty() {
+        insert_rows(postgres, table, page).await?;
+    }
+    Ok(digest)
+}
+
+async fn insert_rows(postgres: &mut PgConnection, table: &Table, rows: Vec<Vec<Value>>) -> Result<(), sqlx::Error> {
+    let mut builder =
+        QueryBuilder::<Postgres>::new(format!("INSERT INTO {} ({}) ", quote(&table.name), table.column_list()));
+    builder.push_values(rows, |mut separated, row| {
+        for (value, column) in row.into_iter().zip(&table.columns) {
+            match value {
+                Value::Null => match column.kind {
+                    ColumnKind::Integer => separated.push_bind(None::<i64>),
+                    ColumnKind::Real => separated.push_bind(None::<f64>),
+                    ColumnKind::Text => separated.push_bind(None::<String>),
+                    ColumnKind::Blob => separated.push_bind(None::<Vec<u8>>),
+                },
+                Value::Integer(value) => separated.push_bind(value),
+                Value::Real(value) => separated.push_bind(value),
+                Value::Text(value) => separated.push_bind(value),
+                Value::Blob(value) => separated.push_bind(value),
+            };
+        }
+    });
+    builder.build().execute(postgres).await?;
+    Ok(())
+}
+
+/// Reads the copied rows of `table` back from Postgres and returns their digest.
+async fn digest_rows(postgres: &mut PgConnection, table: &Table) -> Result<TableDigest, sqlx::Error> {
+    let mut digest = TableDigest::default();
+    let sql = table.select_statement();
+    let mut rows = sqlx::query(&sql).fetch(postgres);
+    while let Some(row) = rows.try_next().await? {
+        let values = table
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| postgres_value(&row, index, column.kind))
+            .collect::<Result<Vec<_>, _>>()?;
+        digest.add_row(&values);
+    }
+    Ok(digest)
+}
+
+/// Reads a column with the type SQLite stored the value with, which may differ from the declared type.
+fn sqlite_value(row: &SqliteRow, index: usize) -> Result<Value, sqlx::Error> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let value = match raw.type_info().name() {
+        "INTEGER" => Value::Integer(row.try_get(index)?),
+        "REAL" => Value::Real(row.try_get(index)?),
+        "BLOB" => Value::Blob(row.try_get(index)?),
+        _ => Value::Text(row.try_get(index)?),
+    };
+    Ok(value)
+}
+
+fn postgres_value(row: &PgRow, index: usize, kind: ColumnKind) -> Result<Value, sqlx::Error> {
+    let value = match kind {
+        ColumnKind::Integer => row.try_get::<Option<i64>, _>(index)?.map(Value::Integer),
+        ColumnKind::Real => row.try_get::<Option<f64>, _>(index)?.map(Value::Real),
+        ColumnKind::Text => row.try_get::<Option<String>, _>(index)?.map(Value::Text),
+        ColumnKind::Blob => row.try_get::<Option<Vec<u8>>, _>(index)?.map(Value::Blob),
+    };
+    Ok(value.unwrap_or(Value::Null))
+}
+
+/// Quotes an identifier read from `sqlite_master`, valid in both SQLite and Postgres.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::db::memory_pool;
+
+    fn digest(rows: &[Vec<Value>]) -> (u64, String) {
+        let mut digest = TableDigest::default();
+        for row in rows {
+            digest.add_row(row);
+        }
+        digest.finish()
+    }
+
+    #[tokio::test]
+    async fn every_column_of_the_schema_maps_to_a_postgres_type() -> anyhow::Result<()> {
+        let pool = memory_pool().await?;
+        let tables = list_tables(&mut *pool.acquire().await?).await?;
+        let names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+        assert!(names.contains(&"payments"));
+        assert!(names.contains(&"payment_batches"));
+        assert!(names.contains(&"audit_log"));
+        assert!(
+            !names
+                .iter()
+                .any(|name| name.starts_with("sqlite_") || *name == "_sqlx_migrations")
+        );
+
+        let idempotency_keys = tables.iter().find(|table| table.name == "idempotency_keys").unwrap();
+        assert!(
+            idempotency_keys
+                .create_statement()
+                .ends_with(r#"PRIMARY KEY ("idempotency_key", "request_path", "client_name"))"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn declared_types_follow_sqlite_affinity() {
+        assert_eq!(ColumnKind::from_declared("BIGINT"), Some(ColumnKind::Integer));
+        assert_eq!(ColumnKind::from_declared("BOOLEAN"), Some(ColumnKind::Integer));
+        assert_eq!(ColumnKind::from_declared("TIMESTAMP"), Some(ColumnKind::Text));
+        assert_eq!(ColumnKind::from_declared("BLOB"), Some(ColumnKind::Blob));
+        assert_eq!(ColumnKind::from_declared("REAL"), Some(ColumnKind::Real));
+        assert_eq!(ColumnKind::from_declared(""), None);
+    }
+
+    #[test]
+    fn digest_ignores_row_order() {
+        let first = vec![Value::Text("a".to_string()), Value::Integer(1)];
+        let second = vec![Value::Text("b".to_string()), Value::Null];
+        assert_eq!(digest(&[first.clone(), second.clone()]), digest(&[second, first]));
+    }
+
+    #[test]
+    fn digest_detects_changed_values() {
+        let original = digest(&[vec![Value::Text(String::new()), Value::Integer(1)]]);
+        assert_ne!(original, digest(&[vec![Value::Null, Value::Integer(1)]]));
+        assert_ne!(original, digest(&[vec![Value::Text(String::new()), Value::Integer(2)]]));
+        assert_ne!(original, digest(&[vec![Value::Blob(Vec::new()), Value::Integer(1)]]));
+        // Values are length-prefixed, so moving text between columns changes the digest.
+        assert_ne!(
+            digest(&[vec![Value::Text("ab".to_string()), Value::Text("c".to_string())]]),
+            digest(&[vec![Value::Text("a".to_string()), Value::Text("bc".to_string())]])
+        );
+    }
+}
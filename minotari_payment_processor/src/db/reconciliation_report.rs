@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReconciliationStatus {
+    Ok,
+    Discrepancy,
+}
+
+impl From<String> for ReconciliationStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "OK" => ReconciliationStatus::Ok,
+            "DISCREPANCY" => ReconciliationStatus::Discrepancy,
+            _ => panic!("Unknown ReconciliationStatus: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for ReconciliationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReconciliationStatus::Ok => write!(f, "OK"),
+            ReconciliationStatus::Discrepancy => write!(f, "DISCREPANCY"),
+        }
+    }
+}
+
+/// Outcome of comparing an account's Payment Receiver balance with the processor's view of its payments.
+#[derive(Debug, Clone, FromRow)]
+pub struct ReconciliationReport {
+    pub id: i64,
+    pub account_name: String,
+    pub available: i64,
+    pub locked: i64,
+    pub unconfirmed: i64,
+    pub total: i64,
+    pub in_flight_batch_count: i64,
+    pub in_flight_amount: i64,
+    pub confirmed_amount: i64,
+    pub discrepancy: i64,
+    pub status: ReconciliationStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Values of a report that is about to be stored.
+#[derive(Debug, Clone)]
+pub struct NewReconciliationReport<'a> {
+    pub account_name: &'a str,
+    pub available: i64,
+    pub locked: i64,
+    pub unconfirmed: i64,
+    pub total: i64,
+    pub in_flight_batch_count: i64,
+    pub in_flight_amount: i64,
+    pub confirmed_amount: i64,
+    pub discrepancy: i64,
+    pub status: ReconciliationStatus,
+}
+
+impl ReconciliationReport {
+    pub async fn create(
+        pool: &mut SqliteConnection,
+        report: &NewReconciliationReport<'_>,
+    ) -> Result<Self, sqlx::Error> {
+        let status = report.status.to_string();
+        sqlx::query_as!(
+            ReconciliationReport,
+            r#"
+            INSERT INTO reconciliation_reports (
+                account_name, available, locked, unconfirmed, total,
+                in_flight_batch_count, in_flight_amount, confirmed_amount, discrepancy, status
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+                id as "id!",
+                account_name,
+                available,
+                locked,
+                unconfirmed,
+                total,
+                in_flight_batch_count,
+                in_flight_amount,
+                confirmed_amount,
+                discrepancy,
+                status,
+                created_at as "created_at: DateTime<Utc>"
+            "#,
+            report.account_name,
+            report.available,
+            report.locked,
+            report.unconfirmed,
+            report.total,
+            report.in_flight_batch_count,
+            report.in_flight_amount,
+            report.confirmed_amount,
+            report.discrepancy,
+            status,
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
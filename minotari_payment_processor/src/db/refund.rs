@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+
+use crate::amount::PaymentAmount;
+use crate::db::payment::PaymentStatus;
+
+/// A refund of a confirmed payment. The refund itself is a regular payment that goes through batching,
+/// signing and broadcasting, so its status is the status of that payment.
+#[derive(Debug, Clone, FromRow)]
+pub struct Refund {
+    pub refund_payment_id: String,
+    pub original_payment_id: String,
+    pub status: PaymentStatus,
+    pub recipient_address: String,
+    pub amount: PaymentAmount,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Refund {
+    /// Links the refund payment `refund_payment_id` to the payment it refunds.
+    pub async fn create(
+        pool: &mut SqliteConnection,
+        refund_payment_id: &str,
+        original_payment_id: &str,
+        reason: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO refunds (refund_payment_id, original_payment_id, reason)
+            VALUES (?, ?, ?)
+            "#,
+            refund_payment_id,
+            original_payment_id,
+            reason
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the refund whose payment is `refund_payment_id`, if that payment is a refund.
+    pub async fn find_by_refund_payment_id(
+        pool: &mut SqliteConnection,
+        refund_payment_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Refund,
+            r#"
+            SELECT
+                r.refund_payment_id,
+                r.original_payment_id,
+                p.status,
+                p.recipient_address,
+                p.amount as "amount: PaymentAmount",
+                r.reason,
+                r.created_at as "created_at: DateTime<Utc>"
+            FROM refunds r
+            JOIN payments p ON p.id = r.refund_payment_id
+            WHERE r.refund_payment_id = ?
+            "#,
+            refund_payment_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Returns all refunds of a payment, oldest first.
+    pub async fn find_by_original_payment_id(
+        pool: &mut SqliteConnection,
+        original_payment_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Refund,
+            r#"
+            SELECT
+                r.refund_payment_id,
+                r.original_payment_id,
+                p.status,
+                p.recipient_address,
+                p.amount as "amount: PaymentAmount",
+                r.reason,
+                r.created_at as "created_at: DateTime<Utc>"
+            FROM refunds r
+            JOIN payments p ON p.id = r.refund_payment_id
+            WHERE r.original_payment_id = ?
+            ORDER BY r.created_at, r.rowid
+            "#,
+            original_payment_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Sums the amounts of a payment's refunds that have not failed or been cancelled, in µT.
+    pub async fn refunded_total(pool: &mut SqliteConnection, original_payment_id: &str) -> Result<i64, sqlx::Error> {
+        let status_cancelled = PaymentStatus::Cancelled.to_string();
+        let status_failed = PaymentStatus::Failed.to_string();
+        sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(p.amount), 0) as "total!: i64"
+            FROM refunds r
+            JOIN payments p ON p.id = r.refund_payment_id
+            WHERE r.original_payment_id = ?
+              AND p.status NOT IN (?, ?)
+            "#,
+            original_payment_id,
+            status_cancelled,
+            status_failed
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
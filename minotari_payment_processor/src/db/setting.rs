@@ -0,0 +1,98 @@
+use sqlx::SqliteConnection;
+
+/// Key of the global pause flag.
+const PROCESSING_PAUSED_KEY: &str = "processing_paused";
+/// Prefix of the per-account pause flags, followed by the lower-cased account name.
+const ACCOUNT_PAUSED_PREFIX: &str = "processing_paused:";
+
+/// Access to the `settings` key/value table.
+pub struct Setting;
+
+impl Setting {
+    pub async fn get(pool: &mut SqliteConnection, key: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT value FROM settings WHERE key = ?", key)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn set(pool: &mut SqliteConnection, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO settings (key, value)
+            VALUES (?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+            "#,
+            key,
+            value,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns whether the setting existed.
+    pub async fn delete(pool: &mut SqliteConnection, key: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM settings WHERE key = ?", key)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Keys starting with `prefix`, sorted.
+    pub async fn keys_with_prefix(pool: &mut SqliteConnection, prefix: &str) -> Result<Vec<String>, sqlx::Error> {
+        let pattern = format!(
+            "{}%",
+            prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        sqlx::query_scalar!(
+            r#"SELECT key as "key!" FROM settings WHERE key LIKE ? ESCAPE '\' ORDER BY key"#,
+            pattern
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// Maintenance mode. While processing is paused, the workers do not start new batches (globally or for the paused
+/// accounts), but batches that are already being signed, broadcast or confirmed are finished.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessingPause {
+    pub global: bool,
+    /// Lower-cased names of the paused accounts.
+    pub accounts: Vec<String>,
+}
+
+impl ProcessingPause {
+    pub async fn load(pool: &mut SqliteConnection) -> Result<Self, sqlx::Error> {
+        let global = Setting::get(pool, PROCESSING_PAUSED_KEY).await?.is_some();
+        let accounts = Setting::keys_with_prefix(pool, ACCOUNT_PAUSED_PREFIX)
+            .await?
+            .into_iter()
+            .map(|key| key[ACCOUNT_PAUSED_PREFIX.len()..].to_string())
+            .collect();
+        Ok(Self { global, accounts })
+    }
+
+    pub fn is_account_paused(&self, account_name: &str) -> bool {
+        self.global || self.accounts.contains(&account_name.to_lowercase())
+    }
+
+    /// Pauses or resumes all accounts, or only `account_name`. Returns whether the state changed.
+    pub async fn set_paused(
+        pool: &mut SqliteConnection,
+        account_name: Option<&str>,
+        paused: bool,
+    ) -> Result<bool, sqlx::Error> {
+        let key = match account_name {
+            Some(account_name) => format!("{}{}", ACCOUNT_PAUSED_PREFIX, account_name.to_lowercase()),
+            None => PROCESSING_PAUSED_KEY.to_string(),
+        };
+        if paused {
+            let changed = Setting::get(pool, &key).await?.is_none();
+            Setting::set(pool, &key, "true").await?;
+            Ok(changed)
+        } else {
+            Setting::delete(pool, &key).await
+        }
+    }
+}
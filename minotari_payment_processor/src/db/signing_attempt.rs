@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+
+/// Longer output is cut off, keeping the beginning, so a chatty wallet cannot bloat the database.
+const MAX_OUTPUT_LENGTH: usize = 64 * 1024;
+
+/// One console wallet invocation that signed a step of a batch.
+#[derive(Debug, Clone, FromRow)]
+pub struct SigningAttempt {
+    pub id: i64,
+    pub batch_id: String,
+    pub step_index: i64,
+    pub exit_code: Option<i64>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of a console wallet invocation, as recorded by `SigningAttempt::record`.
+#[derive(Debug, Clone, Default)]
+pub struct NewSigningAttempt<'a> {
+    pub batch_id: &'a str,
+    pub step_index: i64,
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub stdout: &'a str,
+    pub stderr: &'a str,
+    pub duration_ms: i64,
+}
+
+impl SigningAttempt {
+    pub async fn record(pool: &mut SqliteConnection, attempt: &NewSigningAttempt<'_>) -> Result<(), sqlx::Error> {
+        let stdout = truncate(attempt.stdout);
+        let stderr = truncate(attempt.stderr);
+        sqlx::query!(
+            r#"
+            INSERT INTO signing_attempts
+                (batch_id, step_index, exit_code, success, timed_out, stdout, stderr, duration_ms)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            attempt.batch_id,
+            attempt.step_index,
+            attempt.exit_code,
+            attempt.success,
+            attempt.timed_out,
+            stdout,
+            stderr,
+            attempt.duration_ms,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Attempts of a batch, oldest first.
+    pub async fn find_by_batch_id(pool: &mut SqliteConnection, batch_id: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SigningAttempt,
+            r#"
+            SELECT
+                id as "id!",
+                batch_id,
+                step_index,
+                exit_code,
+                success as "success: bool",
+                timed_out as "timed_out: bool",
+                stdout,
+                stderr,
+                duration_ms,
+                created_at as "created_at: DateTime<Utc>"
+            FROM signing_attempts
+            WHERE batch_id = ?
+            ORDER BY id
+            "#,
+            batch_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+fn truncate(output: &str) -> &str {
+    if output.len() <= MAX_OUTPUT_LENGTH {
+        return output;
+    }
+    let mut end = MAX_OUTPUT_LENGTH;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    &output[..end]
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection};
+
+/// A Payment Receiver lock that a batch gave up when its payments changed, waiting to be released.
+#[derive(Debug, Clone, FromRow)]
+pub struct SupersededPrLock {
+    pub pr_idempotency_key: String,
+    pub batch_id: String,
+    pub account_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SupersededPrLock {
+    /// Records the current lock of `batch_id`, if the unsigned transaction creator may have taken one out, before the
+    /// batch is given a new idempotency key.
+    pub async fn record_for_batch(pool: &mut SqliteConnection, batch_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO superseded_pr_locks (pr_idempotency_key, batch_id, account_name)
+            SELECT pr_idempotency_key, id, account_name
+            FROM payment_batches
+            WHERE id = ? AND tx_creation_started_at IS NOT NULL
+            ON CONFLICT (pr_idempotency_key) DO NOTHING
+            "#,
+            batch_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Finds up to `limit` locks that have not been released yet, oldest first.
+    pub async fn find_unreleased(pool: &mut SqliteConnection, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SupersededPrLock,
+            r#"
+            SELECT
+                pr_idempotency_key,
+                batch_id,
+                account_name,
+                created_at as "created_at: DateTime<Utc>"
+            FROM superseded_pr_locks
+            ORDER BY created_at
+            LIMIT ?
+            "#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Forgets a lock the Payment Receiver has released.
+    pub async fn delete(pool: &mut SqliteConnection, pr_idempotency_key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM superseded_pr_locks
+            WHERE pr_idempotency_key = ?
+            "#,
+            pr_idempotency_key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, anyhow};
+use chacha20poly1305::{
+    XChaCha20Poly1305, XNonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use std::fmt;
+
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+
+/// Symmetric key for secrets stored in the database, e.g. the view keys of accounts created through the API.
+///
+/// Values are encrypted with XChaCha20-Poly1305 under a random nonce and stored as hex encoded `nonce || ciphertext`.
+#[derive(Clone)]
+pub struct EncryptionKey([u8; KEY_LENGTH]);
+
+impl EncryptionKey {
+    /// Parses a hex encoded 32 byte key.
+    pub fn from_hex(key_hex: &str) -> anyhow::Result<Self> {
+        let bytes = hex::decode(key_hex.trim()).context("Encryption key is not valid hex")?;
+        let key: [u8; KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|bytes: Vec<u8>| anyhow!("Encryption key must be {} bytes, got {}", KEY_LENGTH, bytes.len()))?;
+        Ok(Self(key))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<String> {
+        let cipher = XChaCha20Poly1305::new(&self.0.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("Failed to encrypt value"))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(hex::encode(encrypted))
+    }
+
+    /// Fails if the value was encrypted with another key or was tampered with.
+    pub fn decrypt(&self, encrypted_hex: &str) -> anyhow::Result<Vec<u8>> {
+        let encrypted = hex::decode(encrypted_hex).context("Encrypted value is not valid hex")?;
+        if encrypted.len() < NONCE_LENGTH {
+            return Err(anyhow!("Encrypted value is too short"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+
+        let cipher = XChaCha20Poly1305::new(&self.0.into());
+        cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt value. Was it encrypted with another key?"))
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
@@ -1,6 +1,22 @@
+pub mod amount;
 pub mod api;
+pub mod base_node;
+pub mod cli;
 pub mod config;
 pub mod db;
+pub mod encryption;
+pub mod logging;
+pub mod payment_receiver;
+pub mod processor;
+pub mod retry;
+pub mod secrets;
+pub mod simulation;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tls;
+pub mod utils;
 pub mod workers;
 
+pub use processor::{PaymentProcessor, PaymentProcessorBuilder};
+
 pub const MAX_BATCH_SIZE: usize = 100;
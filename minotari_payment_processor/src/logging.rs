@@ -0,0 +1,32 @@
+use anyhow::Context;
+use log::LevelFilter;
+use log4rs::{
+    Config,
+    append::console::ConsoleAppender,
+    config::{Appender, Root},
+    encode::pattern::PatternEncoder,
+};
+use std::path::Path;
+
+const DEFAULT_PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S%.3f)} {h({l:5})} [{t}] {m}{n}";
+
+/// Initializes log4rs from the YAML file at `config_path`.
+/// If the file does not exist, everything at INFO level and above is logged to stdout.
+pub fn init(config_path: &str) -> anyhow::Result<()> {
+    if Path::new(config_path).exists() {
+        log4rs::init_file(config_path, Default::default())
+            .with_context(|| format!("Failed to initialize logging from {}", config_path))?;
+        return Ok(());
+    }
+
+    let stdout = ConsoleAppender::builder()
+        .encoder(Box::new(PatternEncoder::new(DEFAULT_PATTERN)))
+        .build();
+    let config = Config::builder()
+        .appender(Appender::builder().build("stdout", Box::new(stdout)))
+        .build(Root::builder().appender("stdout").build(LevelFilter::Info))
+        .context("Failed to build default logging configuration")?;
+    log4rs::init_config(config).context("Failed to initialize logging")?;
+
+    Ok(())
+}
@@ -1,75 +1,96 @@
+use clap::Parser;
 use dotenv::dotenv;
-use minotari_client::apis::configuration::Configuration as MinotariConfiguration;
-use minotari_node_wallet_client::http::Client as BaseNodeClient;
-use minotari_payment_processor::{api, config::PaymentProcessorEnv, db, workers};
-use std::sync::Arc;
-use tokio::{net::TcpListener, signal};
-use url::Url;
+use log::{error, info};
+use minotari_payment_processor::{
+    PaymentProcessor,
+    cli::{self, Cli, Command},
+    config::PaymentProcessorEnv,
+    logging, secrets,
+};
+use tokio::signal;
+use tokio_util::sync::CancellationToken;
+
+const LOG_TARGET: &str = "minotari_payment_processor";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
-    let env = PaymentProcessorEnv::load()?;
-    let app_env = env.clone();
+    let cli = Cli::parse();
+    let env = PaymentProcessorEnv::load(cli.config.as_deref())?;
 
-    println!("Starting Minotari Payment Processor...");
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(env).await,
+        command => cli::run(command, &env).await,
+    }
+}
 
-    let db_pool = db::init_db(&env.database_url).await?;
-    println!("Database initialized.");
+async fn serve(mut env: PaymentProcessorEnv) -> anyhow::Result<()> {
+    if env.console_wallet_password_prompt {
+        env.console_wallet_password = secrets::prompt("Console wallet password: ")?;
+    }
 
-    let client_config = Arc::new(MinotariConfiguration {
-        base_path: env.payment_receiver,
-        ..MinotariConfiguration::default()
-    });
+    if !env.run_api && env.workers.as_ref().is_some_and(Vec::is_empty) {
+        anyhow::bail!("RUN_API is disabled and WORKERS selects no worker, so there is nothing to run");
+    }
 
-    let base_node_url = Url::parse(&env.base_node)?;
-    let base_node_client = BaseNodeClient::new(base_node_url.clone(), base_node_url.clone());
+    logging::init(&env.log_config_path)?;
 
-    // Spawn workers
-    tokio::spawn(workers::batch_creator::run(
-        db_pool.clone(),
-        env.batch_creator_sleep_secs,
-    ));
-    tokio::spawn(workers::unsigned_tx_creator::run(
-        db_pool.clone(),
-        client_config.clone(),
-        env.tari_network,
-        env.accounts.clone(),
-        env.max_input_count_per_tx,
-        env.unsigned_tx_creator_sleep_secs,
-    ));
-    tokio::spawn(workers::transaction_signer::run(
-        db_pool.clone(),
-        env.tari_network,
-        env.console_wallet_path.clone(),
-        env.console_wallet_base_path.clone(),
-        env.console_wallet_password.clone(),
-        env.transaction_signer_sleep_secs,
-    ));
-    tokio::spawn(workers::broadcaster::run(
-        db_pool.clone(),
-        base_node_client.clone(),
-        env.broadcaster_sleep_secs,
-    ));
-    tokio::spawn(workers::confirmation_checker::run(
-        db_pool.clone(),
-        base_node_client.clone(),
-        env.confirmation_checker_sleep_secs,
-        env.confirmation_checker_required_confirmations.unwrap_or(10),
+    info!(target: LOG_TARGET, "Starting Minotari Payment Processor...");
+
+    let mut processor = PaymentProcessor::builder(env).build().await?;
+    processor.start_workers()?;
+    #[cfg(unix)]
+    tokio::spawn(reload_accounts_on_sighup(
+        processor.env().clone(),
+        processor.shutdown_token(),
     ));
-    println!("Minotari Payment Processor started. Press Ctrl+C to shut down.");
+    info!(target: LOG_TARGET, "Minotari Payment Processor started. Press Ctrl+C to shut down.");
 
-    // Create Axum API router
-    let app = api::create_router(db_pool.clone(), app_env);
-    let addr = format!("{}:{}", env.listen_ip, env.listen_port);
-    let listener = TcpListener::bind(&addr).await?;
-    println!("Axum API server listening on {}", addr);
-    tokio::spawn(async move {
-        axum::serve(listener, app.into_make_service()).await.unwrap();
-    });
+    let api_server = if processor.env().run_api {
+        Some(processor.serve_api().await?)
+    } else {
+        None
+    };
 
     signal::ctrl_c().await?;
-    println!("Ctrl+C received, shutting down.");
+    info!(target: LOG_TARGET, "Ctrl+C received, shutting down. Waiting for workers to finish their current batch...");
+    processor.shutdown().await;
+    if let Some(api_server) = api_server {
+        api_server.join().await?;
+    }
+    info!(target: LOG_TARGET, "Shutdown complete.");
 
     Ok(())
 }
+
+/// Reloads the account configuration whenever the process receives SIGHUP.
+#[cfg(unix)]
+async fn reload_accounts_on_sighup(env: PaymentProcessorEnv, shutdown: CancellationToken) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Failed to listen for SIGHUP, account reload is unavailable: {}", e);
+            return;
+        },
+    };
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = hangup.recv() => {},
+        }
+
+        let env = env.clone();
+        match tokio::task::spawn_blocking(move || env.reload_accounts()).await {
+            Ok(Ok(count)) => info!(target: LOG_TARGET, "SIGHUP received, reloaded {} accounts.", count),
+            Ok(Err(e)) => error!(
+                target: LOG_TARGET,
+                "SIGHUP received, but reloading accounts failed: {:#}. Keeping the previous accounts.",
+                e
+            ),
+            Err(e) => error!(target: LOG_TARGET, "Account reload task failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::anyhow;
+use minotari_client::apis::accounts_api::{self, ApiGetBalanceError, ApiLockFundsError};
+use minotari_client::apis::{Error, ResponseContent, configuration::Configuration, urlencode};
+use minotari_client::models::{AccountBalance, LockFundsRequest, LockFundsResult};
+use serde_json::json;
+
+use crate::config::NetworkRetry;
+use crate::retry::with_retry;
+
+/// Whether a failed Payment Receiver call may succeed when repeated: network errors, timeouts, `429 Too Many
+/// Requests` and server errors.
+pub fn is_transient<T>(error: &Error<T>) -> bool {
+    match error {
+        Error::Reqwest(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        Error::Io(_) => true,
+        Error::ResponseError(content) => content.status.is_server_error() || content.status.as_u16() == 429,
+        Error::Serde(_) => false,
+    }
+}
+
+/// Fetches the balance of `account_name`, retrying transient failures.
+pub async fn get_balance(
+    configuration: &Configuration,
+    retry: &NetworkRetry,
+    account_name: &str,
+) -> Result<AccountBalance, Error<ApiGetBalanceError>> {
+    with_retry(retry, "get_balance", is_transient, || {
+        accounts_api::api_get_balance(configuration, account_name)
+    })
+    .await
+}
+
+/// Locks funds of `account_name`, retrying transient failures. Repeating the request is safe as long as it carries
+/// an idempotency key, which makes the Payment Receiver return the original lock.
+pub async fn lock_funds(
+    configuration: &Configuration,
+    retry: &NetworkRetry,
+    account_name: &str,
+    request: LockFundsRequest,
+) -> Result<LockFundsResult, Error<ApiLockFundsError>> {
+    with_retry(retry, "lock_funds", is_transient, || {
+        accounts_api::api_lock_funds(configuration, account_name, request.clone())
+    })
+    .await
+}
+
+/// Releases the UTXOs the Payment Receiver locked for `account_name` under `idempotency_key`, so they become
+/// available to later lock requests right away instead of when the lock expires.
+///
+/// The generated client has no binding for this endpoint yet, so the request is made with its HTTP client. A
+/// `404 Not Found` is an error like any other: it can't be told apart from a Payment Receiver that doesn't offer the
+/// endpoint, in which case the lock is only freed when it expires.
+pub async fn release_funds(
+    configuration: &Configuration,
+    retry: &NetworkRetry,
+    account_name: &str,
+    idempotency_key: &str,
+) -> Result<(), anyhow::Error> {
+    match with_retry(retry, "release_funds", is_transient, || {
+        request_release_funds(configuration, account_name, idempotency_key)
+    })
+    .await
+    {
+        Ok(()) => Ok(()),
+        Err(Error::ResponseError(content)) => Err(anyhow!("PR API Error: {} - {}", content.status, content.content)),
+        Err(e) => Err(anyhow!("Network error calling PR API: {:?}", e)),
+    }
+}
+
+async fn request_release_funds(
+    configuration: &Configuration,
+    account_name: &str,
+    idempotency_key: &str,
+) -> Result<(), Error<()>> {
+    let uri = format!(
+        "{}/accounts/{}/release_funds",
+        configuration.base_path,
+        urlencode(account_name)
+    );
+    let mut request = configuration
+        .client
+        .post(&uri)
+        .json(&json!({ "idempotency_key": idempotency_key }));
+    if let Some(user_agent) = &configuration.user_agent {
+        request = request.header("User-Agent", user_agent.clone());
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let content = response.text().await.unwrap_or_default();
+        return Err(Error::ResponseError(ResponseContent {
+            status,
+            content,
+            entity: None,
+        }));
+    }
+    Ok(())
+}
@@ -0,0 +1,610 @@
+use anyhow::bail;
+use axum::Router;
+use log::{info, warn};
+use minotari_client::apis::configuration::Configuration as MinotariConfiguration;
+use sqlx::SqlitePool;
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::api;
+use crate::base_node::{FailoverBaseNodeClient, NodeClient};
+use crate::config::PaymentProcessorEnv;
+use crate::db;
+use crate::simulation::SimulatedBaseNode;
+use crate::tls;
+use crate::workers::{
+    self,
+    balance_monitor::BalanceMonitor,
+    batch_creator::BatchCreator,
+    broadcaster::Broadcaster,
+    chain_state::{ChainState, ChainStateWorker},
+    chat_notifier::ChatNotifier,
+    confirmation_checker::ConfirmationChecker,
+    console_wallet_check::{ConsoleWalletCheck, ConsoleWalletHealth},
+    consolidation::Consolidation,
+    import_job_runner::ImportJobRunner,
+    maintenance::Maintenance,
+    reconciliation::Reconciliation,
+    registry::WorkerRegistry,
+    supervisor::WorkerHealth,
+    transaction_signer::TransactionSigner,
+    unsigned_tx_creator::UnsignedTxCreator,
+    wakeup::WorkerWakeups,
+};
+
+const LOG_TARGET: &str = "minotari_payment_processor::processor";
+
+/// Builds a `PaymentProcessor`. Everything that isn't passed in is created from the configuration, the same way the
+/// binary does it.
+pub struct PaymentProcessorBuilder {
+    env: PaymentProcessorEnv,
+    db_pool: Option<SqlitePool>,
+    db_writer: Option<SqlitePool>,
+    payment_receiver: Option<MinotariConfiguration>,
+    base_node_client: Option<FailoverBaseNodeClient>,
+    shutdown: Option<CancellationToken>,
+}
+
+impl PaymentProcessorBuilder {
+    /// Uses an existing pool instead of opening `DATABASE_URL`. Pending migrations are applied to it on `build`,
+    /// unless `DATABASE_AUTO_MIGRATE` is disabled. Unless `db_writer` is set as well, writes go through this pool too.
+    pub fn db_pool(mut self, db_pool: SqlitePool) -> Self {
+        self.db_pool = Some(db_pool);
+        self
+    }
+
+    /// Pool that the workers and the mutating API endpoints write through, instead of a single-connection pool on
+    /// `DATABASE_URL`. It should have a single connection as well, see `db::connect_writer`.
+    pub fn db_writer(mut self, db_writer: SqlitePool) -> Self {
+        self.db_writer = Some(db_writer);
+        self
+    }
+
+    /// Client configuration for the Payment Receiver, e.g. with a custom HTTP client or credentials, instead of one
+    /// pointing at `PAYMENT_RECEIVER`.
+    pub fn payment_receiver(mut self, configuration: MinotariConfiguration) -> Self {
+        self.payment_receiver = Some(configuration);
+        self
+    }
+
+    /// Base node client to use instead of the one built from the `base_node` settings or simulation mode.
+    pub fn base_node_client(mut self, client: FailoverBaseNodeClient) -> Self {
+        self.base_node_client = Some(client);
+        self
+    }
+
+    /// Token that shuts the processor down when cancelled. Defaults to a new token; a child of the host's token ties
+    /// the processor to the host's shutdown.
+    pub fn shutdown_token(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Prepares the database, loads the accounts created through the API and recovers batches a previous process
+    /// left in a transient status, if this process runs the worker that owns them. No worker is started yet.
+    pub async fn build(self) -> Result<PaymentProcessor, anyhow::Error> {
+        let env = self.env;
+
+        let (db_pool, db_writer) = match self.db_pool {
+            Some(db_pool) => {
+                db::prepare_schema(&db_pool, &env.database).await?;
+                let db_writer = self.db_writer.unwrap_or_else(|| db_pool.clone());
+                (db_pool, db_writer)
+            },
+            None => {
+                let db_pool = db::init_db(&env.database_url, &env.database).await?;
+                let db_writer = match self.db_writer {
+                    Some(db_writer) => db_writer,
+                    None => db::connect_writer(&env.database_url, &env.database).await?,
+                };
+                (db_pool, db_writer)
+            },
+        };
+        info!(target: LOG_TARGET, "Database initialized.");
+
+        let stored_accounts = env.load_stored_accounts(&mut *db_pool.acquire().await?).await?;
+        if stored_accounts > 0 {
+            info!(target: LOG_TARGET, "Loaded {} accounts created through the API.", stored_accounts);
+        }
+
+        let client_config = Arc::new(self.payment_receiver.unwrap_or_else(|| MinotariConfiguration {
+            base_path: env.payment_receiver.clone(),
+            ..MinotariConfiguration::default()
+        }));
+
+        let base_node_client = match (self.base_node_client, env.simulation) {
+            (Some(client), _) => client,
+            (None, Some(simulation)) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "SIMULATION MODE: transactions are signed with fake signatures and submitted to a simulated base \
+                     node. Nothing is paid out."
+                );
+                FailoverBaseNodeClient::from_clients(
+                    vec![(
+                        "simulated://base-node".to_string(),
+                        NodeClient::Simulated(SimulatedBaseNode::new(simulation)),
+                    )],
+                    env.network_retry,
+                )?
+            },
+            (None, None) => FailoverBaseNodeClient::new(&env.base_nodes, env.network_retry)?,
+        };
+
+        // Only the process running a worker may recover its batches; an API-only replica would otherwise pull them
+        // from under the worker process.
+        if env.runs_worker("transaction_signer") {
+            workers::recovery::recover_interrupted_signing(&db_writer).await?;
+        }
+        if env.runs_worker("broadcaster") {
+            workers::recovery::recover_interrupted_broadcasts(&db_writer, &base_node_client).await?;
+        }
+
+        let shutdown = self.shutdown.unwrap_or_default();
+        let mut processor = PaymentProcessor {
+            chain_state: ChainState::new(env.chain_state_sleep_secs),
+            env,
+            db_pool,
+            db_writer,
+            client_config,
+            wakeups: WorkerWakeups::default(),
+            console_wallet_health: ConsoleWalletHealth::default(),
+            workers: WorkerRegistry::new(shutdown.clone()),
+            shutdown,
+        };
+        processor.register_workers(base_node_client);
+        let enabled = processor.workers.names();
+        if let Some(unknown) = processor
+            .env
+            .workers
+            .iter()
+            .flatten()
+            .find(|name| !enabled.contains(&name.as_str()))
+        {
+            bail!(
+                "WORKERS contains '{}', which is not a worker enabled by the configuration. Enabled workers: {}",
+                unknown,
+                enabled.join(", ")
+            );
+        }
+        Ok(processor)
+    }
+}
+
+/// The payment processor as a library: its workers and HTTP API, started and stopped by the embedding service.
+///
+/// ```ignore
+/// let mut processor = PaymentProcessor::builder(env).db_pool(pool).build().await?;
+/// processor.start_workers()?;
+/// let api = processor.serve_api().await?;
+/// // ...
+/// processor.shutdown().await;
+/// api.join().await?;
+/// ```
+pub struct PaymentProcessor {
+    env: PaymentProcessorEnv,
+    db_pool: SqlitePool,
+    db_writer: SqlitePool,
+    client_config: Arc<MinotariConfiguration>,
+    wakeups: WorkerWakeups,
+    console_wallet_health: ConsoleWalletHealth,
+    chain_state: ChainState,
+    workers: WorkerRegistry,
+    shutdown: CancellationToken,
+}
+
+impl PaymentProcessor {
+    pub fn builder(env: PaymentProcessorEnv) -> PaymentProcessorBuilder {
+        PaymentProcessorBuilder {
+            env,
+            db_pool: None,
+            db_writer: None,
+            payment_receiver: None,
+            base_node_client: None,
+            shutdown: None,
+        }
+    }
+
+    pub fn env(&self) -> &PaymentProcessorEnv {
+        &self.env
+    }
+
+    pub fn db_pool(&self) -> &SqlitePool {
+        &self.db_pool
+    }
+
+    pub fn db_writer(&self) -> &SqlitePool {
+        &self.db_writer
+    }
+
+    pub fn worker_health(&self) -> WorkerHealth {
+        self.workers.health()
+    }
+
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Names of the workers enabled by the configuration, in the order `start_workers` starts them.
+    /// Consolidation and the chat notifier are opt-in, and the console wallet check does not run in simulation mode.
+    pub fn worker_names(&self) -> Vec<&'static str> {
+        self.workers.names()
+    }
+
+    /// Starts every enabled worker selected by `WORKERS` that isn't running yet.
+    pub fn start_workers(&mut self) -> Result<(), anyhow::Error> {
+        if self.shutdown.is_cancelled() {
+            bail!("The payment processor is shutting down");
+        }
+        let env = &self.env;
+        self.workers.start_all(|name| env.runs_worker(name));
+        Ok(())
+    }
+
+    /// Starts a single worker. Fails if the worker is unknown, disabled by the configuration, or still running.
+    pub fn start_worker(&mut self, name: &str) -> Result<(), anyhow::Error> {
+        if self.shutdown.is_cancelled() {
+            bail!("The payment processor is shutting down");
+        }
+        self.workers.start(name)
+    }
+
+    /// Signals a single worker to stop. It finishes the batch it is working on first; `worker_health` reports it as
+    /// not running once it has exited. Returns `false` if the worker was never started.
+    pub fn stop_worker(&self, name: &str) -> bool {
+        self.workers.stop(name)
+    }
+
+    /// The HTTP API, for serving it from the host's own server. It must be served with
+    /// `into_make_service_with_connect_info::<SocketAddr>()`, as the IP allowlist and rate limiter need the client
+    /// address.
+    pub fn router(&self) -> Router {
+        api::create_router(
+            self.db_pool.clone(),
+            self.db_writer.clone(),
+            self.env.clone(),
+            self.workers.health(),
+            self.client_config.clone(),
+            self.wakeups.clone(),
+            self.console_wallet_health.clone(),
+            self.chain_state.clone(),
+        )
+    }
+
+    /// Serves the HTTP API on `LISTEN_IP:LISTEN_PORT`, over TLS if configured, until the returned server is stopped
+    /// or the processor shuts down.
+    pub async fn serve_api(&self) -> Result<ApiServer, anyhow::Error> {
+        let app = self.router().into_make_service_with_connect_info::<SocketAddr>();
+        let addr = format!("{}:{}", self.env.listen_ip, self.env.listen_port);
+        let shutdown = self.shutdown.child_token();
+        let server_shutdown = shutdown.clone();
+        let (local_addr, task) = match &self.env.tls {
+            Some(tls_config) => {
+                let rustls_config = tls::rustls_config(tls_config)?;
+                let socket_addr: SocketAddr = addr.parse()?;
+                let handle = axum_server::Handle::new();
+                tokio::spawn({
+                    let handle = handle.clone();
+                    async move {
+                        server_shutdown.cancelled().await;
+                        handle.graceful_shutdown(None);
+                    }
+                });
+                let task = tokio::spawn({
+                    let handle = handle.clone();
+                    async move {
+                        axum_server::bind_rustls(socket_addr, rustls_config)
+                            .handle(handle)
+                            .serve(app)
+                            .await
+                    }
+                });
+                let Some(local_addr) = handle.listening().await else {
+                    task.await??;
+                    bail!("API server on {} exited before it was listening", addr);
+                };
+                info!(
+                    target: LOG_TARGET,
+                    "Axum API server listening on {} (TLS{})",
+                    local_addr,
+                    if tls_config.client_ca_path.is_some() { ", client certificates required" } else { "" }
+                );
+                (local_addr, task)
+            },
+            None => {
+                let listener = TcpListener::bind(&addr).await?;
+                let local_addr = listener.local_addr()?;
+                info!(target: LOG_TARGET, "Axum API server listening on {}", local_addr);
+                let task = tokio::spawn(async move {
+                    axum::serve(listener, app)
+                        .with_graceful_shutdown(async move { server_shutdown.cancelled().await })
+                        .await
+                });
+                (local_addr, task)
+            },
+        };
+        Ok(ApiServer {
+            local_addr,
+            shutdown,
+            task,
+        })
+    }
+
+    /// Signals every worker and the API server to stop, and waits until the workers have finished their current
+    /// batch and exited.
+    pub async fn shutdown(self) {
+        self.shutdown.cancel();
+        self.workers.join_all().await;
+        info!(target: LOG_TARGET, "All workers stopped.");
+    }
+
+    /// Registers the workers enabled by the configuration, in the order they are started. They all write, so they are
+    /// given the writer pool.
+    fn register_workers(&mut self, base_node_client: FailoverBaseNodeClient) {
+        let env = &self.env;
+        let registry = &mut self.workers;
+
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let sleep_secs = env.batch_creator_sleep_secs;
+            let dedicated_high_priority_batches = env.batch_creator_dedicated_high_priority_batches;
+            let batching_window = env.batching_window;
+            let accounts = env.accounts.clone();
+            let default_required_confirmations = env.confirmation_checker_required_confirmations;
+            let wakeups = self.wakeups.clone();
+            move || {
+                BatchCreator::new(
+                    db_writer.clone(),
+                    sleep_secs,
+                    dedicated_high_priority_batches,
+                    batching_window,
+                    accounts.clone(),
+                    default_required_confirmations,
+                    wakeups.clone(),
+                )
+            }
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let client_config = self.client_config.clone();
+            let network_retry = env.network_retry;
+            let accounts = env.accounts.clone();
+            let (network, max_input_count_per_tx) = (env.tari_network, env.max_input_count_per_tx);
+            let sleep_secs = env.unsigned_tx_creator_sleep_secs;
+            let max_concurrency = env.unsigned_tx_creator_max_concurrency;
+            let batches_per_cycle = env.worker_batches_per_cycle;
+            let wakeups = self.wakeups.clone();
+            let retry_policies = env.retry_policies.clone();
+            move || {
+                UnsignedTxCreator::new(
+                    db_writer.clone(),
+                    client_config.clone(),
+                    network_retry,
+                    network,
+                    accounts.clone(),
+                    max_input_count_per_tx,
+                    retry_policies.clone(),
+                    sleep_secs,
+                    max_concurrency,
+                    batches_per_cycle,
+                    wakeups.clone(),
+                )
+            }
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let network = env.tari_network;
+            let console_wallet_path = env.console_wallet_path.clone();
+            let console_wallet_base_path = env.console_wallet_base_path.clone();
+            let console_wallet_password = env.console_wallet_password.clone();
+            let simulation = env.simulation.is_some();
+            let sleep_secs = env.transaction_signer_sleep_secs;
+            let max_parallelism = env.transaction_signer_max_parallelism;
+            let signing_timeout_secs = env.transaction_signer_timeout_secs;
+            let batches_per_cycle = env.worker_batches_per_cycle;
+            let wakeups = self.wakeups.clone();
+            let retry_policies = env.retry_policies.clone();
+            move || {
+                TransactionSigner::new(
+                    db_writer.clone(),
+                    network,
+                    console_wallet_path.clone(),
+                    console_wallet_base_path.clone(),
+                    console_wallet_password.clone(),
+                    simulation,
+                    sleep_secs,
+                    max_parallelism,
+                    signing_timeout_secs,
+                    retry_policies.clone(),
+                    batches_per_cycle,
+                    wakeups.clone(),
+                )
+            }
+        });
+        if env.simulation.is_none() {
+            registry.register({
+                let network = env.tari_network;
+                let console_wallet_path = env.console_wallet_path.clone();
+                let console_wallet_base_path = env.console_wallet_base_path.clone();
+                let console_wallet_password = env.console_wallet_password.clone();
+                let sleep_secs = env.console_wallet_check_sleep_secs;
+                let console_wallet_health = self.console_wallet_health.clone();
+                move || {
+                    ConsoleWalletCheck::new(
+                        network,
+                        console_wallet_path.clone(),
+                        console_wallet_base_path.clone(),
+                        console_wallet_password.clone(),
+                        sleep_secs,
+                        console_wallet_health.clone(),
+                    )
+                }
+            });
+        }
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let base_node_client = base_node_client.clone();
+            let sleep_secs = env.broadcaster_sleep_secs;
+            let batches_per_cycle = env.worker_batches_per_cycle;
+            let wakeups = self.wakeups.clone();
+            let retry_policies = env.retry_policies.clone();
+            let max_transaction_fee = env.max_transaction_fee;
+            let broadcast_to_all_base_nodes = env.broadcast_to_all_base_nodes;
+            let mempool_check = env.mempool_check;
+            let accounts = env.accounts.clone();
+            move || {
+                Broadcaster::new(
+                    db_writer.clone(),
+                    base_node_client.clone(),
+                    accounts.clone(),
+                    sleep_secs,
+                    retry_policies.clone(),
+                    max_transaction_fee,
+                    broadcast_to_all_base_nodes,
+                    mempool_check,
+                    batches_per_cycle,
+                    wakeups.clone(),
+                )
+            }
+        });
+        registry.register({
+            let base_node_client = base_node_client.clone();
+            let chain_state = self.chain_state.clone();
+            let sleep_secs = env.chain_state_sleep_secs;
+            move || ChainStateWorker::new(base_node_client.clone(), chain_state.clone(), sleep_secs)
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let chain_state = self.chain_state.clone();
+            let sleep_secs = env.confirmation_checker_sleep_secs;
+            let default_required_confirmations = env.confirmation_checker_required_confirmations;
+            let rebroadcast_after_checks = env.confirmation_checker_rebroadcast_after_checks;
+            let settlement_confirmations = env.settlement_confirmations;
+            let batches_per_cycle = env.worker_batches_per_cycle;
+            let retry_policies = env.retry_policies.clone();
+            move || {
+                ConfirmationChecker::new(
+                    db_writer.clone(),
+                    base_node_client.clone(),
+                    chain_state.clone(),
+                    sleep_secs,
+                    default_required_confirmations,
+                    rebroadcast_after_checks,
+                    settlement_confirmations,
+                    retry_policies.clone(),
+                    batches_per_cycle,
+                )
+            }
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let client_config = self.client_config.clone();
+            let network_retry = env.network_retry;
+            let sleep_secs = env.maintenance_sleep_secs;
+            move || Maintenance::new(db_writer.clone(), client_config.clone(), network_retry, sleep_secs)
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let client_config = self.client_config.clone();
+            let network_retry = env.network_retry;
+            let accounts = env.accounts.clone();
+            let sleep_secs = env.reconciliation_sleep_secs;
+            let discrepancy_threshold = env.reconciliation_discrepancy_threshold;
+            move || {
+                Reconciliation::new(
+                    db_writer.clone(),
+                    client_config.clone(),
+                    network_retry,
+                    accounts.clone(),
+                    sleep_secs,
+                    discrepancy_threshold,
+                )
+            }
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let client_config = self.client_config.clone();
+            let network_retry = env.network_retry;
+            let accounts = env.accounts.clone();
+            let sleep_secs = env.balance_monitor_sleep_secs;
+            let reserve = env.low_balance_reserve;
+            move || {
+                BalanceMonitor::new(
+                    db_writer.clone(),
+                    client_config.clone(),
+                    network_retry,
+                    accounts.clone(),
+                    sleep_secs,
+                    reserve,
+                )
+            }
+        });
+        registry.register({
+            let db_writer = self.db_writer.clone();
+            let env = env.clone();
+            let sleep_secs = env.import_job_runner_sleep_secs;
+            let wakeups = self.wakeups.clone();
+            move || ImportJobRunner::new(db_writer.clone(), env.clone(), sleep_secs, wakeups.clone())
+        });
+        if let Some(consolidation_config) = env.consolidation {
+            registry.register({
+                let db_writer = self.db_writer.clone();
+                let client_config = self.client_config.clone();
+                let network_retry = env.network_retry;
+                let accounts = env.accounts.clone();
+                let (network, max_input_count_per_tx) = (env.tari_network, env.max_input_count_per_tx);
+                let sleep_secs = env.consolidation_sleep_secs;
+                let wakeups = self.wakeups.clone();
+                move || {
+                    Consolidation::new(
+                        db_writer.clone(),
+                        client_config.clone(),
+                        network_retry,
+                        network,
+                        accounts.clone(),
+                        consolidation_config,
+                        max_input_count_per_tx,
+                        sleep_secs,
+                        wakeups.clone(),
+                    )
+                }
+            });
+        }
+        if let Some(chat_webhook) = env.chat_webhook.clone() {
+            registry.register({
+                let db_writer = self.db_writer.clone();
+                let network_retry = env.network_retry;
+                let sleep_secs = env.chat_notifier_sleep_secs;
+                move || ChatNotifier::new(db_writer.clone(), chat_webhook.clone(), network_retry, sleep_secs)
+            });
+        }
+    }
+}
+
+/// Handle of the API server started by `PaymentProcessor::serve_api`.
+pub struct ApiServer {
+    local_addr: SocketAddr,
+    shutdown: CancellationToken,
+    task: JoinHandle<std::io::Result<()>>,
+}
+
+impl ApiServer {
+    /// The address the server is bound to, e.g. to find the port when `LISTEN_PORT` is 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stops accepting connections and waits for in-flight requests to finish.
+    pub async fn stop(self) -> Result<(), anyhow::Error> {
+        self.shutdown.cancel();
+        self.join().await
+    }
+
+    /// Waits until the server has stopped, either through `stop` or the processor's shutdown.
+    pub async fn join(self) -> Result<(), anyhow::Error> {
+        self.task.await??;
+        info!(target: LOG_TARGET, "API server stopped.");
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use log::warn;
+use std::fmt::Display;
+use std::future::Future;
+
+use crate::config::NetworkRetry;
+
+const LOG_TARGET: &str = "minotari_payment_processor::retry";
+
+/// Runs `operation` until it succeeds, fails with an error `is_transient` rejects, or `retry.attempts` are used up,
+/// waiting with exponential backoff between attempts.
+///
+/// Each attempt is limited to `retry.timeout`. A timed out attempt fails with an [`std::io::ErrorKind::TimedOut`]
+/// error and is always retried.
+pub async fn with_retry<T, E, F, Fut>(
+    retry: &NetworkRetry,
+    operation_name: &str,
+    is_transient: impl Fn(&E) -> bool,
+    operation: F,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: From<std::io::Error> + Display,
+{
+    let mut attempt = 1;
+    loop {
+        let (error, timed_out) = match tokio::time::timeout(retry.timeout, operation()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) => (e, false),
+            Err(elapsed) => (E::from(std::io::Error::from(elapsed)), true),
+        };
+        if attempt >= retry.attempts || !(timed_out || is_transient(&error)) {
+            return Err(error);
+        }
+
+        let delay = retry.backoff_delay(attempt);
+        warn!(
+            target: LOG_TARGET,
+            "'{}' failed (attempt {} of {}): {}. Retrying in {:?}.",
+            operation_name,
+            attempt,
+            retry.attempts,
+            error,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
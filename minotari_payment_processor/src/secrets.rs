@@ -0,0 +1,123 @@
+use anyhow::{Context, anyhow};
+use std::{fmt, process::Command, sync::Arc};
+use zeroize::Zeroizing;
+
+const VAULT_PREFIX: &str = "vault:";
+const AWS_SECRETS_MANAGER_PREFIX: &str = "aws-sm:";
+
+/// A secret held in memory that is overwritten when the last clone is dropped. `Debug` does not reveal it.
+#[derive(Clone, Default)]
+pub struct SecretString(Arc<Zeroizing<String>>);
+
+impl SecretString {
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(Arc::new(Zeroizing::new(secret)))
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+/// Asks for a secret on the controlling terminal without echoing it.
+pub fn prompt(message: &str) -> anyhow::Result<SecretString> {
+    let secret = Zeroizing::new(rpassword::prompt_password(message).context("Failed to read from the terminal")?);
+    if secret.is_empty() {
+        return Err(anyhow!("No value was entered"));
+    }
+    Ok(SecretString::from(secret.as_str().to_string()))
+}
+
+/// Resolves a secret setting, so it does not have to be passed in the process environment.
+///
+/// * If `file` is set (e.g. `CONSOLE_WALLET_PASSWORD_FILE`), the secret is read from that file, as mounted by
+///   Docker or Kubernetes secrets. A trailing newline is stripped. `value` is ignored in that case.
+/// * Otherwise `value` is used. It may reference a secret manager instead of holding the secret itself:
+///   * `vault:<path>#<field>` reads a field of a HashiCorp Vault KV secret with `vault kv get`.
+///   * `aws-sm:<secret-id>[#<key>]` reads an AWS Secrets Manager secret with `aws secretsmanager get-secret-value`.
+///     With `#<key>`, the secret must be a JSON object and the value of `key` is returned.
+///
+/// The `vault` and `aws` CLIs use their usual configuration (`VAULT_ADDR`, AWS profiles, instance roles, ...).
+pub fn resolve(name: &str, value: Option<String>, file: Option<String>) -> anyhow::Result<String> {
+    let value = match (value, file) {
+        (_, Some(file)) => {
+            let contents = Zeroizing::new(
+                std::fs::read_to_string(&file).with_context(|| format!("Failed to read {} from {}", name, file))?,
+            );
+            return Ok(contents.trim_end_matches(['\r', '\n']).to_string());
+        },
+        (Some(value), None) => value,
+        (None, None) => return Err(anyhow!("{} (or {}_file) must be set", name, name)),
+    };
+
+    if let Some(reference) = value.strip_prefix(VAULT_PREFIX) {
+        read_from_vault(reference).with_context(|| format!("Failed to read {} from Vault", name))
+    } else if let Some(reference) = value.strip_prefix(AWS_SECRETS_MANAGER_PREFIX) {
+        read_from_aws_secrets_manager(reference)
+            .with_context(|| format!("Failed to read {} from AWS Secrets Manager", name))
+    } else {
+        Ok(value)
+    }
+}
+
+fn read_from_vault(reference: &str) -> anyhow::Result<String> {
+    let (path, field) = reference
+        .split_once('#')
+        .ok_or_else(|| anyhow!("Expected 'vault:<path>#<field>', got 'vault:{}'", reference))?;
+
+    run_cli(Command::new("vault").args(["kv", "get", &format!("-field={}", field), path]))
+}
+
+fn read_from_aws_secrets_manager(reference: &str) -> anyhow::Result<String> {
+    let (secret_id, key) = match reference.split_once('#') {
+        Some((secret_id, key)) => (secret_id, Some(key)),
+        None => (reference, None),
+    };
+
+    let secret = run_cli(Command::new("aws").args([
+        "secretsmanager",
+        "get-secret-value",
+        "--secret-id",
+        secret_id,
+        "--query",
+        "SecretString",
+        "--output",
+        "text",
+    ]))?;
+
+    match key {
+        None => Ok(secret),
+        Some(key) => {
+            let json: serde_json::Value = serde_json::from_str(&secret).context("Secret is not a JSON object")?;
+            json.get(key)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| anyhow!("Secret has no string value for key '{}'", key))
+        },
+    }
+}
+
+fn run_cli(cmd: &mut Command) -> anyhow::Result<String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd.output().with_context(|| format!("Failed to run '{}'", program))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "'{}' exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8(output.stdout).with_context(|| format!("'{}' returned invalid UTF-8", program))?;
+    Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+}
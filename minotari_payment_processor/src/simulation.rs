@@ -0,0 +1,144 @@
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tari_transaction_components::rpc::models::TxLocation;
+use tokio::time;
+
+use crate::base_node::{KernelSignature, TipInfo, TxStatus, TxSubmission};
+use crate::config::SimulationConfig;
+use crate::db::payment_batch::TransactionStep;
+
+/// Signed payload of a step in simulation mode, stored in place of the console wallet's signed transaction.
+///
+/// Everything is derived from the unsigned transaction, so signing the same step again yields the same transaction.
+/// Simulated transactions pay no fee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedTransaction {
+    /// Hex encoded public nonce of the fake kernel excess signature.
+    pub excess_public_nonce: String,
+    /// Hex encoded fake kernel excess signature.
+    pub excess_signature: String,
+    /// Hex encoded made-up hashes of the outputs sent to the recipients, followed by those split off the change.
+    pub sent_hashes: Vec<String>,
+}
+
+impl SimulatedTransaction {
+    pub fn sign(step: &TransactionStep, unsigned_json: &str) -> Self {
+        let sent_count = step.expected.as_ref().map_or(step.payment_ids.len(), |expected| {
+            expected.recipients.len() + expected.change_outputs.len()
+        });
+        let unsigned = unsigned_json.as_bytes();
+        Self {
+            excess_public_nonce: hex::encode(digest(&[b"excess_public_nonce", unsigned])),
+            excess_signature: hex::encode(digest(&[b"excess_signature", unsigned])),
+            sent_hashes: (0..sent_count as u64)
+                .map(|i| hex::encode(digest(&[b"sent_hash", unsigned, &i.to_be_bytes()])))
+                .collect(),
+        }
+    }
+
+    pub fn kernel_signature(&self) -> anyhow::Result<KernelSignature> {
+        Ok((
+            hex::decode(&self.excess_public_nonce).context("Invalid simulated excess public nonce")?,
+            hex::decode(&self.excess_signature).context("Invalid simulated excess signature")?,
+        ))
+    }
+}
+
+/// Base node of simulation mode. It accepts every transaction after the broadcast delay and mines it in the first
+/// block after the mining delay.
+///
+/// The chain follows the wall clock: block `n` is mined `n` block times after the Unix epoch, so heights and
+/// confirmations stay consistent across restarts. Submitted transactions are only kept in memory; after a restart
+/// the confirmation checker resubmits the ones the node no longer knows.
+#[derive(Clone)]
+pub struct SimulatedBaseNode {
+    config: SimulationConfig,
+    /// Submission time of every accepted transaction, in Unix milliseconds.
+    submitted_at: Arc<Mutex<HashMap<KernelSignature, u64>>>,
+}
+
+impl SimulatedBaseNode {
+    pub fn new(config: SimulationConfig) -> Self {
+        Self {
+            config,
+            submitted_at: Arc::default(),
+        }
+    }
+
+    pub async fn submit(&self, kernel_signature: KernelSignature) -> anyhow::Result<TxSubmission> {
+        time::sleep(self.config.broadcast_delay).await;
+        self.submitted_at().entry(kernel_signature).or_insert_with(now_millis);
+        Ok(TxSubmission {
+            accepted: true,
+            rejection_reason: String::new(),
+        })
+    }
+
+    pub async fn transaction_query(&self, kernel_signature: KernelSignature) -> anyhow::Result<TxStatus> {
+        let Some(submitted_at) = self.submitted_at().get(&kernel_signature).copied() else {
+            return Ok(TxStatus {
+                location: TxLocation::NotStored,
+                mined_height: None,
+                mined_header_hash: None,
+                mined_timestamp: None,
+            });
+        };
+
+        let mineable_at = submitted_at.saturating_add(self.config.mining_delay.as_millis() as u64);
+        let mined_height = mineable_at.div_ceil(self.block_time_millis());
+        if mined_height > self.tip_height() {
+            return Ok(TxStatus {
+                location: TxLocation::InMempool,
+                mined_height: None,
+                mined_header_hash: None,
+                mined_timestamp: None,
+            });
+        }
+        Ok(TxStatus {
+            location: TxLocation::Mined,
+            mined_height: Some(mined_height),
+            mined_header_hash: Some(block_hash(mined_height)),
+            mined_timestamp: Some(mined_height * self.block_time_millis() / 1000),
+        })
+    }
+
+    pub async fn get_tip_info(&self) -> anyhow::Result<TipInfo> {
+        let height = self.tip_height();
+        Ok(TipInfo {
+            best_block_height: height,
+            best_block_hash: block_hash(height),
+        })
+    }
+
+    fn tip_height(&self) -> u64 {
+        now_millis() / self.block_time_millis()
+    }
+
+    fn block_time_millis(&self) -> u64 {
+        (self.config.block_time.as_millis() as u64).max(1)
+    }
+
+    fn submitted_at(&self) -> MutexGuard<'_, HashMap<KernelSignature, u64>> {
+        self.submitted_at.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn now_millis() -> u64 {
+    Utc::now().timestamp_millis().max(0) as u64
+}
+
+fn block_hash(height: u64) -> Vec<u8> {
+    digest(&[b"block", &height.to_be_bytes()]).to_vec()
+}
+
+fn digest(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
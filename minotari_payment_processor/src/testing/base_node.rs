@@ -0,0 +1,238 @@
+use anyhow::anyhow;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tari_transaction_components::rpc::models::TxLocation;
+use tari_transaction_components::transaction_components::Transaction;
+
+use crate::base_node::{
+    FailoverBaseNodeClient, KernelSignature, NodeClient, OutputSpendInfo, TipInfo, TxStatus, TxSubmission,
+    kernel_signature,
+};
+use crate::config::NetworkRetry;
+use crate::simulation::SimulatedTransaction;
+
+struct MockTransaction {
+    /// Hashes of the outputs the transaction spends.
+    inputs: Vec<Vec<u8>>,
+    mined_height: Option<u64>,
+}
+
+#[derive(Default)]
+struct MockBaseNodeState {
+    tip_height: u64,
+    transactions: HashMap<KernelSignature, MockTransaction>,
+    /// Height each spent output was spent at, keyed by output hash.
+    spent_outputs: HashMap<Vec<u8>, u64>,
+    submitted: Vec<Transaction>,
+    failures_left: usize,
+    rejection_reason: Option<String>,
+}
+
+/// In-memory base node. Submitted transactions wait in its mempool until the test mines a block. Like the simulated
+/// base node, it also accepts the transactions signed in simulation mode, so the lifecycle can be driven without a
+/// console wallet.
+///
+/// Clones share the same chain, so the test keeps one handle to drive the chain while the processor uses another.
+#[derive(Clone, Default)]
+pub struct MockBaseNode {
+    state: Arc<Mutex<MockBaseNodeState>>,
+}
+
+impl MockBaseNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A failover client whose only node is this mock.
+    pub fn client(&self, retry: NetworkRetry) -> FailoverBaseNodeClient {
+        FailoverBaseNodeClient::from_clients(
+            vec![("mock://base-node".to_string(), NodeClient::Mock(self.clone()))],
+            retry,
+        )
+        .expect("a single mock node is a valid configuration")
+    }
+
+    pub fn tip_height(&self) -> u64 {
+        self.state().tip_height
+    }
+
+    /// Every transaction submitted so far, including rejected ones, in submission order. Transactions signed in
+    /// simulation mode are not included.
+    pub fn submitted(&self) -> Vec<Transaction> {
+        self.state().submitted.clone()
+    }
+
+    /// The next `count` calls fail as if the node were unreachable.
+    pub fn fail_next_calls(&self, count: usize) {
+        self.state().failures_left = count;
+    }
+
+    /// Rejects submissions with `reason` until called with `None`.
+    pub fn reject_submissions(&self, reason: Option<&str>) {
+        self.state().rejection_reason = reason.map(str::to_string);
+    }
+
+    /// Mines a block with every transaction in the mempool and returns its height.
+    pub fn mine_block(&self) -> u64 {
+        let mut state = self.state();
+        state.tip_height += 1;
+        let height = state.tip_height;
+        let mut spent = Vec::new();
+        for transaction in state.transactions.values_mut() {
+            if transaction.mined_height.is_none() {
+                transaction.mined_height = Some(height);
+                spent.extend(transaction.inputs.iter().cloned());
+            }
+        }
+        state.spent_outputs.extend(spent.into_iter().map(|hash| (hash, height)));
+        height
+    }
+
+    /// Mines `count` blocks and returns the new tip height.
+    pub fn mine_blocks(&self, count: u64) -> u64 {
+        (0..count).fold(self.tip_height(), |_, _| self.mine_block())
+    }
+
+    /// Drops the unmined transactions, as if the mempool evicted them.
+    pub fn clear_mempool(&self) {
+        self.state()
+            .transactions
+            .retain(|_, transaction| transaction.mined_height.is_some());
+    }
+
+    /// Rolls the chain back to `height`. Transactions mined above it return to the mempool.
+    pub fn reorg_to(&self, height: u64) {
+        let mut state = self.state();
+        state.tip_height = height;
+        for transaction in state.transactions.values_mut() {
+            if transaction.mined_height.is_some_and(|mined| mined > height) {
+                transaction.mined_height = None;
+            }
+        }
+        state.spent_outputs.retain(|_, spent_at| *spent_at <= height);
+    }
+
+    /// Marks an output as spent at the current tip, e.g. to simulate funds moved outside the processor.
+    pub fn spend_output(&self, output_hash: Vec<u8>) {
+        let mut state = self.state();
+        let height = state.tip_height;
+        state.spent_outputs.insert(output_hash, height);
+    }
+
+    pub async fn submit_transaction(&self, tx: Transaction) -> anyhow::Result<TxSubmission> {
+        let mut state = self.state();
+        state.take_failure()?;
+        let key = kernel_signature(&tx)?;
+        let inputs = tx
+            .body
+            .inputs()
+            .iter()
+            .map(|input| input.output_hash().to_vec())
+            .collect();
+        state.submitted.push(tx);
+        Ok(state.accept(key, inputs))
+    }
+
+    /// Submits a transaction signed in simulation mode. It spends no outputs the mock knows of.
+    pub async fn submit_simulated_transaction(&self, tx: SimulatedTransaction) -> anyhow::Result<TxSubmission> {
+        let mut state = self.state();
+        state.take_failure()?;
+        Ok(state.accept(tx.kernel_signature()?, Vec::new()))
+    }
+
+    pub async fn transaction_query(
+        &self,
+        excess_public_nonce: Vec<u8>,
+        excess_sig: Vec<u8>,
+    ) -> anyhow::Result<TxStatus> {
+        let mut state = self.state();
+        state.take_failure()?;
+        let mined_height = state
+            .transactions
+            .get(&(excess_public_nonce, excess_sig))
+            .map(|transaction| transaction.mined_height);
+        Ok(match mined_height {
+            Some(Some(height)) => TxStatus {
+                location: TxLocation::Mined,
+                mined_height: Some(height),
+                mined_header_hash: Some(block_hash(height)),
+                mined_timestamp: Some(Utc::now().timestamp() as u64),
+            },
+            Some(None) => TxStatus {
+                location: TxLocation::InMempool,
+                mined_height: None,
+                mined_header_hash: None,
+                mined_timestamp: None,
+            },
+            None => TxStatus {
+                location: TxLocation::NotStored,
+                mined_height: None,
+                mined_header_hash: None,
+                mined_timestamp: None,
+            },
+        })
+    }
+
+    pub async fn get_tip_info(&self) -> anyhow::Result<TipInfo> {
+        let mut state = self.state();
+        state.take_failure()?;
+        Ok(TipInfo {
+            best_block_height: state.tip_height,
+            best_block_hash: block_hash(state.tip_height),
+        })
+    }
+
+    pub async fn spent_outputs(&self, output_hashes: Vec<Vec<u8>>) -> anyhow::Result<Option<Vec<OutputSpendInfo>>> {
+        let mut state = self.state();
+        state.take_failure()?;
+        Ok(Some(
+            output_hashes
+                .into_iter()
+                .map(|output_hash| OutputSpendInfo {
+                    spent_at_height: state.spent_outputs.get(&output_hash).copied(),
+                    output_hash,
+                })
+                .collect(),
+        ))
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockBaseNodeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MockBaseNodeState {
+    /// Adds the transaction to the mempool, unless submissions are being rejected.
+    fn accept(&mut self, key: KernelSignature, inputs: Vec<Vec<u8>>) -> TxSubmission {
+        if let Some(reason) = self.rejection_reason.clone() {
+            return TxSubmission {
+                accepted: false,
+                rejection_reason: reason,
+            };
+        }
+        self.transactions.entry(key).or_insert(MockTransaction {
+            inputs,
+            mined_height: None,
+        });
+        TxSubmission {
+            accepted: true,
+            rejection_reason: String::new(),
+        }
+    }
+
+    fn take_failure(&mut self) -> anyhow::Result<()> {
+        if self.failures_left > 0 {
+            self.failures_left -= 1;
+            return Err(anyhow!("Mock base node is unavailable"));
+        }
+        Ok(())
+    }
+}
+
+/// A stable, made-up hash for the block at `height`.
+fn block_hash(height: u64) -> Vec<u8> {
+    let mut hash = vec![0u8; 32];
+    hash[24..].copy_from_slice(&height.to_be_bytes());
+    hash
+}
@@ -0,0 +1,23 @@
+use sqlx::{
+    SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::str::FromStr;
+
+/// Opens a fresh in-memory database with all migrations applied.
+///
+/// Every connection to `sqlite::memory:` gets its own database, so the pool holds exactly one connection and never
+/// closes it. Each call returns an independent database.
+pub async fn memory_pool() -> Result<SqlitePool, anyhow::Error> {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")?;
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await?;
+
+    sqlx::migrate!("../migrations").run(&pool).await?;
+    Ok(pool)
+}
@@ -0,0 +1,16 @@
+//! In-process stand-ins for the external services, so the batch lifecycle can be exercised in `cargo test` without
+//! a base node, a Payment Receiver or a database file. Only compiled with the `testing` feature.
+//!
+//! A typical test opens [`db::memory_pool`], starts a [`payment_receiver::MockPaymentReceiver`] and passes its
+//! [`configuration`](payment_receiver::MockPaymentReceiver::configuration) and a [`base_node::MockBaseNode`]'s
+//! [`client`](base_node::MockBaseNode::client) to the worker functions, then drives the chain with
+//! [`mine_block`](base_node::MockBaseNode::mine_block). The mock base node also accepts the transactions of simulation
+//! mode, so a whole [`PaymentProcessor`](crate::processor::PaymentProcessor) can run its workers against the mocks;
+//! see `cargo test --features testing`.
+
+pub mod base_node;
+pub mod db;
+pub mod payment_receiver;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests;
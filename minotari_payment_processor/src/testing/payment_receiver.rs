@@ -0,0 +1,240 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use minotari_client::apis::configuration::Configuration;
+use minotari_client::models::{AccountBalance, LockFundsRequest, LockFundsResult};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// A UTXO the mock hands out when funds are locked. `output` is returned as is and must deserialize into a
+/// `WalletOutput` for the unsigned transaction creator to accept it.
+#[derive(Debug, Clone)]
+pub struct MockUtxo {
+    pub value: i64,
+    pub output: Value,
+}
+
+/// Funds locked under an idempotency key.
+#[derive(Debug, Clone)]
+pub struct MockLock {
+    pub account_name: String,
+    pub idempotency_key: Option<String>,
+    pub amount: i64,
+    pub utxos: Vec<MockUtxo>,
+    pub released: bool,
+}
+
+#[derive(Default)]
+struct MockPaymentReceiverState {
+    /// Unlocked UTXOs per lower-cased account name.
+    utxos: HashMap<String, Vec<MockUtxo>>,
+    locks: Vec<MockLock>,
+    failures_left: usize,
+}
+
+/// Payment Receiver API served over HTTP on a local port, so the generated client and the processor's own requests
+/// go through the same code paths as in production.
+///
+/// Each account's balance is the sum of the UTXOs added to it. Locking takes UTXOs until the amount is covered and
+/// returns the same lock for a repeated idempotency key; releasing returns them. The server stops when the mock is
+/// dropped.
+pub struct MockPaymentReceiver {
+    state: Arc<Mutex<MockPaymentReceiverState>>,
+    base_path: String,
+    shutdown: CancellationToken,
+}
+
+impl MockPaymentReceiver {
+    pub async fn start() -> Result<Self, anyhow::Error> {
+        let state = Arc::new(Mutex::new(MockPaymentReceiverState::default()));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_path = format!("http://{}", listener.local_addr()?);
+        let shutdown = CancellationToken::new();
+
+        let app = Router::new()
+            .route("/accounts/{name}/balance", get(get_balance))
+            .route("/accounts/{name}/lock_funds", post(lock_funds))
+            .route("/accounts/{name}/release_funds", post(release_funds))
+            .with_state(state.clone());
+        let server_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async move { server_shutdown.cancelled().await })
+                .await
+        });
+
+        Ok(Self {
+            state,
+            base_path,
+            shutdown,
+        })
+    }
+
+    /// Client configuration pointing at the mock.
+    pub fn configuration(&self) -> Configuration {
+        Configuration {
+            base_path: self.base_path.clone(),
+            ..Configuration::default()
+        }
+    }
+
+    /// Adds unlocked UTXOs to the account.
+    pub fn add_utxos(&self, account_name: &str, utxos: Vec<MockUtxo>) {
+        lock(&self.state)
+            .utxos
+            .entry(account_name.to_lowercase())
+            .or_default()
+            .extend(utxos);
+    }
+
+    /// The next `count` requests fail with `503 Service Unavailable`.
+    pub fn fail_next_requests(&self, count: usize) {
+        lock(&self.state).failures_left = count;
+    }
+
+    /// Every lock made so far, in order, including released ones.
+    pub fn locks(&self) -> Vec<MockLock> {
+        lock(&self.state).locks.clone()
+    }
+}
+
+impl Drop for MockPaymentReceiver {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+type SharedState = Arc<Mutex<MockPaymentReceiverState>>;
+
+fn lock(state: &SharedState) -> MutexGuard<'_, MockPaymentReceiverState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl MockPaymentReceiverState {
+    fn take_failure(&mut self) -> Option<Response> {
+        if self.failures_left == 0 {
+            return None;
+        }
+        self.failures_left -= 1;
+        Some((StatusCode::SERVICE_UNAVAILABLE, "Mock Payment Receiver is unavailable").into_response())
+    }
+}
+
+async fn get_balance(State(state): State<SharedState>, Path(name): Path<String>) -> Response {
+    let mut state = lock(&state);
+    if let Some(response) = state.take_failure() {
+        return response;
+    }
+    let account_name = name.to_lowercase();
+    let available: i64 = state
+        .utxos
+        .get(&account_name)
+        .map(|utxos| utxos.iter().map(|utxo| utxo.value).sum())
+        .unwrap_or(0);
+    let locked: i64 = state
+        .locks
+        .iter()
+        .filter(|lock| !lock.released && lock.account_name == account_name)
+        .flat_map(|lock| lock.utxos.iter().map(|utxo| utxo.value))
+        .sum();
+    Json(AccountBalance::new(available, locked, available + locked, 0)).into_response()
+}
+
+async fn lock_funds(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    Json(request): Json<LockFundsRequest>,
+) -> Response {
+    let mut state = lock(&state);
+    if let Some(response) = state.take_failure() {
+        return response;
+    }
+    let account_name = name.to_lowercase();
+    let idempotency_key = request.idempotency_key.flatten();
+    if let Some(existing) = state
+        .locks
+        .iter()
+        .find(|lock| idempotency_key.is_some() && lock.idempotency_key == idempotency_key && !lock.released)
+    {
+        return Json(lock_result(existing)).into_response();
+    }
+
+    let available = state.utxos.entry(account_name.clone()).or_default();
+    let mut taken = Vec::new();
+    let mut total = 0i64;
+    while total < request.amount
+        && let Some(utxo) = available.pop()
+    {
+        total += utxo.value;
+        taken.push(utxo);
+    }
+    if total < request.amount {
+        available.extend(taken.into_iter().rev());
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Insufficient funds: {} µT available, {} µT requested",
+                total, request.amount
+            ),
+        )
+            .into_response();
+    }
+
+    let new_lock = MockLock {
+        account_name,
+        idempotency_key,
+        amount: request.amount,
+        utxos: taken,
+        released: false,
+    };
+    let result = lock_result(&new_lock);
+    state.locks.push(new_lock);
+    Json(result).into_response()
+}
+
+#[derive(Deserialize)]
+struct ReleaseFundsRequest {
+    idempotency_key: String,
+}
+
+async fn release_funds(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    Json(request): Json<ReleaseFundsRequest>,
+) -> Response {
+    let mut state = lock(&state);
+    if let Some(response) = state.take_failure() {
+        return response;
+    }
+    let account_name = name.to_lowercase();
+    let Some(released) = state.locks.iter_mut().find(|lock| {
+        !lock.released
+            && lock.account_name == account_name
+            && lock.idempotency_key.as_deref() == Some(request.idempotency_key.as_str())
+    }) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    released.released = true;
+    let utxos = released.utxos.clone();
+    state.utxos.entry(account_name).or_default().extend(utxos);
+    StatusCode::OK.into_response()
+}
+
+fn lock_result(lock: &MockLock) -> LockFundsResult {
+    let total_value = lock.utxos.iter().map(|utxo| utxo.value).sum();
+    LockFundsResult::new(
+        0,
+        0,
+        total_value > lock.amount,
+        total_value,
+        lock.utxos.iter().map(|utxo| utxo.output.clone()).collect(),
+    )
+}
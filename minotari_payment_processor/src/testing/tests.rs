@@ -0,0 +1,168 @@
+use anyhow::Context;
+use std::io::Write;
+use tari_common::configuration::Network;
+use tari_common_types::types::CompressedPublicKey;
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_script::{ExecutionStack, TariScript};
+use tari_transaction_components::key_manager::{
+    KeyManager,
+    wallet_types::{ViewWallet, WalletType},
+};
+use tari_transaction_components::tari_amount::MicroMinotari;
+use tari_transaction_components::transaction_components::{OutputFeatures, WalletOutput, WalletOutputBuilder};
+use tari_utilities::ByteArray;
+use tokio::time::{Duration, Instant};
+
+use crate::amount::PaymentAmount;
+use crate::config::{PaymentProcessorEnv, derive_address};
+use crate::db::payment::{Payment, PaymentPriority, PaymentStatus};
+use crate::processor::PaymentProcessor;
+use crate::testing::base_node::MockBaseNode;
+use crate::testing::db::memory_pool;
+use crate::testing::payment_receiver::{MockPaymentReceiver, MockUtxo};
+
+const ACCOUNT: &str = "default";
+const UTXO_VALUE: u64 = 5_000_000;
+const REQUIRED_CONFIRMATIONS: u64 = 2;
+
+/// Secret key with the canonical little-endian encoding of `value`.
+fn secret_key(value: u8) -> RistrettoSecretKey {
+    let mut bytes = [0u8; 32];
+    bytes[0] = value;
+    RistrettoSecretKey::from_canonical_bytes(&bytes).expect("small scalars are canonical")
+}
+
+fn public_key(secret: &RistrettoSecretKey) -> CompressedPublicKey {
+    CompressedPublicKey::new_from_pk(RistrettoPublicKey::from_secret_key(secret))
+}
+
+/// An output of `value` owned by the view wallet, in the form the Payment Receiver returns it when funds are locked.
+fn wallet_output(key_manager: &KeyManager, value: u64) -> anyhow::Result<WalletOutput> {
+    let commitment_mask = key_manager.get_next_commitment_mask_and_script_key()?;
+    let sender_offset = key_manager.get_random_key()?;
+    let output = WalletOutputBuilder::new(MicroMinotari(value), commitment_mask.key_id.clone())
+        .with_features(OutputFeatures::default())
+        .with_script(TariScript::default())
+        .with_input_data(ExecutionStack::default())
+        .with_sender_offset_public_key(sender_offset.pub_key)
+        .with_script_key(commitment_mask.key_id)
+        .sign_as_sender_and_receiver(key_manager, &sender_offset.key_id)?
+        .try_build(key_manager)?;
+    Ok(output)
+}
+
+/// Writes the configuration of a simulation mode processor running the lifecycle workers against the mocks.
+fn write_config(
+    payment_receiver: &str,
+    view_key: &RistrettoSecretKey,
+    public_spend_key: &CompressedPublicKey,
+) -> anyhow::Result<tempfile::NamedTempFile> {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile()?;
+    write!(
+        file,
+        r#"
+tari_network = "esmeralda"
+database_url = "sqlite::memory:"
+payment_receiver = "{payment_receiver}"
+base_node = "http://127.0.0.1:1"
+console_wallet_path = "unused"
+console_wallet_base_path = "unused"
+console_wallet_password = "unused"
+simulation_mode = true
+run_api = false
+workers = "batch_creator,unsigned_tx_creator,transaction_signer,broadcaster,confirmation_checker,chain_state"
+batch_creator_sleep_secs = 1
+unsigned_tx_creator_sleep_secs = 1
+transaction_signer_sleep_secs = 1
+broadcaster_sleep_secs = 1
+confirmation_checker_sleep_secs = 1
+chain_state_sleep_secs = 1
+
+[accounts.{ACCOUNT}]
+name = "{ACCOUNT}"
+view_key = "{view_key}"
+public_spend_key = "{public_spend_key}"
+required_confirmations = {REQUIRED_CONFIRMATIONS}
+"#,
+        view_key = hex::encode(view_key.as_bytes()),
+        public_spend_key = hex::encode(public_spend_key.as_bytes()),
+    )?;
+    Ok(file)
+}
+
+#[tokio::test]
+async fn payment_is_confirmed_through_the_workers() -> anyhow::Result<()> {
+    let view_key = secret_key(5);
+    let public_spend_key = public_key(&secret_key(7));
+    let recipient = derive_address(&secret_key(11), &public_key(&secret_key(13)), Network::Esmeralda)?;
+
+    let payment_receiver = MockPaymentReceiver::start().await?;
+    let key_manager = KeyManager::new(WalletType::ViewWallet(ViewWallet::new(
+        public_spend_key.clone(),
+        view_key.clone(),
+        None,
+    )))?;
+    let utxos = (0..2)
+        .map(|_| {
+            Ok(MockUtxo {
+                value: UTXO_VALUE as i64,
+                output: serde_json::to_value(wallet_output(&key_manager, UTXO_VALUE)?)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    payment_receiver.add_utxos(ACCOUNT, utxos);
+
+    let config = write_config(
+        &payment_receiver.configuration().base_path,
+        &view_key,
+        &public_spend_key,
+    )?;
+    let env = PaymentProcessorEnv::load(Some(config.path()))?;
+    let network_retry = env.network_retry;
+    let base_node = MockBaseNode::new();
+    let db_pool = memory_pool().await?;
+
+    let mut processor = PaymentProcessor::builder(env)
+        .db_pool(db_pool.clone())
+        .payment_receiver(payment_receiver.configuration())
+        .base_node_client(base_node.client(network_retry))
+        .build()
+        .await?;
+    processor.start_workers()?;
+
+    let payment = Payment::create(
+        &mut *db_pool.acquire().await?,
+        "lifecycle-test",
+        ACCOUNT,
+        &recipient.to_base58(),
+        PaymentAmount::try_from(1_000_000u64)?,
+        None,
+        None,
+        None,
+        PaymentPriority::Normal,
+    )
+    .await?;
+
+    // Mine a block on every poll, so the transaction is mined once broadcast and then gains its confirmations.
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let status = loop {
+        let current = Payment::get_by_id(&mut *db_pool.acquire().await?, &payment.id)
+            .await?
+            .context("Payment disappeared")?;
+        if matches!(current.status, PaymentStatus::Confirmed) || Instant::now() >= deadline {
+            break current.status;
+        }
+        base_node.mine_block();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    };
+    processor.shutdown().await;
+
+    assert!(
+        matches!(status, PaymentStatus::Confirmed),
+        "payment ended up {status:?}"
+    );
+    assert_eq!(payment_receiver.locks().len(), 1);
+    assert!(base_node.tip_height() >= REQUIRED_CONFIRMATIONS);
+    Ok(())
+}
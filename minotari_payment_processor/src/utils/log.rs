@@ -0,0 +1,23 @@
+/// Number of characters `mask_string` keeps at each end.
+const VISIBLE_CHARS: usize = 4;
+
+/// Masks the middle of a value for logging, e.g. `f4Fo...8a2C`. Values too short to keep both ends are
+/// masked entirely.
+pub fn mask_string(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= VISIBLE_CHARS * 2 {
+        return "***".to_string();
+    }
+
+    let head: String = chars[..VISIBLE_CHARS].iter().collect();
+    let tail: String = chars[chars.len() - VISIBLE_CHARS..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+/// Reduces an amount to its order of magnitude for logging, e.g. `~10^6` for 2_500_000.
+pub fn mask_amount(amount: u64) -> String {
+    match amount.checked_ilog10() {
+        Some(magnitude) => format!("~10^{}", magnitude),
+        None => "0".to_string(),
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::anyhow;
+use log::{error, info, warn};
+use minotari_client::apis::configuration::Configuration;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{NetworkRetry, PaymentReceiverAccount, SharedAccounts};
+use crate::db::event::{Event, EventType};
+use crate::db::payment::Payment;
+use crate::payment_receiver;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::balance_monitor";
+
+const DEFAULT_SLEEP_SECS: u64 = 5 * 60;
+
+/// Periodically checks whether the available balance the Payment Receiver reports for every account covers the
+/// account's payments that are still waiting for funds, plus one fee buffer and `reserve` µT. Otherwise their
+/// batches would wait in PENDING_BATCHING until the wallet is topped up, so a LOW_BALANCE event is recorded.
+///
+/// The event is recorded once when an account runs low, and again only after its balance recovered in between.
+pub struct BalanceMonitor {
+    db_pool: SqlitePool,
+    client_config: Arc<Configuration>,
+    network_retry: NetworkRetry,
+    accounts: SharedAccounts,
+    sleep_secs: u64,
+    reserve: i64,
+    /// Accounts a LOW_BALANCE event was recorded for that have not recovered since.
+    low_accounts: HashSet<String>,
+}
+
+impl BalanceMonitor {
+    pub fn new(
+        db_pool: SqlitePool,
+        client_config: Arc<Configuration>,
+        network_retry: NetworkRetry,
+        accounts: SharedAccounts,
+        sleep_secs: Option<u64>,
+        reserve: Option<i64>,
+    ) -> Self {
+        Self {
+            db_pool,
+            client_config,
+            network_retry,
+            accounts,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            reserve: reserve.unwrap_or(0).max(0),
+            low_accounts: HashSet::new(),
+        }
+    }
+}
+
+impl Worker for BalanceMonitor {
+    const NAME: &'static str = "balance_monitor";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Balance Monitor worker started. Checking every {} seconds. Reserve: {} µT.",
+            self.sleep_secs, self.reserve
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        for account in self.accounts.current().values() {
+            cycle.add_items(1);
+            let check = match check_account(
+                &self.db_pool,
+                &self.client_config,
+                &self.network_retry,
+                account,
+                self.reserve,
+            )
+            .await
+            {
+                Ok(check) => check,
+                Err(e) => {
+                    error!(
+                        target: LOG_TARGET,
+                        "Balance check of account '{}' failed: {:?}",
+                        account.name, e
+                    );
+                    cycle.fail(&e);
+                    continue;
+                },
+            };
+
+            if !check.is_low() {
+                if self.low_accounts.remove(&account.name) {
+                    info!(target: LOG_TARGET, "Balance of account '{}' covers its pending payments again.", account.name);
+                }
+                continue;
+            }
+            if self.low_accounts.contains(&account.name) {
+                continue;
+            }
+            warn!(
+                target: LOG_TARGET,
+                "Account '{}' is low on funds. Available: {} µT, required: {} µT for {} pending payments of {} µT.",
+                account.name, check.available, check.required, check.payment_count, check.pending_amount
+            );
+            match record_low_balance(&self.db_pool, account, &check, self.reserve).await {
+                Ok(()) => {
+                    self.low_accounts.insert(account.name.clone());
+                },
+                Err(e) => {
+                    error!(target: LOG_TARGET, "Failed to record low balance of account '{}': {:?}", account.name, e);
+                    cycle.fail(&e);
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The balance of an account compared to what its pending payments need, in µT.
+struct BalanceCheck {
+    available: i64,
+    required: i64,
+    payment_count: i64,
+    pending_amount: i64,
+}
+
+impl BalanceCheck {
+    fn is_low(&self) -> bool {
+        self.available < self.required
+    }
+}
+
+async fn check_account(
+    db_pool: &SqlitePool,
+    client_config: &Configuration,
+    network_retry: &NetworkRetry,
+    account: &PaymentReceiverAccount,
+    reserve: i64,
+) -> Result<BalanceCheck, anyhow::Error> {
+    let unfunded = {
+        let mut conn = db_pool.acquire().await?;
+        Payment::unfunded_summary(&mut conn, &account.name).await?
+    };
+
+    let balance = payment_receiver::get_balance(client_config, network_retry, &account.name)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch balance from Payment Receiver: {}", e))?;
+
+    // Like the unsigned transaction creator, payments are only funded with the fee buffer on top.
+    let fee_buffer = if unfunded.payment_count > 0 {
+        account.fee_buffer_amount
+    } else {
+        0
+    };
+    Ok(BalanceCheck {
+        available: balance.available,
+        required: unfunded.total_amount.saturating_add(fee_buffer).saturating_add(reserve),
+        payment_count: unfunded.payment_count,
+        pending_amount: unfunded.total_amount,
+    })
+}
+
+async fn record_low_balance(
+    db_pool: &SqlitePool,
+    account: &PaymentReceiverAccount,
+    check: &BalanceCheck,
+    reserve: i64,
+) -> Result<(), anyhow::Error> {
+    let mut conn = db_pool.acquire().await?;
+    Event::record(
+        &mut conn,
+        EventType::LowBalance,
+        &account.name,
+        json!({
+            "account_name": account.name,
+            "available": check.available,
+            "required": check.required,
+            "pending_payment_count": check.payment_count,
+            "pending_amount": check.pending_amount,
+            "reserve": reserve,
+            "shortfall": check.required - check.available,
+        }),
+    )
+    .await?;
+    Ok(())
+}
@@ -1,79 +1,191 @@
 use anyhow::Context;
+use chrono::Utc;
+use log::{debug, error, info};
 use sqlx::SqlitePool;
 use std::collections::HashMap;
-use tokio::time::{self, Duration};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::MAX_BATCH_SIZE;
-use crate::db::{payment::Payment, payment_batch::PaymentBatch};
+use crate::config::{BatchingWindow, PaymentReceiverAccount, SharedAccounts};
+use crate::db::{audit_log::SYSTEM_ACTOR, payment::Payment, payment_batch::PaymentBatch, setting::ProcessingPause};
+use crate::workers::supervisor::Cycle;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
 
-const DEFAULT_SLEEP_SECS: u64 = 10 * 60; // 10 minutes
+const LOG_TARGET: &str = "minotari_payment_processor::workers::batch_creator";
 
-pub async fn run(db_pool: SqlitePool, sleep_secs: Option<u64>) {
-    let sleep_duration = Duration::from_secs(sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS));
+const DEFAULT_SLEEP_SECS: u64 = 10 * 60; // 10 minutes
 
-    println!("Batch Creator worker started. Cycle interval: {:?}.", sleep_duration);
+/// `wakeups.batch_creator` starts the next cycle early, e.g. when a high-priority payment arrives. New batches wake
+/// the unsigned transaction creator. With `dedicated_high_priority_batches`, HIGH priority payments are never mixed
+/// with other payments of the same account and are batched right away; other payments are held back until
+/// `batching_window` is ready.
+pub struct BatchCreator {
+    db_pool: SqlitePool,
+    sleep_duration: Duration,
+    dedicated_high_priority_batches: bool,
+    batching_window: BatchingWindow,
+    accounts: SharedAccounts,
+    default_required_confirmations: u64,
+    wakeups: WorkerWakeups,
+}
 
-    loop {
-        match process_payment_cycle(&db_pool).await {
-            Ok(more_batches_expected) => {
-                if !more_batches_expected {
-                    time::sleep(sleep_duration).await;
-                } else {
-                    println!("INFO: Max batch size reached. Continuing to next cycle immediately.");
-                }
-            },
-            Err(e) => {
-                eprintln!("Batch Creator worker critical error: {:?}. Sleeping...", e);
-                time::sleep(sleep_duration).await;
-            },
+impl BatchCreator {
+    pub fn new(
+        db_pool: SqlitePool,
+        sleep_secs: Option<u64>,
+        dedicated_high_priority_batches: bool,
+        batching_window: BatchingWindow,
+        accounts: SharedAccounts,
+        default_required_confirmations: u64,
+        wakeups: WorkerWakeups,
+    ) -> Self {
+        Self {
+            db_pool,
+            sleep_duration: Duration::from_secs(sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS)),
+            dedicated_high_priority_batches,
+            batching_window,
+            accounts,
+            default_required_confirmations,
+            wakeups,
         }
     }
 }
 
-async fn process_payment_cycle(db_pool: &SqlitePool) -> Result<bool, anyhow::Error> {
-    let mut conn = db_pool.acquire().await.context("Failed to acquire DB connection")?;
+impl Worker for BatchCreator {
+    const NAME: &'static str = "batch_creator";
 
-    let limit = MAX_BATCH_SIZE as i64;
-    let payments = Payment::find_receivable_payments(&mut conn, limit)
-        .await
-        .context("Failed to find receivable payments")?;
+    fn interval(&self) -> Duration {
+        self.sleep_duration
+    }
 
-    let payments_count = payments.len();
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        Some(self.wakeups.batch_creator.clone())
+    }
 
-    if payments.is_empty() {
-        return Ok(false);
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Batch Creator worker started. Cycle interval: {:?}. Dedicated high-priority batches: {}. Minimum batch \
+             size: {} (max wait {:?}).",
+            self.sleep_duration,
+            self.dedicated_high_priority_batches,
+            self.batching_window.min_batch_size,
+            self.batching_window.max_wait
+        );
     }
 
-    println!("INFO: Found {} receivable payments to process.", payments_count);
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let (batched_count, more_waiting) = process_payment_cycle(
+            &self.db_pool,
+            self.dedicated_high_priority_batches,
+            &self.batching_window,
+            &self.accounts.current(),
+            self.default_required_confirmations,
+            &self.wakeups,
+        )
+        .await?;
+        cycle.add_items(batched_count);
+
+        if more_waiting {
+            info!(target: LOG_TARGET, "Max batch size reached. Continuing to next cycle immediately.");
+            self.wakeups.batch_creator.notify_one();
+        }
+        Ok(())
+    }
+}
 
-    let mut payments_by_account: HashMap<String, Vec<Payment>> = HashMap::new();
-    for payment in payments {
-        payments_by_account
-            .entry(payment.account_name.clone())
-            .or_default()
-            .push(payment);
+/// Batches the receivable payments of every account whose batching window is ready, up to `MAX_BATCH_SIZE` per
+/// batch. Accounts that are held back don't take up room of the others. Returns the number of batched payments and
+/// whether a full batch left more payments waiting.
+async fn process_payment_cycle(
+    db_pool: &SqlitePool,
+    dedicated_high_priority_batches: bool,
+    batching_window: &BatchingWindow,
+    accounts: &HashMap<String, PaymentReceiverAccount>,
+    default_required_confirmations: u64,
+    wakeups: &WorkerWakeups,
+) -> Result<(usize, bool), anyhow::Error> {
+    let mut conn = db_pool.acquire().await.context("Failed to acquire DB connection")?;
+
+    let pause = ProcessingPause::load(&mut conn)
+        .await
+        .context("Failed to load the pause state")?;
+    if pause.global {
+        info!(target: LOG_TARGET, "Processing is paused. Not creating batches.");
+        return Ok((0, false));
     }
 
-    for (account_name, account_payments) in payments_by_account {
-        println!(
-            "INFO: Processing group for account '{}' with {} payments.",
+    let groups = Payment::find_receivable_groups(&mut conn, &pause.accounts, dedicated_high_priority_batches)
+        .await
+        .context("Failed to find receivable payments")?;
+    drop(conn);
+
+    let now = Utc::now();
+    let mut batched_count = 0;
+    let mut more_waiting = false;
+    for group in groups {
+        let payment_count = usize::try_from(group.payment_count).unwrap_or_default();
+        if !group.dedicated && !batching_window.is_ready(payment_count, group.oldest_created_at, now) {
+            debug!(
+                target: LOG_TARGET,
+                "Holding back {} payments of account '{}' until {} have accumulated or the oldest has waited {:?}.",
+                payment_count,
+                group.account_name,
+                batching_window.min_batch_size,
+                batching_window.max_wait
+            );
+            continue;
+        }
+
+        let account_payments = Payment::find_receivable_payments(
+            &mut *db_pool.acquire().await?,
+            &group,
+            dedicated_high_priority_batches,
+            MAX_BATCH_SIZE as i64,
+        )
+        .await
+        .context("Failed to find receivable payments")?;
+        if account_payments.is_empty() {
+            continue;
+        }
+
+        let account_name = &group.account_name;
+        info!(
+            target: LOG_TARGET,
+            "Processing {}group for account '{}' with {} payments.",
+            if group.dedicated { "high-priority " } else { "" },
             account_name,
             account_payments.len()
         );
 
-        if let Err(e) = process_account_batch(db_pool, &account_name, &account_payments).await {
-            eprintln!("Failed to create batch for account '{}': {:?}", account_name, e);
+        let required_confirmations = accounts
+            .get(&account_name.to_lowercase())
+            .map(|account| account.required_confirmations)
+            .unwrap_or(default_required_confirmations);
+
+        match process_account_batch(db_pool, account_name, &account_payments, required_confirmations).await {
+            Ok(()) => {
+                batched_count += account_payments.len();
+                more_waiting |= payment_count > account_payments.len();
+                wakeups.unsigned_tx_creator.notify_one();
+            },
+            Err(e) => error!(target: LOG_TARGET, "Failed to create batch for account '{}': {:?}", account_name, e),
         }
     }
 
-    Ok(payments_count == MAX_BATCH_SIZE)
+    Ok((batched_count, more_waiting))
 }
 
 async fn process_account_batch(
     db_pool: &SqlitePool,
     account_name: &str,
     payments: &[Payment],
+    required_confirmations: u64,
 ) -> Result<(), anyhow::Error> {
     if payments.is_empty() {
         return Ok(());
@@ -82,8 +194,9 @@ async fn process_account_batch(
     let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
     let pr_idempotency_key = Uuid::new_v4().to_string();
 
-    println!(
-        "INFO: Creating batch for Account: '{}'. Idempotency Key: {}. Payment Count: {}",
+    info!(
+        target: LOG_TARGET,
+        "Creating batch for Account: '{}'. Idempotency Key: {}. Payment Count: {}",
         account_name,
         pr_idempotency_key,
         payments.len()
@@ -91,13 +204,21 @@ async fn process_account_batch(
 
     let mut tx = db_pool.begin().await.context("Failed to start transaction")?;
 
-    PaymentBatch::create_with_payments(&mut tx, account_name, &pr_idempotency_key, &payment_ids)
-        .await
-        .with_context(|| format!("Failed to create batch entry for account {}", account_name))?;
+    PaymentBatch::create_with_payments(
+        &mut tx,
+        SYSTEM_ACTOR,
+        account_name,
+        &pr_idempotency_key,
+        &payment_ids,
+        None,
+        required_confirmations,
+    )
+    .await
+    .with_context(|| format!("Failed to create batch entry for account {}", account_name))?;
 
     tx.commit().await.context("Failed to commit batch transaction")?;
 
-    println!("INFO: Successfully committed batch for Account: '{}'.", account_name);
+    info!(target: LOG_TARGET, "Successfully committed batch for Account: '{}'.", account_name);
 
     Ok(())
 }
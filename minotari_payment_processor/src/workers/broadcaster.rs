@@ -1,80 +1,198 @@
 use anyhow::{Context, anyhow};
-use minotari_node_wallet_client::{BaseNodeWalletClient, http::Client};
-use sqlx::{SqliteConnection, SqlitePool};
+use log::{debug, error, info, warn};
+use sqlx::SqlitePool;
+use std::sync::Arc;
 use tari_transaction_components::rpc::models::TxLocation;
-use tari_transaction_components::{
-    offline_signing::models::SignedOneSidedTransactionResult, transaction_components::Transaction,
-};
-use tari_utilities::ByteArray;
-use tari_utilities::message_format::MessageFormat;
-use tokio::time::{self, Duration};
-
-use crate::db::payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus, StepPayload};
+use tari_utilities::hex::Hex;
+use tokio::sync::Notify;
+use tokio::time::{self, Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::amount::PaymentAmount;
+use crate::base_node::{FailoverBaseNodeClient, NodeClient, TxStatus, TxSubmission};
+use crate::config::{MempoolCheck, RetryPolicies, SharedAccounts};
+use crate::db::payment::Payment;
+use crate::db::payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus, StepTransaction};
+use crate::workers::supervisor::Cycle;
+use crate::workers::tx_validation;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::broadcaster";
 
 const DEFAULT_SLEEP_SECS: u64 = 15;
-const MEMPOOL_CHECK_RETRIES: usize = 10;
-const MEMPOOL_CHECK_DELAY: Duration = Duration::from_secs(2);
-
-pub async fn run(db_pool: SqlitePool, base_node_client: Client, sleep_secs: Option<u64>) {
-    let sleep_secs = sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS);
-    println!(
-        "Transaction Broadcaster worker started. Polling every {} seconds.",
-        sleep_secs
-    );
 
-    let mut interval = time::interval(Duration::from_secs(sleep_secs));
+pub struct Broadcaster {
+    db_pool: SqlitePool,
+    base_node_client: FailoverBaseNodeClient,
+    accounts: SharedAccounts,
+    sleep_secs: u64,
+    retry_policies: RetryPolicies,
+    max_transaction_fee: u64,
+    broadcast_to_all_base_nodes: bool,
+    mempool_check: MempoolCheck,
+    batches_per_cycle: i64,
+    wakeups: WorkerWakeups,
+}
 
-    loop {
-        interval.tick().await;
-        if let Err(e) = process_transactions_to_broadcast(&db_pool, &base_node_client).await {
-            eprintln!("Transaction Broadcaster worker error: {:?}", e);
+impl Broadcaster {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: SqlitePool,
+        base_node_client: FailoverBaseNodeClient,
+        accounts: SharedAccounts,
+        sleep_secs: Option<u64>,
+        retry_policies: RetryPolicies,
+        max_transaction_fee: u64,
+        broadcast_to_all_base_nodes: bool,
+        mempool_check: MempoolCheck,
+        batches_per_cycle: i64,
+        wakeups: WorkerWakeups,
+    ) -> Self {
+        Self {
+            db_pool,
+            base_node_client,
+            accounts,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            retry_policies,
+            max_transaction_fee,
+            broadcast_to_all_base_nodes,
+            mempool_check,
+            batches_per_cycle,
+            wakeups,
         }
     }
 }
 
+impl Worker for Broadcaster {
+    const NAME: &'static str = "broadcaster";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        Some(self.wakeups.broadcaster.clone())
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Transaction Broadcaster worker started. Polling every {} seconds.",
+            self.sleep_secs
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let count = process_transactions_to_broadcast(
+            &self.db_pool,
+            &self.base_node_client,
+            &self.accounts,
+            &self.retry_policies,
+            self.max_transaction_fee,
+            self.broadcast_to_all_base_nodes,
+            self.mempool_check,
+            self.batches_per_cycle,
+            &self.wakeups,
+            shutdown,
+        )
+        .await?;
+        cycle.add_items(count);
+        Ok(())
+    }
+}
+
 async fn process_transactions_to_broadcast(
     db_pool: &SqlitePool,
-    base_node_client: &Client,
-) -> Result<(), anyhow::Error> {
-    let mut conn = db_pool.acquire().await?;
-
-    let batches = PaymentBatch::find_by_status(&mut conn, PaymentBatchStatus::AwaitingBroadcast).await?;
+    base_node_client: &FailoverBaseNodeClient,
+    accounts: &SharedAccounts,
+    retry_policies: &RetryPolicies,
+    max_transaction_fee: u64,
+    broadcast_to_all_base_nodes: bool,
+    mempool_check: MempoolCheck,
+    batches_per_cycle: i64,
+    wakeups: &WorkerWakeups,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let batches = PaymentBatch::find_by_status_fair(
+        &mut *db_pool.acquire().await?,
+        PaymentBatchStatus::AwaitingBroadcast,
+        batches_per_cycle,
+        &[],
+    )
+    .await?;
+    // A full cycle means more batches are probably waiting, so run again right away instead of sleeping.
+    if batches.len() as i64 >= batches_per_cycle {
+        wakeups.broadcaster.notify_one();
+    }
 
     if !batches.is_empty() {
-        println!("INFO: Found {} batches awaiting broadcast.", batches.len());
+        info!(target: LOG_TARGET, "Found {} batches awaiting broadcast.", batches.len());
     }
+    let batch_count = batches.len();
 
     for batch in batches {
-        if let Err(e) = process_single_batch(&mut conn, base_node_client, &batch).await {
+        if shutdown.is_cancelled() {
+            info!(target: LOG_TARGET, "Shutdown requested. Leaving remaining batches for the next run.");
+            break;
+        }
+
+        if let Err(e) = process_single_batch(
+            db_pool,
+            base_node_client,
+            accounts,
+            max_transaction_fee,
+            broadcast_to_all_base_nodes,
+            mempool_check,
+            wakeups,
+            &batch,
+        )
+        .await
+        {
             let error_message = e.to_string();
-            eprintln!(
+            error!(
+                target: LOG_TARGET,
                 "Error broadcasting batch {}: {}. Attempting to revert status...",
                 batch.id, error_message
             );
 
-            match PaymentBatch::update_to_awaiting_broadcast_for_retry(&mut conn, &batch.id).await {
-                Ok(_) => println!("INFO: Batch {} reverted to 'AwaitingBroadcast'.", batch.id),
+            let retry_policy = retry_policies.for_account(&batch.account_name);
+            match PaymentBatch::update_to_awaiting_broadcast_for_retry(
+                &mut *db_pool.acquire().await?,
+                &batch.id,
+                &error_message,
+                retry_policy,
+            )
+            .await
+            {
+                Ok(PaymentBatchStatus::NeedsReview) => error!(
+                    target: LOG_TARGET,
+                    "Batch {} exhausted its broadcast retries and was moved to 'NeedsReview'. Manual resolution required.",
+                    batch.id
+                ),
+                Ok(_) => info!(target: LOG_TARGET, "Batch {} reverted to 'AwaitingBroadcast'.", batch.id),
                 Err(revert_e) => {
-                    eprintln!("CRITICAL: Failed to revert batch {} status: {:?}", batch.id, revert_e)
+                    error!(target: LOG_TARGET, "Failed to revert batch {} status: {:?}", batch.id, revert_e)
                 },
             }
         }
     }
 
-    Ok(())
+    Ok(batch_count)
 }
 
 async fn process_single_batch(
-    conn: &mut SqliteConnection,
-    base_node_client: &Client,
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+    accounts: &SharedAccounts,
+    max_transaction_fee: u64,
+    broadcast_to_all_base_nodes: bool,
+    mempool_check: MempoolCheck,
+    wakeups: &WorkerWakeups,
     batch: &PaymentBatch,
 ) -> Result<(), anyhow::Error> {
     let batch_id = &batch.id;
-    println!("INFO: Starting broadcast sequence for Batch ID: {}", batch_id);
-
-    PaymentBatch::update_to_broadcasting(conn, batch_id)
-        .await
-        .context("Failed to set status to broadcasting")?;
+    info!(target: LOG_TARGET, "Starting broadcast sequence for Batch ID: {}", batch_id);
 
     let signed_json_str = batch
         .signed_tx_json
@@ -82,84 +200,145 @@ async fn process_single_batch(
         .ok_or_else(|| anyhow!("Batch {} has no signed_tx_json", batch_id))?;
 
     let payload = BatchPayload::from_json(&signed_json_str)?;
+
+    let payments = Payment::find_by_batch_id(&mut *db_pool.acquire().await?, batch_id)
+        .await
+        .context("Failed to fetch associated payments")?;
+    let payment_total = PaymentAmount::checked_sum(payments.iter().map(|p| p.amount))?;
+    let max_batch_fee = accounts
+        .current()
+        .get(&batch.account_name.to_lowercase())
+        .and_then(|account| account.max_fee.cap_for(payment_total));
+    if let Err(e) = tx_validation::validate_signed_payload(&payload, &payments, max_transaction_fee, max_batch_fee) {
+        error!(
+            target: LOG_TARGET,
+            "Batch {}: Signed transaction failed validation: {:#}. Moved to 'NeedsReview' without broadcasting.",
+            batch_id, e
+        );
+        PaymentBatch::update_to_needs_review(
+            &mut *db_pool.acquire().await?,
+            batch_id,
+            &format!("Signed transaction rejected: {:#}", e),
+        )
+        .await
+        .context("Failed to move batch to NeedsReview")?;
+        return Ok(());
+    }
+
+    let mut step_txs = Vec::with_capacity(payload.steps.len());
+    for (i, step) in payload.steps.iter().enumerate() {
+        let tx = step
+            .transaction()?
+            .ok_or_else(|| anyhow!("Step {} is not signed!", i))?;
+        step_txs.push(tx);
+    }
+
+    if let Some(conflict) = find_conflicting_spend(base_node_client, &step_txs).await? {
+        error!(
+            target: LOG_TARGET,
+            "Batch {}: {}. Moved to 'NeedsReview' without broadcasting.",
+            batch_id, conflict
+        );
+        PaymentBatch::update_to_needs_review(
+            &mut *db_pool.acquire().await?,
+            batch_id,
+            &format!("Conflicting spend: {}", conflict),
+        )
+        .await
+        .context("Failed to move batch to NeedsReview")?;
+        return Ok(());
+    }
+
+    PaymentBatch::update_to_broadcasting(&mut *db_pool.acquire().await?, batch_id, batch.version)
+        .await
+        .context("Failed to set status to broadcasting")?;
     let is_consolidation_cycle = payload.steps.first().map(|s| s.is_consolidation).unwrap_or(false);
 
-    println!(
-        "INFO: Batch {}: Broadcasting {} transactions... (Consolidation: {})",
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Broadcasting {} transactions... (Consolidation: {})",
         batch_id,
         payload.steps.len(),
         is_consolidation_cycle
     );
 
-    let mut step_tx_objects = Vec::new();
-
-    for (i, step) in payload.steps.iter().enumerate() {
-        let signed_json = match &step.payload {
-            StepPayload::Signed(s) => s,
-            StepPayload::Unsigned(_) => return Err(anyhow!("Step {} is not signed!", i)),
-        };
-        let signed_tx_wrapper = SignedOneSidedTransactionResult::from_json(signed_json)
-            .map_err(|e| anyhow!("Failed to deserialize signed tx for step {}: {}", i, e))?;
-
-        let tx = signed_tx_wrapper.signed_transaction.transaction.clone();
-        step_tx_objects.push(tx.clone());
-
-        println!(
-            "INFO: Batch {}: Submitting TX for Step {}/{} (Internal ID: {})",
+    for (i, (step, tx)) in payload.steps.iter().zip(&step_txs).enumerate() {
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Submitting TX for Step {}/{} (Internal ID: {})",
             batch_id,
             i + 1,
             payload.steps.len(),
             step.tx_id
         );
 
-        let response = base_node_client
-            .submit_transaction(tx)
-            .await
-            .context("Network error submitting transaction to Base Node")?;
+        let rejection_reason = submit_transaction(base_node_client, tx, broadcast_to_all_base_nodes).await?;
 
-        if response.accepted {
-            println!("INFO: Batch {}: Step {} ACCEPTED by Base Node.", batch_id, i + 1);
-        } else {
-            println!(
-                "WARN: Batch {}: Step {} REJECTED by Base Node. Reason: {}",
+        if let Some(rejection_reason) = rejection_reason {
+            warn!(
+                target: LOG_TARGET,
+                "Batch {}: Step {} REJECTED by Base Node. Reason: {}",
                 batch_id,
                 i + 1,
-                response.rejection_reason
+                rejection_reason
             );
+            // A rejection caused by inputs spent elsewhere won't go away on retry, so don't burn retries on it.
+            if let Some(conflict) = find_conflicting_spend(base_node_client, std::slice::from_ref(tx)).await? {
+                error!(
+                    target: LOG_TARGET,
+                    "Batch {}: Step {}: {}. Moved to 'NeedsReview'.",
+                    batch_id,
+                    i + 1,
+                    conflict
+                );
+                PaymentBatch::update_to_needs_review(
+                    &mut *db_pool.acquire().await?,
+                    batch_id,
+                    &format!("Conflicting spend in step {}: {}", i + 1, conflict),
+                )
+                .await
+                .context("Failed to move batch to NeedsReview")?;
+                return Ok(());
+            }
             return Err(anyhow!(
                 "Tari base node rejected transaction in step {}: {}",
                 i + 1,
-                response.rejection_reason
+                rejection_reason
             ));
         }
+        info!(target: LOG_TARGET, "Batch {}: Step {} ACCEPTED by Base Node.", batch_id, i + 1);
     }
 
     if is_consolidation_cycle {
         // === SPLIT CYCLE DETECTED ===
-        println!(
-            "INFO: Batch {}: Split Cycle detected. Verifying Mempool propagation...",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Split Cycle detected. Verifying Mempool propagation...",
             batch_id
         );
 
-        verify_txs_in_mempool(base_node_client, &step_tx_objects).await?;
+        verify_txs_in_mempool(base_node_client, &step_txs, mempool_check).await?;
 
-        println!("INFO: Batch {}: All split transactions found in Mempool.", batch_id);
-        println!(
-            "INFO: Batch {}: LOOPING BACK state to 'PendingBatching' for Cycle 2.",
+        info!(target: LOG_TARGET, "Batch {}: All split transactions found in Mempool.", batch_id);
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: LOOPING BACK state to 'PendingBatching' for Cycle 2.",
             batch_id
         );
 
-        PaymentBatch::reset_to_pending_batching(conn, batch_id)
+        PaymentBatch::reset_to_pending_batching(&mut *db_pool.acquire().await?, batch_id)
             .await
             .context("Failed to reset batch to PendingBatching")?;
+        wakeups.unsigned_tx_creator.notify_one();
     } else {
         // === NORMAL / FINAL CYCLE ===
-        println!(
-            "INFO: Batch {}: Final transaction submitted. Updating to 'AwaitingConfirmation'.",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Final transaction submitted. Updating to 'AwaitingConfirmation'.",
             batch_id
         );
 
-        PaymentBatch::update_to_awaiting_confirmation(conn, batch_id)
+        PaymentBatch::update_to_awaiting_confirmation(&mut *db_pool.acquire().await?, batch_id)
             .await
             .context("Failed to update status to AwaitingConfirmation")?;
     }
@@ -167,28 +346,166 @@ async fn process_single_batch(
     Ok(())
 }
 
-/// Polls the base node to ensure the submitted transactions are visible in the mempool.
-async fn verify_txs_in_mempool(base_node_client: &Client, txs: &[Transaction]) -> Result<(), anyhow::Error> {
+/// Submits the transaction to the active base node or, with `to_all_nodes`, to every configured node. A fan-out
+/// counts as accepted if any node accepted it. Returns the rejection reason if the transaction was rejected.
+async fn submit_transaction(
+    base_node_client: &FailoverBaseNodeClient,
+    tx: &StepTransaction,
+    to_all_nodes: bool,
+) -> Result<Option<String>, anyhow::Error> {
+    if !to_all_nodes {
+        let response = base_node_client
+            .call("submit_transaction", |client| submit_to_node(client, tx.clone()))
+            .await
+            .context("Network error submitting transaction to Base Node")?;
+        return Ok((!response.accepted).then_some(response.rejection_reason));
+    }
+
+    let results = base_node_client
+        .call_all("submit_transaction", |client| submit_to_node(client, tx.clone()))
+        .await;
+
+    let mut rejections = Vec::new();
+    let mut errors = Vec::new();
+    let mut accepted_by = Vec::new();
+    for (url, result) in results {
+        match result {
+            Ok(response) if response.accepted => accepted_by.push(url),
+            Ok(response) => rejections.push(format!("{}: {}", url, response.rejection_reason)),
+            Err(e) => errors.push(format!("{}: {}", url, e)),
+        }
+    }
+
+    if !accepted_by.is_empty() {
+        if !rejections.is_empty() || !errors.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "Transaction accepted by {}, but not by all base nodes: {}",
+                accepted_by.join(", "),
+                rejections.iter().chain(&errors).cloned().collect::<Vec<_>>().join("; ")
+            );
+        }
+        return Ok(None);
+    }
+    if rejections.is_empty() {
+        return Err(anyhow!(
+            "Network error submitting transaction to all base nodes: {}",
+            errors.join("; ")
+        ));
+    }
+    Ok(Some(rejections.join("; ")))
+}
+
+/// Submits the transaction of a step to one base node.
+pub(crate) async fn submit_to_node(client: NodeClient, tx: StepTransaction) -> anyhow::Result<TxSubmission> {
+    match tx {
+        StepTransaction::Signed(signed_tx) => {
+            client
+                .submit_transaction(signed_tx.signed_transaction.transaction)
+                .await
+        },
+        StepTransaction::Simulated(simulated) => client.submit_simulated_transaction(simulated).await,
+    }
+}
+
+/// Asks the base node where a transaction is, identified by the excess signature of its first kernel.
+pub(crate) async fn query_tx_location(
+    base_node_client: &FailoverBaseNodeClient,
+    tx: &StepTransaction,
+) -> Result<TxLocation, anyhow::Error> {
+    Ok(query_tx_status(base_node_client, tx).await?.location)
+}
+
+/// Like `query_tx_location`, but also returns the block the transaction was mined in.
+pub(crate) async fn query_tx_status(
+    base_node_client: &FailoverBaseNodeClient,
+    tx: &StepTransaction,
+) -> Result<TxStatus, anyhow::Error> {
+    let (excess_public, excess_sig) = tx.kernel_signature()?;
+
+    base_node_client
+        .call("transaction_query", |client| {
+            let (excess_public, excess_sig) = (excess_public.clone(), excess_sig.clone());
+            async move { client.transaction_query(excess_public, excess_sig).await }
+        })
+        .await
+}
+
+/// Looks for inputs of the transactions that another transaction already spent, e.g. after an operator moved funds
+/// manually. Transactions the base node already knows by their kernel are our own earlier broadcasts and are skipped.
+/// Returns a description of the first conflict found.
+async fn find_conflicting_spend(
+    base_node_client: &FailoverBaseNodeClient,
+    txs: &[StepTransaction],
+) -> Result<Option<String>, anyhow::Error> {
     for (i, tx) in txs.iter().enumerate() {
-        let kernel = tx
-            .body
-            .kernels()
-            .first()
-            .ok_or_else(|| anyhow!("Transaction {} has no kernels", i))?;
+        let location = query_tx_location(base_node_client, tx)
+            .await
+            .with_context(|| format!("Failed to query status of transaction {}", i))?;
+        if matches!(location, TxLocation::Mined | TxLocation::InMempool) {
+            continue;
+        }
 
-        let excess_public = kernel.excess_sig.get_compressed_public_nonce().to_vec();
-        let excess_sig = kernel.excess_sig.get_signature().to_vec();
+        let input_hashes = tx.input_hashes();
+        if input_hashes.is_empty() {
+            continue;
+        }
 
+        let Some(spent_outputs) = base_node_client
+            .call("spent_outputs", |client| {
+                let input_hashes = input_hashes.clone();
+                async move { client.spent_outputs(input_hashes).await }
+            })
+            .await
+            .context("Failed to query spent state of transaction inputs")?
+        else {
+            debug!(
+                target: LOG_TARGET,
+                "Base node can't report spent outputs. Skipping the conflicting spend check of transaction {}.",
+                i + 1
+            );
+            continue;
+        };
+
+        if let Some((info, height)) = spent_outputs
+            .iter()
+            .find_map(|info| info.spent_at_height.map(|height| (info, height)))
+        {
+            return Ok(Some(format!(
+                "input {} of transaction {} was already spent by another transaction at height {}",
+                info.output_hash.to_hex(),
+                i + 1,
+                height
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Polls the base node to ensure the submitted transactions are visible in the mempool.
+async fn verify_txs_in_mempool(
+    base_node_client: &FailoverBaseNodeClient,
+    txs: &[StepTransaction],
+    mempool_check: MempoolCheck,
+) -> Result<(), anyhow::Error> {
+    let deadline = Instant::now() + mempool_check.deadline;
+    for (i, tx) in txs.iter().enumerate() {
         let mut retries = 0;
         let mut found = false;
 
-        while retries < MEMPOOL_CHECK_RETRIES {
-            let response = base_node_client
-                .transaction_query(excess_public.clone(), excess_sig.clone())
+        while retries < mempool_check.retries {
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Transactions did not appear in mempool within {} seconds. Aborting loop-back.",
+                    mempool_check.deadline.as_secs()
+                ));
+            }
+            let location = query_tx_location(base_node_client, tx)
                 .await
-                .context("Failed to query transaction status")?;
+                .with_context(|| format!("Failed to query status of transaction {}", i))?;
 
-            match response.location {
+            match location {
                 TxLocation::InMempool => {
                     found = true;
                     break;
@@ -198,7 +515,12 @@ async fn verify_txs_in_mempool(base_node_client: &Client, txs: &[Transaction]) -
                     break;
                 },
                 TxLocation::NotStored | TxLocation::None => {
-                    time::sleep(MEMPOOL_CHECK_DELAY).await;
+                    time::sleep(
+                        mempool_check
+                            .delay
+                            .min(deadline.saturating_duration_since(Instant::now())),
+                    )
+                    .await;
                     retries += 1;
                 },
             }
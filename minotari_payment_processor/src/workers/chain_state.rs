@@ -0,0 +1,113 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+use crate::base_node::FailoverBaseNodeClient;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::chain_state";
+
+const DEFAULT_SLEEP_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChainTip {
+    pub height: u64,
+    /// Hex encoded hash of the best block.
+    pub best_block_hash: String,
+    pub observed_at: DateTime<Utc>,
+}
+
+/// The most recently observed chain tip, shared by the workers and the API so they don't each ask the base node.
+/// Refreshed by the `chain_state` worker.
+#[derive(Debug, Clone)]
+pub struct ChainState {
+    tip: Arc<RwLock<Option<ChainTip>>>,
+    /// A cached tip older than this is refreshed on use, e.g. while the worker is failing.
+    max_age: Duration,
+}
+
+impl ChainState {
+    /// `sleep_secs` is the refresh interval of the worker. Tips up to twice as old are served from the cache.
+    pub fn new(sleep_secs: Option<u64>) -> Self {
+        Self {
+            tip: Arc::default(),
+            max_age: Duration::from_secs(sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS).saturating_mul(2)),
+        }
+    }
+
+    /// The cached tip, `None` until it was first observed.
+    pub fn current(&self) -> Option<ChainTip> {
+        self.tip.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the cached tip, or asks the base node if there is none or it is too old.
+    pub async fn tip(&self, base_node_client: &FailoverBaseNodeClient) -> anyhow::Result<ChainTip> {
+        if let Some(tip) = self.current()
+            && (Utc::now() - tip.observed_at).to_std().unwrap_or_default() <= self.max_age
+        {
+            return Ok(tip);
+        }
+        self.refresh(base_node_client).await
+    }
+
+    /// Asks the base node for the current tip and caches it.
+    pub async fn refresh(&self, base_node_client: &FailoverBaseNodeClient) -> anyhow::Result<ChainTip> {
+        let tip_info = base_node_client
+            .call("get_tip_info", |client| async move { client.get_tip_info().await })
+            .await
+            .context("Failed to get tip info from Base Node")?;
+        let tip = ChainTip {
+            height: tip_info.best_block_height,
+            best_block_hash: hex::encode(tip_info.best_block_hash),
+            observed_at: Utc::now(),
+        };
+        *self.tip.write().unwrap_or_else(|e| e.into_inner()) = Some(tip.clone());
+        Ok(tip)
+    }
+}
+
+/// Keeps `ChainState` up to date.
+pub struct ChainStateWorker {
+    base_node_client: FailoverBaseNodeClient,
+    chain_state: ChainState,
+    sleep_secs: u64,
+}
+
+impl ChainStateWorker {
+    pub fn new(base_node_client: FailoverBaseNodeClient, chain_state: ChainState, sleep_secs: Option<u64>) -> Self {
+        Self {
+            base_node_client,
+            chain_state,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+        }
+    }
+}
+
+impl Worker for ChainStateWorker {
+    const NAME: &'static str = "chain_state";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Chain State worker started. Refreshing the chain tip every {} seconds.",
+            self.sleep_secs
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let tip = self.chain_state.refresh(&self.base_node_client).await?;
+        debug!(target: LOG_TARGET, "Chain tip at height {}.", tip.height);
+        cycle.add_items(1);
+        Ok(())
+    }
+}
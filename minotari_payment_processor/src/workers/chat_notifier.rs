@@ -0,0 +1,260 @@
+use anyhow::{Context, anyhow};
+use log::{info, warn};
+use serde_json::Value;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+use std::fmt;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{ChatWebhookConfig, NetworkRetry};
+use crate::db::event::{Event, EventType};
+use crate::db::setting::Setting;
+use crate::retry::with_retry;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::chat_notifier";
+
+const DEFAULT_SLEEP_SECS: u64 = 30;
+/// Settings key of the sequence number of the last event that was handled.
+const LAST_SEQ_KEY: &str = "chat_notifier_last_seq";
+const PAGE_SIZE: i64 = 100;
+
+/// Posts the configured critical events from the `events` outbox to a chat webhook, so they reach the on-call
+/// channel instead of only the logs.
+///
+/// The worker keeps its position in the outbox in the `settings` table, so events are posted once, in order, even
+/// across restarts. On the first start it begins at the latest event instead of posting the history. If the webhook
+/// is unreachable, the event is posted again on the next cycle; if it rejects the message, the event is skipped.
+pub struct ChatNotifier {
+    db_pool: SqlitePool,
+    client: reqwest::Client,
+    config: ChatWebhookConfig,
+    network_retry: NetworkRetry,
+    sleep_secs: u64,
+}
+
+impl ChatNotifier {
+    pub fn new(
+        db_pool: SqlitePool,
+        config: ChatWebhookConfig,
+        network_retry: NetworkRetry,
+        sleep_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            db_pool,
+            client: reqwest::Client::new(),
+            config,
+            network_retry,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+        }
+    }
+}
+
+impl Worker for ChatNotifier {
+    const NAME: &'static str = "chat_notifier";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Chat Notifier worker started. Polling every {} seconds for events: {}.",
+            self.sleep_secs,
+            self.config.events.join(", ")
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let count = post_new_events(&self.db_pool, &self.client, &self.config, &self.network_retry, shutdown).await?;
+        cycle.add_items(count);
+        Ok(())
+    }
+}
+
+/// Returns the number of events that were posted.
+async fn post_new_events(
+    db_pool: &SqlitePool,
+    client: &reqwest::Client,
+    config: &ChatWebhookConfig,
+    network_retry: &NetworkRetry,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let stored = Setting::get(&mut *db_pool.acquire().await?, LAST_SEQ_KEY).await?;
+    let mut last_seq = match stored {
+        Some(value) => value
+            .parse::<i64>()
+            .with_context(|| format!("Invalid {} setting: {}", LAST_SEQ_KEY, value))?,
+        None => {
+            let mut conn = db_pool.acquire().await?;
+            let last_seq = Event::last_seq(&mut conn).await?;
+            store_last_seq(&mut conn, last_seq).await?;
+            return Ok(0);
+        },
+    };
+
+    let mut posted = 0;
+    loop {
+        // No connection is held while posting, so the webhook can't stall the other workers' writes.
+        let events = Event::find_after(&mut *db_pool.acquire().await?, last_seq, PAGE_SIZE).await?;
+        let page_size = events.len();
+        for event in events {
+            if shutdown.is_cancelled() {
+                return Ok(posted);
+            }
+            let event_type = event.event_type.to_string();
+            if config.events.contains(&event_type) {
+                match post_event(client, config, network_retry, &event).await {
+                    Ok(()) => posted += 1,
+                    Err(PostError::Rejected(e)) => warn!(
+                        target: LOG_TARGET,
+                        "Chat webhook rejected event {} ({}), skipping it: {}",
+                        event.seq, event_type, e
+                    ),
+                    Err(PostError::Unreachable(e)) => {
+                        return Err(e.context(format!("Failed to post event {} to the chat webhook", event.seq)));
+                    },
+                }
+            }
+            last_seq = event.seq;
+            store_last_seq(&mut *db_pool.acquire().await?, last_seq).await?;
+        }
+        if (page_size as i64) < PAGE_SIZE {
+            return Ok(posted);
+        }
+    }
+}
+
+async fn store_last_seq(conn: &mut SqliteConnection, last_seq: i64) -> Result<(), sqlx::Error> {
+    Setting::set(conn, LAST_SEQ_KEY, &last_seq.to_string()).await
+}
+
+enum PostError {
+    /// Network errors, timeouts, `429` and `5xx` responses. Worth trying again later.
+    Unreachable(anyhow::Error),
+    /// Any other error response, e.g. because the body template doesn't match what the webhook expects.
+    Rejected(anyhow::Error),
+}
+
+impl From<std::io::Error> for PostError {
+    fn from(e: std::io::Error) -> Self {
+        PostError::Unreachable(e.into())
+    }
+}
+
+impl fmt::Display for PostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PostError::Unreachable(e) | PostError::Rejected(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+async fn post_event(
+    client: &reqwest::Client,
+    config: &ChatWebhookConfig,
+    network_retry: &NetworkRetry,
+    event: &Event,
+) -> Result<(), PostError> {
+    let message = render_message(config, event);
+    let message_json = serde_json::to_string(&message).map_err(|e| PostError::Rejected(e.into()))?;
+    let body = config.body_template.replace("{message}", &message_json);
+
+    with_retry(
+        network_retry,
+        "post_chat_webhook",
+        |e| matches!(e, PostError::Unreachable(_)),
+        || post_body(client, &config.url, body.clone()),
+    )
+    .await
+}
+
+async fn post_body(client: &reqwest::Client, url: &str, body: String) -> Result<(), PostError> {
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| PostError::Unreachable(anyhow!("Network error calling chat webhook: {}", e)))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let content = response.text().await.unwrap_or_default();
+    let error = anyhow!("Chat webhook error: {} - {}", status, content);
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        Err(PostError::Unreachable(error))
+    } else {
+        Err(PostError::Rejected(error))
+    }
+}
+
+fn default_template(event_type: &EventType) -> &'static str {
+    match event_type {
+        EventType::BatchFailed => "Batch {entity_id} failed: {error_message}",
+        EventType::ReconciliationDiscrepancy => {
+            "Account {account_name} has a discrepancy of {discrepancy} µT: {locked} µT locked, expected between \
+             {expected_locked_min} and {expected_locked_max} µT (report {entity_id})."
+        },
+        EventType::LowBalance => {
+            "Account {account_name} is low on funds: {available} µT available, {required} µT required for \
+             {pending_payment_count} pending payments (shortfall {shortfall} µT)."
+        },
+        _ => "{event_type} {entity_id}: {payload}",
+    }
+}
+
+fn render_message(config: &ChatWebhookConfig, event: &Event) -> String {
+    let mut vars = HashMap::from([
+        ("event_type".to_string(), event.event_type.to_string()),
+        ("entity_id".to_string(), event.entity_id.clone()),
+        ("seq".to_string(), event.seq.to_string()),
+        ("created_at".to_string(), event.created_at.to_rfc3339()),
+        ("payload".to_string(), event.payload.clone()),
+    ]);
+    if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(&event.payload) {
+        for (key, value) in fields {
+            let value = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            vars.entry(key).or_insert(value);
+        }
+    }
+
+    let template = config
+        .message_template
+        .as_deref()
+        .unwrap_or_else(|| default_template(&event.event_type));
+    render(template, &vars)
+}
+
+/// Replaces every `{name}` in `template` with its value. Unknown placeholders are kept as they are.
+fn render(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find('}')
+            .and_then(|end| vars.get(&after[..end]).map(|value| (end, value)))
+        {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            },
+            None => {
+                rendered.push('{');
+                rest = after;
+            },
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
@@ -0,0 +1,133 @@
+use tari_transaction_components::{
+    fee::Fee, tari_amount::MicroMinotari, transaction_components::WalletOutput, weight::TransactionWeight,
+};
+
+use crate::config::CoinSelectionStrategy;
+use crate::workers::unsigned_tx_creator::{FEE_PER_GRAM, get_single_output_metadata_size};
+
+/// Picks the inputs for a transaction paying `payment_total` µT to `recipient_count` recipients (plus change) from
+/// the locked `inputs`. The fee is re-estimated for every candidate input count.
+///
+/// Returns `None` if the strategy spends everything or no selection covers the payments and fee; the caller then
+/// uses all inputs. Selected inputs keep their original order.
+pub fn select_inputs(
+    strategy: CoinSelectionStrategy,
+    inputs: &[WalletOutput],
+    payment_total: u64,
+    recipient_count: usize,
+) -> Result<Option<Vec<WalletOutput>>, anyhow::Error> {
+    if strategy == CoinSelectionStrategy::All || inputs.is_empty() {
+        return Ok(None);
+    }
+
+    let fee_calc = Fee::new(TransactionWeight::latest());
+    let output_metadata_size = get_single_output_metadata_size(&fee_calc)?;
+    let output_count = recipient_count + 1;
+    let required = |input_count: usize| {
+        let fee = fee_calc.calculate(
+            MicroMinotari(FEE_PER_GRAM),
+            1,
+            input_count,
+            output_count,
+            output_metadata_size * output_count,
+        );
+        payment_total.saturating_add(fee.as_u64())
+    };
+
+    let values: Vec<u64> = inputs.iter().map(|input| input.value().as_u64()).collect();
+    let mut descending: Vec<usize> = (0..inputs.len()).collect();
+    descending.sort_by(|a, b| values[*b].cmp(&values[*a]));
+
+    let selection = match strategy {
+        CoinSelectionStrategy::All => None,
+        CoinSelectionStrategy::LargestFirst => take_until_covered(&descending, &values, &required),
+        CoinSelectionStrategy::OldestFirst => {
+            let in_order: Vec<usize> = (0..inputs.len()).collect();
+            take_until_covered(&in_order, &values, &required)
+        },
+        CoinSelectionStrategy::MinimizeInputs => minimize_inputs(&descending, &values, &required),
+        CoinSelectionStrategy::MinimizeChange => minimize_change(&descending, &values, &required),
+    };
+
+    Ok(selection.map(|mut indices| {
+        indices.sort_unstable();
+        indices.into_iter().map(|i| inputs[i].clone()).collect()
+    }))
+}
+
+fn sum(indices: &[usize], values: &[u64]) -> u64 {
+    indices.iter().fold(0u64, |total, i| total.saturating_add(values[*i]))
+}
+
+fn excess(indices: &[usize], values: &[u64], required: &impl Fn(usize) -> u64) -> u64 {
+    sum(indices, values) - required(indices.len())
+}
+
+/// Takes inputs in `order` until they cover the payments and the fee for that many inputs.
+fn take_until_covered(order: &[usize], values: &[u64], required: &impl Fn(usize) -> u64) -> Option<Vec<usize>> {
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    for &i in order {
+        selected.push(i);
+        total = total.saturating_add(values[i]);
+        if total >= required(selected.len()) {
+            return Some(selected);
+        }
+    }
+    None
+}
+
+/// The fewest inputs are found by taking the largest first. The last of them is then swapped for the smallest
+/// remaining input that still covers the total.
+fn minimize_inputs(descending: &[usize], values: &[u64], required: &impl Fn(usize) -> u64) -> Option<Vec<usize>> {
+    let largest = take_until_covered(descending, values, required)?;
+    let count = largest.len();
+    let base = &descending[..count - 1];
+    let base_total = sum(base, values);
+
+    let last = descending[count - 1..]
+        .iter()
+        .rev()
+        .find(|i| base_total.saturating_add(values[**i]) >= required(count))?;
+
+    let mut selected = base.to_vec();
+    selected.push(*last);
+    Some(selected)
+}
+
+/// Heuristic: compares the smallest single covering input, the smallest inputs first with every input dropped
+/// that is not needed, and the `minimize_inputs` selection, and keeps the one with the least excess.
+fn minimize_change(descending: &[usize], values: &[u64], required: &impl Fn(usize) -> u64) -> Option<Vec<usize>> {
+    let mut candidates = Vec::new();
+
+    if let Some(single) = descending.iter().rev().find(|i| values[**i] >= required(1)) {
+        candidates.push(vec![*single]);
+    }
+
+    let ascending: Vec<usize> = descending.iter().rev().copied().collect();
+    if let Some(mut selected) = take_until_covered(&ascending, values, required) {
+        // Drop the largest inputs first, as long as the rest still covers the total.
+        let mut position = selected.len();
+        while position > 0 {
+            position -= 1;
+            let without: Vec<usize> = selected
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != position)
+                .map(|(_, i)| *i)
+                .collect();
+            if !without.is_empty() && sum(&without, values) >= required(without.len()) {
+                selected = without;
+            }
+        }
+        candidates.push(selected);
+    }
+
+    if let Some(selected) = minimize_inputs(descending, values, required) {
+        candidates.push(selected);
+    }
+
+    candidates
+        .into_iter()
+        .min_by_key(|selected| (excess(selected, values, required), selected.len()))
+}
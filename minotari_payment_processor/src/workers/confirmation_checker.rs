@@ -1,235 +1,668 @@
 use anyhow::{Context, anyhow};
-use minotari_node_wallet_client::{BaseNodeWalletClient, http::Client};
+use log::{debug, error, info, warn};
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
 use tari_common_types::payment_reference::generate_payment_reference;
 use tari_common_types::types::FixedHash;
-use tari_transaction_components::offline_signing::models::SignedOneSidedTransactionResult;
-use tari_transaction_components::offline_signing::models::TransactionResult;
 use tari_transaction_components::rpc::models::TxLocation;
-use tari_utilities::byte_array::ByteArray;
-use tokio::time::{self, Duration};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
 
+use crate::base_node::{FailoverBaseNodeClient, TxStatus};
+use crate::config::RetryPolicies;
 use crate::db::payment::Payment;
 use crate::db::payment_batch::BatchPayload;
-use crate::db::payment_batch::StepPayload;
 use crate::db::payment_batch::{PaymentBatch, PaymentBatchStatus};
+use crate::db::payment_batch::{StepTransaction, TransactionStep};
+use crate::workers::broadcaster::{query_tx_status, submit_to_node};
+use crate::workers::chain_state::ChainState;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
 
-const DEFAULT_SLEEP_SECS: u64 = 60;
+const LOG_TARGET: &str = "minotari_payment_processor::workers::confirmation_checker";
 
-pub async fn run(db_pool: SqlitePool, base_node_client: Client, sleep_secs: Option<u64>, required_confirmations: u64) {
-    let sleep_secs = sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS);
-    println!(
-        "Confirmation Checker worker started. Polling every {} seconds. Required Confirmations: {}",
-        sleep_secs, required_confirmations
-    );
+const DEFAULT_SLEEP_SECS: u64 = 60;
+const DEFAULT_REBROADCAST_AFTER_CHECKS: u32 = 3;
+/// After this many re-submissions, a missing transaction is treated as an error again.
+const MAX_REBROADCASTS: u32 = 5;
+
+/// A payout transaction the base node did not know on the most recent checks.
+#[derive(Debug, Default)]
+struct MissingTransaction {
+    consecutive_misses: u32,
+    rebroadcasts: u32,
+}
 
-    let mut interval = time::interval(Duration::from_secs(sleep_secs));
+/// Keyed by batch id and step index.
+type MissingTransactions = HashMap<(String, usize), MissingTransaction>;
+
+pub struct ConfirmationChecker {
+    db_pool: SqlitePool,
+    base_node_client: FailoverBaseNodeClient,
+    chain_state: ChainState,
+    sleep_secs: u64,
+    default_required_confirmations: u64,
+    rebroadcast_after_checks: u32,
+    settlement_confirmations: Option<u64>,
+    retry_policies: RetryPolicies,
+    batches_per_cycle: i64,
+    missing_transactions: MissingTransactions,
+}
 
-    loop {
-        interval.tick().await;
-        if let Err(e) = check_transaction_confirmations(&db_pool, &base_node_client, required_confirmations).await {
-            eprintln!("Confirmation Checker worker error: {:?}", e);
+impl ConfirmationChecker {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: SqlitePool,
+        base_node_client: FailoverBaseNodeClient,
+        chain_state: ChainState,
+        sleep_secs: Option<u64>,
+        default_required_confirmations: u64,
+        rebroadcast_after_checks: Option<u32>,
+        settlement_confirmations: Option<u64>,
+        retry_policies: RetryPolicies,
+        batches_per_cycle: i64,
+    ) -> Self {
+        Self {
+            db_pool,
+            base_node_client,
+            chain_state,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            default_required_confirmations,
+            rebroadcast_after_checks: rebroadcast_after_checks
+                .unwrap_or(DEFAULT_REBROADCAST_AFTER_CHECKS)
+                .max(1),
+            settlement_confirmations,
+            retry_policies,
+            batches_per_cycle,
+            missing_transactions: HashMap::new(),
         }
     }
 }
 
+impl Worker for ConfirmationChecker {
+    const NAME: &'static str = "confirmation_checker";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Confirmation Checker worker started. Polling every {} seconds. Default Required Confirmations: {}. \
+             Rebroadcasting after {} checks without the transaction.",
+            self.sleep_secs, self.default_required_confirmations, self.rebroadcast_after_checks
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let count = check_transaction_confirmations(
+            &self.db_pool,
+            &self.base_node_client,
+            &self.chain_state,
+            self.default_required_confirmations,
+            self.rebroadcast_after_checks,
+            self.settlement_confirmations,
+            &mut self.missing_transactions,
+            &self.retry_policies,
+            self.batches_per_cycle,
+            shutdown,
+        )
+        .await?;
+        cycle.add_items(count);
+        Ok(())
+    }
+}
+
 async fn check_transaction_confirmations(
     db_pool: &SqlitePool,
-    base_node_client: &Client,
-    required_confirmations: u64,
-) -> Result<(), anyhow::Error> {
-    let mut conn = db_pool.acquire().await?;
-
-    let batches = PaymentBatch::find_by_status(&mut conn, PaymentBatchStatus::AwaitingConfirmation).await?;
+    base_node_client: &FailoverBaseNodeClient,
+    chain_state: &ChainState,
+    default_required_confirmations: u64,
+    rebroadcast_after_checks: u32,
+    settlement_confirmations: Option<u64>,
+    missing_transactions: &mut MissingTransactions,
+    retry_policies: &RetryPolicies,
+    batches_per_cycle: i64,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    // Batches stay in `AwaitingConfirmation` for many cycles, so every cycle pages through all of them instead of
+    // loading them at once.
+    let mut seen_batch_ids = HashSet::new();
+    let mut after_id: Option<String> = None;
+    loop {
+        let batches = PaymentBatch::find_by_status_page(
+            &mut *db_pool.acquire().await?,
+            PaymentBatchStatus::AwaitingConfirmation,
+            after_id.as_deref(),
+            batches_per_cycle,
+        )
+        .await?;
+        let Some(last) = batches.last() else {
+            break;
+        };
+        after_id = Some(last.id.clone());
+        let is_last_page = (batches.len() as i64) < batches_per_cycle;
+
+        info!(target: LOG_TARGET, "Found {} batches awaiting confirmation.", batches.len());
+
+        for batch in batches {
+            if shutdown.is_cancelled() {
+                info!(target: LOG_TARGET, "Shutdown requested. Leaving remaining batches for the next run.");
+                return Ok(seen_batch_ids.len());
+            }
+            seen_batch_ids.insert(batch.id.clone());
+            check_batch(
+                db_pool,
+                base_node_client,
+                chain_state,
+                &batch,
+                default_required_confirmations,
+                rebroadcast_after_checks,
+                missing_transactions,
+                retry_policies,
+            )
+            .await;
+        }
 
-    if !batches.is_empty() {
-        println!("INFO: Found {} batches awaiting confirmation.", batches.len());
+        if is_last_page {
+            break;
+        }
     }
+    missing_transactions.retain(|(batch_id, _), _| seen_batch_ids.contains(batch_id));
 
-    for batch in batches {
-        if let Err(e) = process_single_batch(db_pool, base_node_client, &batch, required_confirmations).await {
-            let error_message = e.to_string();
-            eprintln!(
-                "Error checking confirmation for batch {}: {}. Incrementing retry count.",
-                batch.id, error_message
-            );
+    let settled = match settlement_confirmations {
+        Some(settlement_confirmations) => {
+            settle_confirmed_batches(
+                db_pool,
+                base_node_client,
+                chain_state,
+                settlement_confirmations,
+                batches_per_cycle,
+                shutdown,
+            )
+            .await?
+        },
+        None => 0,
+    };
 
-            if let Err(db_err) = PaymentBatch::increment_retry_count(&mut conn, &batch.id, &error_message).await {
-                eprintln!(
-                    "CRITICAL: Failed to update retry count for batch {}: {:?}",
-                    batch.id, db_err
-                );
+    Ok(seen_batch_ids.len() + settled)
+}
+
+/// Moves 'CONFIRMED' batches whose payout transactions are at least `settlement_confirmations` deep to 'SETTLED'.
+/// Returns the number of settled batches.
+async fn settle_confirmed_batches(
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+    chain_state: &ChainState,
+    settlement_confirmations: u64,
+    batches_per_cycle: i64,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let tip_height = chain_state.tip(base_node_client).await?.height;
+    // The stored height is only a pre-filter; each candidate is checked against the chain before it settles.
+    let max_mined_height = (tip_height + 1).saturating_sub(settlement_confirmations) as i64;
+
+    let mut settled = 0;
+    let mut after_id: Option<String> = None;
+    loop {
+        let batches = PaymentBatch::find_settleable_page(
+            &mut *db_pool.acquire().await?,
+            max_mined_height,
+            after_id.as_deref(),
+            batches_per_cycle,
+        )
+        .await?;
+        let Some(last) = batches.last() else {
+            break;
+        };
+        after_id = Some(last.id.clone());
+        let is_last_page = (batches.len() as i64) < batches_per_cycle;
+
+        for batch in batches {
+            if shutdown.is_cancelled() {
+                return Ok(settled);
+            }
+            match settle_batch(db_pool, base_node_client, &batch, tip_height, settlement_confirmations).await {
+                Ok(true) => settled += 1,
+                Ok(false) => {},
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Failed to settle batch {}: {:#}. Retrying next cycle.",
+                    batch.id, e
+                ),
             }
         }
+
+        if is_last_page {
+            break;
+        }
     }
 
-    Ok(())
+    Ok(settled)
+}
+
+/// Settles the batch if all of its payout transactions are still mined and deep enough. A transaction that is no
+/// longer mined, e.g. after a reorg, keeps the batch 'CONFIRMED' and is reported for manual investigation.
+async fn settle_batch(
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+    batch: &PaymentBatch,
+    tip_height: u64,
+    settlement_confirmations: u64,
+) -> Result<bool, anyhow::Error> {
+    let batch_id = &batch.id;
+    let payload = match &batch.signed_tx_json {
+        Some(payload) => BatchPayload::from_json(payload)?,
+        None => return Err(anyhow!("Batch {} has no signed_tx_json", batch_id)),
+    };
+
+    let mut mined_height = 0;
+    for step in payload.payout_steps() {
+        let tx = step.transaction()?.ok_or_else(|| anyhow!("Payload is not signed!"))?;
+        let tx_status = query_tx_status(base_node_client, &tx).await?;
+        let step_height = match (tx_status.location, tx_status.mined_height) {
+            (TxLocation::Mined, Some(height)) => height,
+            (location, _) => {
+                error!(
+                    target: LOG_TARGET,
+                    "Batch {} is CONFIRMED, but the transaction of step {} is no longer mined (location: {:?}), \
+                     possibly due to a reorg. Not settling it.",
+                    batch_id,
+                    step.step_index + 1,
+                    location
+                );
+                return Ok(false);
+            },
+        };
+        mined_height = mined_height.max(step_height);
+    }
+
+    let confirmations = tip_height.saturating_sub(mined_height) + 1;
+    PaymentBatch::record_confirmations(&mut *db_pool.acquire().await?, batch_id, confirmations, tip_height).await?;
+    if confirmations < settlement_confirmations {
+        debug!(
+            target: LOG_TARGET,
+            "Batch {}: {}/{} confirmations for settlement.",
+            batch_id, confirmations, settlement_confirmations
+        );
+        return Ok(false);
+    }
+
+    PaymentBatch::update_to_settled(&mut *db_pool.acquire().await?, batch_id, mined_height, confirmations).await?;
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Settled after {} confirmations.",
+        batch_id, confirmations
+    );
+    Ok(true)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn check_batch(
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+    chain_state: &ChainState,
+    batch: &PaymentBatch,
+    default_required_confirmations: u64,
+    rebroadcast_after_checks: u32,
+    missing_transactions: &mut MissingTransactions,
+    retry_policies: &RetryPolicies,
+) {
+    // Batches created before confirmations were tracked per batch use the global setting.
+    let required_confirmations = batch
+        .required_confirmations
+        .map(|c| c as u64)
+        .unwrap_or(default_required_confirmations);
+
+    if let Err(e) = process_single_batch(
+        db_pool,
+        base_node_client,
+        chain_state,
+        batch,
+        required_confirmations,
+        rebroadcast_after_checks,
+        missing_transactions,
+    )
+    .await
+    {
+        let error_message = e.to_string();
+        error!(
+            target: LOG_TARGET,
+            "Error checking confirmation for batch {}: {}. Incrementing retry count.",
+            batch.id, error_message
+        );
+
+        let retry_policy = retry_policies.for_account(&batch.account_name);
+        let result = match db_pool.acquire().await {
+            Ok(mut conn) => {
+                PaymentBatch::increment_retry_count(&mut conn, &batch.id, &error_message, retry_policy).await
+            },
+            Err(e) => Err(e.into()),
+        };
+        if let Err(db_err) = result {
+            error!(
+                target: LOG_TARGET,
+                "Failed to update retry count for batch {}: {:?}",
+                batch.id, db_err
+            );
+        }
+    }
 }
 
 async fn process_single_batch(
     db_pool: &SqlitePool,
-    base_node_client: &Client,
+    base_node_client: &FailoverBaseNodeClient,
+    chain_state: &ChainState,
     batch: &PaymentBatch,
     required_confirmations: u64,
+    rebroadcast_after_checks: u32,
+    missing_transactions: &mut MissingTransactions,
 ) -> Result<(), anyhow::Error> {
     let batch_id = &batch.id;
 
-    println!("INFO: Checking status for Batch ID: {}", batch_id);
+    info!(target: LOG_TARGET, "Checking status for Batch ID: {}", batch_id);
 
     let payload = match &batch.signed_tx_json {
         Some(payload) => BatchPayload::from_json(payload)?,
         None => return Err(anyhow!("Batch {} has no signed_tx_json", batch_id)),
     };
-    let signed_tx_json = match &payload.steps[..] {
-        [step] => match &step.payload {
-            StepPayload::Signed(s) => s,
-            StepPayload::Unsigned(_) => return Err(anyhow!("Payload is not signed!")),
-        },
-        _ => return Err(anyhow!("Batch {} does not have exactly one step", batch_id)),
-    };
+    let payout_steps = payload
+        .payout_steps()
+        .map(|step| match step.transaction()? {
+            Some(tx) => Ok((step, tx)),
+            None => Err(anyhow!("Payload is not signed!")),
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+    if payout_steps.is_empty() {
+        return Err(anyhow!("Batch {} has no payout transaction", batch_id));
+    }
 
-    let signed_tx = SignedOneSidedTransactionResult::from_json(signed_tx_json)?;
+    let mut mined_steps = Vec::with_capacity(payout_steps.len());
+    for (step, tx) in &payout_steps {
+        if let Some(tx_status) = check_step(
+            base_node_client,
+            batch_id,
+            step.step_index,
+            tx,
+            rebroadcast_after_checks,
+            missing_transactions,
+        )
+        .await?
+        {
+            mined_steps.push(MinedStep { step, tx, tx_status });
+        }
+    }
 
-    let kernel = signed_tx
-        .signed_transaction
-        .transaction
-        .body
-        .kernels()
-        .first()
-        .ok_or_else(|| anyhow!("Transaction has no kernels"))?;
+    if mined_steps.len() < payout_steps.len() {
+        if payout_steps.len() > 1 {
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: {} of {} payout transactions mined.",
+                batch_id,
+                mined_steps.len(),
+                payout_steps.len()
+            );
+        }
+        return Ok(());
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: All payout transactions mined. Processing confirmations...",
+        batch_id
+    );
+    handle_mined_transactions(
+        db_pool,
+        base_node_client,
+        chain_state,
+        batch_id,
+        &mined_steps,
+        required_confirmations,
+    )
+    .await
+}
 
-    let excess_sig_nonce = kernel.excess_sig.get_compressed_public_nonce().to_vec();
-    let excess_sig_sig = kernel.excess_sig.get_signature().to_vec();
+/// A payout step of a batch together with where the base node found it mined.
+struct MinedStep<'a> {
+    step: &'a TransactionStep,
+    tx: &'a StepTransaction,
+    tx_status: TxStatus,
+}
 
-    println!(
-        "DEBUG: Batch {}: Querying Base Node for Kernel Signature (Nonce start: {:?})",
+/// Looks up a payout transaction on the base node and returns its location if it is mined. A transaction missing
+/// for `rebroadcast_after_checks` checks in a row is resubmitted.
+async fn check_step(
+    base_node_client: &FailoverBaseNodeClient,
+    batch_id: &str,
+    step_index: usize,
+    tx: &StepTransaction,
+    rebroadcast_after_checks: u32,
+    missing_transactions: &mut MissingTransactions,
+) -> Result<Option<TxStatus>, anyhow::Error> {
+    let (excess_sig_nonce, excess_sig_sig) = tx.kernel_signature()?;
+
+    debug!(
+        target: LOG_TARGET,
+        "Batch {}: Querying Base Node for Kernel Signature of step {} (Nonce start: {:?})",
         batch_id,
+        step_index + 1,
         &excess_sig_nonce[0..4]
     );
 
-    let tx_query_response = base_node_client
-        .transaction_query(excess_sig_nonce, excess_sig_sig)
+    let tx_status = base_node_client
+        .call("transaction_query", |client| {
+            let (excess_sig_nonce, excess_sig_sig) = (excess_sig_nonce.clone(), excess_sig_sig.clone());
+            async move { client.transaction_query(excess_sig_nonce, excess_sig_sig).await }
+        })
         .await
         .context("Failed to query transaction from Base Node")?;
 
-    match tx_query_response.location {
+    let key = (batch_id.to_string(), step_index);
+    match tx_status.location {
         TxLocation::Mined => {
-            println!(
-                "INFO: Batch {}: Location 'Mined'. Processing confirmations...",
-                batch_id
-            );
-            handle_mined_transaction(
-                db_pool,
-                base_node_client,
-                batch_id,
-                &tx_query_response,
-                &signed_tx,
-                required_confirmations,
-            )
-            .await?
+            missing_transactions.remove(&key);
+            return Ok(Some(tx_status));
         },
         TxLocation::InMempool => {
-            println!("INFO: Batch {} is currently in the mempool, awaiting mining.", batch_id);
+            missing_transactions.remove(&key);
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Step {} is currently in the mempool, awaiting mining.",
+                batch_id,
+                step_index + 1
+            );
         },
         TxLocation::None | TxLocation::NotStored => {
-            println!(
-                "WARN: Batch {} location returned as '{:?}'.",
-                batch_id, tx_query_response.location
+            let missing = missing_transactions.entry(key).or_default();
+            missing.consecutive_misses += 1;
+            warn!(
+                target: LOG_TARGET,
+                "Batch {}: Step {} location returned as '{:?}' ({} consecutive checks).",
+                batch_id,
+                step_index + 1,
+                tx_status.location,
+                missing.consecutive_misses
             );
-            return Err(anyhow!(
-                "Transaction not found on Base Node (Location: {:?}). It may have been dropped or reorged.",
-                tx_query_response.location
-            ));
+
+            if missing.rebroadcasts >= MAX_REBROADCASTS {
+                return Err(anyhow!(
+                    "Transaction of step {} not found on Base Node (Location: {:?}) after {} rebroadcasts. It may \
+                     have been dropped or reorged.",
+                    step_index + 1,
+                    tx_status.location,
+                    missing.rebroadcasts
+                ));
+            }
+            if missing.consecutive_misses >= rebroadcast_after_checks {
+                // Most likely evicted from the mempool. Re-submitting the same signed transaction cannot spend
+                // funds twice.
+                missing.consecutive_misses = 0;
+                missing.rebroadcasts += 1;
+                info!(
+                    target: LOG_TARGET,
+                    "Batch {}: Rebroadcasting transaction of step {} (attempt {}/{}).",
+                    batch_id,
+                    step_index + 1,
+                    missing.rebroadcasts,
+                    MAX_REBROADCASTS
+                );
+                rebroadcast(base_node_client, batch_id, tx).await?;
+            }
         },
     }
 
+    Ok(None)
+}
+
+async fn rebroadcast(
+    base_node_client: &FailoverBaseNodeClient,
+    batch_id: &str,
+    tx: &StepTransaction,
+) -> Result<(), anyhow::Error> {
+    let response = base_node_client
+        .call("submit_transaction", |client| submit_to_node(client, tx.clone()))
+        .await
+        .context("Network error resubmitting transaction to Base Node")?;
+
+    if !response.accepted {
+        return Err(anyhow!(
+            "Tari base node rejected the rebroadcast transaction: {}",
+            response.rejection_reason
+        ));
+    }
+
+    info!(target: LOG_TARGET, "Batch {}: Rebroadcast transaction ACCEPTED by Base Node.", batch_id);
     Ok(())
 }
 
-async fn handle_mined_transaction(
+/// Confirms the batch once its least confirmed payout transaction has `required_confirmations`. The batch records
+/// the block of the transaction mined last, while every payment's payref is derived from the block of the
+/// transaction paying it.
+async fn handle_mined_transactions(
     db_pool: &SqlitePool,
-    base_node_client: &Client,
+    base_node_client: &FailoverBaseNodeClient,
+    chain_state: &ChainState,
     batch_id: &str,
-    tx_query_response: &tari_transaction_components::rpc::models::TxQueryResponse,
-    signed_tx: &SignedOneSidedTransactionResult,
+    mined_steps: &[MinedStep<'_>],
     required_confirmations: u64,
 ) -> Result<(), anyhow::Error> {
-    let mined_height = tx_query_response
-        .mined_height
-        .ok_or_else(|| anyhow!("Mined transaction missing mined_height"))?;
-
-    let tip_info = base_node_client
-        .get_tip_info()
-        .await
-        .context("Failed to get tip info from Base Node")?;
+    let mut last_mined: Option<(&TxStatus, u64)> = None;
+    for mined in mined_steps {
+        let mined_height = mined
+            .tx_status
+            .mined_height
+            .ok_or_else(|| anyhow!("Mined transaction missing mined_height"))?;
+        if last_mined.is_none_or(|(_, height)| mined_height > height) {
+            last_mined = Some((&mined.tx_status, mined_height));
+        }
+    }
+    let Some((last_mined, mined_height)) = last_mined else {
+        return Err(anyhow!("Batch {} has no mined payout transaction", batch_id));
+    };
 
-    let best_block_height = tip_info
-        .metadata
-        .ok_or_else(|| anyhow!("Tip info missing metadata"))?
-        .best_block_height();
+    let best_block_height = chain_state.tip(base_node_client).await?.height;
 
     let confirmations = best_block_height.saturating_sub(mined_height) + 1;
 
-    println!(
-        "INFO: Batch {}: Mined Height: {}, Tip Height: {}, Confirmations: {}/{}",
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Mined Height: {}, Tip Height: {}, Confirmations: {}/{}",
         batch_id, mined_height, best_block_height, confirmations, required_confirmations
     );
+    PaymentBatch::record_confirmations(
+        &mut *db_pool.acquire().await?,
+        batch_id,
+        confirmations,
+        best_block_height,
+    )
+    .await
+    .context("Failed to record confirmations")?;
 
     if confirmations >= required_confirmations {
-        println!(
-            "INFO: Batch {}: Confirmation threshold reached. Finalizing...",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Confirmation threshold reached. Finalizing...",
             batch_id
         );
 
-        let mined_header_hash = tx_query_response
+        let mined_header_hash = last_mined
             .mined_header_hash
             .clone()
             .ok_or_else(|| anyhow!("Mined transaction missing mined_header_hash"))?;
-        let mined_timestamp = tx_query_response
+        let mined_timestamp = last_mined
             .mined_timestamp
             .ok_or_else(|| anyhow!("Mined transaction missing mined_timestamp"))?;
 
         let mut tx = db_pool.begin().await.context("Failed to begin DB transaction")?;
 
-        PaymentBatch::update_to_confirmed(
-            &mut tx,
-            batch_id,
-            mined_height,
-            mined_header_hash.clone(),
-            mined_timestamp,
-        )
-        .await
-        .context("Failed to update batch to Confirmed")?;
+        PaymentBatch::update_to_confirmed(&mut tx, batch_id, mined_height, mined_header_hash, mined_timestamp)
+            .await
+            .context("Failed to update batch to Confirmed")?;
 
         let associated_payments = Payment::find_by_batch_id(&mut tx, batch_id)
             .await
             .context("Failed to fetch associated payments")?;
 
-        println!(
-            "INFO: Batch {}: Marking {} associated payments as confirmed.",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Marking {} associated payments as confirmed.",
             batch_id,
             associated_payments.len()
         );
 
-        let sent_hashes = &signed_tx.signed_transaction.sent_hashes;
-        anyhow::ensure!(
-            associated_payments.len() == sent_hashes.len(),
-            "Mismatch between associated payments count ({}) and sent hashes count ({})",
-            associated_payments.len(),
-            sent_hashes.len()
-        );
+        let mut payrefs = HashMap::new();
+        for mined in mined_steps {
+            // Steps prepared by older versions don't list their payments; they are the only payout step and pay
+            // all payments of the batch.
+            let payment_ids: Vec<&str> = if mined.step.payment_ids.is_empty() && mined_steps.len() == 1 {
+                associated_payments.iter().map(|p| p.id.as_str()).collect()
+            } else {
+                mined.step.payment_ids.iter().map(String::as_str).collect()
+            };
+
+            // Consolidation batches have no payments; their single output back to the account has nothing to
+            // confirm.
+            // Outputs split off the change are sent after the recipients.
+            let sent_hashes = mined.tx.sent_hashes()?;
+            let change_output_count = mined
+                .step
+                .expected
+                .as_ref()
+                .map_or(0, |expected| expected.change_outputs.len());
+            anyhow::ensure!(
+                payment_ids.is_empty() || payment_ids.len() + change_output_count == sent_hashes.len(),
+                "Mismatch between payments count ({}) and sent hashes count ({}) of step {}",
+                payment_ids.len(),
+                sent_hashes.len(),
+                mined.step.step_index + 1
+            );
+
+            let header_hash = mined
+                .tx_status
+                .mined_header_hash
+                .clone()
+                .ok_or_else(|| anyhow!("Mined transaction missing mined_header_hash"))?;
+            let header_hash = FixedHash::try_from(header_hash)?;
+            for (payment_id, sent_hash) in payment_ids.into_iter().zip(&sent_hashes) {
+                let payref = hex::encode(generate_payment_reference(&header_hash, sent_hash));
+                payrefs.insert(payment_id, payref);
+            }
+        }
 
-        let mined_header_hash = FixedHash::try_from(mined_header_hash)?;
-        for (payment, sent_hash) in associated_payments.iter().zip(sent_hashes) {
-            let payref = hex::encode(generate_payment_reference(&mined_header_hash, sent_hash));
-            Payment::update_payment_to_confirmed(&mut tx, &payment.id, &payref).await?;
+        for payment in &associated_payments {
+            let payref = payrefs
+                .get(payment.id.as_str())
+                .ok_or_else(|| anyhow!("Payment {} is not paid by any payout transaction", payment.id))?;
+            Payment::update_payment_to_confirmed(&mut tx, &payment.id, payref).await?;
         }
         tx.commit().await.context("Failed to commit DB transaction")?;
 
-        println!("INFO: Batch {} confirmed successfully and DB updated.", batch_id);
+        info!(target: LOG_TARGET, "Batch {} confirmed successfully and DB updated.", batch_id);
     } else {
-        println!(
-            "INFO: Batch {} awaiting more confirmations. (Current: {}, Required: {})",
+        info!(
+            target: LOG_TARGET,
+            "Batch {} awaiting more confirmations. (Current: {}, Required: {})",
             batch_id, confirmations, required_confirmations
         );
     }
@@ -0,0 +1,127 @@
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use tari_common::configuration::Network;
+use tokio::time::{self, Duration};
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+use crate::secrets::SecretString;
+use crate::workers::supervisor::Cycle;
+use crate::workers::transaction_signer::CliWallet;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::console_wallet_check";
+
+const DEFAULT_SLEEP_SECS: u64 = 5 * 60; // 5 minutes
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+/// Read-only command that needs the wallet database to be opened with the password.
+const CHECK_COMMAND: &str = "get-balance";
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConsoleWalletStatus {
+    /// Whether the console wallet could be started with the configured path, base path, network and password.
+    pub ok: bool,
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of the most recent console wallet check, shared with the API. `None` until the first check finished.
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleWalletHealth(Arc<RwLock<Option<ConsoleWalletStatus>>>);
+
+impl ConsoleWalletHealth {
+    pub fn current(&self) -> Option<ConsoleWalletStatus> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set(&self, status: ConsoleWalletStatus) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Some(status);
+    }
+}
+
+/// Runs the console wallet with a harmless command on start and then every `sleep_secs`, so a wrong path,
+/// base path, network or password shows up on `/health/ready` before the first batch needs signing.
+pub struct ConsoleWalletCheck {
+    wallet: CliWallet,
+    sleep_secs: u64,
+    health: ConsoleWalletHealth,
+}
+
+impl ConsoleWalletCheck {
+    pub fn new(
+        network: Network,
+        console_wallet_path: String,
+        console_wallet_base_path: String,
+        console_wallet_password: SecretString,
+        sleep_secs: Option<u64>,
+        health: ConsoleWalletHealth,
+    ) -> Self {
+        Self {
+            wallet: CliWallet {
+                network,
+                executable_path: console_wallet_path,
+                password: console_wallet_password,
+                base_path: console_wallet_base_path,
+                timeout: CHECK_TIMEOUT,
+            },
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            health,
+        }
+    }
+}
+
+impl Worker for ConsoleWalletCheck {
+    const NAME: &'static str = "console_wallet_check";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Console Wallet Check worker started. Checking every {} seconds.",
+            self.sleep_secs
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        cycle.add_items(1);
+        let was_ok = self.health.current().map(|status| status.ok);
+        let result = check(&self.wallet).await;
+        self.health.set(ConsoleWalletStatus {
+            ok: result.is_ok(),
+            checked_at: Utc::now(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        });
+        if result.is_ok() && was_ok != Some(true) {
+            info!(target: LOG_TARGET, "Console wallet check passed.");
+        }
+        result.context("Console wallet check failed")
+    }
+}
+
+async fn check(wallet: &CliWallet) -> Result<(), anyhow::Error> {
+    let mut cmd = wallet.command();
+    cmd.arg(CHECK_COMMAND);
+
+    let output = match time::timeout(wallet.timeout, cmd.output()).await {
+        Ok(output) => output.map_err(|e| anyhow!("Failed to start '{}': {}", wallet.executable_path, e))?,
+        Err(_) => return Err(anyhow!("Console wallet did not finish within {:?}", wallet.timeout)),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "Console wallet exited with {}: {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+
+    Ok(())
+}
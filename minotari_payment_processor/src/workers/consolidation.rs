@@ -0,0 +1,235 @@
+use anyhow::anyhow;
+use chrono::{Timelike, Utc};
+use log::{error, info};
+use minotari_client::apis::{Error as ApiError, configuration::Configuration};
+use minotari_client::models::LockFundsRequest;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tari_common::configuration::Network;
+use tari_transaction_components::transaction_components::WalletOutput;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::config::{ConsolidationConfig, NetworkRetry, PaymentReceiverAccount, SharedAccounts};
+use crate::db::payment::Payment;
+use crate::db::payment_batch::{BatchPayload, PaymentBatch};
+use crate::db::setting::ProcessingPause;
+use crate::payment_receiver;
+use crate::workers::supervisor::Cycle;
+use crate::workers::unsigned_tx_creator;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::consolidation";
+
+const DEFAULT_SLEEP_SECS: u64 = 60 * 60; // 1 hour
+
+/// During the configured quiet hours, locks the available balance of every idle account and, if the wallet is
+/// fragmented into at least `min_inputs` UTXOs, spends them back to the account in chunks of
+/// `max_input_count_per_tx`. Each chunk becomes a batch without payments that is signed, broadcast and confirmed
+/// like any other batch, so later payout batches find a few large UTXOs and rarely need a split cycle.
+///
+/// Accounts with RECEIVED payments or in-flight batches are skipped, so consolidation never competes with payouts.
+/// Paused accounts are skipped as well.
+pub struct Consolidation {
+    db_pool: SqlitePool,
+    client_config: Arc<Configuration>,
+    network_retry: NetworkRetry,
+    network: Network,
+    accounts: SharedAccounts,
+    config: ConsolidationConfig,
+    max_input_count_per_tx: usize,
+    sleep_secs: u64,
+    wakeups: WorkerWakeups,
+}
+
+impl Consolidation {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: SqlitePool,
+        client_config: Arc<Configuration>,
+        network_retry: NetworkRetry,
+        network: Network,
+        accounts: SharedAccounts,
+        config: ConsolidationConfig,
+        max_input_count_per_tx: usize,
+        sleep_secs: Option<u64>,
+        wakeups: WorkerWakeups,
+    ) -> Self {
+        Self {
+            db_pool,
+            client_config,
+            network_retry,
+            network,
+            accounts,
+            config,
+            max_input_count_per_tx,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            wakeups,
+        }
+    }
+}
+
+impl Worker for Consolidation {
+    const NAME: &'static str = "consolidation";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Consolidation worker started. Checking every {} seconds, quiet hours {}:00-{}:00 UTC, min inputs: {}.",
+            self.sleep_secs, self.config.quiet_hours_start, self.config.quiet_hours_end, self.config.min_inputs
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        if !self.config.is_quiet_hour(Utc::now().hour()) {
+            return Ok(());
+        }
+        for account in self.accounts.current().values() {
+            if shutdown.is_cancelled() {
+                break;
+            }
+            cycle.add_items(1);
+            if let Err(e) = consolidate_account(
+                &self.db_pool,
+                &self.client_config,
+                &self.network_retry,
+                self.network,
+                account,
+                &self.config,
+                self.max_input_count_per_tx,
+                &self.wakeups,
+            )
+            .await
+            {
+                error!(
+                    target: LOG_TARGET,
+                    "Consolidation of account '{}' failed: {:?}",
+                    account.name, e
+                );
+                cycle.fail(&e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn consolidate_account(
+    db_pool: &SqlitePool,
+    client_config: &Configuration,
+    network_retry: &NetworkRetry,
+    network: Network,
+    account: &PaymentReceiverAccount,
+    config: &ConsolidationConfig,
+    max_input_count_per_tx: usize,
+    wakeups: &WorkerWakeups,
+) -> Result<(), anyhow::Error> {
+    let mut conn = db_pool.acquire().await?;
+
+    if ProcessingPause::load(&mut conn).await?.is_account_paused(&account.name) {
+        info!(target: LOG_TARGET, "Account '{}': Processing is paused. Skipping consolidation.", account.name);
+        return Ok(());
+    }
+
+    let pending = Payment::pending_summary(&mut conn, &account.name).await?;
+    let in_flight = PaymentBatch::in_flight_summary(&mut conn, &account.name).await?;
+    if pending.payment_count > 0 || in_flight.batch_count > 0 {
+        info!(
+            target: LOG_TARGET,
+            "Account '{}': {} pending payments and {} in-flight batches. Skipping consolidation.",
+            account.name, pending.payment_count, in_flight.batch_count
+        );
+        return Ok(());
+    }
+    drop(conn);
+
+    let balance = payment_receiver::get_balance(client_config, network_retry, &account.name)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch balance from Payment Receiver: {}", e))?;
+    // The consolidation fees are paid out of the inputs, the buffer only keeps the lock request within the balance.
+    let amount_to_lock = balance.available - account.fee_buffer_amount;
+    if amount_to_lock <= 0 {
+        return Ok(());
+    }
+
+    let lock_key = Uuid::new_v4().to_string();
+    let lock_request = LockFundsRequest {
+        amount: amount_to_lock,
+        idempotency_key: Some(Some(lock_key.clone())),
+        seconds_to_lock_utxos: Some(Some(config.lock_secs)),
+        ..Default::default()
+    };
+    let locked_funds =
+        match payment_receiver::lock_funds(client_config, network_retry, &account.name, lock_request).await {
+            Ok(res) => res,
+            Err(ApiError::ResponseError(c)) => return Err(anyhow!("PR API Error: {} - {}", c.status, c.content)),
+            Err(e) => return Err(anyhow!("Network error calling PR API: {:?}", e)),
+        };
+
+    let inputs = locked_funds
+        .utxos
+        .into_iter()
+        .map(|utxo| {
+            serde_json::from_value::<WalletOutput>(utxo).map_err(|e| anyhow!("Failed to deserialize UTXO: {}", e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if inputs.len() < config.min_inputs {
+        // Nothing is spent, the lock expires after `lock_secs`.
+        info!(
+            target: LOG_TARGET,
+            "Account '{}': {} UTXOs, below the consolidation threshold of {}.",
+            account.name,
+            inputs.len(),
+            config.min_inputs
+        );
+        return Ok(());
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Account '{}': Consolidating {} UTXOs in chunks of {}.",
+        account.name,
+        inputs.len(),
+        max_input_count_per_tx
+    );
+
+    for (i, chunk) in inputs.chunks(max_input_count_per_tx.max(2)).enumerate() {
+        if chunk.len() < 2 {
+            continue;
+        }
+
+        let mut step = unsigned_tx_creator::create_self_spend_step(network, account, chunk.to_vec(), 0).await?;
+        // Unlike the split cycle of a payout batch, nothing is paid from the consolidated output afterwards, so the
+        // transaction is final and the batch is confirmed once it is mined.
+        step.is_consolidation = false;
+        let payload_json = BatchPayload { steps: vec![step] }.to_json()?;
+
+        let batch = PaymentBatch::create_consolidation(
+            &mut *db_pool.acquire().await?,
+            &account.name,
+            &format!("{}-{}", lock_key, i),
+            &payload_json,
+            chunk.len(),
+            account.required_confirmations,
+        )
+        .await?;
+
+        info!(
+            target: LOG_TARGET,
+            "Account '{}': Created consolidation batch {} spending {} UTXOs.",
+            account.name,
+            batch.id,
+            chunk.len()
+        );
+        wakeups.transaction_signer.notify_one();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use log::{info, warn};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::api::import::{self, IMPORT_CHUNK_SIZE, ImportFormat, ImportRowStatus};
+use crate::config::PaymentProcessorEnv;
+use crate::db::import_job::{ImportJob, ImportJobProgress, ImportJobRow, ImportJobStatus};
+use crate::workers::supervisor::Cycle;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::import_job_runner";
+
+const DEFAULT_SLEEP_SECS: u64 = 30;
+
+/// Imports the files submitted through `POST /v1/import-jobs`, one job at a time in submission order.
+///
+/// Rows are validated and created like those of `POST /v1/payments/import`, in chunks of `IMPORT_CHUNK_SIZE`. The
+/// payments of a chunk, its row results and the job's progress are stored in one transaction, so a job interrupted
+/// by a restart continues after the last stored chunk instead of starting over.
+pub struct ImportJobRunner {
+    db_pool: SqlitePool,
+    env: PaymentProcessorEnv,
+    sleep_secs: u64,
+    wakeups: WorkerWakeups,
+}
+
+impl ImportJobRunner {
+    pub fn new(db_pool: SqlitePool, env: PaymentProcessorEnv, sleep_secs: Option<u64>, wakeups: WorkerWakeups) -> Self {
+        Self {
+            db_pool,
+            env,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            wakeups,
+        }
+    }
+}
+
+impl Worker for ImportJobRunner {
+    const NAME: &'static str = "import_job_runner";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        Some(self.wakeups.import_job_runner.clone())
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Import Job Runner worker started. Polling every {} seconds.", self.sleep_secs
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        while !shutdown.is_cancelled() {
+            let Some(job) = ImportJob::find_next_pending(&mut *self.db_pool.acquire().await?).await? else {
+                break;
+            };
+            let rows = run_job(&self.db_pool, &self.env, &job, shutdown).await?;
+            cycle.add_items(rows);
+        }
+        Ok(())
+    }
+}
+
+/// Imports the remaining rows of `job` until it is finished or `shutdown` is cancelled. Returns the number of rows
+/// processed.
+async fn run_job(
+    db_pool: &SqlitePool,
+    env: &PaymentProcessorEnv,
+    job: &ImportJob,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let mut conn = db_pool.acquire().await?;
+    let Some(content) = ImportJob::content(&mut conn, &job.id).await? else {
+        ImportJob::fail(&mut conn, &job.id, "The import file is missing").await?;
+        return Ok(0);
+    };
+    let allowed_accounts = match job.allowed_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            ImportJob::fail(&mut conn, &job.id, &format!("Invalid allowed accounts: {}", e)).await?;
+            return Ok(0);
+        },
+    };
+    // The file was read when it was submitted, so this only fails if it was changed in the database since.
+    let rows = match import::parse_rows(ImportFormat::from(job.format.clone()), &content) {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(target: LOG_TARGET, "Import job {} failed: {}", job.id, e);
+            ImportJob::fail(&mut conn, &job.id, &e.to_string()).await?;
+            return Ok(0);
+        },
+    };
+    drop(content);
+
+    if job.status == ImportJobStatus::Queued {
+        ImportJob::mark_running(&mut conn, &job.id).await?;
+        info!(target: LOG_TARGET, "Import job {} started with {} rows.", job.id, rows.len());
+    } else {
+        info!(
+            target: LOG_TARGET,
+            "Import job {} resumed at row {} of {}.", job.id, job.processed_rows + 1, rows.len()
+        );
+    }
+    drop(conn);
+
+    let first_row = job.processed_rows as usize;
+    let mut remaining = rows.into_iter().skip(first_row);
+    let mut row_number = first_row + 1;
+    let mut processed = 0;
+    loop {
+        if shutdown.is_cancelled() {
+            return Ok(processed);
+        }
+        let chunk: Vec<_> = remaining.by_ref().take(IMPORT_CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        let chunk_len = chunk.len();
+        let (valid_rows, mut results) = import::validate_rows(
+            env,
+            allowed_accounts.as_deref(),
+            job.default_account_name.as_deref(),
+            row_number,
+            chunk,
+        );
+
+        let mut tx = db_pool.begin().await?;
+        results.extend(import::create_rows(&mut tx, &valid_rows).await?);
+        results.sort_by_key(|result| result.row);
+        let mut progress = ImportJobProgress::default();
+        for result in results {
+            match result.status {
+                ImportRowStatus::Created => progress.created += 1,
+                ImportRowStatus::Duplicate => progress.duplicates += 1,
+                ImportRowStatus::Invalid => progress.invalid += 1,
+            }
+            progress.rows.push(ImportJobRow {
+                row_number: result.row as i64,
+                client_id: result.client_id,
+                status: result.status.to_string(),
+                payment_id: result.payment_id,
+                error: result.error,
+            });
+        }
+        ImportJob::record_progress(&mut tx, &job.id, &progress).await?;
+        tx.commit().await?;
+
+        row_number += chunk_len;
+        processed += chunk_len;
+    }
+
+    ImportJob::complete(&mut *db_pool.acquire().await?, &job.id).await?;
+    info!(target: LOG_TARGET, "Import job {} completed.", job.id);
+    Ok(processed)
+}
@@ -0,0 +1,186 @@
+use anyhow::Context;
+use log::{error, info, warn};
+use minotari_client::apis::configuration::Configuration;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::NetworkRetry;
+use crate::db::idempotency_key::IdempotencyRecord;
+use crate::db::payment::Payment;
+use crate::db::payment_batch::PaymentBatch;
+use crate::db::superseded_pr_lock::SupersededPrLock;
+use crate::payment_receiver;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::maintenance";
+
+const DEFAULT_SLEEP_SECS: u64 = 60;
+/// How long stored responses of requests with an Idempotency-Key are replayed.
+const IDEMPOTENCY_KEY_RETENTION_SECS: i64 = 24 * 60 * 60;
+/// Reservations of requests that never completed, e.g. because the process stopped mid-request.
+const IDEMPOTENCY_KEY_ABANDONED_SECS: i64 = 5 * 60;
+/// Maximum number of batches whose Payment Receiver lock is released per cycle.
+const RELEASE_FUNDS_LIMIT: i64 = 50;
+
+/// Periodic housekeeping: cancels expired payments, releases the Payment Receiver locks of failed and cancelled
+/// batches and prunes stale idempotency keys.
+pub struct Maintenance {
+    db_pool: SqlitePool,
+    client_config: Arc<Configuration>,
+    network_retry: NetworkRetry,
+    sleep_secs: u64,
+}
+
+impl Maintenance {
+    pub fn new(
+        db_pool: SqlitePool,
+        client_config: Arc<Configuration>,
+        network_retry: NetworkRetry,
+        sleep_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            db_pool,
+            client_config,
+            network_retry,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+        }
+    }
+}
+
+impl Worker for Maintenance {
+    const NAME: &'static str = "maintenance";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(target: LOG_TARGET, "Maintenance worker started. Polling every {} seconds.", self.sleep_secs);
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        for result in [
+            cancel_expired_payments(&self.db_pool).await,
+            release_locked_funds(&self.db_pool, &self.client_config, &self.network_retry).await,
+            prune_idempotency_keys(&self.db_pool).await,
+        ] {
+            match result {
+                Ok(count) => cycle.add_items(count),
+                Err(e) => {
+                    error!(target: LOG_TARGET, "Maintenance worker error: {:?}", e);
+                    cycle.fail(&e);
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The maintenance tasks return the number of payments, batches or keys they handled.
+async fn cancel_expired_payments(db_pool: &SqlitePool) -> Result<usize, anyhow::Error> {
+    let mut conn = db_pool.acquire().await?;
+
+    let expired_payments = Payment::cancel_expired_payments(&mut conn)
+        .await
+        .context("Failed to cancel expired payments")?;
+
+    for payment in &expired_payments {
+        info!(
+            target: LOG_TARGET,
+            "Payment {} (client_id: {}, account: {}) expired at {:?} before being batched. Status set to 'Cancelled'.",
+            payment.id, payment.client_id, payment.account_name, payment.expires_at
+        );
+    }
+
+    Ok(expired_payments.len())
+}
+
+/// Releases the UTXOs locked for batches that failed or were cancelled, and the locks batches gave up when their
+/// payments changed, otherwise they stay unavailable to the hot wallet until the lock expires. A lock is only marked
+/// as released once the Payment Receiver confirmed it, and is retried on the next cycle otherwise.
+async fn release_locked_funds(
+    db_pool: &SqlitePool,
+    client_config: &Configuration,
+    network_retry: &NetworkRetry,
+) -> Result<usize, anyhow::Error> {
+    let batches = PaymentBatch::find_with_unreleased_funds(&mut *db_pool.acquire().await?, RELEASE_FUNDS_LIMIT)
+        .await
+        .context("Failed to fetch batches with locked funds")?;
+
+    for batch in &batches {
+        match payment_receiver::release_funds(
+            client_config,
+            network_retry,
+            &batch.account_name,
+            &batch.pr_idempotency_key,
+        )
+        .await
+        {
+            Ok(()) => {
+                PaymentBatch::mark_funds_released(&mut *db_pool.acquire().await?, &batch.id).await?;
+                info!(
+                    target: LOG_TARGET,
+                    "Released the funds locked for {} batch {} (account: {}).",
+                    batch.status, batch.id, batch.account_name
+                );
+            },
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Failed to release the funds locked for batch {}: {:?}",
+                batch.id, e
+            ),
+        }
+    }
+
+    let superseded = SupersededPrLock::find_unreleased(&mut *db_pool.acquire().await?, RELEASE_FUNDS_LIMIT)
+        .await
+        .context("Failed to fetch superseded locks")?;
+
+    for lock in &superseded {
+        match payment_receiver::release_funds(
+            client_config,
+            network_retry,
+            &lock.account_name,
+            &lock.pr_idempotency_key,
+        )
+        .await
+        {
+            Ok(()) => {
+                SupersededPrLock::delete(&mut *db_pool.acquire().await?, &lock.pr_idempotency_key).await?;
+                info!(
+                    target: LOG_TARGET,
+                    "Released the superseded lock {} of batch {} (account: {}).",
+                    lock.pr_idempotency_key, lock.batch_id, lock.account_name
+                );
+            },
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Failed to release the superseded lock {} of batch {}: {:?}",
+                lock.pr_idempotency_key, lock.batch_id, e
+            ),
+        }
+    }
+
+    Ok(batches.len() + superseded.len())
+}
+
+async fn prune_idempotency_keys(db_pool: &SqlitePool) -> Result<usize, anyhow::Error> {
+    let mut conn = db_pool.acquire().await?;
+
+    let deleted = IdempotencyRecord::delete_expired(
+        &mut conn,
+        IDEMPOTENCY_KEY_RETENTION_SECS,
+        IDEMPOTENCY_KEY_ABANDONED_SECS,
+    )
+    .await
+    .context("Failed to prune idempotency keys")?;
+
+    if deleted > 0 {
+        info!(target: LOG_TARGET, "Pruned {} stale idempotency keys.", deleted);
+    }
+
+    Ok(deleted as usize)
+}
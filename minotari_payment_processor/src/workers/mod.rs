@@ -1,6 +1,21 @@
+pub mod balance_monitor;
 pub mod batch_creator;
 pub mod broadcaster;
+pub mod chain_state;
+pub mod chat_notifier;
+pub mod coin_selection;
 pub mod confirmation_checker;
+pub mod console_wallet_check;
+pub mod consolidation;
+pub mod import_job_runner;
+pub mod maintenance;
+pub mod reconciliation;
+pub mod recovery;
+pub mod registry;
+pub mod supervisor;
 pub mod transaction_signer;
+pub mod tx_validation;
 pub mod types;
 pub mod unsigned_tx_creator;
+pub mod wakeup;
+pub mod worker;
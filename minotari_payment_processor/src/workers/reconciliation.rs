@@ -0,0 +1,178 @@
+use anyhow::{Context, anyhow};
+use log::{error, info, warn};
+use minotari_client::apis::configuration::Configuration;
+use serde_json::json;
+use sqlx::{Connection, SqlitePool};
+use std::sync::Arc;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{NetworkRetry, PaymentReceiverAccount, SharedAccounts};
+use crate::db::event::{Event, EventType};
+use crate::db::payment::Payment;
+use crate::db::payment_batch::PaymentBatch;
+use crate::db::reconciliation_report::{NewReconciliationReport, ReconciliationReport, ReconciliationStatus};
+use crate::payment_receiver;
+use crate::workers::supervisor::Cycle;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::reconciliation";
+
+const DEFAULT_SLEEP_SECS: u64 = 24 * 60 * 60; // 1 day
+const DEFAULT_DISCREPANCY_THRESHOLD: i64 = 1_000_000; // 1 T
+
+/// Periodically compares the balance the Payment Receiver reports for every account with the processor's view of
+/// the account's payments, stores a report per account and records a RECONCILIATION_DISCREPANCY event for accounts
+/// whose discrepancy exceeds `discrepancy_threshold` (in µT).
+///
+/// The locked balance is expected to cover the payments of in-flight batches, and at most their fee buffers on
+/// top. The discrepancy is how far the locked balance lies outside that range.
+pub struct Reconciliation {
+    db_pool: SqlitePool,
+    client_config: Arc<Configuration>,
+    network_retry: NetworkRetry,
+    accounts: SharedAccounts,
+    sleep_secs: u64,
+    discrepancy_threshold: i64,
+}
+
+impl Reconciliation {
+    pub fn new(
+        db_pool: SqlitePool,
+        client_config: Arc<Configuration>,
+        network_retry: NetworkRetry,
+        accounts: SharedAccounts,
+        sleep_secs: Option<u64>,
+        discrepancy_threshold: Option<i64>,
+    ) -> Self {
+        Self {
+            db_pool,
+            client_config,
+            network_retry,
+            accounts,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            discrepancy_threshold: discrepancy_threshold.unwrap_or(DEFAULT_DISCREPANCY_THRESHOLD),
+        }
+    }
+}
+
+impl Worker for Reconciliation {
+    const NAME: &'static str = "reconciliation";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Reconciliation worker started. Running every {} seconds. Discrepancy threshold: {} µT.",
+            self.sleep_secs, self.discrepancy_threshold
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, _shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        for account in self.accounts.current().values() {
+            cycle.add_items(1);
+            if let Err(e) = reconcile_account(
+                &self.db_pool,
+                &self.client_config,
+                &self.network_retry,
+                account,
+                self.discrepancy_threshold,
+            )
+            .await
+            {
+                error!(
+                    target: LOG_TARGET,
+                    "Reconciliation of account '{}' failed: {:?}",
+                    account.name, e
+                );
+                cycle.fail(&e);
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn reconcile_account(
+    db_pool: &SqlitePool,
+    client_config: &Configuration,
+    network_retry: &NetworkRetry,
+    account: &PaymentReceiverAccount,
+    discrepancy_threshold: i64,
+) -> Result<(), anyhow::Error> {
+    let balance = payment_receiver::get_balance(client_config, network_retry, &account.name)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch balance from Payment Receiver: {}", e))?;
+
+    let mut conn = db_pool.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    let in_flight = PaymentBatch::in_flight_summary(&mut tx, &account.name).await?;
+    let confirmed_amount = Payment::confirmed_total(&mut tx, &account.name).await?;
+
+    let expected_locked_min = in_flight.total_amount;
+    let expected_locked_max = in_flight.total_amount + in_flight.batch_count.saturating_mul(account.fee_buffer_amount);
+    let discrepancy = if balance.locked < expected_locked_min {
+        expected_locked_min - balance.locked
+    } else {
+        balance.locked.saturating_sub(expected_locked_max).max(0)
+    };
+    let status = if discrepancy > discrepancy_threshold {
+        ReconciliationStatus::Discrepancy
+    } else {
+        ReconciliationStatus::Ok
+    };
+
+    let report = ReconciliationReport::create(
+        &mut tx,
+        &NewReconciliationReport {
+            account_name: &account.name,
+            available: balance.available,
+            locked: balance.locked,
+            unconfirmed: balance.unconfirmed,
+            total: balance.total,
+            in_flight_batch_count: in_flight.batch_count,
+            in_flight_amount: in_flight.total_amount,
+            confirmed_amount,
+            discrepancy,
+            status,
+        },
+    )
+    .await
+    .context("Failed to store reconciliation report")?;
+
+    if status == ReconciliationStatus::Discrepancy {
+        Event::record(
+            &mut tx,
+            EventType::ReconciliationDiscrepancy,
+            &report.id.to_string(),
+            json!({
+                "account_name": account.name,
+                "locked": balance.locked,
+                "expected_locked_min": expected_locked_min,
+                "expected_locked_max": expected_locked_max,
+                "discrepancy": discrepancy,
+            }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    match status {
+        ReconciliationStatus::Ok => info!(
+            target: LOG_TARGET,
+            "Account '{}' reconciled. Locked: {} µT, in flight: {} µT in {} batches.",
+            account.name, balance.locked, in_flight.total_amount, in_flight.batch_count
+        ),
+        ReconciliationStatus::Discrepancy => warn!(
+            target: LOG_TARGET,
+            "Account '{}' has a discrepancy of {} µT. Locked: {} µT, expected between {} and {} µT. Report {}.",
+            account.name, discrepancy, balance.locked, expected_locked_min, expected_locked_max, report.id
+        ),
+    }
+
+    Ok(())
+}
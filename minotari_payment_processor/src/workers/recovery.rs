@@ -0,0 +1,167 @@
+use anyhow::{Context, anyhow};
+use log::{error, info, warn};
+use sqlx::SqlitePool;
+use tari_transaction_components::rpc::models::TxLocation;
+
+use crate::base_node::FailoverBaseNodeClient;
+use crate::db::payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus};
+use crate::workers::broadcaster::query_tx_location;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::recovery";
+
+/// Where the transactions of an interrupted broadcast ended up.
+enum BroadcastState {
+    /// Every step is in the mempool or mined.
+    Complete { is_consolidation: bool },
+    /// No step reached the network.
+    NotBroadcast,
+    /// Only some steps reached the network.
+    Partial { found: usize, total: usize },
+}
+
+/// Moves 'SIGNING_IN_PROGRESS' batches that a previous process left behind back to 'AWAITING_SIGNATURE'. Signing has
+/// no side effects.
+///
+/// Must run before the transaction signer is spawned, and only in the process that runs it: another process may be
+/// signing these batches right now.
+pub async fn recover_interrupted_signing(db_pool: &SqlitePool) -> Result<(), anyhow::Error> {
+    let signing =
+        PaymentBatch::find_by_status(&mut *db_pool.acquire().await?, PaymentBatchStatus::SigningInProgress).await?;
+    for batch in signing {
+        PaymentBatch::recover_to_status(
+            &mut *db_pool.acquire().await?,
+            &batch.id,
+            PaymentBatchStatus::AwaitingSignature,
+            "Signing was interrupted by a restart",
+        )
+        .await
+        .with_context(|| format!("Failed to recover batch {}", batch.id))?;
+        warn!(
+            target: LOG_TARGET,
+            "Batch {} was left in 'SigningInProgress'. Reverted to 'AwaitingSignature'.",
+            batch.id
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks 'BROADCASTING' batches that a previous process left behind against the base node. If all transactions
+/// reached the network, the batch continues as if the broadcast had finished. If none did, or the base node cannot be
+/// reached, it goes back to 'AWAITING_BROADCAST' (resubmitting the same signed transactions cannot spend funds twice).
+/// If only some did, it is moved to 'NEEDS_REVIEW'.
+///
+/// Must run before the broadcaster is spawned, and only in the process that runs it: another process may be
+/// broadcasting these batches right now.
+pub async fn recover_interrupted_broadcasts(
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+) -> Result<(), anyhow::Error> {
+    let broadcasting =
+        PaymentBatch::find_by_status(&mut *db_pool.acquire().await?, PaymentBatchStatus::Broadcasting).await?;
+    for batch in broadcasting {
+        recover_broadcasting_batch(db_pool, base_node_client, &batch)
+            .await
+            .with_context(|| format!("Failed to recover batch {}", batch.id))?;
+    }
+
+    Ok(())
+}
+
+async fn recover_broadcasting_batch(
+    db_pool: &SqlitePool,
+    base_node_client: &FailoverBaseNodeClient,
+    batch: &PaymentBatch,
+) -> Result<(), anyhow::Error> {
+    let state = match broadcast_state(base_node_client, batch).await {
+        Ok(state) => state,
+        Err(e) => {
+            error!(
+                target: LOG_TARGET,
+                "Batch {}: Could not verify the interrupted broadcast: {:?}. Falling back to a rebroadcast.",
+                batch.id, e
+            );
+            BroadcastState::NotBroadcast
+        },
+    };
+
+    match state {
+        BroadcastState::Complete { is_consolidation: true } => {
+            PaymentBatch::reset_to_pending_batching(&mut *db_pool.acquire().await?, &batch.id).await?;
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Interrupted split broadcast had reached the network. Continuing with Cycle 2.",
+                batch.id
+            );
+        },
+        BroadcastState::Complete {
+            is_consolidation: false,
+        } => {
+            PaymentBatch::update_to_awaiting_confirmation(&mut *db_pool.acquire().await?, &batch.id).await?;
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Interrupted broadcast had reached the network. Moved to 'AwaitingConfirmation'.",
+                batch.id
+            );
+        },
+        BroadcastState::NotBroadcast => {
+            PaymentBatch::recover_to_status(
+                &mut *db_pool.acquire().await?,
+                &batch.id,
+                PaymentBatchStatus::AwaitingBroadcast,
+                "Broadcast was interrupted by a restart",
+            )
+            .await?;
+            warn!(
+                target: LOG_TARGET,
+                "Batch {}: Interrupted broadcast did not reach the network. Reverted to 'AwaitingBroadcast'.",
+                batch.id
+            );
+        },
+        BroadcastState::Partial { found, total } => {
+            let reason = format!(
+                "Broadcast was interrupted by a restart after {} of {} transactions reached the network",
+                found, total
+            );
+            PaymentBatch::update_to_needs_review(&mut *db_pool.acquire().await?, &batch.id, &reason).await?;
+            error!(
+                target: LOG_TARGET,
+                "Batch {}: {}. Moved to 'NeedsReview'. Manual resolution required.",
+                batch.id, reason
+            );
+        },
+    }
+
+    Ok(())
+}
+
+async fn broadcast_state(
+    base_node_client: &FailoverBaseNodeClient,
+    batch: &PaymentBatch,
+) -> Result<BroadcastState, anyhow::Error> {
+    let signed_json = batch
+        .signed_tx_json
+        .as_deref()
+        .ok_or_else(|| anyhow!("Batch {} has no signed_tx_json", batch.id))?;
+    let payload = BatchPayload::from_json(signed_json)?;
+    let is_consolidation = payload.steps.first().map(|s| s.is_consolidation).unwrap_or(false);
+
+    let mut found = 0;
+    for (i, step) in payload.steps.iter().enumerate() {
+        let tx = step.transaction()?.ok_or_else(|| anyhow!("Step {} is not signed", i))?;
+
+        match query_tx_location(base_node_client, &tx).await? {
+            TxLocation::InMempool | TxLocation::Mined => found += 1,
+            TxLocation::NotStored | TxLocation::None => {},
+        }
+    }
+
+    Ok(match found {
+        0 => BroadcastState::NotBroadcast,
+        n if n == payload.steps.len() => BroadcastState::Complete { is_consolidation },
+        n => BroadcastState::Partial {
+            found: n,
+            total: payload.steps.len(),
+        },
+    })
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, bail};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use crate::workers::supervisor::{Supervisor, WorkerHealth};
+use crate::workers::worker::{self, Worker};
+
+type SpawnFn = Box<dyn Fn(&mut Supervisor) + Send + Sync>;
+
+struct RegisteredWorker {
+    name: &'static str,
+    spawn: SpawnFn,
+}
+
+/// The workers available to a service, started and stopped by name under one `Supervisor`.
+///
+/// A worker is registered with a function that creates it, so every start, and every restart after a panic, begins
+/// with fresh worker state.
+pub struct WorkerRegistry {
+    supervisor: Supervisor,
+    workers: Vec<RegisteredWorker>,
+}
+
+impl WorkerRegistry {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        Self {
+            supervisor: Supervisor::new(shutdown),
+            workers: Vec::new(),
+        }
+    }
+
+    /// Registers a worker. Registering another worker with the same name replaces it.
+    pub fn register<W, F>(&mut self, create: F)
+    where
+        W: Worker,
+        F: Fn() -> W + Send + Sync + 'static,
+    {
+        let create = Arc::new(create);
+        let spawn: SpawnFn = Box::new(move |supervisor| {
+            let create = create.clone();
+            supervisor.spawn(W::NAME, move |heartbeat, shutdown| {
+                worker::run(create(), heartbeat, shutdown)
+            });
+        });
+        match self.workers.iter_mut().find(|registered| registered.name == W::NAME) {
+            Some(registered) => registered.spawn = spawn,
+            None => self.workers.push(RegisteredWorker { name: W::NAME, spawn }),
+        }
+    }
+
+    /// Names of the registered workers, in registration order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.workers.iter().map(|registered| registered.name).collect()
+    }
+
+    pub fn health(&self) -> WorkerHealth {
+        self.supervisor.health()
+    }
+
+    /// Starts every registered worker accepted by `filter` that isn't running yet, in registration order.
+    pub fn start_all(&mut self, filter: impl Fn(&str) -> bool) {
+        for registered in &self.workers {
+            if filter(registered.name) && !self.supervisor.is_running(registered.name) {
+                (registered.spawn)(&mut self.supervisor);
+            }
+        }
+    }
+
+    /// Starts a registered worker. Fails if it is unknown or still running.
+    pub fn start(&mut self, name: &str) -> Result<(), anyhow::Error> {
+        let registered = self
+            .workers
+            .iter()
+            .find(|registered| registered.name == name)
+            .ok_or_else(|| anyhow!("Unknown worker '{}'", name))?;
+        if self.supervisor.is_running(name) {
+            bail!("Worker '{}' is already running", name);
+        }
+        (registered.spawn)(&mut self.supervisor);
+        Ok(())
+    }
+
+    /// Signals a worker to stop, see `Supervisor::stop`.
+    pub fn stop(&self, name: &str) -> bool {
+        self.supervisor.stop(name)
+    }
+
+    /// Waits until every started worker has exited.
+    pub async fn join_all(self) {
+        self.supervisor.join_all().await;
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use serde::Serialize;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tokio::task::JoinSet;
+use tokio::time::{self, Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::supervisor";
+
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct WorkerStatus {
+    /// Whether the worker task is currently alive (it is `false` only after shutdown or once the worker was stopped).
+    pub running: bool,
+    /// Timestamp of the last completed worker cycle.
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    /// How many times the worker was restarted after a panic.
+    pub restart_count: u64,
+    /// Message of the most recent panic, if any.
+    pub last_panic: Option<String>,
+    /// Number of completed cycles, successful or not.
+    pub cycles: u64,
+    /// Duration of the last completed cycle in milliseconds.
+    pub last_cycle_duration_ms: Option<u64>,
+    /// Items (e.g. batches or accounts) handled by the last completed cycle.
+    pub last_cycle_items: u64,
+    /// Items handled by all cycles since the service started.
+    pub items_processed: u64,
+    /// Cycles in a row that reported an error. Reset by a successful cycle.
+    pub consecutive_failures: u64,
+    /// Timestamp of the last cycle that completed without an error.
+    pub last_success: Option<DateTime<Utc>>,
+    /// Error of the last failed cycle, kept until the next successful one.
+    pub last_error: Option<String>,
+}
+
+/// Shared, thread-safe view of the state of all supervised workers.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerHealth {
+    workers: Arc<RwLock<BTreeMap<&'static str, WorkerStatus>>>,
+}
+
+impl WorkerHealth {
+    /// Returns a point-in-time copy of every worker's status, ordered by worker name.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, WorkerStatus> {
+        self.workers.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn is_running(&self, name: &str) -> bool {
+        self.workers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .is_some_and(|status| status.running)
+    }
+
+    fn update(&self, name: &'static str, f: impl FnOnce(&mut WorkerStatus)) {
+        let mut workers = self.workers.write().unwrap_or_else(|e| e.into_inner());
+        f(workers.entry(name).or_default());
+    }
+}
+
+/// Handle passed to a worker so it can report that its loop is still making progress.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    name: &'static str,
+    health: WorkerHealth,
+}
+
+impl Heartbeat {
+    /// Starts measuring a worker cycle. The cycle is recorded when `Cycle::finish` is called.
+    pub fn cycle_started(&self) -> Cycle<'_> {
+        Cycle {
+            heartbeat: self,
+            started: Instant::now(),
+            items: 0,
+            error: None,
+        }
+    }
+}
+
+/// A worker cycle in progress, see `Heartbeat::cycle_started`.
+#[must_use = "a cycle is only recorded once it is finished"]
+pub struct Cycle<'a> {
+    heartbeat: &'a Heartbeat,
+    started: Instant,
+    items: u64,
+    error: Option<String>,
+}
+
+impl Cycle<'_> {
+    pub fn add_items(&mut self, count: usize) {
+        self.items += count as u64;
+    }
+
+    /// Marks the cycle as failed. With several errors, the last one is kept.
+    pub fn fail(&mut self, error: &anyhow::Error) {
+        self.error = Some(format!("{:#}", error));
+    }
+
+    pub fn finish(self) {
+        let now = Utc::now();
+        let duration_ms = self.started.elapsed().as_millis() as u64;
+        self.heartbeat.health.update(self.heartbeat.name, |status| {
+            status.last_heartbeat = Some(now);
+            status.cycles += 1;
+            status.last_cycle_duration_ms = Some(duration_ms);
+            status.last_cycle_items = self.items;
+            status.items_processed += self.items;
+            match self.error {
+                Some(error) => {
+                    status.consecutive_failures += 1;
+                    status.last_error = Some(error);
+                },
+                None => {
+                    status.consecutive_failures = 0;
+                    status.last_success = Some(now);
+                    status.last_error = None;
+                },
+            }
+        });
+    }
+}
+
+/// Owns all worker tasks, restarting any worker whose loop panics until shutdown is requested.
+///
+/// Every worker gets its own child of the shutdown token, so it can also be stopped on its own.
+pub struct Supervisor {
+    tasks: JoinSet<()>,
+    shutdown: CancellationToken,
+    health: WorkerHealth,
+    worker_shutdowns: HashMap<&'static str, CancellationToken>,
+}
+
+impl Supervisor {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            shutdown,
+            health: WorkerHealth::default(),
+            worker_shutdowns: HashMap::new(),
+        }
+    }
+
+    pub fn health(&self) -> WorkerHealth {
+        self.health.clone()
+    }
+
+    /// Whether a worker with this name is alive. A stopped worker counts as running until it has exited.
+    pub fn is_running(&self, name: &str) -> bool {
+        self.health.is_running(name)
+    }
+
+    /// Spawns a supervised worker. `worker` is invoked again to create a fresh worker loop after a panic, and gets
+    /// the token that stops this worker.
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, worker: F)
+    where
+        F: Fn(Heartbeat, CancellationToken) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // Drop the results of workers that were stopped, so they don't pile up until `join_all`.
+        while self.tasks.try_join_next().is_some() {}
+
+        let heartbeat = Heartbeat {
+            name,
+            health: self.health.clone(),
+        };
+        let health = self.health.clone();
+        let shutdown = self.shutdown.child_token();
+        self.worker_shutdowns.insert(name, shutdown.clone());
+
+        health.update(name, |status| status.running = true);
+
+        self.tasks.spawn(async move {
+            loop {
+                match tokio::spawn(worker(heartbeat.clone(), shutdown.clone())).await {
+                    Ok(()) => break,
+                    Err(e) if e.is_panic() => {
+                        let reason = panic_message(e.into_panic());
+                        error!(
+                            target: LOG_TARGET,
+                            "Worker '{}' panicked: {}. Restarting in {:?}.",
+                            name, reason, RESTART_DELAY
+                        );
+                        health.update(name, |status| {
+                            status.restart_count += 1;
+                            status.last_panic = Some(reason);
+                        });
+                    },
+                    Err(e) => {
+                        error!(target: LOG_TARGET, "Worker '{}' task was cancelled: {:?}", name, e);
+                        break;
+                    },
+                }
+
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = time::sleep(RESTART_DELAY) => {},
+                }
+                info!(target: LOG_TARGET, "Restarting worker '{}'.", name);
+            }
+
+            health.update(name, |status| status.running = false);
+        });
+    }
+
+    /// Signals a single worker to stop. Like on shutdown, it finishes what it is working on first. Returns `false`
+    /// if no worker with this name was spawned.
+    pub fn stop(&self, name: &str) -> bool {
+        match self.worker_shutdowns.get(name) {
+            Some(shutdown) => {
+                shutdown.cancel();
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Waits until every supervised worker has exited.
+    pub async fn join_all(mut self) {
+        while let Some(result) = self.tasks.join_next().await {
+            if let Err(e) = result {
+                error!(target: LOG_TARGET, "Supervisor task terminated abnormally: {:?}", e);
+            }
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
@@ -1,125 +1,227 @@
 use anyhow::{Context, anyhow};
+use futures::{StreamExt, TryStreamExt, stream};
+use log::{debug, error, info, warn};
 use sqlx::{SqliteConnection, SqlitePool};
 use std::io::Write;
+use std::sync::Arc;
 use tari_common::configuration::Network;
 use tari_transaction_components::key_manager::SerializedKeyString;
 use tari_transaction_components::key_manager::TariKeyId;
 use tari_transaction_components::offline_signing::models::SignedOneSidedTransactionResult;
 use tari_transaction_components::offline_signing::models::TransactionResult;
+use tari_transaction_components::transaction_components::WalletOutput;
 use tempfile::NamedTempFile;
 use tokio::fs;
 use tokio::process::Command;
-use tokio::time::{self, Duration};
+use tokio::sync::Notify;
+use tokio::time::{self, Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
-use crate::db::payment_batch::StepPayload;
+use crate::config::RetryPolicies;
 use crate::db::payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus};
+use crate::db::payment_batch::{StepPayload, StepTransaction, TransactionStep};
+use crate::db::signing_attempt::{NewSigningAttempt, SigningAttempt};
+use crate::secrets::SecretString;
+use crate::simulation::SimulatedTransaction;
+use crate::workers::supervisor::Cycle;
 use crate::workers::types::IntermediateContext;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::transaction_signer";
 
 const DEFAULT_SLEEP_SECS: u64 = 10;
+const DEFAULT_MAX_PARALLELISM: usize = 1;
+const DEFAULT_SIGNING_TIMEOUT_SECS: u64 = 5 * 60; // 5 minutes
 
-pub async fn run(
+pub struct TransactionSigner {
     db_pool: SqlitePool,
-    network: Network,
-    console_wallet_path: String,
-    console_wallet_base_path: String,
-    console_wallet_password: String,
-    sleep_secs: Option<u64>,
-) {
-    let sleep_secs = sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS);
-    println!(
-        "Transaction Signer worker started. Polling every {} seconds.",
-        sleep_secs
-    );
+    signer: Signer,
+    sleep_secs: u64,
+    max_parallelism: usize,
+    signing_timeout: Duration,
+    retry_policies: RetryPolicies,
+    batches_per_cycle: i64,
+    wakeups: WorkerWakeups,
+}
 
-    let mut interval = time::interval(Duration::from_secs(sleep_secs));
+impl TransactionSigner {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: SqlitePool,
+        network: Network,
+        console_wallet_path: String,
+        console_wallet_base_path: String,
+        console_wallet_password: SecretString,
+        simulation: bool,
+        sleep_secs: Option<u64>,
+        max_parallelism: Option<usize>,
+        signing_timeout_secs: Option<u64>,
+        retry_policies: RetryPolicies,
+        batches_per_cycle: i64,
+        wakeups: WorkerWakeups,
+    ) -> Self {
+        let signing_timeout = Duration::from_secs(signing_timeout_secs.unwrap_or(DEFAULT_SIGNING_TIMEOUT_SECS));
+        let signer = if simulation {
+            Signer::Simulated
+        } else {
+            Signer::ConsoleWallet(CliWallet {
+                network,
+                executable_path: console_wallet_path,
+                password: console_wallet_password,
+                base_path: console_wallet_base_path,
+                timeout: signing_timeout,
+            })
+        };
+        Self {
+            db_pool,
+            signer,
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            max_parallelism: max_parallelism.unwrap_or(DEFAULT_MAX_PARALLELISM).max(1),
+            signing_timeout,
+            retry_policies,
+            batches_per_cycle,
+            wakeups,
+        }
+    }
+}
 
-    loop {
-        interval.tick().await;
-        if let Err(e) = process_transactions_to_sign(
-            &db_pool,
-            network,
-            &console_wallet_path,
-            &console_wallet_base_path,
-            &console_wallet_password,
-        )
-        .await
-        {
-            eprintln!("Transaction Signer worker error: {:?}", e);
+impl Worker for TransactionSigner {
+    const NAME: &'static str = "transaction_signer";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        Some(self.wakeups.transaction_signer.clone())
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Transaction Signer worker started. Polling every {} seconds. Max parallel signings: {}. Signing timeout: \
+             {:?}",
+            self.sleep_secs, self.max_parallelism, self.signing_timeout
+        );
+        if matches!(self.signer, Signer::Simulated) {
+            warn!(
+                target: LOG_TARGET,
+                "Simulation mode: transactions get fake signatures instead of being signed by the console wallet."
+            );
         }
     }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let count = process_transactions_to_sign(
+            &self.db_pool,
+            &self.signer,
+            self.max_parallelism,
+            &self.retry_policies,
+            self.batches_per_cycle,
+            &self.wakeups,
+            shutdown,
+        )
+        .await?;
+        cycle.add_items(count);
+        Ok(())
+    }
 }
 
 async fn process_transactions_to_sign(
     db_pool: &SqlitePool,
-    network: Network,
-    console_wallet_path: &str,
-    console_wallet_base_path: &str,
-    console_wallet_password: &str,
-) -> Result<(), anyhow::Error> {
-    let mut conn = db_pool.acquire().await?;
-
-    let batches = PaymentBatch::find_by_status(&mut conn, PaymentBatchStatus::AwaitingSignature).await?;
+    signer: &Signer,
+    max_parallelism: usize,
+    retry_policies: &RetryPolicies,
+    batches_per_cycle: i64,
+    wakeups: &WorkerWakeups,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let batches = PaymentBatch::find_by_status_fair(
+        &mut *db_pool.acquire().await?,
+        PaymentBatchStatus::AwaitingSignature,
+        batches_per_cycle,
+        &[],
+    )
+    .await?;
+    // A full cycle means more batches are probably waiting, so run again right away instead of sleeping.
+    if batches.len() as i64 >= batches_per_cycle {
+        wakeups.transaction_signer.notify_one();
+    }
 
     if !batches.is_empty() {
-        println!("INFO: Found {} batches awaiting signature.", batches.len());
+        info!(target: LOG_TARGET, "Found {} batches awaiting signature.", batches.len());
     }
+    let batch_count = batches.len();
 
     for batch in batches {
-        if let Err(e) = process_single_batch(
-            &mut conn,
-            network,
-            console_wallet_path,
-            console_wallet_base_path,
-            console_wallet_password,
-            &batch,
-        )
-        .await
-        {
-            let error_message = format!("{:#}", e);
-            eprintln!(
-                "Error signing batch {}: {}. Attempting to revert status...",
-                batch.id, error_message
-            );
+        if shutdown.is_cancelled() {
+            info!(target: LOG_TARGET, "Shutdown requested. Leaving remaining batches for the next run.");
+            break;
+        }
 
-            let revert_result = if let Some(json) = &batch.unsigned_tx_json {
-                PaymentBatch::update_to_awaiting_signature(&mut conn, &batch.id, json).await
-            } else {
-                Err(anyhow::anyhow!("Cannot revert: Batch missing unsigned_tx_json"))?
-            };
+        match process_single_batch(db_pool, signer, &batch, max_parallelism).await {
+            Ok(()) => wakeups.broadcaster.notify_one(),
+            Err(e) => {
+                let error_message = format!("{:#}", e);
+                error!(
+                    target: LOG_TARGET,
+                    "Error signing batch {}: {}. Attempting to revert status...",
+                    batch.id, error_message
+                );
 
-            match revert_result {
-                Ok(_) => println!("INFO: Batch {} reverted to 'AwaitingSignature'.", batch.id),
-                Err(revert_e) => eprintln!("CRITICAL: Failed to revert batch {} status: {:?}", batch.id, revert_e),
-            }
+                let revert_result = if let Some(json) = &batch.unsigned_tx_json {
+                    PaymentBatch::update_to_awaiting_signature(&mut *db_pool.acquire().await?, &batch.id, json, None)
+                        .await
+                } else {
+                    Err(anyhow::anyhow!("Cannot revert: Batch missing unsigned_tx_json"))?
+                };
 
-            if let Err(db_err) = PaymentBatch::increment_retry_count(&mut conn, &batch.id, &error_message).await {
-                eprintln!(
-                    "CRITICAL: Failed to update retry count for batch {}: {:?}",
-                    batch.id, db_err
-                );
-            }
+                match revert_result {
+                    Ok(_) => info!(target: LOG_TARGET, "Batch {} reverted to 'AwaitingSignature'.", batch.id),
+                    Err(revert_e) => {
+                        error!(target: LOG_TARGET, "Failed to revert batch {} status: {:?}", batch.id, revert_e)
+                    },
+                }
+
+                let retry_policy = retry_policies.for_account(&batch.account_name);
+                if let Err(db_err) = PaymentBatch::increment_retry_count(
+                    &mut *db_pool.acquire().await?,
+                    &batch.id,
+                    &error_message,
+                    retry_policy,
+                )
+                .await
+                {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to update retry count for batch {}: {:?}",
+                        batch.id, db_err
+                    );
+                }
+            },
         }
     }
 
-    Ok(())
+    Ok(batch_count)
 }
 
+/// Signs the steps of `batch`. No connection is held while the console wallet runs, so the other workers can keep
+/// writing in the meantime.
 async fn process_single_batch(
-    conn: &mut SqliteConnection,
-    network: Network,
-    console_wallet_path: &str,
-    console_wallet_base_path: &str,
-    console_wallet_password: &str,
+    db_pool: &SqlitePool,
+    signer: &Signer,
     batch: &PaymentBatch,
+    max_parallelism: usize,
 ) -> Result<(), anyhow::Error> {
     let batch_id = &batch.id;
-    println!("INFO: Starting processing for Batch ID: {}", batch_id);
+    info!(target: LOG_TARGET, "Starting processing for Batch ID: {}", batch_id);
 
-    PaymentBatch::update_to_signing_in_progress(conn, batch_id)
+    PaymentBatch::update_to_signing_in_progress(&mut *db_pool.acquire().await?, batch_id, batch.version)
         .await
         .context("Failed to update status to SigningInProgress")?;
 
-    println!("INFO: Batch {}: Status updated to 'SigningInProgress'.", batch_id);
+    info!(target: LOG_TARGET, "Batch {}: Status updated to 'SigningInProgress'.", batch_id);
 
     let unsigned_json_str = batch
         .unsigned_tx_json
@@ -129,110 +231,203 @@ async fn process_single_batch(
     let mut payload = BatchPayload::from_json(&unsigned_json_str)?;
     let steps_count = payload.steps.len();
 
-    println!("INFO: Batch {}: Found {} steps to sign.", batch_id, payload.steps.len());
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Found {} steps to sign. Parallelism: {}",
+        batch_id,
+        steps_count,
+        max_parallelism.min(steps_count.max(1))
+    );
 
-    let mut consolidated_wallet_outputs = vec![];
-    for (i, step) in payload.steps.iter_mut().enumerate() {
-        println!(
-            "INFO: Batch {}: Signing Step {}/{} (ID: {})",
-            batch_id,
-            i + 1,
-            steps_count,
-            step.tx_id
-        );
+    // Each step is signed by its own console wallet process with its own temp files, so independent
+    // steps (e.g. the consolidation transactions of a split cycle) can be signed concurrently.
+    // `buffered` keeps the results in step order.
+    let signed_payloads: Vec<StepPayload> = stream::iter(
+        payload
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| sign_step(db_pool, signer, batch_id, i, steps_count, step)),
+    )
+    .buffered(max_parallelism)
+    .try_collect()
+    .await?;
+
+    for (step, signed_payload) in payload.steps.iter_mut().zip(signed_payloads) {
+        step.payload = signed_payload;
+    }
 
-        let unsigned_json = match &step.payload {
-            StepPayload::Unsigned(s) => s,
-            StepPayload::Signed(_) => return Err(anyhow!("Step {} is already signed!", i)),
-        };
+    info!(target: LOG_TARGET, "Batch {}: All steps signed successfully.", batch_id);
 
-        let mut input_file = NamedTempFile::with_prefix(format!("unsigned-tx-{}-step{}-", batch_id, i))
-            .context("Failed to create temp input file")?;
-        let input_path = input_file.path().to_path_buf();
-
-        input_file
-            .write_all(unsigned_json.as_bytes())
-            .context("Failed to write unsigned tx to temp file")?;
-        input_file.flush().context("Failed to flush input file")?;
-
-        let output_file = NamedTempFile::with_prefix(format!("signed-tx-{}-step{}-", batch_id, i))
-            .context("Failed to create temp output file")?;
-        let output_path = output_file.path().to_path_buf();
-
-        sign_with_cli(
-            network,
-            console_wallet_path,
-            console_wallet_password,
-            console_wallet_base_path,
-            &input_path,
-            &output_path,
-        )
-        .await
-        .context(format!("External signing process failed for step {}", i))?;
+    store_signed_payload(&mut *db_pool.acquire().await?, batch, &payload).await?;
 
-        let signed_json = fs::read_to_string(&output_path)
-            .await
-            .context("Failed to read signed transaction from output file")?;
-        let signed_tx_wrapper = SignedOneSidedTransactionResult::from_json(&signed_json)
-            .map_err(|e| anyhow!("Failed to deserialize signed tx for step {}: {}", i, e))?;
-
-        if step.is_consolidation {
-            for output in &signed_tx_wrapper.signed_transaction.outputs {
-                let mut cloned_output = output.clone();
-                let script_key_id = TariKeyId::Derived {
-                    key: SerializedKeyString::from(output.commitment_mask_key_id().to_string()),
-                };
-                cloned_output.set_script_key_id(script_key_id);
-                consolidated_wallet_outputs.push(cloned_output);
-            }
-        }
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Status updated to 'AwaitingBroadcast'. Processing complete.",
+        batch_id
+    );
 
-        step.payload = StepPayload::Signed(signed_json);
-    }
+    Ok(())
+}
 
-    println!("INFO: Batch {}: All steps signed successfully.", batch_id);
+/// Stores the signed `payload` of `batch`, which is 'SIGNING_IN_PROGRESS', and moves the batch to
+/// 'AWAITING_BROADCAST'. The outputs of signed consolidation steps are kept as the inputs of the next cycle.
+///
+/// Also used for payloads signed on an offline machine and uploaded through the API.
+pub(crate) async fn store_signed_payload(
+    conn: &mut SqliteConnection,
+    batch: &PaymentBatch,
+    payload: &BatchPayload,
+) -> Result<(), anyhow::Error> {
+    let mut consolidated_wallet_outputs = vec![];
+    for step in payload.steps.iter().filter(|step| step.is_consolidation) {
+        if let Some(StepTransaction::Signed(signed_tx)) = step.transaction()? {
+            consolidated_wallet_outputs.extend(consolidated_outputs(&signed_tx));
+        }
+    }
 
     let intermediate_context = if consolidated_wallet_outputs.is_empty() {
         None
     } else {
+        let previous_cycle = match &batch.intermediate_context_json {
+            Some(json) => IntermediateContext::from_json(json)?.cycle,
+            None => 0,
+        };
         let ctx = IntermediateContext {
             utxos: consolidated_wallet_outputs,
+            cycle: previous_cycle + 1,
         };
         Some(ctx.to_json()?)
     };
 
     let signed_payload_json = payload.to_json()?;
-    PaymentBatch::update_to_awaiting_broadcast(conn, batch_id, &signed_payload_json, intermediate_context.as_deref())
+    PaymentBatch::update_to_awaiting_broadcast(conn, &batch.id, &signed_payload_json, intermediate_context.as_deref())
         .await
         .context("Failed to update status to AwaitingBroadcast")?;
+    Ok(())
+}
 
-    println!(
-        "INFO: Batch {}: Status updated to 'AwaitingBroadcast'. Processing complete.",
-        batch_id
+/// Outputs of a signed consolidation transaction, usable as inputs for the final transaction.
+fn consolidated_outputs(signed_tx: &SignedOneSidedTransactionResult) -> Vec<WalletOutput> {
+    signed_tx
+        .signed_transaction
+        .outputs
+        .iter()
+        .map(|output| {
+            let mut cloned_output = output.clone();
+            let script_key_id = TariKeyId::Derived {
+                key: SerializedKeyString::from(output.commitment_mask_key_id().to_string()),
+            };
+            cloned_output.set_script_key_id(script_key_id);
+            cloned_output
+        })
+        .collect()
+}
+
+enum Signer {
+    ConsoleWallet(CliWallet),
+    /// Simulation mode: steps get deterministic fake signatures and the console wallet is never started.
+    Simulated,
+}
+
+/// Console wallet invocation settings shared by all signing attempts.
+pub(crate) struct CliWallet {
+    pub(crate) network: Network,
+    pub(crate) executable_path: String,
+    pub(crate) password: SecretString,
+    pub(crate) base_path: String,
+    /// Maximum time a single console wallet invocation may take before it is killed.
+    pub(crate) timeout: Duration,
+}
+
+impl CliWallet {
+    /// Console wallet command with the password, base path and network set, ready for a subcommand.
+    pub(crate) fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.executable_path);
+        cmd.current_dir(&self.base_path)
+            .env("MINOTARI_WALLET_PASSWORD", self.password.expose())
+            // Ensures the console wallet is killed if the invocation times out.
+            .kill_on_drop(true)
+            .arg("--command-mode-auto-exit")
+            .arg("--base-path")
+            .arg(&self.base_path)
+            .arg("--network")
+            .arg(self.network.to_string())
+            .arg("--skip-recovery");
+        cmd
+    }
+}
+
+async fn sign_step(
+    db_pool: &SqlitePool,
+    signer: &Signer,
+    batch_id: &str,
+    i: usize,
+    steps_count: usize,
+    step: &TransactionStep,
+) -> Result<StepPayload, anyhow::Error> {
+    info!(
+        target: LOG_TARGET,
+        "Batch {}: Signing Step {}/{} (ID: {})",
+        batch_id,
+        i + 1,
+        steps_count,
+        step.tx_id
     );
 
-    Ok(())
+    let unsigned_json = match &step.payload {
+        StepPayload::Unsigned(s) => s,
+        StepPayload::Signed(_) | StepPayload::Simulated(_) => return Err(anyhow!("Step {} is already signed!", i)),
+    };
+    let wallet = match signer {
+        Signer::ConsoleWallet(wallet) => wallet,
+        Signer::Simulated => {
+            // Consolidation outputs only exist in a transaction signed by the console wallet, so the next cycle
+            // would have nothing to spend.
+            if step.is_consolidation {
+                return Err(anyhow!("Step {} is a consolidation step, which can't be simulated", i));
+            }
+            return Ok(StepPayload::Simulated(SimulatedTransaction::sign(step, unsigned_json)));
+        },
+    };
+
+    let mut input_file = NamedTempFile::with_prefix(format!("unsigned-tx-{}-step{}-", batch_id, i))
+        .context("Failed to create temp input file")?;
+    let input_path = input_file.path().to_path_buf();
+
+    input_file
+        .write_all(unsigned_json.as_bytes())
+        .context("Failed to write unsigned tx to temp file")?;
+    input_file.flush().context("Failed to flush input file")?;
+
+    let output_file = NamedTempFile::with_prefix(format!("signed-tx-{}-step{}-", batch_id, i))
+        .context("Failed to create temp output file")?;
+    let output_path = output_file.path().to_path_buf();
+
+    sign_with_cli(db_pool, wallet, batch_id, i, &input_path, &output_path)
+        .await
+        .context(format!("External signing process failed for step {}", i))?;
+
+    let signed_json = fs::read_to_string(&output_path)
+        .await
+        .context("Failed to read signed transaction from output file")?;
+    SignedOneSidedTransactionResult::from_json(&signed_json)
+        .map_err(|e| anyhow!("Failed to deserialize signed tx for step {}: {}", i, e))?;
+
+    Ok(StepPayload::Signed(signed_json))
 }
 
-/// Executes the Minotari Console Wallet.
+/// Executes the Minotari Console Wallet. The outcome is recorded in `signing_attempts`.
 async fn sign_with_cli(
-    network: Network,
-    executable_path: &str,
-    password: &str,
-    base_path: &str,
+    db_pool: &SqlitePool,
+    wallet: &CliWallet,
+    batch_id: &str,
+    step_index: usize,
     input_path: &std::path::Path,
     output_path: &std::path::Path,
 ) -> Result<(), anyhow::Error> {
-    let mut cmd = Command::new(executable_path);
-    cmd.current_dir(base_path)
-        .env("MINOTARI_WALLET_PASSWORD", password)
-        .arg("--command-mode-auto-exit")
-        .arg("--base-path")
-        .arg(base_path)
-        .arg("--network")
-        .arg(network.to_string())
-        .arg("--skip-recovery")
-        .arg("sign-one-sided-transaction")
+    let mut cmd = wallet.command();
+    cmd.arg("sign-one-sided-transaction")
         .arg("--input-file")
         .arg(input_path)
         .arg("--output-file")
@@ -248,25 +443,73 @@ async fn sign_with_cli(
             .join(" ")
     );
 
-    println!("DEBUG: Executing Command: {}", command_string);
+    debug!(target: LOG_TARGET, "Executing Command: {}", command_string);
+
+    let started = Instant::now();
+    let mut attempt = NewSigningAttempt {
+        batch_id,
+        step_index: step_index as i64,
+        ..Default::default()
+    };
+    let cmd_output = match time::timeout(wallet.timeout, cmd.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            let error = format!("Failed to execute console wallet command: {}", e);
+            attempt.stderr = error.as_str();
+            attempt.duration_ms = started.elapsed().as_millis() as i64;
+            record_attempt(db_pool, &attempt).await;
+            return Err(anyhow!(error));
+        },
+        Err(_) => {
+            warn!(
+                target: LOG_TARGET,
+                "Console wallet did not finish within {:?}. The process has been killed.",
+                wallet.timeout
+            );
+            attempt.timed_out = true;
+            attempt.duration_ms = started.elapsed().as_millis() as i64;
+            record_attempt(db_pool, &attempt).await;
+            return Err(anyhow!(
+                "Console wallet timed out after {:?} and was killed",
+                wallet.timeout
+            ));
+        },
+    };
 
-    let cmd_output = cmd.output().await.context("Failed to execute console wallet command")?;
+    let stderr = String::from_utf8_lossy(&cmd_output.stderr);
+    let stdout = String::from_utf8_lossy(&cmd_output.stdout);
+    attempt.exit_code = cmd_output.status.code();
+    attempt.success = cmd_output.status.success();
+    attempt.stdout = &*stdout;
+    attempt.stderr = &*stderr;
+    attempt.duration_ms = started.elapsed().as_millis() as i64;
+    record_attempt(db_pool, &attempt).await;
 
     if !cmd_output.status.success() {
-        let stderr = String::from_utf8_lossy(&cmd_output.stderr);
-        let stdout = String::from_utf8_lossy(&cmd_output.stdout);
         return Err(anyhow!(
             "CLI exited with error code: {}.\nStderr: {}\nStdout: {}",
             cmd_output.status,
             stderr,
             stdout
         ));
-    } else {
-        let stdout = String::from_utf8_lossy(&cmd_output.stdout);
-        if !stdout.trim().is_empty() {
-            println!("DEBUG: CLI Stdout: {}", stdout);
-        }
+    } else if !stdout.trim().is_empty() {
+        debug!(target: LOG_TARGET, "CLI Stdout: {}", stdout);
     }
 
     Ok(())
 }
+
+/// Failing to record an attempt must not fail the signing itself.
+async fn record_attempt(db_pool: &SqlitePool, attempt: &NewSigningAttempt<'_>) {
+    let result = match db_pool.acquire().await {
+        Ok(mut conn) => SigningAttempt::record(&mut conn, attempt).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!(
+            target: LOG_TARGET,
+            "Failed to record signing attempt of batch {} step {}: {:?}",
+            attempt.batch_id, attempt.step_index, e
+        );
+    }
+}
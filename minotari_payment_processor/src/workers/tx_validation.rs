@@ -0,0 +1,214 @@
+use anyhow::{anyhow, bail, ensure};
+use std::collections::{HashMap, HashSet};
+
+use crate::db::payment::Payment;
+use crate::db::payment_batch::{BatchPayload, ExpectedRecipient, StepExpectation, StepTransaction, TransactionStep};
+use crate::simulation::SimulatedTransaction;
+
+/// Checks the signed transactions of a batch before they are broadcast. Any error means the signer output can't be
+/// trusted and the batch has to be reviewed manually.
+///
+/// Every step must carry valid kernel, script and metadata signatures and a fee of at most `max_fee` µT, and all
+/// steps together at most `max_batch_fee` µT, if given. Steps prepared with an expectation are also checked against
+/// it: every recipient output must be present, and the spent inputs must equal the fee, the recipient amounts, the
+/// outputs split off the change and the outputs returned to the account. Together, the payout steps must pay exactly
+/// the batch's payments, each step the payments it was prepared for. Recipient addresses and amounts are sealed into
+/// one-sided outputs and can't be read back without the recipients' keys, hence the balance check.
+pub fn validate_signed_payload(
+    payload: &BatchPayload,
+    payments: &[Payment],
+    max_fee: u64,
+    max_batch_fee: Option<u64>,
+) -> anyhow::Result<()> {
+    ensure!(!payload.steps.is_empty(), "Payload has no steps");
+
+    let mut total_fee = 0u64;
+    for step in &payload.steps {
+        let fee = validate_step(step, max_fee).map_err(|e| anyhow!("Step {}: {}", step.step_index + 1, e))?;
+        total_fee = total_fee.saturating_add(fee);
+    }
+    ensure_recipients_match(payload, payments)?;
+    if let Some(max_batch_fee) = max_batch_fee {
+        ensure!(
+            total_fee <= max_batch_fee,
+            "fee of {} µT exceeds the account's maximum fee of {} µT",
+            total_fee,
+            max_batch_fee
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the fee of the step in µT.
+fn validate_step(step: &TransactionStep, max_fee: u64) -> anyhow::Result<u64> {
+    let signed_tx = match step.transaction()? {
+        Some(StepTransaction::Signed(signed_tx)) => signed_tx,
+        Some(StepTransaction::Simulated(simulated)) => return validate_simulated_step(step, &simulated),
+        None => bail!("not signed"),
+    };
+    let tx = &signed_tx.signed_transaction.transaction;
+
+    let kernels = tx.body.kernels();
+    ensure!(!kernels.is_empty(), "transaction has no kernels");
+    let mut fee = 0u64;
+    for (i, kernel) in kernels.iter().enumerate() {
+        kernel
+            .verify_signature()
+            .map_err(|e| anyhow!("invalid signature on kernel {}: {}", i, e))?;
+        fee = fee.saturating_add(kernel.fee.as_u64());
+    }
+    ensure!(fee <= max_fee, "fee of {} µT exceeds the cap of {} µT", fee, max_fee);
+
+    for (i, input) in tx.body.inputs().iter().enumerate() {
+        input
+            .run_and_verify_script(None)
+            .map_err(|e| anyhow!("invalid script signature on input {}: {}", i, e))?;
+    }
+    for (i, output) in tx.body.outputs().iter().enumerate() {
+        output
+            .verify_metadata_signature()
+            .map_err(|e| anyhow!("invalid metadata signature on output {}: {}", i, e))?;
+    }
+
+    let Some(expected) = &step.expected else {
+        return Ok(fee);
+    };
+
+    if !step.is_consolidation {
+        let sent_hashes = &signed_tx.signed_transaction.sent_hashes;
+        let sent_count = expected.recipients.len() + expected.change_outputs.len();
+        ensure!(
+            sent_hashes.len() == sent_count,
+            "transaction sends {} outputs, expected {}",
+            sent_hashes.len(),
+            sent_count
+        );
+        let output_hashes: HashSet<_> = tx.body.outputs().iter().map(|output| output.hash()).collect();
+        ensure!(
+            sent_hashes.iter().all(|hash| output_hashes.contains(hash)),
+            "a recipient output is missing from the transaction"
+        );
+    }
+
+    let sent_total = expected
+        .recipients
+        .iter()
+        .map(|recipient| recipient.amount)
+        .chain(expected.change_outputs.iter().copied())
+        .try_fold(0u64, |total, amount| total.checked_add(amount))
+        .ok_or_else(|| anyhow!("sent total overflows"))?;
+    let returned_total = signed_tx
+        .signed_transaction
+        .outputs
+        .iter()
+        .try_fold(0u64, |total, output| total.checked_add(output.value().as_u64()))
+        .ok_or_else(|| anyhow!("returned output total overflows"))?;
+    let spent = sent_total
+        .checked_add(returned_total)
+        .and_then(|total| total.checked_add(fee));
+    ensure!(
+        spent == Some(expected.input_total),
+        "inputs of {} µT do not balance {} µT sent, {} µT returned to the account and a fee of {} µT",
+        expected.input_total,
+        sent_total,
+        returned_total,
+        fee
+    );
+
+    Ok(fee)
+}
+
+/// A simulated step has no signatures or balances to verify, only one sent output per expected recipient and change
+/// output. It pays no fee.
+fn validate_simulated_step(step: &TransactionStep, simulated: &SimulatedTransaction) -> anyhow::Result<u64> {
+    if let Some(expected) = &step.expected
+        && !step.is_consolidation
+    {
+        let sent_count = expected.recipients.len() + expected.change_outputs.len();
+        ensure!(
+            simulated.sent_hashes.len() == sent_count,
+            "transaction sends {} outputs, expected {}",
+            simulated.sent_hashes.len(),
+            sent_count
+        );
+    }
+    Ok(0)
+}
+
+/// The recipients the payout steps were built for must be the batch's payments, regardless of order, and each step
+/// that lists its payments must pay exactly those. Skipped for consolidation cycles and steps prepared by older
+/// versions.
+fn ensure_recipients_match(payload: &BatchPayload, payments: &[Payment]) -> anyhow::Result<()> {
+    let mut expectations = Vec::new();
+    for step in payload.payout_steps() {
+        let Some(expected) = &step.expected else {
+            return Ok(());
+        };
+        expectations.push((step, expected));
+    }
+    if expectations.is_empty() {
+        return Ok(());
+    }
+
+    let mut from_payments: Vec<ExpectedRecipient> = payments
+        .iter()
+        .map(|payment| ExpectedRecipient {
+            address: payment.recipient_address.clone(),
+            amount: payment.amount.as_u64(),
+        })
+        .collect();
+    let mut from_steps: Vec<ExpectedRecipient> = expectations
+        .iter()
+        .flat_map(|(_, expected)| expected.recipients.iter().cloned())
+        .collect();
+    from_payments.sort_by(|a, b| (&a.address, a.amount).cmp(&(&b.address, b.amount)));
+    from_steps.sort_by(|a, b| (&a.address, a.amount).cmp(&(&b.address, b.amount)));
+    ensure!(
+        from_payments == from_steps,
+        "recipients of the transactions do not match the {} payments of the batch",
+        payments.len()
+    );
+
+    let payments_by_id: HashMap<&str, &Payment> = payments.iter().map(|p| (p.id.as_str(), p)).collect();
+    let mut paid_ids = HashSet::new();
+    for (step, expected) in expectations {
+        if step.payment_ids.is_empty() {
+            continue;
+        }
+        ensure_step_pays(step, expected, &payments_by_id, &mut paid_ids)
+            .map_err(|e| anyhow!("Step {}: {}", step.step_index + 1, e))?;
+    }
+    Ok(())
+}
+
+/// Checks that the recipients of the step are the payments it lists, in order, and that no earlier step paid them.
+fn ensure_step_pays<'a>(
+    step: &'a TransactionStep,
+    expected: &StepExpectation,
+    payments_by_id: &HashMap<&str, &Payment>,
+    paid_ids: &mut HashSet<&'a str>,
+) -> anyhow::Result<()> {
+    ensure!(
+        step.payment_ids.len() == expected.recipients.len(),
+        "lists {} payments for {} recipients",
+        step.payment_ids.len(),
+        expected.recipients.len()
+    );
+    for (payment_id, recipient) in step.payment_ids.iter().zip(&expected.recipients) {
+        let payment = payments_by_id
+            .get(payment_id.as_str())
+            .ok_or_else(|| anyhow!("payment {} is not part of the batch", payment_id))?;
+        ensure!(
+            payment.recipient_address == recipient.address && payment.amount.as_u64() == recipient.amount,
+            "recipient does not match payment {}",
+            payment_id
+        );
+        ensure!(
+            paid_ids.insert(payment_id.as_str()),
+            "payment {} is paid twice",
+            payment_id
+        );
+    }
+    Ok(())
+}
@@ -5,6 +5,13 @@ use tari_transaction_components::transaction_components::WalletOutput;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IntermediateContext {
     pub utxos: Vec<WalletOutput>,
+    /// Number of consolidation cycles completed so far. Contexts written before cycles were counted have done one.
+    #[serde(default = "default_cycle")]
+    pub cycle: u32,
+}
+
+fn default_cycle() -> u32 {
+    1
 }
 
 impl IntermediateContext {
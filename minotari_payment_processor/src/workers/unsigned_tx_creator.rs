@@ -1,7 +1,8 @@
 use anyhow::{Context, anyhow};
-use minotari_client::apis::{Error as ApiError, accounts_api, configuration::Configuration};
+use log::{debug, error, info, warn};
+use minotari_client::apis::{Error as ApiError, configuration::Configuration};
 use minotari_client::models::LockFundsRequest;
-use sqlx::{SqliteConnection, SqlitePool};
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tari_common::configuration::Network;
@@ -22,66 +23,307 @@ use tari_transaction_components::{
     transaction_components::{MemoField, OutputFeatures, WalletOutput, covenants::Covenant, memo_field::TxType},
     weight::TransactionWeight,
 };
-use tokio::time::{self, Duration};
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
-use crate::config::PaymentReceiverAccount;
+use crate::MAX_BATCH_SIZE;
+use crate::amount::PaymentAmount;
+use crate::config::{
+    ChangeSplit, MaxFee, NetworkRetry, PaymentReceiverAccount, RetryPolicies, RetryPolicy, SharedAccounts,
+};
+use crate::db::audit_log::SYSTEM_ACTOR;
 use crate::db::payment::Payment;
-use crate::db::payment_batch::{BatchPayload, PaymentBatch, PaymentBatchStatus, StepPayload, TransactionStep};
+use crate::db::payment_batch::{
+    BatchPayload, ExpectedRecipient, PaymentBatch, PaymentBatchStatus, StepExpectation, StepPayload, TransactionStep,
+};
+use crate::db::setting::ProcessingPause;
+use crate::payment_receiver;
+use crate::workers::coin_selection;
+use crate::workers::supervisor::Cycle;
 use crate::workers::types::IntermediateContext;
+use crate::workers::wakeup::WorkerWakeups;
+use crate::workers::worker::Worker;
+
+const LOG_TARGET: &str = "minotari_payment_processor::workers::unsigned_tx_creator";
 
 const DEFAULT_SLEEP_SECS: u64 = 15;
-const FEE_PER_GRAM: u64 = 5;
-// Buffer to ensure we have enough funds left for the final payment after paying for split fees.
-const FEE_BUFFER_AMOUNT: i64 = 200_000;
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+/// Every cycle divides the input count by `max_input_count_per_tx`, so this covers any realistic wallet.
+const MAX_CONSOLIDATION_CYCLES: u32 = 5;
+pub(crate) const FEE_PER_GRAM: u64 = 5;
+
+/// Expected shape and cost of a batch, computed without building any transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchEstimate {
+    /// Number of transactions needed, including consolidation (split) transactions.
+    pub step_count: usize,
+    /// Whether the inputs have to be consolidated in a separate cycle before the payments can be sent.
+    pub requires_consolidation: bool,
+    /// Total fee of all transactions.
+    pub fee: MicroMinotari,
+}
+
+/// Mirrors the split decision of the worker for a batch paying `recipient_count` recipients from `input_count`
+/// UTXOs, and estimates the fees of the resulting transactions. Nothing is locked or persisted.
+pub fn estimate_batch(
+    input_count: usize,
+    recipient_count: usize,
+    max_input_count_per_tx: usize,
+) -> Result<BatchEstimate, anyhow::Error> {
+    let fee_calc = Fee::new(TransactionWeight::latest());
+    let output_metadata_size = get_single_output_metadata_size(&fee_calc)?;
+    let fee_per_gram = MicroMinotari(FEE_PER_GRAM);
+    // A limit of one input would never shrink the input count.
+    let max_input_count_per_tx = max_input_count_per_tx.max(2);
+
+    let mut fee = MicroMinotari(0);
+    let mut final_input_count = input_count.max(1);
+    let mut consolidation_steps = 0;
+
+    // Each cycle spends every chunk into a single output, which becomes an input of the next cycle or the final
+    // transaction.
+    while final_input_count > max_input_count_per_tx {
+        let mut cycle_outputs = 0;
+        for chunk_start in (0..final_input_count).step_by(max_input_count_per_tx) {
+            let chunk_len = max_input_count_per_tx.min(final_input_count - chunk_start);
+            fee += fee_calc.calculate(fee_per_gram, 1, chunk_len, 1, output_metadata_size);
+            cycle_outputs += 1;
+        }
+        consolidation_steps += cycle_outputs;
+        final_input_count = cycle_outputs;
+    }
+
+    // Payment outputs plus a change output.
+    let output_count = recipient_count + 1;
+    fee += fee_calc.calculate(
+        fee_per_gram,
+        1,
+        final_input_count,
+        output_count,
+        output_metadata_size * output_count,
+    );
+
+    Ok(BatchEstimate {
+        step_count: consolidation_steps + 1,
+        requires_consolidation: consolidation_steps > 0,
+        fee,
+    })
+}
 
-pub async fn run(
+pub struct UnsignedTxCreator {
     db_pool: SqlitePool,
     client_config: Arc<Configuration>,
+    network_retry: NetworkRetry,
     network: Network,
-    accounts: HashMap<String, PaymentReceiverAccount>,
+    accounts: SharedAccounts,
     max_input_count_per_tx: usize,
-    sleep_secs: Option<u64>,
-) {
-    let sleep_secs = sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS);
-    println!(
-        "Unsigned Transaction Creator worker started. Polling every {} seconds.",
-        sleep_secs
-    );
-
-    let mut interval = time::interval(Duration::from_secs(sleep_secs));
+    retry_policies: Arc<RetryPolicies>,
+    sleep_secs: u64,
+    max_concurrency: usize,
+    semaphore: Arc<Semaphore>,
+    batches_per_cycle: i64,
+    wakeups: WorkerWakeups,
+}
 
-    loop {
-        interval.tick().await;
-        if let Err(e) =
-            process_unsigned_transactions(&db_pool, &client_config, network, &accounts, max_input_count_per_tx).await
-        {
-            eprintln!("Unsigned Transaction Creator worker error: {:?}", e);
+impl UnsignedTxCreator {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: SqlitePool,
+        client_config: Arc<Configuration>,
+        network_retry: NetworkRetry,
+        network: Network,
+        accounts: SharedAccounts,
+        max_input_count_per_tx: usize,
+        retry_policies: RetryPolicies,
+        sleep_secs: Option<u64>,
+        max_concurrency: Option<usize>,
+        batches_per_cycle: i64,
+        wakeups: WorkerWakeups,
+    ) -> Self {
+        let max_concurrency = max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).max(1);
+        Self {
+            db_pool,
+            client_config,
+            network_retry,
+            network,
+            accounts,
+            max_input_count_per_tx,
+            retry_policies: Arc::new(retry_policies),
+            sleep_secs: sleep_secs.unwrap_or(DEFAULT_SLEEP_SECS),
+            max_concurrency,
+            semaphore: Arc::new(Semaphore::new(max_concurrency)),
+            batches_per_cycle,
+            wakeups,
         }
     }
 }
 
+impl Worker for UnsignedTxCreator {
+    const NAME: &'static str = "unsigned_tx_creator";
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.sleep_secs)
+    }
+
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        Some(self.wakeups.unsigned_tx_creator.clone())
+    }
+
+    async fn start(&mut self) {
+        info!(
+            target: LOG_TARGET,
+            "Unsigned Transaction Creator worker started. Polling every {} seconds. Max concurrent accounts: {}",
+            self.sleep_secs, self.max_concurrency
+        );
+    }
+
+    async fn run_cycle(&mut self, cycle: &mut Cycle<'_>, shutdown: &CancellationToken) -> Result<(), anyhow::Error> {
+        let count = process_unsigned_transactions(
+            &self.db_pool,
+            &self.client_config,
+            &self.network_retry,
+            self.network,
+            &self.accounts.current(),
+            self.max_input_count_per_tx,
+            &self.retry_policies,
+            &self.semaphore,
+            self.batches_per_cycle,
+            &self.wakeups,
+            shutdown,
+        )
+        .await?;
+        cycle.add_items(count);
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn process_unsigned_transactions(
     db_pool: &SqlitePool,
-    client_config: &Configuration,
+    client_config: &Arc<Configuration>,
+    network_retry: &NetworkRetry,
     network: Network,
-    accounts: &HashMap<String, PaymentReceiverAccount>,
+    accounts: &Arc<HashMap<String, PaymentReceiverAccount>>,
     max_input_count_per_tx: usize,
-) -> Result<(), anyhow::Error> {
-    let mut conn = db_pool.acquire().await?;
-
-    let batches = PaymentBatch::find_by_status(&mut conn, PaymentBatchStatus::PendingBatching).await?;
+    retry_policies: &Arc<RetryPolicies>,
+    semaphore: &Arc<Semaphore>,
+    batches_per_cycle: i64,
+    wakeups: &WorkerWakeups,
+    shutdown: &CancellationToken,
+) -> Result<usize, anyhow::Error> {
+    let batches = {
+        let mut conn = db_pool.acquire().await?;
+        // Batches of paused accounts wait in PENDING_BATCHING until processing is resumed. This includes split
+        // cycle batches between two cycles, whose consolidation transactions have already been broadcast.
+        let pause = ProcessingPause::load(&mut conn).await?;
+        if pause.global {
+            return Ok(0);
+        }
+        PaymentBatch::find_by_status_fair(
+            &mut conn,
+            PaymentBatchStatus::PendingBatching,
+            batches_per_cycle,
+            &pause.accounts,
+        )
+        .await?
+    };
+    // A full cycle means more batches are probably waiting, so run again right away instead of sleeping.
+    let more_pending = batches.len() as i64 >= batches_per_cycle;
+    let batch_count = batches.len();
 
     if !batches.is_empty() {
-        println!(
-            "INFO: Found {} batches pending unsigned transaction creation.",
+        info!(
+            target: LOG_TARGET,
+            "Found {} batches pending unsigned transaction creation.",
             batches.len()
         );
     }
 
+    // Batches of the same account are processed in order, while different accounts are processed
+    // concurrently so a slow PR call for one account does not hold back the others.
+    let mut batches_by_account: HashMap<String, Vec<PaymentBatch>> = HashMap::new();
     for batch in batches {
-        if let Err(e) = process_single_batch(
-            &mut conn,
+        batches_by_account
+            .entry(batch.account_name.to_lowercase())
+            .or_default()
+            .push(batch);
+    }
+
+    let mut account_tasks = JoinSet::new();
+    for (account_name, account_batches) in batches_by_account {
+        let db_pool = db_pool.clone();
+        let client_config = client_config.clone();
+        let network_retry = *network_retry;
+        let accounts = accounts.clone();
+        let retry_policies = retry_policies.clone();
+        let semaphore = semaphore.clone();
+        let wakeups = wakeups.clone();
+        let shutdown = shutdown.clone();
+
+        account_tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .context("Concurrency limiter was closed")?;
+
+            process_account_batches(
+                &db_pool,
+                &client_config,
+                &network_retry,
+                network,
+                &accounts,
+                account_batches,
+                max_input_count_per_tx,
+                retry_policies.for_account(&account_name),
+                &wakeups,
+                &shutdown,
+            )
+            .await
+            .with_context(|| format!("Failed to process batches for account '{}'", account_name))
+        });
+    }
+
+    while let Some(result) = account_tasks.join_next().await {
+        match result {
+            Ok(Ok(())) => {},
+            Ok(Err(e)) => error!(target: LOG_TARGET, "Unsigned Transaction Creator worker error: {:?}", e),
+            Err(e) => error!(target: LOG_TARGET, "Account processing task terminated abnormally: {:?}", e),
+        }
+    }
+
+    if more_pending && !shutdown.is_cancelled() {
+        wakeups.unsigned_tx_creator.notify_one();
+    }
+
+    Ok(batch_count)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_account_batches(
+    db_pool: &SqlitePool,
+    client_config: &Configuration,
+    network_retry: &NetworkRetry,
+    network: Network,
+    accounts: &HashMap<String, PaymentReceiverAccount>,
+    batches: Vec<PaymentBatch>,
+    max_input_count_per_tx: usize,
+    retry_policy: &RetryPolicy,
+    wakeups: &WorkerWakeups,
+    shutdown: &CancellationToken,
+) -> Result<(), anyhow::Error> {
+    for batch in batches {
+        if shutdown.is_cancelled() {
+            info!(target: LOG_TARGET, "Shutdown requested. Leaving remaining batches for the next run.");
+            break;
+        }
+
+        match process_single_batch(
+            db_pool,
             client_config,
+            network_retry,
             network,
             accounts,
             &batch,
@@ -89,45 +331,69 @@ async fn process_unsigned_transactions(
         )
         .await
         {
-            let error_message = e.to_string();
-            eprintln!(
-                "Error processing batch {}: {}. Incrementing retry count.",
-                batch.id, error_message
-            );
-
-            if let Err(db_err) = PaymentBatch::increment_retry_count(&mut conn, &batch.id, &error_message).await {
-                eprintln!(
-                    "CRITICAL: Failed to update retry count for batch {}: {:?}",
-                    batch.id, db_err
+            Ok(()) => wakeups.transaction_signer.notify_one(),
+            Err(e) => {
+                let error_message = e.to_string();
+                error!(
+                    target: LOG_TARGET,
+                    "Error processing batch {}: {}. Incrementing retry count.",
+                    batch.id, error_message
                 );
-            }
+
+                let result = match db_pool.acquire().await {
+                    Ok(mut conn) => {
+                        PaymentBatch::increment_retry_count(&mut conn, &batch.id, &error_message, retry_policy).await
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(db_err) = result {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to update retry count for batch {}: {:?}",
+                        batch.id, db_err
+                    );
+                }
+            },
         }
     }
 
     Ok(())
 }
 
+/// Prepares the unsigned transactions of `batch`. No connection is held while the Payment Receiver locks the funds.
 async fn process_single_batch(
-    conn: &mut SqliteConnection,
+    db_pool: &SqlitePool,
     client_config: &Configuration,
+    network_retry: &NetworkRetry,
     network: Network,
     accounts: &HashMap<String, PaymentReceiverAccount>,
     batch: &PaymentBatch,
     max_input_count_per_tx: usize,
 ) -> Result<(), anyhow::Error> {
     let batch_id = &batch.id;
-    println!("INFO: Starting processing for Batch ID: {}", batch_id);
+    info!(target: LOG_TARGET, "Starting processing for Batch ID: {}", batch_id);
 
-    let associated_payments = Payment::find_by_batch_id(conn, batch_id)
+    let max_input_count_per_tx = max_inputs_per_transaction(network, max_input_count_per_tx)?;
+    // Closes the batch to appended payments before reading them, so every payment it holds is paid.
+    PaymentBatch::mark_tx_creation_started(&mut *db_pool.acquire().await?, batch_id)
+        .await
+        .context("Failed to mark transaction creation as started")?;
+    let mut associated_payments = Payment::find_by_batch_id(&mut *db_pool.acquire().await?, batch_id)
         .await
         .context("Failed to fetch payments for batch")?;
 
     if associated_payments.is_empty() {
-        println!(
-            "WARN: Batch {} has no active payments. Marking batch as CANCELLED.",
+        warn!(
+            target: LOG_TARGET,
+            "Batch {} has no active payments. Marking batch as CANCELLED.",
             batch_id
         );
-        PaymentBatch::update_to_failed(conn, batch_id, "No active payments found in batch").await?;
+        PaymentBatch::update_to_failed(
+            &mut *db_pool.acquire().await?,
+            batch_id,
+            "No active payments found in batch",
+        )
+        .await?;
         return Ok(());
     }
 
@@ -139,16 +405,74 @@ async fn process_single_batch(
     // --- CYCLE 2 (Finalize) OR CYCLE 1 (Inputs Check) ---
     if let Some(context_json) = &batch.intermediate_context_json {
         // === CYCLE 2: FINALIZE ===
-        println!(
-            "INFO: Batch {}: Found intermediate context. Executing CYCLE 2 (Finalize).",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Found intermediate context. Executing CYCLE 2 (Finalize).",
             batch_id
         );
 
         let context = IntermediateContext::from_json(context_json)?;
         let inputs = context.utxos;
 
-        println!(
-            "INFO: Batch {}: Using {} intermediate inputs for final transaction.",
+        if fail_if_fee_exceeds_limit(
+            db_pool,
+            batch_id,
+            sender_account,
+            inputs.len(),
+            &associated_payments,
+            max_input_count_per_tx,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        if inputs.len() > max_input_count_per_tx {
+            // === ANOTHER SPLIT CYCLE ===
+            if context.cycle >= MAX_CONSOLIDATION_CYCLES {
+                return Err(anyhow!(
+                    "Still {} inputs after {} consolidation cycles, giving up",
+                    inputs.len(),
+                    context.cycle
+                ));
+            }
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: {} intermediate inputs after cycle {} still exceed limit ({}). Initiating split cycle {}.",
+                batch_id,
+                inputs.len(),
+                context.cycle,
+                max_input_count_per_tx,
+                context.cycle + 1
+            );
+
+            let payload = BatchPayload {
+                steps: create_split_steps(network, sender_account, &inputs, max_input_count_per_tx).await?,
+            };
+            let payload_json = payload.to_json()?;
+
+            PaymentBatch::update_to_awaiting_signature(
+                &mut *db_pool.acquire().await?,
+                batch_id,
+                &payload_json,
+                Some(batch.version),
+            )
+            .await
+            .context("Failed to update batch to AwaitingSignature (Split Cycle)")?;
+
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Split cycle {} preparation complete. {} steps created.",
+                batch_id,
+                context.cycle + 1,
+                payload.steps.len()
+            );
+            return Ok(());
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Using {} intermediate inputs for final transaction.",
             batch_id,
             inputs.len()
         );
@@ -160,29 +484,71 @@ async fn process_single_batch(
         };
         let payload_json = payload.to_json()?;
 
-        PaymentBatch::update_to_awaiting_signature(conn, batch_id, &payload_json)
-            .await
-            .context("Failed to update batch to AwaitingSignature (Cycle 2)")?;
+        PaymentBatch::update_to_awaiting_signature(
+            &mut *db_pool.acquire().await?,
+            batch_id,
+            &payload_json,
+            Some(batch.version),
+        )
+        .await
+        .context("Failed to update batch to AwaitingSignature (Cycle 2)")?;
 
-        println!(
-            "INFO: Batch {}: Cycle 2 preparation complete. Ready for signature.",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: Cycle 2 preparation complete. Ready for signature.",
             batch_id
         );
     } else {
         // === CYCLE 1: FETCH & ANALYZE ===
-        println!(
-            "INFO: Batch {}: No context found. Fetching fresh UTXOs from API.",
+        info!(
+            target: LOG_TARGET,
+            "Batch {}: No context found. Fetching fresh UTXOs from API.",
             batch_id
         );
 
-        let payment_total: i64 = associated_payments.iter().map(|p| p.amount).sum();
-        let amount_to_lock = payment_total + FEE_BUFFER_AMOUNT;
-        let account_balance = accounts_api::api_get_balance(client_config, account_name).await?;
+        let max_recipients = max_recipients_per_transaction(network, max_input_count_per_tx)?;
+        if associated_payments.len() > max_recipients {
+            let moved_payments = associated_payments.split_off(max_recipients);
+            let moved_ids: Vec<String> = moved_payments.iter().map(|p| p.id.clone()).collect();
+            let new_batch = PaymentBatch::create_with_payments(
+                &mut *db_pool.acquire().await?,
+                SYSTEM_ACTOR,
+                account_name,
+                &Uuid::new_v4().to_string(),
+                &moved_ids,
+                Some(batch_id.as_str()),
+                batch
+                    .required_confirmations
+                    .map_or(sender_account.required_confirmations, |confirmations| {
+                        confirmations as u64
+                    }),
+            )
+            .await
+            .context("Failed to move payments exceeding the transaction weight limit to a new batch")?;
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: A transaction paying {} recipients would exceed the network's weight limit. Moved {} \
+                 payments to batch {}.",
+                batch_id,
+                max_recipients + moved_payments.len(),
+                moved_payments.len(),
+                new_batch.id
+            );
+        }
+
+        let payment_total = PaymentAmount::checked_sum(associated_payments.iter().map(|p| p.amount))
+            .context("Batch payment total overflows")? as i64;
+        // Buffer to ensure we have enough funds left for the final payment after paying for split fees.
+        let amount_to_lock = payment_total
+            .checked_add(sender_account.fee_buffer_amount)
+            .ok_or_else(|| anyhow!("Batch amount to lock overflows"))?;
+        let account_balance = payment_receiver::get_balance(client_config, network_retry, account_name).await?;
         let balance = account_balance.available;
 
         if balance < amount_to_lock {
-            println!(
-                "WARN: Batch {}: Not enough funds in wallet {}. Requested (w/ buffer): {}, Actual: {}.",
+            warn!(
+                target: LOG_TARGET,
+                "Batch {}: Not enough funds in wallet {}. Requested (w/ buffer): {}, Actual: {}.",
                 batch_id, account_name, amount_to_lock, balance
             );
             return Ok(());
@@ -194,11 +560,12 @@ async fn process_single_batch(
             ..Default::default()
         };
 
-        let locked_funds = match accounts_api::api_lock_funds(client_config, account_name, lock_request).await {
-            Ok(res) => res,
-            Err(ApiError::ResponseError(c)) => return Err(anyhow!("PR API Error: {} - {}", c.status, c.content)),
-            Err(e) => return Err(anyhow!("Network error calling PR API: {:?}", e)),
-        };
+        let locked_funds =
+            match payment_receiver::lock_funds(client_config, network_retry, account_name, lock_request).await {
+                Ok(res) => res,
+                Err(ApiError::ResponseError(c)) => return Err(anyhow!("PR API Error: {} - {}", c.status, c.content)),
+                Err(e) => return Err(anyhow!("Network error calling PR API: {:?}", e)),
+            };
 
         let mut inputs: Vec<WalletOutput> = Vec::new();
         for utxo_val in locked_funds.utxos {
@@ -207,41 +574,73 @@ async fn process_single_batch(
             inputs.push(utxo);
         }
 
-        println!("INFO: Batch {}: API returned {} UTXOs.", batch_id, inputs.len());
+        info!(target: LOG_TARGET, "Batch {}: API returned {} UTXOs.", batch_id, inputs.len());
+
+        if let Some(selected) = coin_selection::select_inputs(
+            sender_account.coin_selection,
+            &inputs,
+            payment_total as u64,
+            associated_payments.len(),
+        )? {
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Coin selection ({:?}) picked {} of {} UTXOs.",
+                batch_id,
+                sender_account.coin_selection,
+                selected.len(),
+                inputs.len()
+            );
+            inputs = selected;
+        }
+
+        if fail_if_fee_exceeds_limit(
+            db_pool,
+            batch_id,
+            sender_account,
+            inputs.len(),
+            &associated_payments,
+            max_input_count_per_tx,
+        )
+        .await?
+        {
+            return Ok(());
+        }
 
         if inputs.len() > max_input_count_per_tx {
             // === SPLIT LOGIC ===
-            println!(
-                "INFO: Batch {}: Input count ({}) exceeds limit ({}). Initiating SPLIT (CoinJoin).",
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Input count ({}) exceeds limit ({}). Initiating SPLIT (CoinJoin).",
                 batch_id,
                 inputs.len(),
                 max_input_count_per_tx
             );
 
-            let chunks = inputs.chunks(max_input_count_per_tx);
-            let mut steps = Vec::new();
-
-            for (i, chunk) in chunks.enumerate() {
-                let tx_step = create_self_spend_step(network, sender_account, chunk.to_vec(), i).await?;
-                steps.push(tx_step);
-            }
-
-            let payload = BatchPayload { steps };
+            let payload = BatchPayload {
+                steps: create_split_steps(network, sender_account, &inputs, max_input_count_per_tx).await?,
+            };
             let payload_json = payload.to_json()?;
 
-            PaymentBatch::update_to_awaiting_signature(conn, batch_id, &payload_json)
-                .await
-                .context("Failed to update batch to AwaitingSignature (Split Cycle)")?;
+            PaymentBatch::update_to_awaiting_signature(
+                &mut *db_pool.acquire().await?,
+                batch_id,
+                &payload_json,
+                Some(batch.version),
+            )
+            .await
+            .context("Failed to update batch to AwaitingSignature (Split Cycle)")?;
 
-            println!(
-                "INFO: Batch {}: Split Cycle preparation complete. {} steps created.",
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Split Cycle preparation complete. {} steps created.",
                 batch_id,
                 payload.steps.len()
             );
         } else {
             // === NORMAL LOGIC ===
-            println!(
-                "INFO: Batch {}: Input count within limits. creating standard transaction.",
+            info!(
+                target: LOG_TARGET,
+                "Batch {}: Input count within limits. creating standard transaction.",
                 batch_id
             );
 
@@ -250,17 +649,59 @@ async fn process_single_batch(
             let payload = BatchPayload { steps: vec![step] };
             let payload_json = payload.to_json()?;
 
-            PaymentBatch::update_to_awaiting_signature(conn, batch_id, &payload_json)
-                .await
-                .context("Failed to update batch to AwaitingSignature (Normal)")?;
+            PaymentBatch::update_to_awaiting_signature(
+                &mut *db_pool.acquire().await?,
+                batch_id,
+                &payload_json,
+                Some(batch.version),
+            )
+            .await
+            .context("Failed to update batch to AwaitingSignature (Normal)")?;
 
-            println!("INFO: Batch {}: Normal preparation complete.", batch_id);
+            info!(target: LOG_TARGET, "Batch {}: Normal preparation complete.", batch_id);
         }
     }
 
     Ok(())
 }
 
+/// Fails the batch instead of building its remaining transactions if their estimated fee exceeds the account's
+/// `max_fee`. Returns whether the batch was failed.
+async fn fail_if_fee_exceeds_limit(
+    db_pool: &SqlitePool,
+    batch_id: &str,
+    sender_account: &PaymentReceiverAccount,
+    input_count: usize,
+    payments: &[Payment],
+    max_input_count_per_tx: usize,
+) -> Result<bool, anyhow::Error> {
+    let payment_total =
+        PaymentAmount::checked_sum(payments.iter().map(|p| p.amount)).context("Batch payment total overflows")?;
+    if sender_account.max_fee.cap_for(payment_total).is_none() {
+        return Ok(false);
+    }
+    let fee = estimate_batch(input_count, payments.len(), max_input_count_per_tx)?
+        .fee
+        .as_u64();
+    let Some(max_fee) = exceeded_max_fee(&sender_account.max_fee, payment_total, fee) else {
+        return Ok(false);
+    };
+
+    let error_message = format!(
+        "Estimated fee of {} µT exceeds the maximum fee of {} µT of account '{}' for a payment total of {} µT",
+        fee, max_fee, sender_account.name, payment_total
+    );
+    warn!(target: LOG_TARGET, "Batch {}: {}. Marking batch as FAILED.", batch_id, error_message);
+    PaymentBatch::update_to_failed(&mut *db_pool.acquire().await?, batch_id, &error_message).await?;
+    Ok(true)
+}
+
+/// The maximum fee of a batch paying `payment_total` µT, if the estimated `fee` exceeds it. A fee equal to the
+/// maximum is accepted.
+fn exceeded_max_fee(max_fee: &MaxFee, payment_total: u64, fee: u64) -> Option<u64> {
+    max_fee.cap_for(payment_total).filter(|&cap| fee > cap)
+}
+
 async fn prepare_signing_request(
     network: Network,
     tx_id: TxId,
@@ -298,7 +739,49 @@ async fn prepare_signing_request(
     Ok(tx_json)
 }
 
-fn get_single_output_metadata_size(fee_calc: &Fee) -> Result<usize, anyhow::Error> {
+/// Weight in grams of a transaction with a single kernel spending `input_count` inputs into `output_count` outputs.
+fn transaction_weight(fee_calc: &Fee, output_metadata_size: usize, input_count: usize, output_count: usize) -> u64 {
+    fee_calc
+        .weighting()
+        .calculate(1, input_count, output_count, output_metadata_size * output_count)
+}
+
+/// Lowers `max_input_count_per_tx` until a transaction with that many inputs, a recipient and change still fits the
+/// network's transaction weight limit.
+fn max_inputs_per_transaction(network: Network, max_input_count_per_tx: usize) -> Result<usize, anyhow::Error> {
+    let fee_calc = Fee::new(TransactionWeight::latest());
+    let output_metadata_size = get_single_output_metadata_size(&fee_calc)?;
+    let max_weight = ConsensusConstantsBuilder::new(network)
+        .build()
+        .max_block_transaction_weight();
+
+    // Consolidating needs at least two inputs per transaction.
+    let mut input_count = max_input_count_per_tx.max(2);
+    while input_count > 2 && transaction_weight(&fee_calc, output_metadata_size, input_count, 2) > max_weight {
+        input_count -= 1;
+    }
+    Ok(input_count)
+}
+
+/// The most recipients a transaction spending up to `input_count` inputs can pay, next to a change output, without
+/// exceeding the network's transaction weight limit. Capped at `MAX_BATCH_SIZE`.
+fn max_recipients_per_transaction(network: Network, input_count: usize) -> Result<usize, anyhow::Error> {
+    let fee_calc = Fee::new(TransactionWeight::latest());
+    let output_metadata_size = get_single_output_metadata_size(&fee_calc)?;
+    let max_weight = ConsensusConstantsBuilder::new(network)
+        .build()
+        .max_block_transaction_weight();
+
+    let mut recipient_count = 1;
+    while recipient_count < MAX_BATCH_SIZE
+        && transaction_weight(&fee_calc, output_metadata_size, input_count, recipient_count + 2) <= max_weight
+    {
+        recipient_count += 1;
+    }
+    Ok(recipient_count)
+}
+
+pub(crate) fn get_single_output_metadata_size(fee_calc: &Fee) -> Result<usize, anyhow::Error> {
     let output_features_size = OutputFeatures::default()
         .get_serialized_size()
         .map_err(|e| anyhow!("Serialization error: {}", e))?;
@@ -323,7 +806,7 @@ async fn create_transaction_step(
 ) -> Result<TransactionStep, anyhow::Error> {
     let tx_id = TxId::new_random();
     let output_features = OutputFeatures::default();
-    let recipients: Vec<PaymentRecipient> = payments
+    let mut recipients: Vec<PaymentRecipient> = payments
         .iter()
         .map(|p| -> Result<PaymentRecipient, anyhow::Error> {
             let payment_id = match &p.payment_id {
@@ -338,24 +821,118 @@ async fn create_transaction_step(
                 .context("Invalid recipient address")?;
 
             Ok(PaymentRecipient {
-                amount: MicroMinotari(p.amount as u64),
+                amount: p.amount.as_micro_minotari(),
                 output_features: output_features.clone(),
                 address: recipient_address,
                 payment_id,
             })
         })
         .collect::<Result<Vec<PaymentRecipient>, anyhow::Error>>()?;
+
+    let input_total = inputs.iter().map(|input| input.value()).sum::<MicroMinotari>().as_u64();
+    let payment_total =
+        PaymentAmount::checked_sum(payments.iter().map(|p| p.amount)).context("Batch payment total overflows")?;
+    let change_outputs = plan_change_outputs(
+        &sender_account.change_split,
+        input_total,
+        payment_total,
+        inputs.len(),
+        payments.len(),
+    )?;
+    recipients.extend(change_outputs.iter().map(|amount| PaymentRecipient {
+        amount: MicroMinotari(*amount),
+        output_features: output_features.clone(),
+        address: sender_account.address.clone(),
+        payment_id: MemoField::new_empty(),
+    }));
+    if !change_outputs.is_empty() {
+        debug!(
+            target: LOG_TARGET,
+            "Splitting {} outputs of {} µT off the change.",
+            change_outputs.len(),
+            sender_account.change_split.denomination
+        );
+    }
+
     let tx_json = prepare_signing_request(network, tx_id, sender_account, &inputs, &recipients).await?;
 
+    let expected = StepExpectation {
+        input_total,
+        recipients: payments
+            .iter()
+            .map(|p| ExpectedRecipient {
+                address: p.recipient_address.clone(),
+                amount: p.amount.as_u64(),
+            })
+            .collect(),
+        change_outputs,
+    };
+
     Ok(TransactionStep {
         step_index,
         is_consolidation: false,
         payload: StepPayload::Unsigned(tx_json),
         tx_id,
+        expected: Some(expected),
+        payment_ids: payments.iter().map(|p| p.id.clone()).collect(),
     })
 }
 
-async fn create_self_spend_step(
+/// Amounts of the outputs to split off the change of a transaction paying `payment_total` µT to `recipient_count`
+/// recipients: up to `change_split.count` outputs of `change_split.denomination` µT, as many as the change covers
+/// after the fees of the additional outputs while still leaving some regular change.
+fn plan_change_outputs(
+    change_split: &ChangeSplit,
+    input_total: u64,
+    payment_total: u64,
+    input_count: usize,
+    recipient_count: usize,
+) -> Result<Vec<u64>, anyhow::Error> {
+    if change_split.count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let fee_calc = Fee::new(TransactionWeight::latest());
+    let output_metadata_size = get_single_output_metadata_size(&fee_calc)?;
+    for count in (1..=change_split.count).rev() {
+        // Recipients, split outputs and the regular change output.
+        let output_count = recipient_count + count + 1;
+        let fee = fee_calc
+            .calculate(
+                MicroMinotari(FEE_PER_GRAM),
+                1,
+                input_count,
+                output_count,
+                output_metadata_size * output_count,
+            )
+            .as_u64();
+        let spent = change_split
+            .denomination
+            .checked_mul(count as u64)
+            .and_then(|split_total| split_total.checked_add(payment_total))
+            .and_then(|total| total.checked_add(fee));
+        if spent.is_some_and(|spent| spent < input_total) {
+            return Ok(vec![change_split.denomination; count]);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Consolidates `inputs` into one output per chunk of `max_input_count_per_tx` inputs.
+async fn create_split_steps(
+    network: Network,
+    sender_account: &PaymentReceiverAccount,
+    inputs: &[WalletOutput],
+    max_input_count_per_tx: usize,
+) -> Result<Vec<TransactionStep>, anyhow::Error> {
+    let mut steps = Vec::new();
+    for (i, chunk) in inputs.chunks(max_input_count_per_tx).enumerate() {
+        steps.push(create_self_spend_step(network, sender_account, chunk.to_vec(), i).await?);
+    }
+    Ok(steps)
+}
+
+pub(crate) async fn create_self_spend_step(
     network: Network,
     sender_account: &PaymentReceiverAccount,
     inputs: Vec<WalletOutput>,
@@ -378,8 +955,9 @@ async fn create_self_spend_step(
 
     let amount_to_self = total_input_value - calculated_fee;
 
-    println!(
-        "DEBUG: Self-Spend Step {}: Inputs Sum: {:?}, Inputs Count: {}, Fee: {:?}, Net Output: {:?}",
+    debug!(
+        target: LOG_TARGET,
+        "Self-Spend Step {}: Inputs Sum: {:?}, Inputs Count: {}, Fee: {:?}, Net Output: {:?}",
         step_index,
         total_input_value,
         inputs.len(),
@@ -403,5 +981,128 @@ async fn create_self_spend_step(
         is_consolidation: true,
         payload: StepPayload::Unsigned(tx_json),
         tx_id,
+        expected: Some(StepExpectation {
+            input_total: total_input_value.as_u64(),
+            recipients: Vec::new(),
+            change_outputs: Vec::new(),
+        }),
+        payment_ids: Vec::new(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fee of a transaction spending `input_count` inputs into `output_count` outputs, as the worker computes it.
+    fn fee(input_count: usize, output_count: usize) -> u64 {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        let output_metadata_size = get_single_output_metadata_size(&fee_calc).unwrap();
+        fee_calc
+            .calculate(
+                MicroMinotari(FEE_PER_GRAM),
+                1,
+                input_count,
+                output_count,
+                output_metadata_size * output_count,
+            )
+            .as_u64()
+    }
+
+    #[test]
+    fn batch_within_input_limit_is_a_single_transaction() {
+        let estimate = estimate_batch(10, 3, 10).unwrap();
+        assert_eq!(estimate.step_count, 1);
+        assert!(!estimate.requires_consolidation);
+        // Three recipients and the change.
+        assert_eq!(estimate.fee.as_u64(), fee(10, 4));
+    }
+
+    #[test]
+    fn batch_over_input_limit_is_consolidated_first() {
+        // 25 inputs are spent in chunks of 10, 10 and 5 into three outputs, which the payment then spends.
+        let estimate = estimate_batch(25, 1, 10).unwrap();
+        assert_eq!(estimate.step_count, 4);
+        assert!(estimate.requires_consolidation);
+        assert_eq!(estimate.fee.as_u64(), 2 * fee(10, 1) + fee(5, 1) + fee(3, 2));
+    }
+
+    #[test]
+    fn input_limit_below_two_still_converges() {
+        let estimate = estimate_batch(5, 1, 1).unwrap();
+        let with_two = estimate_batch(5, 1, 2).unwrap();
+        assert_eq!(estimate.step_count, with_two.step_count);
+        assert_eq!(estimate.fee, with_two.fee);
+    }
+
+    #[test]
+    fn fee_equal_to_the_maximum_is_accepted() {
+        let fee = estimate_batch(4, 2, 10).unwrap().fee.as_u64();
+        let absolute = MaxFee {
+            micro_minotari: Some(fee),
+            percent: None,
+        };
+        assert_eq!(exceeded_max_fee(&absolute, 1_000_000, fee), None);
+        assert_eq!(exceeded_max_fee(&absolute, 1_000_000, fee + 1), Some(fee));
+    }
+
+    #[test]
+    fn percentage_and_absolute_maximum_apply_the_lower_one() {
+        let max_fee = MaxFee {
+            micro_minotari: Some(5_000),
+            percent: Some(1.0),
+        };
+        // 1% of 400_000 µT is below the absolute maximum.
+        assert_eq!(exceeded_max_fee(&max_fee, 400_000, 4_000), None);
+        assert_eq!(exceeded_max_fee(&max_fee, 400_000, 4_001), Some(4_000));
+        // 1% of 1_000_000 µT is above it.
+        assert_eq!(exceeded_max_fee(&max_fee, 1_000_000, 5_001), Some(5_000));
+    }
+
+    #[test]
+    fn percentage_maximum_does_not_limit_batches_without_payments() {
+        let max_fee = MaxFee {
+            micro_minotari: None,
+            percent: Some(1.0),
+        };
+        assert_eq!(exceeded_max_fee(&max_fee, 0, u64::MAX), None);
+    }
+
+    #[test]
+    fn change_is_split_only_as_far_as_it_covers_the_extra_outputs() {
+        let change_split = ChangeSplit {
+            count: 3,
+            denomination: 10_000,
+        };
+        let payment_total = 50_000;
+        // One recipient, three split outputs and the change output, spending two inputs.
+        let all_three = payment_total + 3 * 10_000 + fee(2, 5);
+        assert_eq!(
+            plan_change_outputs(&change_split, all_three + 1, payment_total, 2, 1).unwrap(),
+            vec![10_000; 3]
+        );
+        // Exactly covering them would leave no regular change.
+        assert_eq!(
+            plan_change_outputs(&change_split, all_three, payment_total, 2, 1).unwrap(),
+            vec![10_000; 2]
+        );
+        assert!(
+            plan_change_outputs(&change_split, payment_total + fee(2, 2), payment_total, 2, 1)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn change_is_not_split_when_disabled() {
+        let change_split = ChangeSplit {
+            count: 0,
+            denomination: 10_000,
+        };
+        assert!(
+            plan_change_outputs(&change_split, u64::MAX, 1, 1, 1)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
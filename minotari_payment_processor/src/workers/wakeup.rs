@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Wakes pipeline workers before their next scheduled cycle, so a batch moves on as soon as the previous stage
+/// is done with it instead of waiting for every polling interval in turn. The intervals stay in place as a
+/// fallback, e.g. for batches whose retry backoff elapses.
+///
+/// A notification sent while a worker is busy is kept, so the worker runs another cycle right after the current
+/// one. Several notifications before that cycle are coalesced.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerWakeups {
+    pub batch_creator: Arc<Notify>,
+    pub unsigned_tx_creator: Arc<Notify>,
+    pub transaction_signer: Arc<Notify>,
+    pub broadcaster: Arc<Notify>,
+    /// Notified when a file is submitted for import.
+    pub import_job_runner: Arc<Notify>,
+}
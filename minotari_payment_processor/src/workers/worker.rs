@@ -0,0 +1,79 @@
+use log::{error, info};
+use std::future::{self, Future};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::{self, Duration};
+use tokio_util::sync::CancellationToken;
+
+use crate::workers::supervisor::{Cycle, Heartbeat};
+
+/// A background worker that runs a cycle on a fixed interval until it is stopped.
+///
+/// The loop around the cycles is shared by all workers, see `run`: it waits for the interval or the worker's wakeup,
+/// records every cycle in the worker's heartbeat, and logs the error of a failed cycle. A worker that handles
+/// several independent items (e.g. accounts) reports each failure through `Cycle::fail` itself and carries on with
+/// the next item.
+pub trait Worker: Send + 'static {
+    /// Name the worker is supervised and reported under, e.g. in `GET /health/workers`. It is also the last segment
+    /// of the log target of the worker's module.
+    const NAME: &'static str;
+
+    /// Time between the start of two cycles.
+    fn interval(&self) -> Duration;
+
+    /// Notification that starts the next cycle right away, see `WorkerWakeups`.
+    fn wakeup(&self) -> Option<Arc<Notify>> {
+        None
+    }
+
+    /// Runs before the first cycle, e.g. to log the worker's settings.
+    fn start(&mut self) -> impl Future<Output = ()> + Send {
+        future::ready(())
+    }
+
+    /// Runs one cycle. `shutdown` is cancelled when the worker should stop; the cycle then finishes the item it is
+    /// working on and skips the rest.
+    fn run_cycle(
+        &mut self,
+        cycle: &mut Cycle<'_>,
+        shutdown: &CancellationToken,
+    ) -> impl Future<Output = Result<(), anyhow::Error>> + Send;
+
+    /// Runs after the last cycle once the worker was stopped.
+    fn shutdown(&mut self) -> impl Future<Output = ()> + Send {
+        future::ready(())
+    }
+}
+
+/// Runs `worker` until `shutdown` is cancelled.
+pub async fn run<W: Worker>(mut worker: W, heartbeat: Heartbeat, shutdown: CancellationToken) {
+    let log_target = format!("minotari_payment_processor::workers::{}", W::NAME);
+    worker.start().await;
+
+    let wakeup = worker.wakeup();
+    let mut interval = time::interval(worker.interval());
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {},
+            _ = notified(wakeup.as_deref()) => {},
+        }
+        let mut cycle = heartbeat.cycle_started();
+        if let Err(e) = worker.run_cycle(&mut cycle, &shutdown).await {
+            error!(target: &log_target, "Worker '{}' error: {:?}", W::NAME, e);
+            cycle.fail(&e);
+        }
+        cycle.finish();
+    }
+
+    worker.shutdown().await;
+    info!(target: &log_target, "Worker '{}' stopped.", W::NAME);
+}
+
+async fn notified(wakeup: Option<&Notify>) {
+    match wakeup {
+        Some(wakeup) => wakeup.notified().await,
+        None => future::pending().await,
+    }
+}